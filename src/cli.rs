@@ -1,6 +1,11 @@
 //! Command-line interface definitions and parsing
 
-use clap::{Parser, Subcommand};
+use crate::{
+  exif::tags::TagGroup,
+  journal::BackupMode,
+  models::{Exposure, ExposureProgram, MeteringMode},
+};
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
 /// Main CLI structure for parsing command-line arguments
@@ -19,6 +24,10 @@ pub struct Cli {
   /// Enable automatic creation date adjustment with 1-second increments for photos with identical timestamps
   #[arg(long = "one-sec")]
   pub one_sec: bool,
+
+  /// What to back up before modifying files, so that runs can be undone
+  #[arg(long, value_enum, default_value = "metadata", global = true)]
+  pub backup: BackupMode,
 }
 
 /// Available CLI commands
@@ -36,6 +45,665 @@ pub enum Commands {
     #[arg(long)]
     json: bool,
   },
+  /// Apply equipment metadata to image files without interactive prompts
+  Apply(ApplyArgs),
+  /// Erase metadata from image files without interactive prompts
+  Erase(EraseArgs),
+  /// Write the positions of a GPX track log into image files by capture time
+  Geotag(GeotagArgs),
+  /// List, add, edit or delete cameras
+  Camera(CameraArgs),
+  /// List, add, edit or delete lenses
+  Lens(LensArgs),
+  /// List, add, edit or delete film stocks
+  Film(FilmArgs),
+  /// List, add, edit or delete photographers
+  Photographer(PhotographerArgs),
+  /// List, add, edit or delete setups
+  Setup(SetupArgs),
+  /// List, add, edit or delete rolls of film
+  Roll(RollArgs),
+  /// List, add, edit or delete locations
+  Location(LocationArgs),
+  /// Restore the files changed by a run from their backups
+  Undo {
+    /// Run to undo (defaults to the latest run not yet undone)
+    run_id: Option<String>,
+    /// Output in JSON format instead of a summary
+    #[arg(long)]
+    json: bool,
+  },
+  /// List the runs that can be undone
+  History {
+    /// Output in JSON format instead of a table
+    #[arg(long)]
+    json: bool,
+  },
+}
+
+/// Arguments for the non-interactive `apply` command
+#[derive(Args)]
+pub struct ApplyArgs {
+  /// Roll to apply, by name or ID, instead of a setup, film and photographer
+  #[arg(long, conflicts_with_all = ["setup", "film", "photographer"])]
+  pub roll: Option<String>,
+  /// Setup to apply, by name or ID
+  #[arg(long, required_unless_present = "roll")]
+  pub setup: Option<String>,
+  /// Film stock to apply, by name or ID
+  #[arg(long, required_unless_present = "roll")]
+  pub film: Option<String>,
+  /// Photographer to credit, by name or ID
+  #[arg(long, required_unless_present = "roll")]
+  pub photographer: Option<String>,
+  /// ISO the film was shot at, if pushed or pulled (defaults to the roll's
+  /// ISO or the film's box speed)
+  #[arg(long)]
+  pub iso: Option<u32>,
+  /// Caption to write to the images
+  #[arg(long)]
+  pub caption: Option<String>,
+  /// Comma-separated keywords to write to the images, replacing existing ones
+  #[arg(long)]
+  pub keywords: Option<String>,
+  /// Shutter speed, such as "1/125" or "2s"
+  #[arg(long, value_parser = Exposure::parse_shutter_speed)]
+  pub shutter: Option<(u32, u32)>,
+  /// Aperture the frames were shot at, such as "5.6" or "f/8"
+  #[arg(long, value_parser = Exposure::parse_aperture)]
+  pub aperture: Option<(u32, u32)>,
+  /// Exposure compensation in stops, such as "+1/3" or "-0.7"
+  #[arg(long, value_parser = Exposure::parse_compensation, allow_hyphen_values = true)]
+  pub ev: Option<(i32, i32)>,
+  /// Metering mode of the camera
+  #[arg(long, value_enum)]
+  pub metering: Option<MeteringMode>,
+  /// Exposure program of the camera
+  #[arg(long, value_enum)]
+  pub program: Option<ExposureProgram>,
+  /// Development and scanning of the film, on top of the roll's
+  #[command(flatten)]
+  pub development: DevelopmentArgs,
+  /// Saved location the frames were shot at, by name or ID, whose position
+  /// and address are written to the images
+  #[arg(long = "location", value_name = "LOCATION")]
+  pub saved_location: Option<String>,
+  /// Shot log (CSV or JSON) whose per-frame values are applied on top of the
+  /// other values
+  #[arg(long)]
+  pub shot_log: Option<PathBuf>,
+  /// Regular expression capturing the frame number from each file name, such
+  /// as "_(\d+)\." (by default, files sorted by name are matched to frames in
+  /// order)
+  #[arg(long, requires = "shot_log", value_parser = regex::Regex::new)]
+  pub frame_pattern: Option<regex::Regex>,
+  /// Show the tag changes for each file without modifying anything
+  #[arg(long)]
+  pub dry_run: bool,
+  /// Descend into subdirectories of the given directories
+  #[arg(short, long)]
+  pub recursive: bool,
+  /// Print a JSON report instead of the formatted summary
+  #[arg(long)]
+  pub json: bool,
+  /// Paths to image files or directories
+  #[arg(required = true)]
+  pub paths: Vec<PathBuf>,
+}
+
+/// Arguments for the non-interactive `erase` command
+#[derive(Args)]
+pub struct EraseArgs {
+  /// Comma-separated groups of metadata to erase
+  #[arg(long, value_enum, value_delimiter = ',', default_value = "all")]
+  pub only: Vec<TagGroup>,
+  /// Comma-separated groups of metadata to keep, overriding --only
+  #[arg(long, value_enum, value_delimiter = ',')]
+  pub keep: Vec<TagGroup>,
+  /// Erase without asking for confirmation
+  #[arg(short, long)]
+  pub yes: bool,
+  /// Descend into subdirectories of the given directories
+  #[arg(short, long)]
+  pub recursive: bool,
+  /// Print a JSON report instead of the formatted summary
+  #[arg(long)]
+  pub json: bool,
+  /// Paths to image files or directories
+  #[arg(required = true)]
+  pub paths: Vec<PathBuf>,
+}
+
+/// Arguments for the `geotag` command
+#[derive(Args)]
+pub struct GeotagArgs {
+  /// GPX track log recorded while shooting
+  #[arg(long)]
+  pub gpx: PathBuf,
+  /// UTC offset the camera clock was set to, such as "+01:00" (defaults to
+  /// the local time zone)
+  #[arg(long, value_parser = crate::gpx::parse_utc_offset, allow_hyphen_values = true)]
+  pub offset: Option<chrono::FixedOffset>,
+  /// Longest time in seconds between a photo and the track points its
+  /// position is taken from
+  #[arg(long, default_value_t = 300)]
+  pub max_gap: u32,
+  /// Show the GPS tags for each file without modifying anything
+  #[arg(long)]
+  pub dry_run: bool,
+  /// Descend into subdirectories of the given directories
+  #[arg(short, long)]
+  pub recursive: bool,
+  /// Print a JSON report instead of the formatted summary
+  #[arg(long)]
+  pub json: bool,
+  /// Paths to image files or directories
+  #[arg(required = true)]
+  pub paths: Vec<PathBuf>,
+}
+
+/// Arguments for the `camera` command
+#[derive(Args)]
+pub struct CameraArgs {
+  /// Operation to perform
+  #[command(subcommand)]
+  pub action: CameraAction,
+  /// Print JSON instead of a table or summary
+  #[arg(long, global = true)]
+  pub json: bool,
+}
+
+/// Camera operations
+#[derive(Subcommand)]
+pub enum CameraAction {
+  /// List all cameras
+  List,
+  /// Add a camera
+  Add {
+    /// Camera manufacturer
+    #[arg(long)]
+    maker: String,
+    /// Camera model
+    #[arg(long)]
+    model: String,
+  },
+  /// Edit a camera, keeping the fields that are not given
+  Edit {
+    /// Camera to edit, by name or ID
+    camera: String,
+    /// New manufacturer
+    #[arg(long)]
+    maker: Option<String>,
+    /// New model
+    #[arg(long)]
+    model: Option<String>,
+  },
+  /// Delete a camera that is not used by any setup
+  Delete {
+    /// Camera to delete, by name or ID
+    camera: String,
+  },
+}
+
+/// Arguments for the `lens` command
+#[derive(Args)]
+pub struct LensArgs {
+  /// Operation to perform
+  #[command(subcommand)]
+  pub action: LensAction,
+  /// Print JSON instead of a table or summary
+  #[arg(long, global = true)]
+  pub json: bool,
+}
+
+/// Lens operations
+#[derive(Subcommand)]
+pub enum LensAction {
+  /// List all lenses
+  List,
+  /// Add a lens
+  Add {
+    /// Lens manufacturer
+    #[arg(long)]
+    maker: String,
+    /// Lens model
+    #[arg(long)]
+    model: String,
+    /// Focal length in mm (e.g. "50" or "28-70")
+    #[arg(long)]
+    focal_length: String,
+    /// Maximum aperture (e.g. "1.4")
+    #[arg(long)]
+    aperture: String,
+    /// Lens mount
+    #[arg(long)]
+    mount: String,
+  },
+  /// Edit a lens, keeping the fields that are not given
+  Edit {
+    /// Lens to edit, by name or ID
+    lens: String,
+    /// New manufacturer
+    #[arg(long)]
+    maker: Option<String>,
+    /// New model
+    #[arg(long)]
+    model: Option<String>,
+    /// New focal length in mm
+    #[arg(long)]
+    focal_length: Option<String>,
+    /// New maximum aperture
+    #[arg(long)]
+    aperture: Option<String>,
+    /// New mount
+    #[arg(long)]
+    mount: Option<String>,
+  },
+  /// Delete a lens that is not used by any setup
+  Delete {
+    /// Lens to delete, by name or ID
+    lens: String,
+  },
+}
+
+/// Arguments for the `film` command
+#[derive(Args)]
+pub struct FilmArgs {
+  /// Operation to perform
+  #[command(subcommand)]
+  pub action: FilmAction,
+  /// Print JSON instead of a table or summary
+  #[arg(long, global = true)]
+  pub json: bool,
+}
+
+/// Film stock operations
+#[derive(Subcommand)]
+pub enum FilmAction {
+  /// List all film stocks
+  List,
+  /// Add a film stock
+  Add {
+    /// Film manufacturer
+    #[arg(long)]
+    maker: String,
+    /// Film stock name
+    #[arg(long)]
+    name: String,
+    /// Box speed
+    #[arg(long)]
+    iso: u32,
+  },
+  /// Edit a film stock, keeping the fields that are not given
+  Edit {
+    /// Film stock to edit, by name or ID
+    film: String,
+    /// New manufacturer
+    #[arg(long)]
+    maker: Option<String>,
+    /// New name
+    #[arg(long)]
+    name: Option<String>,
+    /// New box speed
+    #[arg(long)]
+    iso: Option<u32>,
+  },
+  /// Delete a film stock
+  Delete {
+    /// Film stock to delete, by name or ID
+    film: String,
+  },
+}
+
+/// Arguments for the `photographer` command
+#[derive(Args)]
+pub struct PhotographerArgs {
+  /// Operation to perform
+  #[command(subcommand)]
+  pub action: PhotographerAction,
+  /// Print JSON instead of a table or summary
+  #[arg(long, global = true)]
+  pub json: bool,
+}
+
+/// Photographer operations
+#[derive(Subcommand)]
+pub enum PhotographerAction {
+  /// List all photographers
+  List,
+  /// Add a photographer
+  Add {
+    /// Photographer's name
+    #[arg(long)]
+    name: String,
+    /// Photographer's email address
+    #[arg(long)]
+    email: Option<String>,
+    /// Copyright notice template, where {year} and {name} are filled in
+    /// (default: "© {year} {name}")
+    #[arg(long)]
+    copyright: Option<String>,
+    /// Licence URL or Creative Commons identifier, such as "CC BY-NC 4.0"
+    #[arg(long)]
+    license: Option<String>,
+    /// Terms under which the images may be used
+    #[arg(long)]
+    usage_terms: Option<String>,
+    /// Contact details
+    #[command(flatten)]
+    contact: ContactArgs,
+  },
+  /// Edit a photographer, keeping the fields that are not given
+  Edit {
+    /// Photographer to edit, by name or ID
+    photographer: String,
+    /// New name
+    #[arg(long)]
+    name: Option<String>,
+    /// New email address
+    #[arg(long, conflicts_with = "no_email")]
+    email: Option<String>,
+    /// Remove the email address
+    #[arg(long)]
+    no_email: bool,
+    /// New copyright notice template, where {year} and {name} are filled in
+    #[arg(long, conflicts_with = "no_copyright")]
+    copyright: Option<String>,
+    /// Go back to the default copyright notice
+    #[arg(long)]
+    no_copyright: bool,
+    /// New licence URL or Creative Commons identifier
+    #[arg(long, conflicts_with = "no_license")]
+    license: Option<String>,
+    /// Remove the licence
+    #[arg(long)]
+    no_license: bool,
+    /// New usage terms
+    #[arg(long, conflicts_with = "no_usage_terms")]
+    usage_terms: Option<String>,
+    /// Remove the usage terms
+    #[arg(long)]
+    no_usage_terms: bool,
+    /// New contact details, where an empty value removes the field
+    #[command(flatten)]
+    contact: ContactArgs,
+  },
+  /// Delete a photographer
+  Delete {
+    /// Photographer to delete, by name or ID
+    photographer: String,
+  },
+}
+
+/// Contact details of a photographer
+#[derive(Args)]
+pub struct ContactArgs {
+  /// Street address
+  #[arg(long)]
+  pub address: Option<String>,
+  /// City
+  #[arg(long)]
+  pub city: Option<String>,
+  /// State, province or region
+  #[arg(long)]
+  pub region: Option<String>,
+  /// Postal code
+  #[arg(long)]
+  pub postal_code: Option<String>,
+  /// Country
+  #[arg(long)]
+  pub country: Option<String>,
+  /// Phone number
+  #[arg(long)]
+  pub phone: Option<String>,
+  /// Website URL
+  #[arg(long)]
+  pub website: Option<String>,
+}
+
+/// Arguments for the `setup` command
+#[derive(Args)]
+pub struct SetupArgs {
+  /// Operation to perform
+  #[command(subcommand)]
+  pub action: SetupAction,
+  /// Print JSON instead of a table or summary
+  #[arg(long, global = true)]
+  pub json: bool,
+}
+
+/// Setup operations
+#[derive(Subcommand)]
+pub enum SetupAction {
+  /// List all setups
+  List,
+  /// Add a setup
+  Add {
+    /// Setup name
+    #[arg(long)]
+    name: String,
+    /// Camera of the setup, by name or ID
+    #[arg(long)]
+    camera: String,
+    /// Lens of the setup, by name or ID (omit for a camera-only setup)
+    #[arg(long)]
+    lens: Option<String>,
+  },
+  /// Edit a setup, keeping the fields that are not given
+  Edit {
+    /// Setup to edit, by name or ID
+    setup: String,
+    /// New name
+    #[arg(long)]
+    name: Option<String>,
+    /// New camera, by name or ID
+    #[arg(long)]
+    camera: Option<String>,
+    /// New lens, by name or ID
+    #[arg(long, conflicts_with = "no_lens")]
+    lens: Option<String>,
+    /// Make this a camera-only setup
+    #[arg(long)]
+    no_lens: bool,
+  },
+  /// Delete a setup
+  Delete {
+    /// Setup to delete, by name or ID
+    setup: String,
+  },
+}
+
+/// Arguments for the `roll` command
+#[derive(Args)]
+pub struct RollArgs {
+  /// Operation to perform
+  #[command(subcommand)]
+  pub action: RollAction,
+  /// Print JSON instead of a table or summary
+  #[arg(long, global = true)]
+  pub json: bool,
+}
+
+/// Roll operations
+#[derive(Subcommand)]
+pub enum RollAction {
+  /// List all rolls
+  List,
+  /// Add a roll
+  Add {
+    /// Roll name, such as "2024-07 Lisbon"
+    #[arg(long)]
+    name: String,
+    /// Film stock of the roll, by name or ID
+    #[arg(long)]
+    film: String,
+    /// Setup the roll was shot with, by name or ID
+    #[arg(long)]
+    setup: String,
+    /// Photographer who shot the roll, by name or ID
+    #[arg(long)]
+    photographer: String,
+    /// ISO the roll was shot at, if pushed or pulled (defaults to the film's box speed)
+    #[arg(long)]
+    iso: Option<u32>,
+    /// Dates, frame count, lab and notes
+    #[command(flatten)]
+    details: RollDetailsArgs,
+  },
+  /// Edit a roll, keeping the fields that are not given
+  Edit {
+    /// Roll to edit, by name or ID
+    roll: String,
+    /// New name
+    #[arg(long)]
+    name: Option<String>,
+    /// New film stock, by name or ID
+    #[arg(long)]
+    film: Option<String>,
+    /// New setup, by name or ID
+    #[arg(long)]
+    setup: Option<String>,
+    /// New photographer, by name or ID
+    #[arg(long)]
+    photographer: Option<String>,
+    /// New ISO the roll was shot at
+    #[arg(long, conflicts_with = "box_speed")]
+    iso: Option<u32>,
+    /// Go back to the film's box speed
+    #[arg(long)]
+    box_speed: bool,
+    /// New dates, frame count, lab and notes, where an empty value removes
+    /// the field
+    #[command(flatten)]
+    details: RollDetailsArgs,
+  },
+  /// Delete a roll
+  Delete {
+    /// Roll to delete, by name or ID
+    roll: String,
+  },
+}
+
+/// Dates, frame count, development and notes of a roll
+#[derive(Args)]
+pub struct RollDetailsArgs {
+  /// Date the roll was loaded, as YYYY-MM-DD
+  #[arg(long)]
+  pub loaded: Option<String>,
+  /// Date the last frame was shot, as YYYY-MM-DD
+  #[arg(long)]
+  pub finished: Option<String>,
+  /// Number of frames shot
+  #[arg(long)]
+  pub frames: Option<u32>,
+  /// Notes about the roll
+  #[arg(long)]
+  pub notes: Option<String>,
+  /// How the roll was developed and scanned
+  #[command(flatten)]
+  pub development: DevelopmentArgs,
+}
+
+/// Arguments for the `location` command
+#[derive(Args)]
+pub struct LocationArgs {
+  /// Operation to perform
+  #[command(subcommand)]
+  pub action: LocationAction,
+  /// Print JSON instead of a table or summary
+  #[arg(long, global = true)]
+  pub json: bool,
+}
+
+/// Location operations
+#[derive(Subcommand)]
+pub enum LocationAction {
+  /// List all locations
+  List,
+  /// Add a location
+  Add {
+    /// Location name, such as "Alfama"
+    #[arg(long)]
+    name: String,
+    /// Latitude in decimal degrees, negative south of the equator
+    #[arg(long, allow_hyphen_values = true)]
+    latitude: f64,
+    /// Longitude in decimal degrees, negative west of Greenwich
+    #[arg(long, allow_hyphen_values = true)]
+    longitude: f64,
+    /// Altitude and address
+    #[command(flatten)]
+    details: LocationDetailsArgs,
+  },
+  /// Edit a location, keeping the fields that are not given
+  Edit {
+    /// Location to edit, by name or ID
+    location: String,
+    /// New name
+    #[arg(long)]
+    name: Option<String>,
+    /// New latitude in decimal degrees
+    #[arg(long, allow_hyphen_values = true)]
+    latitude: Option<f64>,
+    /// New longitude in decimal degrees
+    #[arg(long, allow_hyphen_values = true)]
+    longitude: Option<f64>,
+    /// New altitude and address, where an empty value removes the field
+    #[command(flatten)]
+    details: LocationDetailsArgs,
+  },
+  /// Delete a location
+  Delete {
+    /// Location to delete, by name or ID
+    location: String,
+  },
+}
+
+/// Altitude and address of a location
+#[derive(Args)]
+pub struct LocationDetailsArgs {
+  /// Altitude in metres above sea level
+  #[arg(long, allow_hyphen_values = true)]
+  pub altitude: Option<String>,
+  /// Place within the city, such as a district or landmark
+  #[arg(long)]
+  pub sublocation: Option<String>,
+  /// City
+  #[arg(long)]
+  pub city: Option<String>,
+  /// State, province or region
+  #[arg(long)]
+  pub state: Option<String>,
+  /// Country name
+  #[arg(long)]
+  pub country: Option<String>,
+  /// ISO 3166 country code, such as "PT"
+  #[arg(long)]
+  pub country_code: Option<String>,
+}
+
+/// Development and scanning of a roll of film, where an empty value removes
+/// the field
+#[derive(Args)]
+pub struct DevelopmentArgs {
+  /// Developer, such as "HC-110"
+  #[arg(long)]
+  pub developer: Option<String>,
+  /// Dilution of the developer, such as "1+31"
+  #[arg(long)]
+  pub dilution: Option<String>,
+  /// Development time, such as "9:30" or "9.5" minutes
+  #[arg(long)]
+  pub dev_time: Option<String>,
+  /// Temperature of the developer in degrees Celsius, such as "20"
+  #[arg(long)]
+  pub dev_temp: Option<String>,
+  /// Lab or person who developed the roll
+  #[arg(long)]
+  pub lab: Option<String>,
+  /// Date the negatives were scanned, as YYYY-MM-DD
+  #[arg(long)]
+  pub scanned: Option<String>,
 }
 
 impl Cli {
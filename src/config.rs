@@ -1,7 +1,8 @@
 //! Configuration management for IFEX application settings and persistent data.
 //!
 //! This module handles loading, saving, and managing the application's configuration
-//! data including cameras, lenses, films, photographers, and equipment setups.
+//! data including cameras, lenses, films, photographers, equipment setups,
+//! rolls and locations.
 
 use dirs::config_dir;
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,12 @@ pub struct Config {
   pub photographers: Vec<crate::models::Photographer>,
   /// List of equipment setups (camera + lens combinations)
   pub setups: Vec<crate::models::Setup>,
+  /// List of rolls of film shot with the setups
+  #[serde(default)]
+  pub rolls: Vec<crate::models::Roll>,
+  /// List of saved locations photographs are taken at
+  #[serde(default)]
+  pub locations: Vec<crate::models::Location>,
 }
 
 impl Config {
@@ -64,7 +71,7 @@ impl Config {
     }
 
     let content = serde_json::to_string_pretty(self)?;
-    fs::write(&path, content)?;
+    crate::utils::write_atomic(&path, content.as_bytes())?;
     Ok(())
   }
 }
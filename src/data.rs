@@ -1,12 +1,13 @@
 //! Data management layer for IFEX equipment and configuration operations.
 //!
 //! This module provides a high-level interface for managing photography equipment
-//! data including cameras, lenses, films, photographers, and equipment setups.
+//! data including cameras, lenses, films, photographers, equipment setups,
+//! rolls and locations.
 //! It wraps the configuration system and provides CRUD operations.
 
 use crate::{
   config::Config,
-  models::{Camera, Film, Lens, Photographer, Selection, Setup},
+  models::{Camera, Contact, Film, Lens, Location, Photographer, Rights, Roll, Selection, Setup},
 };
 use uuid::Uuid;
 
@@ -106,6 +107,39 @@ impl DataManager {
     Ok(setup)
   }
 
+  /// Adds a roll to the configuration.
+  ///
+  /// Returns an error if the film, setup or photographer of the roll cannot be
+  /// found in the configuration.
+  pub fn add_roll(&mut self, roll: Roll) -> Result<Roll, String> {
+    self.check_roll_references(&roll)?;
+    self.config.rolls.push(roll.clone());
+    Ok(roll)
+  }
+
+  /// Adds a location to the configuration.
+  ///
+  /// Returns an error if its coordinates or country code are invalid.
+  pub fn add_location(&mut self, location: Location) -> Result<Location, String> {
+    location.validate()?;
+    self.config.locations.push(location.clone());
+    Ok(location)
+  }
+
+  /// Checks that the film, setup and photographer of a roll exist.
+  fn check_roll_references(&self, roll: &Roll) -> Result<(), String> {
+    if self.get_film_by_id(roll.film_id).is_none() {
+      return Err("Film not found".to_string());
+    }
+    if self.get_setup_by_id(roll.setup_id).is_none() {
+      return Err("Setup not found".to_string());
+    }
+    if self.get_photographer_by_id(roll.photographer_id).is_none() {
+      return Err("Photographer not found".to_string());
+    }
+    Ok(())
+  }
+
   /// Returns a reference to all cameras in the configuration.
   #[must_use]
   pub const fn get_cameras(&self) -> &Vec<Camera> {
@@ -136,6 +170,18 @@ impl DataManager {
     &self.config.setups
   }
 
+  /// Returns a reference to all rolls in the configuration.
+  #[must_use]
+  pub const fn get_rolls(&self) -> &Vec<Roll> {
+    &self.config.rolls
+  }
+
+  /// Returns a reference to all locations in the configuration.
+  #[must_use]
+  pub const fn get_locations(&self) -> &Vec<Location> {
+    &self.config.locations
+  }
+
   /// Finds a camera by its unique ID.
   ///
   /// Returns `Some(&Camera)` if found, `None` otherwise.
@@ -176,6 +222,163 @@ impl DataManager {
     self.config.setups.iter().find(|s| s.id == id)
   }
 
+  /// Finds a roll by its unique ID.
+  ///
+  /// Returns None if no roll with the given ID exists.
+  #[must_use]
+  pub fn get_roll_by_id(&self, id: Uuid) -> Option<&Roll> {
+    self.config.rolls.iter().find(|r| r.id == id)
+  }
+
+  /// Finds a location by its unique ID.
+  ///
+  /// Returns None if no location with the given ID exists.
+  #[must_use]
+  pub fn get_location_by_id(&self, id: Uuid) -> Option<&Location> {
+    self.config.locations.iter().find(|l| l.id == id)
+  }
+
+  /// Finds a camera by its ID or by its unique name.
+  ///
+  /// Accepts the model ("M6") or maker and model ("Leica M6"). Returns an
+  /// error if no camera or several cameras match.
+  pub fn find_camera(&self, key: &str) -> Result<&Camera, String> {
+    Self::find_by_key(
+      &self.config.cameras,
+      key,
+      "Camera",
+      |c| c.id,
+      |c| vec![c.model.clone(), c.display_name()],
+    )
+  }
+
+  /// Finds a lens by its ID or by its unique name.
+  ///
+  /// Accepts the model ("Summicron"), maker and model ("Leica Summicron") or
+  /// the full display name. Returns an error if no lens or several lenses match.
+  pub fn find_lens(&self, key: &str) -> Result<&Lens, String> {
+    Self::find_by_key(
+      &self.config.lenses,
+      key,
+      "Lens",
+      |l| l.id,
+      |l| {
+        vec![
+          l.model.clone(),
+          format!("{} {}", l.maker, l.model),
+          l.display_name(),
+        ]
+      },
+    )
+  }
+
+  /// Finds a setup by its ID or by its unique name.
+  ///
+  /// Names are compared case-insensitively. Returns an error if no setup
+  /// matches or if several setups share the given name.
+  pub fn find_setup(&self, key: &str) -> Result<&Setup, String> {
+    Self::find_by_key(
+      &self.config.setups,
+      key,
+      "Setup",
+      |s| s.id,
+      |s| vec![s.name.clone()],
+    )
+  }
+
+  /// Finds a film by its ID or by its unique name.
+  ///
+  /// Accepts the film name ("Tri-X"), maker and name ("Kodak Tri-X") or the
+  /// full display name. Returns an error if no film or several films match.
+  pub fn find_film(&self, key: &str) -> Result<&Film, String> {
+    Self::find_by_key(
+      &self.config.films,
+      key,
+      "Film",
+      |f| f.id,
+      |f| {
+        vec![
+          f.name.clone(),
+          format!("{} {}", f.maker, f.name),
+          f.display_name(),
+        ]
+      },
+    )
+  }
+
+  /// Finds a photographer by their ID or by their unique name.
+  ///
+  /// Returns an error if no photographer or several photographers match.
+  pub fn find_photographer(&self, key: &str) -> Result<&Photographer, String> {
+    Self::find_by_key(
+      &self.config.photographers,
+      key,
+      "Photographer",
+      |p| p.id,
+      |p| vec![p.name.clone(), p.display_name()],
+    )
+  }
+
+  /// Finds a roll by its ID or by its unique name.
+  ///
+  /// Returns an error if no roll or several rolls match.
+  pub fn find_roll(&self, key: &str) -> Result<&Roll, String> {
+    Self::find_by_key(
+      &self.config.rolls,
+      key,
+      "Roll",
+      |r| r.id,
+      |r| vec![r.name.clone(), r.display_name()],
+    )
+  }
+
+  /// Finds a location by its ID or by its unique name.
+  ///
+  /// Returns an error if no location or several locations match.
+  pub fn find_location(&self, key: &str) -> Result<&Location, String> {
+    Self::find_by_key(
+      &self.config.locations,
+      key,
+      "Location",
+      |l| l.id,
+      |l| vec![l.name.clone(), l.display_name()],
+    )
+  }
+
+  /// Resolves `key` as a UUID first, then as a case-insensitive name.
+  fn find_by_key<'a, T>(
+    items: &'a [T],
+    key: &str,
+    kind: &str,
+    id_of: impl Fn(&T) -> Uuid,
+    names_of: impl Fn(&T) -> Vec<String>,
+  ) -> Result<&'a T, String> {
+    let key = key.trim();
+    if let Ok(id) = Uuid::parse_str(key) {
+      return items
+        .iter()
+        .find(|item| id_of(item) == id)
+        .ok_or_else(|| format!("{kind} not found: {key}"));
+    }
+
+    let matches: Vec<&T> = items
+      .iter()
+      .filter(|item| {
+        names_of(item)
+          .iter()
+          .any(|name| name.eq_ignore_ascii_case(key))
+      })
+      .collect();
+    match matches.as_slice() {
+      [item] => Ok(item),
+      [] => Err(format!("{kind} not found: {key}")),
+      _ => Err(format!(
+        "{kind} name \"{key}\" is ambiguous ({} matches); use its ID instead",
+        matches.len()
+      )),
+    }
+  }
+
   /// Creates a complete equipment selection for EXIF metadata application.
   ///
   /// Combines a setup (camera + optional lens), film, and photographer into a single
@@ -207,13 +410,26 @@ impl DataManager {
       .get_photographer_by_id(photographer_id)
       .ok_or("Photographer not found")?;
 
-    Ok(Selection {
-      setup: setup.clone(),
-      camera: camera.clone(),
+    Ok(Selection::new(
+      setup.clone(),
+      camera.clone(),
       lens,
-      film: film.clone(),
-      photographer: photographer.clone(),
-    })
+      film.clone(),
+      photographer.clone(),
+    ))
+  }
+
+  /// Creates the equipment selection of a roll.
+  ///
+  /// Like `create_selection` with the setup, film and photographer of the
+  /// roll, which is attached to the selection so that its ID is written to
+  /// the images. The ISO the roll was shot at is `Roll::shot_iso`.
+  pub fn create_roll_selection(&self, roll_id: Uuid) -> Result<Selection, String> {
+    let roll = self.get_roll_by_id(roll_id).ok_or("Roll not found")?;
+    let mut selection = self.create_selection(roll.setup_id, roll.film_id, roll.photographer_id)?;
+    selection.development = roll.development.clone();
+    selection.roll = Some(roll.clone());
+    Ok(selection)
   }
 
   /// Deletes a camera from the configuration.
@@ -244,26 +460,51 @@ impl DataManager {
 
   /// Deletes a film from the configuration.
   ///
-  /// Films can be safely deleted without checking for references
-  /// since they are not referenced by other entities.
-  pub fn delete_film(&mut self, id: Uuid) {
+  /// Returns an error if the film is currently used by any rolls.
+  pub fn delete_film(&mut self, id: Uuid) -> Result<(), String> {
+    if self.config.rolls.iter().any(|r| r.film_id == id) {
+      return Err("Cannot delete film that is used by rolls".to_string());
+    }
     self.config.films.retain(|f| f.id != id);
+    Ok(())
   }
 
   /// Deletes a photographer from the configuration.
   ///
-  /// Photographers can be safely deleted without checking for references
-  /// since they are not referenced by other entities.
-  pub fn delete_photographer(&mut self, id: Uuid) {
+  /// Returns an error if the photographer is currently used by any rolls.
+  pub fn delete_photographer(&mut self, id: Uuid) -> Result<(), String> {
+    if self.config.rolls.iter().any(|r| r.photographer_id == id) {
+      return Err("Cannot delete photographer who is used by rolls".to_string());
+    }
     self.config.photographers.retain(|p| p.id != id);
+    Ok(())
   }
 
   /// Deletes a setup from the configuration.
   ///
-  /// Setups can be safely deleted without checking for references
-  /// since they are not referenced by other entities.
-  pub fn delete_setup(&mut self, id: Uuid) {
+  /// Returns an error if the setup is currently used by any rolls.
+  pub fn delete_setup(&mut self, id: Uuid) -> Result<(), String> {
+    if self.config.rolls.iter().any(|r| r.setup_id == id) {
+      return Err("Cannot delete setup that is used by rolls".to_string());
+    }
     self.config.setups.retain(|s| s.id != id);
+    Ok(())
+  }
+
+  /// Deletes a roll from the configuration.
+  ///
+  /// Rolls can be safely deleted without checking for references
+  /// since they are not referenced by other entities.
+  pub fn delete_roll(&mut self, id: Uuid) {
+    self.config.rolls.retain(|r| r.id != id);
+  }
+
+  /// Deletes a location from the configuration.
+  ///
+  /// Locations are not referenced by other entities, so they can always be
+  /// deleted.
+  pub fn delete_location(&mut self, id: Uuid) {
+    self.config.locations.retain(|l| l.id != id);
   }
 
   /// Updates an existing camera in the configuration.
@@ -330,6 +571,30 @@ impl DataManager {
     }
   }
 
+  /// Replaces the copyright and licensing terms of an existing photographer.
+  ///
+  /// Returns true if the photographer was found and updated, false otherwise.
+  pub fn edit_photographer_rights(&mut self, id: Uuid, rights: Rights) -> bool {
+    if let Some(photographer) = self.config.photographers.iter_mut().find(|p| p.id == id) {
+      photographer.rights = rights;
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Replaces the contact details of an existing photographer.
+  ///
+  /// Returns true if the photographer was found and updated, false otherwise.
+  pub fn edit_photographer_contact(&mut self, id: Uuid, contact: Contact) -> bool {
+    if let Some(photographer) = self.config.photographers.iter_mut().find(|p| p.id == id) {
+      photographer.contact = contact;
+      true
+    } else {
+      false
+    }
+  }
+
   /// Updates an existing setup in the configuration.
   ///
   /// Returns true if the setup was found and updated, false otherwise.
@@ -360,4 +625,37 @@ impl DataManager {
       Ok(false)
     }
   }
+
+  /// Replaces an existing roll in the configuration with `roll`.
+  ///
+  /// Returns an error if the film, setup or photographer of the roll cannot be
+  /// found, and false if no roll has the ID of `roll`.
+  pub fn edit_roll(&mut self, roll: Roll) -> Result<bool, String> {
+    self.check_roll_references(&roll)?;
+    if let Some(existing) = self.config.rolls.iter_mut().find(|r| r.id == roll.id) {
+      *existing = roll;
+      Ok(true)
+    } else {
+      Ok(false)
+    }
+  }
+
+  /// Replaces an existing location in the configuration with `location`.
+  ///
+  /// Returns an error if its coordinates or country code are invalid, and
+  /// false if no location has the ID of `location`.
+  pub fn edit_location(&mut self, location: Location) -> Result<bool, String> {
+    location.validate()?;
+    if let Some(existing) = self
+      .config
+      .locations
+      .iter_mut()
+      .find(|l| l.id == location.id)
+    {
+      *existing = location;
+      Ok(true)
+    } else {
+      Ok(false)
+    }
+  }
 }
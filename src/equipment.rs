@@ -0,0 +1,793 @@
+//! Non-interactive equipment management commands.
+//!
+//! This module backs the `camera`, `lens`, `film`, `photographer`, `setup`,
+//! `roll` and `location` subcommands, which expose the operations of the
+//! management menus to scripts.
+//! Entities are resolved by ID or by unique name, results are printed as a table
+//! or as JSON, and every failure is returned as an error.
+
+use crate::{
+  cli::{
+    CameraAction, CameraArgs, ContactArgs, DevelopmentArgs, FilmAction, FilmArgs, LensAction,
+    LensArgs, LocationAction, LocationArgs, LocationDetailsArgs, PhotographerAction,
+    PhotographerArgs, RollAction, RollArgs, RollDetailsArgs, SetupAction, SetupArgs,
+  },
+  data::DataManager,
+  models::{Camera, Contact, Development, Film, Lens, Location, Rights, Roll, Setup},
+  prompts::PromptUtils,
+  Result,
+};
+use colored::Colorize;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Runs a `camera` subcommand.
+pub fn run_camera(args: &CameraArgs) -> Result<()> {
+  let mut data_manager = DataManager::new()?;
+
+  match &args.action {
+    CameraAction::List => print_list(
+      args.json,
+      "cameras",
+      data_manager.get_cameras(),
+      &["ID", "Maker", "Model"],
+      |c| vec![c.id.to_string(), c.maker.clone(), c.model.clone()],
+    ),
+    CameraAction::Add { maker, model } => {
+      let camera = data_manager.add_camera(maker.clone(), model.clone());
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Added camera",
+        &camera,
+        &camera.display_name(),
+        camera.id,
+      )
+    }
+    CameraAction::Edit {
+      camera,
+      maker,
+      model,
+    } => {
+      let camera = data_manager.find_camera(camera)?.clone();
+      if !data_manager.edit_camera(
+        camera.id,
+        maker.clone().unwrap_or(camera.maker),
+        model.clone().unwrap_or(camera.model),
+      ) {
+        return Err("Camera not found".into());
+      }
+      data_manager.save()?;
+      let camera = data_manager
+        .get_camera_by_id(camera.id)
+        .ok_or("Camera not found")?;
+      print_change(
+        args.json,
+        "Updated camera",
+        camera,
+        &camera.display_name(),
+        camera.id,
+      )
+    }
+    CameraAction::Delete { camera } => {
+      let camera = data_manager.find_camera(camera)?.clone();
+      data_manager.delete_camera(camera.id)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Deleted camera",
+        &camera,
+        &camera.display_name(),
+        camera.id,
+      )
+    }
+  }
+}
+
+/// Runs a `lens` subcommand.
+pub fn run_lens(args: &LensArgs) -> Result<()> {
+  let mut data_manager = DataManager::new()?;
+
+  match &args.action {
+    LensAction::List => print_list(
+      args.json,
+      "lenses",
+      data_manager.get_lenses(),
+      &["ID", "Maker", "Model", "Focal Length", "Aperture", "Mount"],
+      |l| {
+        vec![
+          l.id.to_string(),
+          l.maker.clone(),
+          l.model.clone(),
+          format!("{}mm", l.focal_length),
+          format!("f/{}", l.aperture),
+          l.mount.clone(),
+        ]
+      },
+    ),
+    LensAction::Add {
+      maker,
+      model,
+      focal_length,
+      aperture,
+      mount,
+    } => {
+      let lens = data_manager.add_lens(
+        maker.clone(),
+        model.clone(),
+        focal_length.clone(),
+        aperture.clone(),
+        mount.clone(),
+      );
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Added lens",
+        &lens,
+        &lens.display_name(),
+        lens.id,
+      )
+    }
+    LensAction::Edit {
+      lens,
+      maker,
+      model,
+      focal_length,
+      aperture,
+      mount,
+    } => {
+      let lens = data_manager.find_lens(lens)?.clone();
+      if !data_manager.edit_lens(
+        lens.id,
+        maker.clone().unwrap_or(lens.maker),
+        model.clone().unwrap_or(lens.model),
+        focal_length.clone().unwrap_or(lens.focal_length),
+        aperture.clone().unwrap_or(lens.aperture),
+        mount.clone().unwrap_or(lens.mount),
+      ) {
+        return Err("Lens not found".into());
+      }
+      data_manager.save()?;
+      let lens = data_manager
+        .get_lens_by_id(lens.id)
+        .ok_or("Lens not found")?;
+      print_change(
+        args.json,
+        "Updated lens",
+        lens,
+        &lens.display_name(),
+        lens.id,
+      )
+    }
+    LensAction::Delete { lens } => {
+      let lens = data_manager.find_lens(lens)?.clone();
+      data_manager.delete_lens(lens.id)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Deleted lens",
+        &lens,
+        &lens.display_name(),
+        lens.id,
+      )
+    }
+  }
+}
+
+/// Runs a `film` subcommand.
+pub fn run_film(args: &FilmArgs) -> Result<()> {
+  let mut data_manager = DataManager::new()?;
+
+  match &args.action {
+    FilmAction::List => print_list(
+      args.json,
+      "films",
+      data_manager.get_films(),
+      &["ID", "Maker", "Name", "ISO"],
+      |f| {
+        vec![
+          f.id.to_string(),
+          f.maker.clone(),
+          f.name.clone(),
+          f.iso.to_string(),
+        ]
+      },
+    ),
+    FilmAction::Add { maker, name, iso } => {
+      let film = data_manager.add_film(maker.clone(), name.clone(), *iso);
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Added film",
+        &film,
+        &film.display_name(),
+        film.id,
+      )
+    }
+    FilmAction::Edit {
+      film,
+      maker,
+      name,
+      iso,
+    } => {
+      let film = data_manager.find_film(film)?.clone();
+      if !data_manager.edit_film(
+        film.id,
+        maker.clone().unwrap_or(film.maker),
+        name.clone().unwrap_or(film.name),
+        iso.unwrap_or(film.iso),
+      ) {
+        return Err("Film not found".into());
+      }
+      data_manager.save()?;
+      let film = data_manager
+        .get_film_by_id(film.id)
+        .ok_or("Film not found")?;
+      print_change(
+        args.json,
+        "Updated film",
+        film,
+        &film.display_name(),
+        film.id,
+      )
+    }
+    FilmAction::Delete { film } => {
+      let film = data_manager.find_film(film)?.clone();
+      data_manager.delete_film(film.id)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Deleted film",
+        &film,
+        &film.display_name(),
+        film.id,
+      )
+    }
+  }
+}
+
+/// Runs a `photographer` subcommand.
+pub fn run_photographer(args: &PhotographerArgs) -> Result<()> {
+  let mut data_manager = DataManager::new()?;
+
+  match &args.action {
+    PhotographerAction::List => print_list(
+      args.json,
+      "photographers",
+      data_manager.get_photographers(),
+      &["ID", "Name", "Email", "License"],
+      |p| {
+        vec![
+          p.id.to_string(),
+          p.name.clone(),
+          p.email.clone().unwrap_or_default(),
+          p.rights.license.clone().unwrap_or_default(),
+        ]
+      },
+    ),
+    PhotographerAction::Add {
+      name,
+      email,
+      copyright,
+      license,
+      usage_terms,
+      contact,
+    } => {
+      let rights = Rights {
+        copyright_template: copyright.clone(),
+        license: license.clone(),
+        usage_terms: usage_terms.clone(),
+      };
+      rights.validate()?;
+      let contact = update_contact(contact, Contact::default());
+      let mut photographer = data_manager.add_photographer(name.clone(), email.clone());
+      data_manager.edit_photographer_rights(photographer.id, rights.clone());
+      data_manager.edit_photographer_contact(photographer.id, contact.clone());
+      photographer.rights = rights;
+      photographer.contact = contact;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Added photographer",
+        &photographer,
+        &photographer.display_name(),
+        photographer.id,
+      )
+    }
+    PhotographerAction::Edit {
+      photographer,
+      name,
+      email,
+      no_email,
+      copyright,
+      no_copyright,
+      license,
+      no_license,
+      usage_terms,
+      no_usage_terms,
+      contact,
+    } => {
+      let photographer = data_manager.find_photographer(photographer)?.clone();
+      let update = |value: &Option<String>, remove: bool, current: Option<String>| {
+        if remove {
+          None
+        } else {
+          value.clone().or(current)
+        }
+      };
+      let email = update(email, *no_email, photographer.email);
+      let current = photographer.rights;
+      let rights = Rights {
+        copyright_template: update(copyright, *no_copyright, current.copyright_template),
+        license: update(license, *no_license, current.license),
+        usage_terms: update(usage_terms, *no_usage_terms, current.usage_terms),
+      };
+      rights.validate()?;
+      let contact = update_contact(contact, photographer.contact);
+      if !data_manager.edit_photographer(
+        photographer.id,
+        name.clone().unwrap_or(photographer.name),
+        email,
+      ) || !data_manager.edit_photographer_rights(photographer.id, rights)
+        || !data_manager.edit_photographer_contact(photographer.id, contact)
+      {
+        return Err("Photographer not found".into());
+      }
+      data_manager.save()?;
+      let photographer = data_manager
+        .get_photographer_by_id(photographer.id)
+        .ok_or("Photographer not found")?;
+      print_change(
+        args.json,
+        "Updated photographer",
+        photographer,
+        &photographer.display_name(),
+        photographer.id,
+      )
+    }
+    PhotographerAction::Delete { photographer } => {
+      let photographer = data_manager.find_photographer(photographer)?.clone();
+      data_manager.delete_photographer(photographer.id)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Deleted photographer",
+        &photographer,
+        &photographer.display_name(),
+        photographer.id,
+      )
+    }
+  }
+}
+
+/// Runs a `setup` subcommand.
+pub fn run_setup(args: &SetupArgs) -> Result<()> {
+  let mut data_manager = DataManager::new()?;
+
+  match &args.action {
+    SetupAction::List => print_list(
+      args.json,
+      "setups",
+      data_manager.get_setups(),
+      &["ID", "Name", "Camera", "Lens"],
+      |s| {
+        vec![
+          s.id.to_string(),
+          s.name.clone(),
+          data_manager
+            .get_camera_by_id(s.camera_id)
+            .map(Camera::display_name)
+            .unwrap_or_default(),
+          s.lens_id
+            .and_then(|id| data_manager.get_lens_by_id(id))
+            .map_or_else(|| "(camera only)".to_string(), Lens::display_name),
+        ]
+      },
+    ),
+    SetupAction::Add { name, camera, lens } => {
+      let camera_id = data_manager.find_camera(camera)?.id;
+      let lens_id = match lens {
+        Some(lens) => Some(data_manager.find_lens(lens)?.id),
+        None => None,
+      };
+      let setup = data_manager.add_setup(name.clone(), camera_id, lens_id)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Added setup",
+        &setup,
+        &setup.display_name(),
+        setup.id,
+      )
+    }
+    SetupAction::Edit {
+      setup,
+      name,
+      camera,
+      lens,
+      no_lens,
+    } => {
+      let setup = data_manager.find_setup(setup)?.clone();
+      let camera_id = match camera {
+        Some(camera) => data_manager.find_camera(camera)?.id,
+        None => setup.camera_id,
+      };
+      let lens_id = match lens {
+        Some(lens) => Some(data_manager.find_lens(lens)?.id),
+        None if *no_lens => None,
+        None => setup.lens_id,
+      };
+      if !data_manager.edit_setup(
+        setup.id,
+        name.clone().unwrap_or(setup.name),
+        camera_id,
+        lens_id,
+      )? {
+        return Err("Setup not found".into());
+      }
+      data_manager.save()?;
+      let setup = data_manager
+        .get_setup_by_id(setup.id)
+        .ok_or("Setup not found")?;
+      print_change(
+        args.json,
+        "Updated setup",
+        setup,
+        &setup.display_name(),
+        setup.id,
+      )
+    }
+    SetupAction::Delete { setup } => {
+      let setup = data_manager.find_setup(setup)?.clone();
+      data_manager.delete_setup(setup.id)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Deleted setup",
+        &setup,
+        &setup.display_name(),
+        setup.id,
+      )
+    }
+  }
+}
+
+/// Runs a `roll` subcommand.
+pub fn run_roll(args: &RollArgs) -> Result<()> {
+  let mut data_manager = DataManager::new()?;
+
+  match &args.action {
+    RollAction::List => {
+      let date = |date: Option<chrono::NaiveDate>| date.map(|d| d.to_string()).unwrap_or_default();
+      print_list(
+        args.json,
+        "rolls",
+        data_manager.get_rolls(),
+        &[
+          "ID", "Name", "Film", "Setup", "ISO", "Loaded", "Finished", "Frames", "Lab",
+        ],
+        |r| {
+          let film = data_manager.get_film_by_id(r.film_id);
+          vec![
+            r.id.to_string(),
+            r.name.clone(),
+            film.map(Film::display_name).unwrap_or_default(),
+            data_manager
+              .get_setup_by_id(r.setup_id)
+              .map(Setup::display_name)
+              .unwrap_or_default(),
+            r.shot_iso
+              .or_else(|| film.map(|f| f.iso))
+              .map(|iso| iso.to_string())
+              .unwrap_or_default(),
+            date(r.loaded_on),
+            date(r.finished_on),
+            r.frame_count.map(|n| n.to_string()).unwrap_or_default(),
+            r.development.lab.clone().unwrap_or_default(),
+          ]
+        },
+      )
+    }
+    RollAction::Add {
+      name,
+      film,
+      setup,
+      photographer,
+      iso,
+      details,
+    } => {
+      let mut roll = Roll::new(
+        name.clone(),
+        data_manager.find_film(film)?.id,
+        data_manager.find_setup(setup)?.id,
+        data_manager.find_photographer(photographer)?.id,
+      );
+      roll.shot_iso = *iso;
+      let roll = data_manager.add_roll(update_roll_details(details, roll)?)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Added roll",
+        &roll,
+        &roll.display_name(),
+        roll.id,
+      )
+    }
+    RollAction::Edit {
+      roll,
+      name,
+      film,
+      setup,
+      photographer,
+      iso,
+      box_speed,
+      details,
+    } => {
+      let mut roll = data_manager.find_roll(roll)?.clone();
+      if let Some(name) = name {
+        roll.name.clone_from(name);
+      }
+      if let Some(film) = film {
+        roll.film_id = data_manager.find_film(film)?.id;
+      }
+      if let Some(setup) = setup {
+        roll.setup_id = data_manager.find_setup(setup)?.id;
+      }
+      if let Some(photographer) = photographer {
+        roll.photographer_id = data_manager.find_photographer(photographer)?.id;
+      }
+      if iso.is_some() || *box_speed {
+        roll.shot_iso = *iso;
+      }
+      let roll = update_roll_details(details, roll)?;
+      if !data_manager.edit_roll(roll.clone())? {
+        return Err("Roll not found".into());
+      }
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Updated roll",
+        &roll,
+        &roll.display_name(),
+        roll.id,
+      )
+    }
+    RollAction::Delete { roll } => {
+      let roll = data_manager.find_roll(roll)?.clone();
+      data_manager.delete_roll(roll.id);
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Deleted roll",
+        &roll,
+        &roll.display_name(),
+        roll.id,
+      )
+    }
+  }
+}
+
+/// Runs a `location` subcommand.
+pub fn run_location(args: &LocationArgs) -> Result<()> {
+  let mut data_manager = DataManager::new()?;
+
+  match &args.action {
+    LocationAction::List => print_list(
+      args.json,
+      "locations",
+      data_manager.get_locations(),
+      &["ID", "Name", "Position", "City", "Country"],
+      |l| {
+        vec![
+          l.id.to_string(),
+          l.name.clone(),
+          l.position().display_name(),
+          l.city.clone().unwrap_or_default(),
+          l.country.clone().unwrap_or_default(),
+        ]
+      },
+    ),
+    LocationAction::Add {
+      name,
+      latitude,
+      longitude,
+      details,
+    } => {
+      let location = Location::new(name.clone(), *latitude, *longitude);
+      let location = data_manager.add_location(update_location_details(details, location)?)?;
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Added location",
+        &location,
+        &location.display_name(),
+        location.id,
+      )
+    }
+    LocationAction::Edit {
+      location,
+      name,
+      latitude,
+      longitude,
+      details,
+    } => {
+      let mut location = data_manager.find_location(location)?.clone();
+      if let Some(name) = name {
+        location.name.clone_from(name);
+      }
+      location.latitude = latitude.unwrap_or(location.latitude);
+      location.longitude = longitude.unwrap_or(location.longitude);
+      let location = update_location_details(details, location)?;
+      if !data_manager.edit_location(location.clone())? {
+        return Err("Location not found".into());
+      }
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Updated location",
+        &location,
+        &location.display_name(),
+        location.id,
+      )
+    }
+    LocationAction::Delete { location } => {
+      let location = data_manager.find_location(location)?.clone();
+      data_manager.delete_location(location.id);
+      data_manager.save()?;
+      print_change(
+        args.json,
+        "Deleted location",
+        &location,
+        &location.display_name(),
+        location.id,
+      )
+    }
+  }
+}
+
+/// Applies the altitude and address given on the command line to
+/// `location`.
+///
+/// Fields that are not given are kept, and fields given as empty are removed.
+/// Fails if the altitude is not a number.
+fn update_location_details(args: &LocationDetailsArgs, mut location: Location) -> Result<Location> {
+  let update = |value: &Option<String>, current: Option<String>| match value.as_deref() {
+    Some(value) if value.trim().is_empty() => None,
+    Some(value) => Some(value.trim().to_string()),
+    None => current,
+  };
+  match args.altitude.as_deref().map(str::trim) {
+    Some("") => location.altitude = None,
+    Some(altitude) => {
+      location.altitude = Some(
+        altitude
+          .parse()
+          .map_err(|_| format!("Invalid altitude \"{altitude}\""))?,
+      );
+    }
+    None => {}
+  }
+  location.sublocation = update(&args.sublocation, location.sublocation);
+  location.city = update(&args.city, location.city);
+  location.state = update(&args.state, location.state);
+  location.country = update(&args.country, location.country);
+  location.country_code =
+    update(&args.country_code, location.country_code).map(|code| code.to_ascii_uppercase());
+  Ok(location)
+}
+
+/// Applies the dates, frame count, lab and notes given on the command line
+/// to `roll`.
+///
+/// Fields that are not given are kept, and fields given as empty are removed.
+fn update_roll_details(args: &RollDetailsArgs, mut roll: Roll) -> Result<Roll> {
+  let update = |value: &Option<String>, current: Option<String>| match value.as_deref() {
+    Some(value) if value.trim().is_empty() => None,
+    Some(value) => Some(value.trim().to_string()),
+    None => current,
+  };
+  if let Some(loaded) = &args.loaded {
+    roll.loaded_on = Roll::parse_date(loaded)?;
+  }
+  if let Some(finished) = &args.finished {
+    roll.finished_on = Roll::parse_date(finished)?;
+  }
+  if args.frames.is_some() {
+    roll.frame_count = args.frames;
+  }
+  roll.notes = update(&args.notes, roll.notes);
+  roll.development = update_development(&args.development, roll.development)?;
+  Ok(roll)
+}
+
+/// Applies the development details given on the command line to `current`.
+///
+/// Fields that are not given are kept, and fields given as empty are removed.
+/// Fails if the time, temperature or scan date cannot be parsed.
+pub fn update_development(args: &DevelopmentArgs, current: Development) -> Result<Development> {
+  fn update<T>(
+    value: Option<&String>,
+    current: Option<T>,
+    parse: impl Fn(&str) -> std::result::Result<T, String>,
+  ) -> std::result::Result<Option<T>, String> {
+    match value.map(|value| value.trim()) {
+      Some("") => Ok(None),
+      Some(value) => parse(value).map(Some),
+      None => Ok(current),
+    }
+  }
+  let text = |value: &str| Ok(value.to_string());
+  Ok(Development {
+    developer: update(args.developer.as_ref(), current.developer, text)?,
+    dilution: update(args.dilution.as_ref(), current.dilution, text)?,
+    time: update(
+      args.dev_time.as_ref(),
+      current.time,
+      Development::parse_time,
+    )?,
+    temperature: update(
+      args.dev_temp.as_ref(),
+      current.temperature,
+      Development::parse_temperature,
+    )?,
+    lab: update(args.lab.as_ref(), current.lab, text)?,
+    scanned_on: update(args.scanned.as_ref(), current.scanned_on, |value| {
+      Roll::parse_date(value)?.ok_or_else(String::new)
+    })?,
+  })
+}
+
+/// Applies the contact details given on the command line to `current`.
+///
+/// Fields that are not given are kept, and fields given as empty are removed.
+fn update_contact(args: &ContactArgs, current: Contact) -> Contact {
+  let update = |value: &Option<String>, current: Option<String>| match value.as_deref() {
+    Some(value) if value.trim().is_empty() => None,
+    Some(value) => Some(value.trim().to_string()),
+    None => current,
+  };
+  Contact {
+    address: update(&args.address, current.address),
+    city: update(&args.city, current.city),
+    region: update(&args.region, current.region),
+    postal_code: update(&args.postal_code, current.postal_code),
+    country: update(&args.country, current.country),
+    phone: update(&args.phone, current.phone),
+    website: update(&args.website, current.website),
+  }
+}
+
+/// Prints a collection as a JSON array or as a table with one row per item.
+fn print_list<T: Serialize>(
+  json: bool,
+  kind: &str,
+  items: &[T],
+  headers: &[&str],
+  row: impl Fn(&T) -> Vec<String>,
+) -> Result<()> {
+  if json {
+    println!("{}", serde_json::to_string_pretty(items)?);
+  } else if items.is_empty() {
+    println!("{}", format!("No {kind} found.").yellow());
+  } else {
+    let rows: Vec<Vec<String>> = items.iter().map(row).collect();
+    PromptUtils::display_table(headers, &rows);
+  }
+  Ok(())
+}
+
+/// Prints an added, updated or deleted item as JSON or as a one-line summary.
+fn print_change<T: Serialize>(
+  json: bool,
+  action: &str,
+  item: &T,
+  name: &str,
+  id: Uuid,
+) -> Result<()> {
+  if json {
+    println!("{}", serde_json::to_string_pretty(item)?);
+  } else {
+    println!("{}", format!("✅ {action}: {name} ({id})").green());
+  }
+  Ok(())
+}
@@ -0,0 +1,204 @@
+//! Tag-level comparison of existing and planned EXIF metadata.
+//!
+//! This module powers dry runs: it compares the tags IFEX would write for a
+//! selection with the tags currently reported for a file, without touching
+//! the file itself.
+
+use crate::exif::tags::ExifTags;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Tags owned by IFEX, as named by `ExifTags::create_exif_object_with_iso`
+/// and as reported by `ExifManager::read_exif_data`.
+const OWNED_TAGS: &[(&str, &str)] = &[
+  ("Make", "Make"),
+  ("Model", "Model"),
+  ("Artist", "Artist"),
+  ("Copyright", "Copyright"),
+  ("Film", "Film"),
+  ("ISOSpeedRatings", "ISO Speed"),
+  ("LensMake", "Lens Make"),
+  ("LensModel", "Lens Model"),
+  ("LensSpecification", "Lens Specification"),
+  ("FocalLength", "Focal Length"),
+  ("ExposureTime", "Exposure Time"),
+  ("FNumber", "F-Number"),
+  ("ExposureBiasValue", "Exposure Bias Value"),
+  ("MeteringMode", "Metering Mode"),
+  ("ExposureProgram", "Exposure Program"),
+  ("ImageUniqueID", "Image Unique ID"),
+  ("UserComment", "User Comment"),
+];
+
+/// Owned tags that are only written when their value is known, such as the
+/// exposure settings, the roll and the development summary, so that a tag
+/// missing from the planned metadata is kept rather than removed.
+const OPTIONAL_TAGS: &[&str] = &[
+  "ExposureTime",
+  "FNumber",
+  "ExposureBiasValue",
+  "MeteringMode",
+  "ExposureProgram",
+  "ImageUniqueID",
+  "UserComment",
+];
+
+/// How a single tag would change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+  /// The tag is not present yet and would be written
+  Added,
+  /// The tag is present and would be overwritten with a different value
+  Changed,
+  /// The tag is present and would be removed
+  Removed,
+}
+
+/// A planned change to one EXIF tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagChange {
+  /// EXIF tag name
+  pub tag: String,
+  /// Kind of change
+  pub kind: ChangeKind,
+  /// Current value, if the tag is present
+  pub old: Option<String>,
+  /// Value that would be written, if any
+  pub new: Option<String>,
+}
+
+/// Computes tag differences between existing and planned metadata.
+pub struct ExifDiff;
+
+impl ExifDiff {
+  /// Compares the tags read from a file with the tags IFEX would write.
+  ///
+  /// Only tags owned by IFEX are considered; an owned tag that is present but
+  /// missing from `planned` (such as lens tags for a camera-only setup) is
+  /// reported as removed, unless it is only written when known, like the
+  /// exposure settings. A development summary IFEX wrote earlier is reported
+  /// as removed when there is no new one. Numeric values are compared by value, so a stored
+  /// rational of "35000/1000" matches a planned "35".
+  #[must_use]
+  pub fn between(
+    current: &[(String, String)],
+    planned: &HashMap<String, String>,
+  ) -> Vec<TagChange> {
+    OWNED_TAGS
+      .iter()
+      .filter_map(|&(tag, display_name)| {
+        let old = current
+          .iter()
+          .find(|(key, _)| key == display_name || key.starts_with(&format!("{display_name} [")))
+          .map(|(_, value)| Self::normalize(value));
+        let new = planned.get(tag).cloned();
+        let kind = match (&old, &new) {
+          (None, Some(_)) => ChangeKind::Added,
+          (Some(_), None) if !OPTIONAL_TAGS.contains(&tag) => ChangeKind::Removed,
+          (Some(old), None) if tag == "UserComment" && ExifTags::is_development_comment(old) => {
+            ChangeKind::Removed
+          }
+          (Some(old), Some(new)) if !Self::same_value(old, new) => ChangeKind::Changed,
+          _ => return None,
+        };
+        Some(TagChange {
+          tag: tag.to_string(),
+          kind,
+          old,
+          new,
+        })
+      })
+      .collect()
+  }
+
+  /// Compares the tags read from a file with tags that would be written
+  /// over them, such as the GPS tags of a position.
+  ///
+  /// Unlike `between`, `planned` is keyed by the names reported for the file,
+  /// and no tag is ever removed. Degrees, minutes and seconds read from the
+  /// file match the decimal values planned for them.
+  #[must_use]
+  pub fn updates(current: &[(String, String)], planned: &[(String, String)]) -> Vec<TagChange> {
+    planned
+      .iter()
+      .filter_map(|(tag, new)| {
+        let old = current
+          .iter()
+          .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+          .map(|(_, value)| Self::normalize(value));
+        let kind = match &old {
+          None => ChangeKind::Added,
+          Some(old) if !Self::same_value(old, new) && !Self::same_reading(old, new) => {
+            ChangeKind::Changed
+          }
+          Some(_) => return None,
+        };
+        Some(TagChange {
+          tag: tag.clone(),
+          kind,
+          old,
+          new: Some(new.clone()),
+        })
+      })
+      .collect()
+  }
+
+  /// Renders displayed rationals such as "35000/1000" as decimals, including
+  /// each element of a comma-separated list.
+  fn normalize(value: &str) -> String {
+    value
+      .split(", ")
+      .map(Self::normalize_one)
+      .collect::<Vec<_>>()
+      .join(", ")
+  }
+
+  /// Renders a single displayed rational as a decimal.
+  fn normalize_one(value: &str) -> String {
+    value
+      .split_once('/')
+      .and_then(|(num, denom)| {
+        Some((
+          num.trim().parse::<f64>().ok()?,
+          denom.trim().parse::<f64>().ok()?,
+        ))
+      })
+      .filter(|&(_, denom)| denom != 0.0)
+      .map_or_else(
+        || value.to_string(),
+        |(num, denom)| (num / denom).to_string(),
+      )
+  }
+
+  /// Compares a list of degrees (or hours), minutes and seconds, such as
+  /// "38, 43, 3", with a described value such as "38.717500 N", "65.00 m" or
+  /// "2024-08-01 10:15:45 UTC", by the first number or time it contains.
+  fn same_reading(old: &str, new: &str) -> bool {
+    let new = new
+      .split_whitespace()
+      .find_map(|token| Self::sexagesimal(token.split(':')));
+    Self::sexagesimal(old.split(", "))
+      .zip(new)
+      .is_some_and(|(old, new)| (old - new).abs() < 1e-6)
+  }
+
+  /// Adds up one to three numbers as units, sixtieths and 3600ths.
+  fn sexagesimal<'a>(parts: impl Iterator<Item = &'a str>) -> Option<f64> {
+    let mut total = 0.0;
+    let mut scale = 1.0;
+    for part in parts {
+      total += part.trim().parse::<f64>().ok()? / scale;
+      scale *= 60.0;
+    }
+    (scale > 1.0 && scale <= 216_000.0).then_some(total)
+  }
+
+  /// Compares two values numerically when both are numbers, textually otherwise.
+  fn same_value(old: &str, new: &str) -> bool {
+    match (old.parse::<f64>(), new.parse::<f64>()) {
+      (Ok(old), Ok(new)) => (old - new).abs() < 1e-6,
+      _ => old == new,
+    }
+  }
+}
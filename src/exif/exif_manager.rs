@@ -4,11 +4,17 @@
 //! from image files. It handles batch processing of directories, file type detection,
 //! and coordination with the appropriate file type processors.
 
+use crate::exif::diff::{ExifDiff, TagChange};
 use crate::exif::file_types::FileType;
-use crate::exif::processors::{JpegProcessor, RawProcessor, TiffProcessor};
-use crate::models::Selection;
+use crate::exif::processors::{DngProcessor, JpegProcessor, RawProcessor, TiffProcessor};
+use crate::exif::tags::{ExifTags, TagGroup};
+use crate::gpx::Track;
+use crate::journal::{BackupMode, Journal};
+use crate::models::{Frame, GpsPosition, Selection};
 use crate::utils::{get_file_type, is_supported_image_format};
-use chrono::{DateTime, Local, NaiveDateTime};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
@@ -16,7 +22,7 @@ use walkdir::WalkDir;
 ///
 /// Contains overall success status, descriptive message, and detailed
 /// statistics about the processing results.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ProcessingResult {
   /// Whether the overall operation succeeded
   pub success: bool,
@@ -30,7 +36,8 @@ pub struct ProcessingResult {
 ///
 /// Tracks the number of successfully processed files, failed files,
 /// and detailed results for each individual file.
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ProcessingStats {
   /// Number of files successfully processed
   pub processed: usize,
@@ -38,13 +45,17 @@ pub struct ProcessingStats {
   pub failed: usize,
   /// Detailed results for each file that was processed
   pub files: Vec<FileResult>,
+  /// ID of the journal run holding backups of the original files, if any
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub run_id: Option<String>,
 }
 
 /// Result information for a single file processing operation.
 ///
 /// Contains the file name, success status, detected file type,
 /// and any error message if processing failed.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FileResult {
   /// Name of the processed file
   pub name: String,
@@ -54,13 +65,20 @@ pub struct FileResult {
   pub file_type: Option<String>,
   /// Error message if processing failed
   pub error: Option<String>,
+  /// Tag changes that would be made, reported in dry-run mode only
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub changes: Option<Vec<TagChange>>,
 }
 
 /// Main EXIF processing manager.
 ///
 /// Provides methods for batch processing of image files, handling both
 /// EXIF application and erasure operations across supported file formats.
-pub struct ExifManager;
+pub struct ExifManager {
+  dry_run: bool,
+  backup_mode: BackupMode,
+  frames: HashMap<PathBuf, Frame>,
+}
 
 impl Default for ExifManager {
   fn default() -> Self {
@@ -71,8 +89,46 @@ impl Default for ExifManager {
 impl ExifManager {
   /// Creates a new `ExifManager` instance.
   #[must_use]
-  pub const fn new() -> Self {
-    Self
+  pub fn new() -> Self {
+    Self {
+      dry_run: false,
+      backup_mode: BackupMode::Off,
+      frames: HashMap::new(),
+    }
+  }
+
+  /// Enables or disables dry-run mode.
+  ///
+  /// In dry-run mode no file is modified: applying a selection reports the
+  /// tag changes it would make for each file instead, and date adjustment is
+  /// skipped.
+  #[must_use]
+  pub const fn with_dry_run(mut self, dry_run: bool) -> Self {
+    self.dry_run = dry_run;
+    self
+  }
+
+  /// Sets how original files are backed up before a batch run modifies them.
+  ///
+  /// Backups are off by default. When enabled, every batch run records the
+  /// originals in the journal under the config directory so that it can be
+  /// reverted with `Journal::undo`; the run ID is reported in
+  /// `ProcessingStats::run_id`.
+  #[must_use]
+  pub const fn with_backups(mut self, backup_mode: BackupMode) -> Self {
+    self.backup_mode = backup_mode;
+    self
+  }
+
+  /// Sets the frame values of individual files, such as those of a shot log.
+  ///
+  /// When applying, the values of a file's frame take precedence over those
+  /// of the selection (see `Frame::apply_to`); files without a frame get the
+  /// selection as is.
+  #[must_use]
+  pub fn with_frames(mut self, frames: HashMap<PathBuf, Frame>) -> Self {
+    self.frames = frames;
+    self
   }
 
   /// Checks if all files have the same creation date
@@ -101,28 +157,39 @@ impl ExifManager {
     Ok(
       creation_dates
         .iter()
-        .all(|&date| (date - first_date).abs() <= chrono::Duration::seconds(1)),
+        .all(|&date| (date - first_date).abs() <= Duration::seconds(1)),
     )
   }
 
   /// Gets the creation date from EXIF data
-  fn get_creation_date(
+  ///
+  /// Uses `DateTimeOriginal`, then `DateTime`, then `DateTimeDigitized`.
+  /// Camera clocks record local time without a time zone, so the date is
+  /// taken to be in the local time zone.
+  pub fn get_creation_date(
     &self,
     file_path: &Path,
   ) -> Result<DateTime<Local>, Box<dyn std::error::Error>> {
     let exif_data = Self::read_exif_data(file_path)?;
 
-    // Look for DateTimeOriginal first, then DateTime, then DateTimeDigitized
-    for (tag_name, value) in &exif_data {
-      if tag_name == "Date/Time Original"
-        || tag_name == "Date/Time"
-        || tag_name == "Date/Time Digitized"
-      {
-        if let Ok(naive_dt) = NaiveDateTime::parse_from_str(value, "%Y:%m:%d %H:%M:%S") {
-          return Ok(DateTime::from_naive_utc_and_offset(
-            naive_dt,
-            *Local::now().offset(),
-          ));
+    // Sidecar and EXIF dates take precedence over the ones embedded in RAW
+    // files and over the XMP packet of JPEG files
+    let embedded = format!(" ({})", RawProcessor::EMBEDDED_SOURCE);
+    let xmp = format!(" ({})", JpegProcessor::XMP_SOURCE);
+    for suffix in ["", embedded.as_str(), xmp.as_str()] {
+      for name in ["Date/Time Original", "Date/Time", "Date/Time Digitized"] {
+        for (tag_name, value) in &exif_data {
+          // Ignore the raw tag info appended to the names of EXIF fields
+          let tag_name = tag_name.split(" [").next().unwrap_or(tag_name);
+          if tag_name.strip_suffix(suffix) != Some(name) {
+            continue;
+          }
+          let date = NaiveDateTime::parse_from_str(value, "%Y:%m:%d %H:%M:%S")
+            .ok()
+            .and_then(|naive_dt| naive_dt.and_local_timezone(Local).earliest());
+          if let Some(date) = date {
+            return Ok(date);
+          }
         }
       }
     }
@@ -145,7 +212,11 @@ impl ExifManager {
   }
 
   /// Adjusts creation dates with 1-second increments
-  fn adjust_creation_dates(&self, files: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+  fn adjust_creation_dates(
+    &self,
+    files: &[PathBuf],
+    mut journal: Option<&mut Journal>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
     if files.is_empty() {
       return Ok(());
     }
@@ -168,8 +239,10 @@ impl ExifManager {
       }
 
       // Add 1 second for each subsequent file
-      let new_date = base_date + chrono::Duration::seconds(i64::try_from(index).unwrap_or(0));
-      self.set_creation_date(file_path, new_date)?;
+      let new_date = base_date + Duration::seconds(i64::try_from(index).unwrap_or(0));
+      Self::backed_up(journal.as_deref_mut(), file_path, || {
+        self.set_creation_date(file_path, new_date)
+      })?;
     }
 
     Ok(())
@@ -190,7 +263,7 @@ impl ExifManager {
     match file_type {
       FileType::Jpeg => JpegProcessor::set_creation_date(file_path, &date_string),
       FileType::Tiff => TiffProcessor::set_creation_date(file_path, &date_string),
-      FileType::Dng => TiffProcessor::set_creation_date(file_path, &date_string),
+      FileType::Dng => DngProcessor::set_creation_date(file_path, &date_string),
       FileType::Raw => RawProcessor::set_creation_date(file_path, &date_string),
     }
   }
@@ -199,17 +272,18 @@ impl ExifManager {
   fn handle_date_adjustment(
     &self,
     file_paths: &[PathBuf],
+    journal: Option<&mut Journal>,
   ) -> Result<(), Box<dyn std::error::Error>> {
     let has_identical_dates = self.check_identical_dates(file_paths)?;
 
     if has_identical_dates {
       println!("All photos have the same creation date. Adjusting with 1-second increments...");
-      self.adjust_creation_dates(file_paths)?;
+      self.adjust_creation_dates(file_paths, journal)?;
       println!("✅ Creation dates adjusted successfully!");
     } else {
       // Ask user if they want to set identical dates for different timestamps
       if self.prompt_set_identical_dates()? {
-        self.adjust_creation_dates(file_paths)?;
+        self.adjust_creation_dates(file_paths, journal)?;
         println!("✅ Creation dates set with 1-second increments!");
       }
     }
@@ -222,6 +296,7 @@ impl ExifManager {
     &self,
     file_paths: &[PathBuf],
     one_sec: bool,
+    journal: Option<&mut Journal>,
   ) -> Result<(), Box<dyn std::error::Error>> {
     if !one_sec {
       return Ok(());
@@ -231,12 +306,12 @@ impl ExifManager {
 
     if has_identical_dates {
       println!("All photos have the same creation date. Adjusting with 1-second increments...");
-      self.adjust_creation_dates(file_paths)?;
+      self.adjust_creation_dates(file_paths, journal)?;
       println!("✅ Creation dates adjusted successfully!");
     } else {
       // Ask user if they want to set identical dates for different timestamps
       if self.prompt_set_identical_dates()? {
-        self.adjust_creation_dates(file_paths)?;
+        self.adjust_creation_dates(file_paths, journal)?;
         println!("✅ Creation dates set with 1-second increments!");
       }
     }
@@ -273,54 +348,13 @@ impl ExifManager {
     operation: &str,
     shot_iso: Option<u32>,
   ) -> ProcessingResult {
-    let mut stats = ProcessingStats {
-      processed: 0,
-      failed: 0,
-      files: Vec::new(),
-    };
-
-    let walker = WalkDir::new(folder_path);
-
-    for entry in walker {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(folder_path) {
       match entry {
         Ok(entry) => {
           let path = entry.path();
-
           if path.is_file() && is_supported_image_format(path) {
-            let file_name = path
-              .file_name()
-              .unwrap_or_default()
-              .to_string_lossy()
-              .to_string();
-
-            let file_type = get_file_type(path);
-
-            let result = match operation {
-              "apply" => self.apply_exif_with_iso(path, selection.unwrap(), shot_iso),
-              "erase" => self.erase_exif(path),
-              _ => Err("Unknown operation".into()),
-            };
-
-            match result {
-              Ok(()) => {
-                stats.processed += 1;
-                stats.files.push(FileResult {
-                  name: file_name,
-                  success: true,
-                  file_type,
-                  error: None,
-                });
-              }
-              Err(e) => {
-                stats.failed += 1;
-                stats.files.push(FileResult {
-                  name: file_name,
-                  success: false,
-                  file_type,
-                  error: Some(e.to_string()),
-                });
-              }
-            }
+            files.push(path.to_path_buf());
           }
         }
         Err(e) => {
@@ -329,19 +363,15 @@ impl ExifManager {
       }
     }
 
-    if stats.processed > 0 || stats.failed > 0 {
-      ProcessingResult {
-        success: true,
-        message: "Processing completed".to_string(),
-        results: stats,
-      }
-    } else {
-      ProcessingResult {
-        success: false,
-        message: "No supported image files found".to_string(),
-        results: stats,
+    self.journaled(operation, |journal| {
+      let mut result = Self::process_files_with(&files, journal, |path| {
+        self.run_operation(path, selection, operation, shot_iso, &[TagGroup::All])
+      });
+      if files.is_empty() {
+        result.message = "No supported image files found".to_string();
       }
-    }
+      result
+    })
   }
 
   /// Processes a specific list of selected files with optional custom shot ISO.
@@ -360,14 +390,23 @@ impl ExifManager {
     operation: &str,
     shot_iso: Option<u32>,
   ) -> ProcessingResult {
-    // Handle date adjustment logic before processing EXIF
-    if operation == "apply" && file_paths.len() > 1 {
-      if let Err(e) = self.handle_date_adjustment(file_paths) {
-        eprintln!("Warning: Failed to adjust creation dates: {e}");
+    self.journaled(operation, |mut journal| {
+      // Handle date adjustment logic before processing EXIF
+      if operation == "apply" && !self.dry_run && file_paths.len() > 1 {
+        if let Err(e) = self.handle_date_adjustment(file_paths, journal.as_deref_mut()) {
+          eprintln!("Warning: Failed to adjust creation dates: {e}");
+        }
       }
-    }
 
-    self.process_files_internal(file_paths, selection, operation, shot_iso)
+      self.process_files_internal(
+        file_paths,
+        journal,
+        selection,
+        operation,
+        shot_iso,
+        &[TagGroup::All],
+      )
+    })
   }
 
   /// Processes a specific list of selected files with optional custom shot ISO and --one-sec flag.
@@ -387,29 +426,196 @@ impl ExifManager {
     shot_iso: Option<u32>,
     one_sec: bool,
   ) -> ProcessingResult {
-    // Handle date adjustment logic before processing EXIF only if --one-sec is enabled
-    if operation == "apply" && file_paths.len() > 1 {
-      if let Err(e) = self.handle_date_adjustment_with_one_sec(file_paths, one_sec) {
-        eprintln!("Warning: Failed to adjust creation dates: {e}");
+    self.journaled(operation, |mut journal| {
+      // Handle date adjustment logic before processing EXIF only if --one-sec is enabled
+      if operation == "apply" && !self.dry_run && file_paths.len() > 1 {
+        if let Err(e) =
+          self.handle_date_adjustment_with_one_sec(file_paths, one_sec, journal.as_deref_mut())
+        {
+          eprintln!("Warning: Failed to adjust creation dates: {e}");
+        }
       }
+
+      self.process_files_internal(
+        file_paths,
+        journal,
+        selection,
+        operation,
+        shot_iso,
+        &[TagGroup::All],
+      )
+    })
+  }
+
+  /// Erases the given groups of metadata from a specific list of files.
+  ///
+  /// Use `TagGroup::All` to remove all metadata, as the "erase" operation of
+  /// `process_selected_files` does.
+  ///
+  /// Returns a `ProcessingResult` with statistics and detailed results for each file.
+  #[must_use]
+  pub fn erase_selected_files(
+    &self,
+    file_paths: &[PathBuf],
+    groups: &[TagGroup],
+  ) -> ProcessingResult {
+    self.journaled("erase", |journal| {
+      self.process_files_internal(file_paths, journal, None, "erase", None, groups)
+    })
+  }
+
+  /// Tags a list of files with their positions on a GPX track.
+  ///
+  /// The capture time of each file (see `get_creation_date`) is converted to
+  /// UTC using `clock_offset`, the UTC offset the camera clock was set to,
+  /// or the local time zone if it is `None`. The position of the track at
+  /// that time (see `Track::position_at`) is then written as by
+  /// `set_position`. Files without a capture date or without a track
+  /// position within `max_gap` fail. In dry-run mode the GPS tags that would
+  /// be written are reported instead.
+  #[must_use]
+  pub fn geotag_files(
+    &self,
+    file_paths: &[PathBuf],
+    track: &Track,
+    clock_offset: Option<FixedOffset>,
+    max_gap: Duration,
+  ) -> ProcessingResult {
+    self.journaled("geotag", |journal| {
+      Self::process_files_with(file_paths, journal, |path| {
+        let taken = self.get_creation_date(path)?;
+        let taken = match clock_offset {
+          Some(offset) => taken
+            .naive_local()
+            .and_local_timezone(offset)
+            .single()
+            .ok_or("Invalid capture date")?
+            .with_timezone(&Utc),
+          None => taken.with_timezone(&Utc),
+        };
+        let position = track.position_at(taken, max_gap).ok_or_else(|| {
+          format!(
+            "No track position within {} s of {}",
+            max_gap.num_seconds(),
+            taken.format("%Y-%m-%d %H:%M:%S UTC")
+          )
+        })?;
+        if self.dry_run {
+          let current = Self::read_current(path)?;
+          let planned = ExifTags::create_gps_object(&position);
+          return Ok(Some(ExifDiff::updates(&current, &planned)));
+        }
+        self.set_position(path, &position).map(|()| None)
+      })
+    })
+  }
+
+  /// Writes a position into the GPS metadata of a single image file.
+  ///
+  /// Determines the file type and delegates to the appropriate processor.
+  pub fn set_position(
+    &self,
+    path: &Path,
+    position: &GpsPosition,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    let file_type = FileType::from_path(path)
+      .ok_or_else(|| format!("Unsupported file type: {}", path.display()))?;
+
+    match file_type {
+      FileType::Jpeg => JpegProcessor::set_position(path, position),
+      FileType::Tiff => TiffProcessor::set_position(path, position),
+      FileType::Dng => DngProcessor::set_position(path, position),
+      FileType::Raw => RawProcessor::set_position(path, position),
+    }
+  }
+
+  /// Runs a batch operation in a new journal run.
+  ///
+  /// `run` gets the journal to back up each file it writes with (see
+  /// `backed_up`), or `None` when backups are off and in dry-run mode. The run
+  /// is aborted before writing anything if the journal cannot be started.
+  fn journaled(
+    &self,
+    operation: &str,
+    run: impl FnOnce(Option<&mut Journal>) -> ProcessingResult,
+  ) -> ProcessingResult {
+    if self.dry_run || self.backup_mode == BackupMode::Off {
+      return run(None);
+    }
+
+    let mut journal = match Journal::default_root()
+      .and_then(|root| Journal::start(&root, operation, self.backup_mode))
+    {
+      Ok(journal) => journal,
+      Err(e) => return Self::backup_failure(e.as_ref()),
+    };
+
+    let mut result = run(Some(&mut journal));
+    match journal.finish() {
+      Ok(run_id) => result.results.run_id = run_id,
+      Err(e) => eprintln!("Warning: Failed to record backups: {e}"),
     }
+    result
+  }
 
-    self.process_files_internal(file_paths, selection, operation, shot_iso)
+  /// Writes a single file, backing up its original in the journal first.
+  ///
+  /// The changes are recorded in the journal right after `write`, so the
+  /// files written so far can be undone even if the batch is interrupted.
+  /// The file is not written if its original cannot be backed up.
+  fn backed_up<T>(
+    journal: Option<&mut Journal>,
+    path: &Path,
+    write: impl FnOnce() -> Result<T, Box<dyn std::error::Error>>,
+  ) -> Result<T, Box<dyn std::error::Error>> {
+    let Some(journal) = journal else {
+      return write();
+    };
+    journal
+      .capture(path)
+      .map_err(|e| format!("Could not back up the original file: {e}"))?;
+    let result = write();
+    if let Err(e) = journal.record() {
+      eprintln!("Warning: Failed to record backups: {e}");
+    }
+    result
+  }
+
+  /// Builds the result of a batch run aborted because backups failed.
+  fn backup_failure(error: &dyn std::error::Error) -> ProcessingResult {
+    ProcessingResult {
+      success: false,
+      message: format!("Could not back up original files: {error}"),
+      results: ProcessingStats::default(),
+    }
   }
 
   /// Internal method to process files without date adjustment logic
   fn process_files_internal(
     &self,
     file_paths: &[PathBuf],
+    journal: Option<&mut Journal>,
     selection: Option<&Selection>,
     operation: &str,
     shot_iso: Option<u32>,
+    erase_groups: &[TagGroup],
   ) -> ProcessingResult {
-    let mut stats = ProcessingStats {
-      processed: 0,
-      failed: 0,
-      files: Vec::new(),
-    };
+    Self::process_files_with(file_paths, journal, |path| {
+      self.run_operation(path, selection, operation, shot_iso, erase_groups)
+    })
+  }
+
+  /// Runs `operation` on each supported file and collects the results.
+  ///
+  /// Each file is backed up in `journal` around its own operation (see
+  /// `backed_up`). Paths that do not exist or are not supported images are
+  /// reported as failures.
+  fn process_files_with(
+    file_paths: &[PathBuf],
+    mut journal: Option<&mut Journal>,
+    operation: impl Fn(&Path) -> Result<Option<Vec<TagChange>>, Box<dyn std::error::Error>>,
+  ) -> ProcessingResult {
+    let mut stats = ProcessingStats::default();
 
     for file_path in file_paths {
       if file_path.is_file() && is_supported_image_format(file_path) {
@@ -421,20 +627,17 @@ impl ExifManager {
 
         let file_type = get_file_type(file_path);
 
-        let result = match operation {
-          "apply" => self.apply_exif_with_iso(file_path, selection.unwrap(), shot_iso),
-          "erase" => self.erase_exif(file_path),
-          _ => Err("Unknown operation".into()),
-        };
+        let result = Self::backed_up(journal.as_deref_mut(), file_path, || operation(file_path));
 
         match result {
-          Ok(()) => {
+          Ok(changes) => {
             stats.processed += 1;
             stats.files.push(FileResult {
               name: file_name,
               success: true,
               file_type,
               error: None,
+              changes,
             });
           }
           Err(e) => {
@@ -444,9 +647,25 @@ impl ExifManager {
               success: false,
               file_type,
               error: Some(e.to_string()),
+              changes: None,
             });
           }
         }
+      } else {
+        // Paths given explicitly by the caller are reported rather than silently skipped
+        let error = if file_path.exists() {
+          "Unsupported file format"
+        } else {
+          "File does not exist"
+        };
+        stats.failed += 1;
+        stats.files.push(FileResult {
+          name: file_path.display().to_string(),
+          success: false,
+          file_type: None,
+          error: Some(error.to_string()),
+          changes: None,
+        });
       }
     }
 
@@ -465,6 +684,101 @@ impl ExifManager {
     }
   }
 
+  /// Runs the requested operation on a single file.
+  ///
+  /// Returns the planned tag changes when applying in dry-run mode, and
+  /// `None` once an operation has actually been carried out.
+  fn run_operation(
+    &self,
+    path: &Path,
+    selection: Option<&Selection>,
+    operation: &str,
+    shot_iso: Option<u32>,
+    erase_groups: &[TagGroup],
+  ) -> Result<Option<Vec<TagChange>>, Box<dyn std::error::Error>> {
+    let file_selection = selection
+      .zip(self.frames.get(path))
+      .map(|(selection, frame)| frame.apply_to(selection));
+    let selection = file_selection.as_ref().or(selection);
+    match (operation, selection) {
+      ("apply", Some(selection)) if self.dry_run => {
+        Self::preview_exif_with_iso(path, selection, shot_iso).map(Some)
+      }
+      ("apply", Some(selection)) => self
+        .apply_exif_with_iso(path, selection, shot_iso)
+        .map(|()| None),
+      ("apply", None) => Err("No equipment selection to apply".into()),
+      ("erase", _) if self.dry_run => Err("Dry run is only supported when applying".into()),
+      ("erase", _) => self.erase_exif(path, erase_groups).map(|()| None),
+      _ => Err("Unknown operation".into()),
+    }
+  }
+
+  /// Computes the tag changes applying a selection would make, without writing.
+  ///
+  /// Compares `ExifTags::create_exif_object_with_iso`, plus the copyright
+  /// notice for the capture year and the GPS tags of a saved location, with
+  /// the metadata currently reported by `read_current`. A file without any
+  /// EXIF data is treated as empty; any other read error is returned.
+  pub fn preview_exif_with_iso(
+    path: &Path,
+    selection: &Selection,
+    shot_iso: Option<u32>,
+  ) -> Result<Vec<TagChange>, Box<dyn std::error::Error>> {
+    let current = match Self::read_current(path) {
+      Ok(current) => current,
+      Err(e) if matches!(e.downcast_ref(), Some(exif::Error::NotFound(_))) => Vec::new(),
+      Err(e) => return Err(e),
+    };
+    let mut planned = ExifTags::create_exif_object_with_iso(selection, shot_iso);
+    let capture_year = ["Date/Time Original", "Date/Time Digitized", "Date/Time"]
+      .iter()
+      .find_map(|name| {
+        current
+          .iter()
+          .filter(|(key, _)| key == name || key.starts_with(&format!("{name} ")))
+          .find_map(|(_, value)| ExifTags::year_of(value))
+      });
+    let year = capture_year.unwrap_or_else(|| Local::now().year());
+    planned.insert(
+      "Copyright".to_string(),
+      selection.photographer.copyright_notice(year),
+    );
+    let mut changes = ExifDiff::between(&current, &planned);
+    if let Some(location) = &selection.saved_location {
+      changes.extend(ExifDiff::updates(
+        &current,
+        &ExifTags::create_gps_object(&location.position()),
+      ));
+    }
+    Ok(changes)
+  }
+
+  /// Reads the metadata a dry run compares the planned tags with.
+  ///
+  /// This is what `read_exif_data` reports, except that DNG files are edited
+  /// in place, so the fields embedded in them are what an apply overwrites
+  /// and they are compared under their plain names. Sidecar fields, which
+  /// sort first, still take precedence over them.
+  fn read_current(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let current = Self::read_exif_data(path)?;
+    if FileType::from_path(path) != Some(FileType::Dng) {
+      return Ok(current);
+    }
+    let source = RawProcessor::EMBEDDED_SOURCE;
+    Ok(
+      current
+        .into_iter()
+        .map(|(name, value)| {
+          let name = name
+            .replace(&format!(" ({source})"), "")
+            .replace(&format!(" ({source} "), " (");
+          (name, value)
+        })
+        .collect(),
+    )
+  }
+
   /// Applies EXIF metadata to a single image file.
   ///
   /// Determines the file type and delegates to the appropriate processor
@@ -476,7 +790,7 @@ impl ExifManager {
     selection: &Selection,
   ) -> Result<(), Box<dyn std::error::Error>> {
     use crate::exif::file_types::FileType;
-    use crate::exif::processors::{JpegProcessor, RawProcessor, TiffProcessor};
+    use crate::exif::processors::{DngProcessor, JpegProcessor, RawProcessor, TiffProcessor};
 
     let file_type = FileType::from_path(path)
       .ok_or_else(|| format!("Unsupported file type: {}", path.display()))?;
@@ -484,7 +798,7 @@ impl ExifManager {
     match file_type {
       FileType::Jpeg => JpegProcessor::apply_exif(path, selection),
       FileType::Tiff => TiffProcessor::apply_exif(path, selection),
-      FileType::Dng => TiffProcessor::apply_exif(path, selection),
+      FileType::Dng => DngProcessor::apply_exif(path, selection),
       FileType::Raw => RawProcessor::apply_exif(path, selection),
     }
   }
@@ -500,7 +814,7 @@ impl ExifManager {
     shot_iso: Option<u32>,
   ) -> Result<(), Box<dyn std::error::Error>> {
     use crate::exif::file_types::FileType;
-    use crate::exif::processors::{JpegProcessor, RawProcessor, TiffProcessor};
+    use crate::exif::processors::{DngProcessor, JpegProcessor, RawProcessor, TiffProcessor};
 
     let file_type = FileType::from_path(path)
       .ok_or_else(|| format!("Unsupported file type: {}", path.display()))?;
@@ -508,7 +822,7 @@ impl ExifManager {
     match file_type {
       FileType::Jpeg => JpegProcessor::apply_exif_with_iso(path, selection, shot_iso),
       FileType::Tiff => TiffProcessor::apply_exif_with_iso(path, selection, shot_iso),
-      FileType::Dng => TiffProcessor::apply_exif_with_iso(path, selection, shot_iso),
+      FileType::Dng => DngProcessor::apply_exif_with_iso(path, selection, shot_iso),
       FileType::Raw => RawProcessor::apply_exif_with_iso(path, selection, shot_iso),
     }
   }
@@ -516,19 +830,19 @@ impl ExifManager {
   /// Erases EXIF metadata from a single image file.
   ///
   /// Determines the file type and delegates to the appropriate processor
-  /// to remove all EXIF metadata from the file.
-  fn erase_exif(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+  /// to remove the given groups of metadata from the file.
+  fn erase_exif(&self, path: &Path, groups: &[TagGroup]) -> Result<(), Box<dyn std::error::Error>> {
     use crate::exif::file_types::FileType;
-    use crate::exif::processors::{JpegProcessor, RawProcessor, TiffProcessor};
+    use crate::exif::processors::{DngProcessor, JpegProcessor, RawProcessor, TiffProcessor};
 
     let file_type = FileType::from_path(path)
       .ok_or_else(|| format!("Unsupported file type: {}", path.display()))?;
 
     match file_type {
-      FileType::Jpeg => JpegProcessor::erase_exif(path),
-      FileType::Tiff => TiffProcessor::erase_exif(path),
-      FileType::Dng => TiffProcessor::erase_exif(path),
-      FileType::Raw => RawProcessor::erase_exif(path),
+      FileType::Jpeg => JpegProcessor::erase_exif_groups(path, groups),
+      FileType::Tiff => TiffProcessor::erase_exif_groups(path, groups),
+      FileType::Dng => DngProcessor::erase_exif_groups(path, groups),
+      FileType::Raw => RawProcessor::erase_exif_groups(path, groups),
     }
   }
 
@@ -540,7 +854,7 @@ impl ExifManager {
   /// Returns a vector of (`tag_name`, value) tuples sorted by tag name.
   pub fn read_exif_data(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
     use crate::exif::file_types::FileType;
-    use crate::exif::processors::{JpegProcessor, RawProcessor, TiffProcessor};
+    use crate::exif::processors::{DngProcessor, JpegProcessor, RawProcessor, TiffProcessor};
 
     let file_type = FileType::from_path(path)
       .ok_or_else(|| format!("Unsupported file type: {}", path.display()))?;
@@ -548,7 +862,7 @@ impl ExifManager {
     match file_type {
       FileType::Jpeg => JpegProcessor::read_exif(path),
       FileType::Tiff => TiffProcessor::read_exif(path),
-      FileType::Dng => TiffProcessor::read_exif(path),
+      FileType::Dng => DngProcessor::read_exif(path),
       FileType::Raw => RawProcessor::read_exif(path),
     }
   }
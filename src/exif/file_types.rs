@@ -59,7 +59,8 @@ impl FileType {
 
   /// Checks if the file type is a DNG file.
   ///
-  /// DNG files require special handling as they are Adobe's digital negative format.
+  /// DNG files require special handling as they are Adobe's digital negative format:
+  /// their tags are edited in place, with an XMP sidecar as fallback.
   #[must_use]
   pub const fn supports_dng_processing(&self) -> bool {
     matches!(self, Self::Dng)
@@ -0,0 +1,1009 @@
+//! Typed in-memory model of TIFF/EXIF image file directories.
+//!
+//! This module parses the IFD structure shared by TIFF files and the EXIF payload
+//! of JPEG APP1 segments into an [`IfdTree`], lets callers edit individual tags
+//! through typed [`ExifValue`]s, and serializes the tree back in either byte order.
+
+use std::collections::BTreeMap;
+
+/// Identifier preceding the TIFF structure in a JPEG APP1 EXIF segment.
+pub const EXIF_HEADER: &[u8; 6] = b"Exif\0\0";
+
+/// Largest payload a JPEG APP1 segment can hold (the length field counts itself).
+pub const MAX_APP1_PAYLOAD: usize = u16::MAX as usize - 2;
+
+/// Well-known tag numbers used by the IFEX writers.
+pub mod tag {
+  /// GPS tag version, 2.3.0.0 (GPS IFD, BYTE x4)
+  pub const GPS_VERSION_ID: u16 = 0x0000;
+  /// "N" or "S" for the hemisphere of the latitude (GPS IFD, ASCII)
+  pub const GPS_LATITUDE_REF: u16 = 0x0001;
+  /// Latitude as degrees, minutes and seconds (GPS IFD, RATIONAL x3)
+  pub const GPS_LATITUDE: u16 = 0x0002;
+  /// "E" or "W" for the hemisphere of the longitude (GPS IFD, ASCII)
+  pub const GPS_LONGITUDE_REF: u16 = 0x0003;
+  /// Longitude as degrees, minutes and seconds (GPS IFD, RATIONAL x3)
+  pub const GPS_LONGITUDE: u16 = 0x0004;
+  /// 0 above sea level, 1 below (GPS IFD, BYTE)
+  pub const GPS_ALTITUDE_REF: u16 = 0x0005;
+  /// Altitude in metres (GPS IFD, RATIONAL)
+  pub const GPS_ALTITUDE: u16 = 0x0006;
+  /// UTC time as hours, minutes and seconds (GPS IFD, RATIONAL x3)
+  pub const GPS_TIME_STAMP: u16 = 0x0007;
+  /// UTC date as "YYYY:MM:DD" (GPS IFD, ASCII)
+  pub const GPS_DATE_STAMP: u16 = 0x001D;
+  /// Image description (IFD0, ASCII)
+  pub const IMAGE_DESCRIPTION: u16 = 0x010E;
+  /// Camera manufacturer (IFD0, ASCII)
+  pub const MAKE: u16 = 0x010F;
+  /// Camera model (IFD0, ASCII)
+  pub const MODEL: u16 = 0x0110;
+  /// Offsets of the image strips (IFD0, SHORT/LONG)
+  pub const STRIP_OFFSETS: u16 = 0x0111;
+  /// Image orientation (IFD0, SHORT)
+  pub const ORIENTATION: u16 = 0x0112;
+  /// Software used to create the image (IFD0, ASCII)
+  pub const SOFTWARE: u16 = 0x0131;
+  /// File change date and time (IFD0, ASCII)
+  pub const DATE_TIME: u16 = 0x0132;
+  /// Photographer name (IFD0, ASCII)
+  pub const ARTIST: u16 = 0x013B;
+  /// Offsets of child IFDs such as DNG raw data (IFD0, LONG/IFD)
+  pub const SUB_IFDS: u16 = 0x014A;
+  /// Offset of the JPEG thumbnail (IFD1, LONG)
+  pub const JPEG_INTERCHANGE_FORMAT: u16 = 0x0201;
+  /// Length of the JPEG thumbnail (IFD1, LONG)
+  pub const JPEG_INTERCHANGE_FORMAT_LENGTH: u16 = 0x0202;
+  /// Film stock information (IFD0, ASCII)
+  pub const FILM: u16 = 0x0289;
+  /// Copyright notice (IFD0, ASCII)
+  pub const COPYRIGHT: u16 = 0x8298;
+  /// Pointer to the Exif sub-IFD (IFD0, LONG)
+  pub const EXIF_IFD_POINTER: u16 = 0x8769;
+  /// Pointer to the GPS IFD (IFD0, LONG)
+  pub const GPS_IFD_POINTER: u16 = 0x8825;
+  /// Exposure time in seconds (Exif IFD, RATIONAL)
+  pub const EXPOSURE_TIME: u16 = 0x829A;
+  /// Lens f-number (Exif IFD, RATIONAL)
+  pub const F_NUMBER: u16 = 0x829D;
+  /// Exposure program (Exif IFD, SHORT)
+  pub const EXPOSURE_PROGRAM: u16 = 0x8822;
+  /// ISO speed (Exif IFD, SHORT)
+  pub const ISO_SPEED_RATINGS: u16 = 0x8827;
+  /// Exif version (Exif IFD, UNDEFINED)
+  pub const EXIF_VERSION: u16 = 0x9000;
+  /// Original capture date and time (Exif IFD, ASCII)
+  pub const DATE_TIME_ORIGINAL: u16 = 0x9003;
+  /// Digitization date and time (Exif IFD, ASCII)
+  pub const DATE_TIME_DIGITIZED: u16 = 0x9004;
+  /// Time zone offset of `DateTime` (Exif IFD, ASCII)
+  pub const OFFSET_TIME: u16 = 0x9010;
+  /// Time zone offset of `DateTimeOriginal` (Exif IFD, ASCII)
+  pub const OFFSET_TIME_ORIGINAL: u16 = 0x9011;
+  /// Time zone offset of `DateTimeDigitized` (Exif IFD, ASCII)
+  pub const OFFSET_TIME_DIGITIZED: u16 = 0x9012;
+  /// Exposure compensation in EV (Exif IFD, SRATIONAL)
+  pub const EXPOSURE_BIAS_VALUE: u16 = 0x9204;
+  /// Widest aperture of the lens in APEX units (Exif IFD, RATIONAL)
+  pub const MAX_APERTURE_VALUE: u16 = 0x9205;
+  /// Metering mode (Exif IFD, SHORT)
+  pub const METERING_MODE: u16 = 0x9207;
+  /// Lens focal length (Exif IFD, RATIONAL)
+  pub const FOCAL_LENGTH: u16 = 0x920A;
+  /// Manufacturer specific data (Exif IFD, UNDEFINED)
+  pub const MAKER_NOTE: u16 = 0x927C;
+  /// Comment prefixed with an 8-byte character code, where IFEX writes the
+  /// development summary (Exif IFD, UNDEFINED)
+  pub const USER_COMMENT: u16 = 0x9286;
+  /// Fractional seconds of `DateTime` (Exif IFD, ASCII)
+  pub const SUB_SEC_TIME: u16 = 0x9290;
+  /// Fractional seconds of `DateTimeOriginal` (Exif IFD, ASCII)
+  pub const SUB_SEC_TIME_ORIGINAL: u16 = 0x9291;
+  /// Fractional seconds of `DateTimeDigitized` (Exif IFD, ASCII)
+  pub const SUB_SEC_TIME_DIGITIZED: u16 = 0x9292;
+  /// Author in UCS-2, as written by Windows (IFD0, BYTE)
+  pub const XP_AUTHOR: u16 = 0x9C9D;
+  /// Pointer to the interoperability IFD (Exif IFD, LONG)
+  pub const INTEROP_IFD_POINTER: u16 = 0xA005;
+  /// Identifier of the image, where IFEX writes the roll (Exif IFD, ASCII)
+  pub const IMAGE_UNIQUE_ID: u16 = 0xA420;
+  /// 35mm-equivalent focal length (Exif IFD, SHORT)
+  pub const FOCAL_LENGTH_IN_35MM_FILM: u16 = 0xA405;
+  /// Camera owner (Exif IFD, ASCII)
+  pub const CAMERA_OWNER_NAME: u16 = 0xA430;
+  /// Camera body serial number (Exif IFD, ASCII)
+  pub const BODY_SERIAL_NUMBER: u16 = 0xA431;
+  /// Focal length and aperture range of the lens (Exif IFD, RATIONAL x4)
+  pub const LENS_SPECIFICATION: u16 = 0xA432;
+  /// Lens manufacturer (Exif IFD, ASCII)
+  pub const LENS_MAKE: u16 = 0xA433;
+  /// Lens model (Exif IFD, ASCII)
+  pub const LENS_MODEL: u16 = 0xA434;
+  /// Lens serial number (Exif IFD, ASCII)
+  pub const LENS_SERIAL_NUMBER: u16 = 0xA435;
+  /// DNG version (IFD0, BYTE)
+  pub const DNG_VERSION: u16 = 0xC612;
+  /// Vendor private data such as relocated maker notes (IFD0, BYTE)
+  pub const DNG_PRIVATE_DATA: u16 = 0xC634;
+}
+
+/// Byte order of a TIFF structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+  /// Intel byte order ("II")
+  LittleEndian,
+  /// Motorola byte order ("MM")
+  BigEndian,
+}
+
+impl ByteOrder {
+  fn u16(self, bytes: &[u8]) -> u16 {
+    let raw = [bytes[0], bytes[1]];
+    match self {
+      Self::LittleEndian => u16::from_le_bytes(raw),
+      Self::BigEndian => u16::from_be_bytes(raw),
+    }
+  }
+
+  fn u32(self, bytes: &[u8]) -> u32 {
+    let raw = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    match self {
+      Self::LittleEndian => u32::from_le_bytes(raw),
+      Self::BigEndian => u32::from_be_bytes(raw),
+    }
+  }
+
+  fn u64(self, bytes: &[u8]) -> u64 {
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(&bytes[..8]);
+    match self {
+      Self::LittleEndian => u64::from_le_bytes(raw),
+      Self::BigEndian => u64::from_be_bytes(raw),
+    }
+  }
+
+  fn put_u16(self, out: &mut Vec<u8>, value: u16) {
+    match self {
+      Self::LittleEndian => out.extend_from_slice(&value.to_le_bytes()),
+      Self::BigEndian => out.extend_from_slice(&value.to_be_bytes()),
+    }
+  }
+
+  fn put_u32(self, out: &mut Vec<u8>, value: u32) {
+    match self {
+      Self::LittleEndian => out.extend_from_slice(&value.to_le_bytes()),
+      Self::BigEndian => out.extend_from_slice(&value.to_be_bytes()),
+    }
+  }
+
+  fn put_u64(self, out: &mut Vec<u8>, value: u64) {
+    match self {
+      Self::LittleEndian => out.extend_from_slice(&value.to_le_bytes()),
+      Self::BigEndian => out.extend_from_slice(&value.to_be_bytes()),
+    }
+  }
+}
+
+/// A typed TIFF field value.
+///
+/// Every TIFF 6.0 field type is represented, plus the IFD type used by
+/// `SubIFDs`. Types the reader does not recognise are kept verbatim.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExifValue {
+  /// Type 1: unsigned 8-bit integers
+  Byte(Vec<u8>),
+  /// Type 2: 7-bit ASCII bytes, including the terminating NUL
+  Ascii(Vec<u8>),
+  /// Type 3: unsigned 16-bit integers
+  Short(Vec<u16>),
+  /// Type 4: unsigned 32-bit integers
+  Long(Vec<u32>),
+  /// Type 5: unsigned rationals as (numerator, denominator)
+  Rational(Vec<(u32, u32)>),
+  /// Type 6: signed 8-bit integers
+  SByte(Vec<i8>),
+  /// Type 7: opaque bytes
+  Undefined(Vec<u8>),
+  /// Type 8: signed 16-bit integers
+  SShort(Vec<i16>),
+  /// Type 9: signed 32-bit integers
+  SLong(Vec<i32>),
+  /// Type 10: signed rationals as (numerator, denominator)
+  SRational(Vec<(i32, i32)>),
+  /// Type 11: single precision floats
+  Float(Vec<f32>),
+  /// Type 12: double precision floats
+  Double(Vec<f64>),
+  /// Type 13: offsets of child IFDs
+  Ifd(Vec<u32>),
+  /// Any other field type, kept as its raw 4-byte value field
+  Unknown {
+    /// Raw field type
+    field_type: u16,
+    /// Raw value count
+    count: u32,
+    /// The 4-byte value/offset field as stored in the file
+    raw: [u8; 4],
+  },
+}
+
+impl ExifValue {
+  /// Creates an ASCII value from a string, appending the NUL terminator.
+  #[must_use]
+  pub fn ascii(text: &str) -> Self {
+    let mut bytes = text.as_bytes().to_vec();
+    bytes.push(0);
+    Self::Ascii(bytes)
+  }
+
+  /// Returns the ASCII value as a string with NUL terminators removed.
+  #[must_use]
+  pub fn as_ascii(&self) -> Option<String> {
+    match self {
+      Self::Ascii(bytes) => Some(
+        String::from_utf8_lossy(bytes)
+          .trim_end_matches('\0')
+          .to_string(),
+      ),
+      _ => None,
+    }
+  }
+
+  /// Returns the first element as an unsigned integer for SHORT, LONG and IFD values.
+  #[must_use]
+  pub fn first_u32(&self) -> Option<u32> {
+    match self {
+      Self::Short(values) => values.first().map(|&v| u32::from(v)),
+      Self::Long(values) | Self::Ifd(values) => values.first().copied(),
+      _ => None,
+    }
+  }
+
+  /// Returns the TIFF field type number.
+  #[must_use]
+  pub const fn field_type(&self) -> u16 {
+    match self {
+      Self::Byte(_) => 1,
+      Self::Ascii(_) => 2,
+      Self::Short(_) => 3,
+      Self::Long(_) => 4,
+      Self::Rational(_) => 5,
+      Self::SByte(_) => 6,
+      Self::Undefined(_) => 7,
+      Self::SShort(_) => 8,
+      Self::SLong(_) => 9,
+      Self::SRational(_) => 10,
+      Self::Float(_) => 11,
+      Self::Double(_) => 12,
+      Self::Ifd(_) => 13,
+      Self::Unknown { field_type, .. } => *field_type,
+    }
+  }
+
+  /// Returns the number of elements as stored in the IFD entry count.
+  #[must_use]
+  pub fn count(&self) -> u32 {
+    let count = match self {
+      Self::Byte(v) | Self::Ascii(v) | Self::Undefined(v) => v.len(),
+      Self::Short(v) => v.len(),
+      Self::Long(v) | Self::Ifd(v) => v.len(),
+      Self::Rational(v) => v.len(),
+      Self::SByte(v) => v.len(),
+      Self::SShort(v) => v.len(),
+      Self::SLong(v) => v.len(),
+      Self::SRational(v) => v.len(),
+      Self::Float(v) => v.len(),
+      Self::Double(v) => v.len(),
+      Self::Unknown { count, .. } => return *count,
+    };
+    u32::try_from(count).unwrap_or(u32::MAX)
+  }
+
+  /// Size in bytes of one element of the given field type, if known.
+  const fn element_size(field_type: u16) -> Option<usize> {
+    match field_type {
+      1 | 2 | 6 | 7 => Some(1),
+      3 | 8 => Some(2),
+      4 | 9 | 11 | 13 => Some(4),
+      5 | 10 | 12 => Some(8),
+      _ => None,
+    }
+  }
+
+  /// Decodes a value of the given type from its raw bytes.
+  fn decode(field_type: u16, count: u32, bytes: &[u8], order: ByteOrder) -> Self {
+    match field_type {
+      1 => Self::Byte(bytes.to_vec()),
+      2 => Self::Ascii(bytes.to_vec()),
+      3 => Self::Short(bytes.chunks_exact(2).map(|c| order.u16(c)).collect()),
+      4 => Self::Long(bytes.chunks_exact(4).map(|c| order.u32(c)).collect()),
+      5 => Self::Rational(
+        bytes
+          .chunks_exact(8)
+          .map(|c| (order.u32(c), order.u32(&c[4..])))
+          .collect(),
+      ),
+      6 => Self::SByte(bytes.iter().map(|&b| i8::from_ne_bytes([b])).collect()),
+      7 => Self::Undefined(bytes.to_vec()),
+      8 => Self::SShort(
+        bytes
+          .chunks_exact(2)
+          .map(|c| i16::from_ne_bytes(order.u16(c).to_ne_bytes()))
+          .collect(),
+      ),
+      9 => Self::SLong(
+        bytes
+          .chunks_exact(4)
+          .map(|c| i32::from_ne_bytes(order.u32(c).to_ne_bytes()))
+          .collect(),
+      ),
+      10 => Self::SRational(
+        bytes
+          .chunks_exact(8)
+          .map(|c| {
+            (
+              i32::from_ne_bytes(order.u32(c).to_ne_bytes()),
+              i32::from_ne_bytes(order.u32(&c[4..]).to_ne_bytes()),
+            )
+          })
+          .collect(),
+      ),
+      11 => Self::Float(
+        bytes
+          .chunks_exact(4)
+          .map(|c| f32::from_bits(order.u32(c)))
+          .collect(),
+      ),
+      12 => Self::Double(
+        bytes
+          .chunks_exact(8)
+          .map(|c| f64::from_bits(order.u64(c)))
+          .collect(),
+      ),
+      13 => Self::Ifd(bytes.chunks_exact(4).map(|c| order.u32(c)).collect()),
+      _ => {
+        let mut raw = [0u8; 4];
+        raw.copy_from_slice(&bytes[..4]);
+        Self::Unknown {
+          field_type,
+          count,
+          raw,
+        }
+      }
+    }
+  }
+
+  /// Encodes the value into its raw bytes in the given byte order.
+  #[must_use]
+  pub fn encode(&self, order: ByteOrder) -> Vec<u8> {
+    let mut out = Vec::new();
+    match self {
+      Self::Byte(v) | Self::Ascii(v) | Self::Undefined(v) => out.extend_from_slice(v),
+      Self::Short(v) => v.iter().for_each(|&x| order.put_u16(&mut out, x)),
+      Self::Long(v) | Self::Ifd(v) => v.iter().for_each(|&x| order.put_u32(&mut out, x)),
+      Self::Rational(v) => v.iter().for_each(|&(n, d)| {
+        order.put_u32(&mut out, n);
+        order.put_u32(&mut out, d);
+      }),
+      Self::SByte(v) => out.extend(v.iter().map(|&x| x.to_ne_bytes()[0])),
+      Self::SShort(v) => v
+        .iter()
+        .for_each(|&x| order.put_u16(&mut out, u16::from_ne_bytes(x.to_ne_bytes()))),
+      Self::SLong(v) => v
+        .iter()
+        .for_each(|&x| order.put_u32(&mut out, u32::from_ne_bytes(x.to_ne_bytes()))),
+      Self::SRational(v) => v.iter().for_each(|&(n, d)| {
+        order.put_u32(&mut out, u32::from_ne_bytes(n.to_ne_bytes()));
+        order.put_u32(&mut out, u32::from_ne_bytes(d.to_ne_bytes()));
+      }),
+      Self::Float(v) => v.iter().for_each(|&x| order.put_u32(&mut out, x.to_bits())),
+      Self::Double(v) => v.iter().for_each(|&x| order.put_u64(&mut out, x.to_bits())),
+      Self::Unknown { raw, .. } => out.extend_from_slice(raw),
+    }
+    out
+  }
+}
+
+/// A single image file directory.
+///
+/// Entries are kept sorted by tag number. For directories read from a file,
+/// the original offset of every out-of-line value is remembered so that an
+/// unchanged value can keep pointing at its original bytes when the file is
+/// rewritten in append mode.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ifd {
+  entries: BTreeMap<u16, ExifValue>,
+  origins: BTreeMap<u16, u32>,
+  /// Offset the directory was read from, if any
+  offset: Option<u32>,
+}
+
+impl Ifd {
+  /// Creates an empty directory.
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the value stored for a tag.
+  #[must_use]
+  pub fn get(&self, tag: u16) -> Option<&ExifValue> {
+    self.entries.get(&tag)
+  }
+
+  /// Inserts or replaces the value for a tag.
+  pub fn set(&mut self, tag: u16, value: ExifValue) {
+    self.origins.remove(&tag);
+    self.entries.insert(tag, value);
+  }
+
+  /// Removes a tag, returning its previous value.
+  pub fn remove(&mut self, tag: u16) -> Option<ExifValue> {
+    self.origins.remove(&tag);
+    self.entries.remove(&tag)
+  }
+
+  /// Returns true if the directory contains the tag.
+  #[must_use]
+  pub fn contains(&self, tag: u16) -> bool {
+    self.entries.contains_key(&tag)
+  }
+
+  /// Iterates over all entries in ascending tag order.
+  pub fn iter(&self) -> impl Iterator<Item = (u16, &ExifValue)> {
+    self.entries.iter().map(|(&tag, value)| (tag, value))
+  }
+
+  /// Returns the number of entries.
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns true if the directory has no entries.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Reads a directory at `offset`, returning it with its next-IFD pointer.
+  fn read(data: &[u8], offset: usize, order: ByteOrder) -> Result<(Self, u32), String> {
+    let count_end = offset.checked_add(2).ok_or("IFD offset overflow")?;
+    if count_end > data.len() {
+      return Err(format!("IFD at offset {offset} is out of bounds"));
+    }
+    let count = order.u16(&data[offset..]) as usize;
+    let entries_end = count_end + count * 12;
+    if entries_end + 4 > data.len() {
+      return Err(format!("Truncated IFD at offset {offset}"));
+    }
+
+    let mut ifd = Self {
+      offset: u32::try_from(offset).ok(),
+      ..Self::new()
+    };
+    for index in 0..count {
+      let entry = &data[count_end + index * 12..count_end + (index + 1) * 12];
+      let tag = order.u16(entry);
+      let field_type = order.u16(&entry[2..]);
+      let value_count = order.u32(&entry[4..]);
+      let field = &entry[8..12];
+
+      let value = match ExifValue::element_size(field_type) {
+        Some(size) => {
+          let total = size
+            .checked_mul(value_count as usize)
+            .ok_or_else(|| format!("Oversized value for tag {tag:#06x}"))?;
+          if total <= 4 {
+            ExifValue::decode(field_type, value_count, &field[..total], order)
+          } else {
+            let value_offset = order.u32(field);
+            let start = value_offset as usize;
+            let end = start
+              .checked_add(total)
+              .filter(|&end| end <= data.len())
+              .ok_or_else(|| format!("Value of tag {tag:#06x} is out of bounds"))?;
+            ifd.origins.insert(tag, value_offset);
+            ExifValue::decode(field_type, value_count, &data[start..end], order)
+          }
+        }
+        None => ExifValue::decode(field_type, value_count, field, order),
+      };
+      ifd.entries.insert(tag, value);
+    }
+
+    let next = order.u32(&data[entries_end..]);
+    Ok((ifd, next))
+  }
+
+  /// Number of bytes this directory occupies when written, including its value area.
+  fn written_size(&self, pointers: &BTreeMap<u16, u32>, keep: KeepOrigins) -> usize {
+    let tags: std::collections::BTreeSet<u16> = self
+      .entries
+      .keys()
+      .chain(pointers.keys())
+      .copied()
+      .collect();
+    let mut size = 2 + tags.len() * 12 + 4;
+    for (&tag, value) in &self.entries {
+      if pointers.contains_key(&tag) || self.kept_origin(tag, keep).is_some() {
+        continue;
+      }
+      let len = value.encode(ByteOrder::LittleEndian).len();
+      if len > 4 {
+        size += len + (len & 1);
+      }
+    }
+    size
+  }
+
+  /// Serializes the directory at `out.len()`, which must equal `base`.
+  ///
+  /// `pointers` are LONG entries that override or extend the stored entries,
+  /// used for sub-IFD and thumbnail offsets that are only known at write time.
+  fn write(
+    &self,
+    out: &mut Vec<u8>,
+    order: ByteOrder,
+    pointers: &BTreeMap<u16, u32>,
+    next: u32,
+    keep: KeepOrigins,
+  ) {
+    let base = out.len();
+    let mut merged: BTreeMap<u16, Option<&ExifValue>> = self
+      .entries
+      .iter()
+      .map(|(&tag, value)| (tag, Some(value)))
+      .collect();
+    for &tag in pointers.keys() {
+      merged.insert(tag, None);
+    }
+
+    let mut value_area = Vec::new();
+    let value_area_start = base + 2 + merged.len() * 12 + 4;
+
+    order.put_u16(out, u16::try_from(merged.len()).unwrap_or(u16::MAX));
+    for (&tag, value) in &merged {
+      order.put_u16(out, tag);
+      let Some(value) = value else {
+        order.put_u16(out, 4);
+        order.put_u32(out, 1);
+        order.put_u32(out, pointers[&tag]);
+        continue;
+      };
+
+      order.put_u16(out, value.field_type());
+      order.put_u32(out, value.count());
+
+      if let Some(origin) = self.kept_origin(tag, keep) {
+        order.put_u32(out, origin);
+        continue;
+      }
+
+      let mut bytes = value.encode(order);
+      if bytes.len() <= 4 {
+        bytes.resize(4, 0);
+        out.extend_from_slice(&bytes);
+      } else {
+        let offset = value_area_start + value_area.len();
+        order.put_u32(out, u32::try_from(offset).unwrap_or(u32::MAX));
+        value_area.extend_from_slice(&bytes);
+        if value_area.len() % 2 == 1 {
+          value_area.push(0);
+        }
+      }
+    }
+    order.put_u32(out, next);
+    out.extend_from_slice(&value_area);
+  }
+
+  /// Original offset the value of `tag` keeps when written with `keep`.
+  fn kept_origin(&self, tag: u16, keep: KeepOrigins) -> Option<u32> {
+    let keeps = match keep {
+      KeepOrigins::None => false,
+      KeepOrigins::MakerNote => tag == tag::MAKER_NOTE,
+      KeepOrigins::All => true,
+    };
+    self.origins.get(&tag).copied().filter(|_| keeps)
+  }
+}
+
+/// Which out-of-line values keep pointing at their original offset when a
+/// directory is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeepOrigins {
+  /// Every value is written into the new value area
+  None,
+  /// Only the maker note stays put; it is placed at its original offset
+  /// separately because its contents may hold absolute offsets into itself
+  MakerNote,
+  /// Every unchanged value stays where it is (append mode)
+  All,
+}
+
+/// The complete set of directories found in a TIFF structure.
+///
+/// IFD0 holds the primary image tags, with optional Exif, GPS and
+/// interoperability sub-IFDs and an optional IFD1 for the thumbnail.
+/// The pointer tags linking these directories are managed by the tree
+/// and never appear in the individual [`Ifd`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfdTree {
+  /// Byte order used when the tree is serialized
+  pub byte_order: ByteOrder,
+  /// Primary image directory
+  pub ifd0: Ifd,
+  /// Exif sub-IFD
+  pub exif: Option<Ifd>,
+  /// GPS sub-IFD
+  pub gps: Option<Ifd>,
+  /// Interoperability sub-IFD (child of the Exif IFD)
+  pub interop: Option<Ifd>,
+  /// Thumbnail directory
+  pub ifd1: Option<Ifd>,
+  /// JPEG thumbnail bytes referenced from IFD1
+  pub thumbnail: Option<Vec<u8>>,
+  /// Original offset of IFD1, kept so append mode leaves the IFD chain untouched
+  next_ifd: u32,
+}
+
+impl IfdTree {
+  /// Creates an empty little-endian tree.
+  #[must_use]
+  pub fn new() -> Self {
+    Self::with_byte_order(ByteOrder::LittleEndian)
+  }
+
+  /// Creates an empty tree using the given byte order.
+  #[must_use]
+  pub fn with_byte_order(byte_order: ByteOrder) -> Self {
+    Self {
+      byte_order,
+      ifd0: Ifd::new(),
+      exif: None,
+      gps: None,
+      interop: None,
+      ifd1: None,
+      thumbnail: None,
+      next_ifd: 0,
+    }
+  }
+
+  /// Returns the Exif sub-IFD, creating it if it does not exist yet.
+  pub fn exif_mut(&mut self) -> &mut Ifd {
+    self.exif.get_or_insert_with(Ifd::new)
+  }
+
+  /// Returns the GPS sub-IFD, creating it if it does not exist yet.
+  pub fn gps_mut(&mut self) -> &mut Ifd {
+    self.gps.get_or_insert_with(Ifd::new)
+  }
+
+  /// Parses a TIFF structure starting with an "II*\0" or "MM\0*" header.
+  ///
+  /// This is both the layout of a TIFF file and of the payload of an EXIF
+  /// APP1 segment after its "Exif\0\0" identifier.
+  pub fn parse(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+    if data.len() < 8 {
+      return Err("TIFF header is truncated".into());
+    }
+    let byte_order = match &data[0..2] {
+      b"II" => ByteOrder::LittleEndian,
+      b"MM" => ByteOrder::BigEndian,
+      _ => return Err("Invalid TIFF byte order marker".into()),
+    };
+    match byte_order.u16(&data[2..]) {
+      42 => {}
+      43 => return Err("BigTIFF files are not supported".into()),
+      _ => return Err("Invalid TIFF magic number".into()),
+    }
+
+    let ifd0_offset = byte_order.u32(&data[4..]) as usize;
+    let (mut ifd0, next_ifd) = Ifd::read(data, ifd0_offset, byte_order)?;
+
+    let exif = Self::read_child(data, &mut ifd0, tag::EXIF_IFD_POINTER, byte_order)?;
+    let gps = Self::read_child(data, &mut ifd0, tag::GPS_IFD_POINTER, byte_order)?;
+    let (exif, interop) = match exif {
+      Some(mut exif) => {
+        let interop = Self::read_child(data, &mut exif, tag::INTEROP_IFD_POINTER, byte_order)?;
+        (Some(exif), interop)
+      }
+      None => (None, None),
+    };
+
+    let ifd1 = if next_ifd == 0 {
+      None
+    } else {
+      Some(Ifd::read(data, next_ifd as usize, byte_order)?.0)
+    };
+    let thumbnail = ifd1.as_ref().and_then(|ifd1| {
+      let start = ifd1.get(tag::JPEG_INTERCHANGE_FORMAT)?.first_u32()? as usize;
+      let len = ifd1.get(tag::JPEG_INTERCHANGE_FORMAT_LENGTH)?.first_u32()? as usize;
+      data.get(start..start.checked_add(len)?).map(<[u8]>::to_vec)
+    });
+
+    Ok(Self {
+      byte_order,
+      ifd0,
+      exif,
+      gps,
+      interop,
+      ifd1,
+      thumbnail,
+      next_ifd,
+    })
+  }
+
+  /// Removes a pointer tag from `parent` and reads the directory it points to.
+  fn read_child(
+    data: &[u8],
+    parent: &mut Ifd,
+    pointer_tag: u16,
+    order: ByteOrder,
+  ) -> Result<Option<Ifd>, String> {
+    match parent.remove(pointer_tag).and_then(|v| v.first_u32()) {
+      Some(0) | None => Ok(None),
+      Some(offset) => Ifd::read(data, offset as usize, order).map(|(ifd, _)| Some(ifd)),
+    }
+  }
+
+  /// Serializes the tree into a self-contained TIFF structure.
+  ///
+  /// All values, including the thumbnail, are written into the returned buffer
+  /// with offsets relative to its start. This is the layout expected inside a
+  /// JPEG APP1 segment.
+  ///
+  /// A maker note read from existing data keeps its original offset, since
+  /// most vendors store absolute offsets inside it. The space in front of it
+  /// is zero-filled and the directories follow it.
+  #[must_use]
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut out = Vec::new();
+    match self.pinned_maker_note() {
+      Some((origin, maker_note)) => {
+        let end = origin as usize + maker_note.len();
+        let ifd0_offset = end + (end & 1);
+        self.write_header(&mut out, u32::try_from(ifd0_offset).unwrap_or(u32::MAX));
+        out.resize(origin as usize, 0);
+        out.extend_from_slice(&maker_note);
+        out.resize(ifd0_offset, 0);
+      }
+      None => self.write_header(&mut out, 8),
+    }
+    self.write_directories(&mut out, false);
+    out
+  }
+
+  /// Original offset and encoded bytes of a maker note that must stay put.
+  fn pinned_maker_note(&self) -> Option<(u32, Vec<u8>)> {
+    let exif = self.exif.as_ref()?;
+    let origin = *exif.origins.get(&tag::MAKER_NOTE)?;
+    let value = exif.entries.get(&tag::MAKER_NOTE)?;
+    let bytes = value.encode(self.byte_order);
+    (origin >= 8 && bytes.len() > 4).then_some((origin, bytes))
+  }
+
+  /// Serializes the tree into the payload of a JPEG APP1 segment.
+  ///
+  /// The result starts with the "Exif\0\0" identifier. If the data does not
+  /// fit into a single APP1 segment, the thumbnail directory is dropped first;
+  /// if it still does not fit, an error is returned.
+  pub fn to_app1_payload(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut payload = EXIF_HEADER.to_vec();
+    payload.extend_from_slice(&self.to_bytes());
+    if payload.len() <= MAX_APP1_PAYLOAD {
+      return Ok(payload);
+    }
+
+    if self.ifd1.is_some() {
+      let mut without_thumbnail = self.clone();
+      without_thumbnail.ifd1 = None;
+      without_thumbnail.thumbnail = None;
+      payload.truncate(EXIF_HEADER.len());
+      payload.extend_from_slice(&without_thumbnail.to_bytes());
+      if payload.len() <= MAX_APP1_PAYLOAD {
+        return Ok(payload);
+      }
+    }
+
+    Err(
+      format!(
+        "EXIF data is {} bytes, exceeding the {MAX_APP1_PAYLOAD} byte limit of a JPEG APP1 segment",
+        payload.len()
+      )
+      .into(),
+    )
+  }
+
+  /// Appends the modified directories to an existing TIFF file.
+  ///
+  /// The original bytes stay where they are, so image strips, tiles, child
+  /// IFDs, maker notes and any other data referenced by absolute offsets stay
+  /// valid. Unchanged out-of-line values keep pointing at their original
+  /// location; new directories are written at the end of the file and the
+  /// header is patched to point at the new IFD0. The superseded directories
+  /// and any values that were replaced or removed are zeroed. IFD1 and any
+  /// further pages are left in place.
+  pub fn append_to(&self, original: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let original_tree = Self::parse(original)?;
+    if original_tree.byte_order != self.byte_order {
+      return Err("Cannot change the byte order of an existing TIFF file".into());
+    }
+
+    let mut out = original.to_vec();
+    self.scrub_stale(&original_tree, &mut out);
+    if out.len() % 2 == 1 {
+      out.push(0);
+    }
+    let ifd0_offset = u32::try_from(out.len()).map_err(|_| "TIFF file exceeds 4GB")?;
+
+    let mut header = Vec::new();
+    self.write_header(&mut header, ifd0_offset);
+    out[..8].copy_from_slice(&header);
+
+    self.write_directories(&mut out, true);
+    if out.len() > u32::MAX as usize {
+      return Err("TIFF file exceeds 4GB".into());
+    }
+    Ok(out)
+  }
+
+  /// Zeroes the directories and out-of-line values of `original` that this
+  /// tree no longer references, so replaced or erased metadata does not
+  /// linger in the file after it has been rewritten in append mode.
+  fn scrub_stale(&self, original: &Self, out: &mut [u8]) {
+    let order = original.byte_order;
+    let kept: std::collections::BTreeSet<u32> = [
+      Some(&self.ifd0),
+      self.exif.as_ref(),
+      self.gps.as_ref(),
+      self.interop.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|ifd| ifd.origins.values().copied())
+    .collect();
+
+    let directories = [
+      Some(&original.ifd0),
+      original.exif.as_ref(),
+      original.gps.as_ref(),
+      original.interop.as_ref(),
+    ];
+    for ifd in directories.into_iter().flatten() {
+      for (tag, &origin) in &ifd.origins {
+        if kept.contains(&origin) {
+          continue;
+        }
+        let len = ifd.entries.get(tag).map_or(0, |v| v.encode(order).len());
+        if let Some(bytes) = out.get_mut(origin as usize..origin as usize + len) {
+          bytes.fill(0);
+        }
+      }
+      if let Some(start) = ifd.offset.map(|offset| offset as usize) {
+        let Some(count) = out
+          .get(start..start + 2)
+          .map(|bytes| order.u16(bytes) as usize)
+        else {
+          continue;
+        };
+        if let Some(bytes) = out.get_mut(start..start + 2 + count * 12 + 4) {
+          bytes.fill(0);
+        }
+      }
+    }
+  }
+
+  fn write_header(&self, out: &mut Vec<u8>, ifd0_offset: u32) {
+    match self.byte_order {
+      ByteOrder::LittleEndian => out.extend_from_slice(b"II"),
+      ByteOrder::BigEndian => out.extend_from_slice(b"MM"),
+    }
+    self.byte_order.put_u16(out, 42);
+    self.byte_order.put_u32(out, ifd0_offset);
+  }
+
+  /// Lays out and writes IFD0 and its children starting at `out.len()`.
+  ///
+  /// In append mode IFD1 is not rewritten; IFD0 keeps the original
+  /// next-IFD pointer instead.
+  fn write_directories(&self, out: &mut Vec<u8>, append: bool) {
+    let order = self.byte_order;
+    let empty = BTreeMap::new();
+    let offset = |position: usize| u32::try_from(position).unwrap_or(u32::MAX);
+    let keep = if append {
+      KeepOrigins::All
+    } else {
+      KeepOrigins::None
+    };
+    let keep_exif = match keep {
+      KeepOrigins::None if self.pinned_maker_note().is_some() => KeepOrigins::MakerNote,
+      other => other,
+    };
+
+    let mut ifd0_pointers = BTreeMap::new();
+    let mut exif_pointers = BTreeMap::new();
+    if self.exif.is_some() {
+      ifd0_pointers.insert(tag::EXIF_IFD_POINTER, 0);
+      if self.interop.is_some() {
+        exif_pointers.insert(tag::INTEROP_IFD_POINTER, 0);
+      }
+    }
+    if self.gps.is_some() {
+      ifd0_pointers.insert(tag::GPS_IFD_POINTER, 0);
+    }
+
+    let ifd0_start = out.len();
+    let exif_start = ifd0_start + self.ifd0.written_size(&ifd0_pointers, keep);
+    let interop_start = exif_start
+      + self
+        .exif
+        .as_ref()
+        .map_or(0, |ifd| ifd.written_size(&exif_pointers, keep_exif));
+    let gps_start = interop_start
+      + self
+        .interop
+        .as_ref()
+        .filter(|_| self.exif.is_some())
+        .map_or(0, |ifd| ifd.written_size(&empty, keep));
+    let ifd1_start = gps_start
+      + self
+        .gps
+        .as_ref()
+        .map_or(0, |ifd| ifd.written_size(&empty, keep));
+
+    if self.exif.is_some() {
+      ifd0_pointers.insert(tag::EXIF_IFD_POINTER, offset(exif_start));
+      if self.interop.is_some() {
+        exif_pointers.insert(tag::INTEROP_IFD_POINTER, offset(interop_start));
+      }
+    }
+    if self.gps.is_some() {
+      ifd0_pointers.insert(tag::GPS_IFD_POINTER, offset(gps_start));
+    }
+
+    let write_ifd1 = !append && self.ifd1.is_some();
+    let next = if append {
+      self.next_ifd
+    } else if write_ifd1 {
+      offset(ifd1_start)
+    } else {
+      0
+    };
+
+    self.ifd0.write(out, order, &ifd0_pointers, next, keep);
+    if let Some(exif) = &self.exif {
+      exif.write(out, order, &exif_pointers, 0, keep_exif);
+      if let Some(interop) = &self.interop {
+        interop.write(out, order, &empty, 0, keep);
+      }
+    }
+    if let Some(gps) = &self.gps {
+      gps.write(out, order, &empty, 0, keep);
+    }
+
+    if let (true, Some(ifd1)) = (write_ifd1, &self.ifd1) {
+      let mut ifd1_pointers = BTreeMap::new();
+      let thumbnail = self.thumbnail.as_ref().filter(|_| {
+        ifd1.contains(tag::JPEG_INTERCHANGE_FORMAT)
+          && ifd1.contains(tag::JPEG_INTERCHANGE_FORMAT_LENGTH)
+      });
+      if let Some(thumbnail) = thumbnail {
+        ifd1_pointers.insert(tag::JPEG_INTERCHANGE_FORMAT, 0);
+        ifd1_pointers.insert(tag::JPEG_INTERCHANGE_FORMAT_LENGTH, offset(thumbnail.len()));
+        let thumbnail_start = ifd1_start + ifd1.written_size(&ifd1_pointers, KeepOrigins::None);
+        ifd1_pointers.insert(tag::JPEG_INTERCHANGE_FORMAT, offset(thumbnail_start));
+      }
+      ifd1.write(out, order, &ifd1_pointers, 0, KeepOrigins::None);
+      if let Some(thumbnail) = thumbnail {
+        out.extend_from_slice(thumbnail);
+      }
+    }
+  }
+}
+
+impl Default for IfdTree {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,382 @@
+//! IPTC-IIM metadata in Photoshop image resources.
+//!
+//! JPEG files carry IPTC metadata in APP13 segments starting with
+//! "Photoshop 3.0". Their contents are a list of Photoshop image resources
+//! ("8BIM" blocks), one of which holds the IPTC-IIM datasets: a sequence of
+//! tagged records such as the by-line, caption or keywords. This module
+//! parses and writes both layers, keeping the other resources as they are.
+
+use md5::{Digest, Md5};
+
+/// Identifier preceding the image resources in a JPEG APP13 segment.
+pub const PHOTOSHOP_HEADER: &[u8] = b"Photoshop 3.0\0";
+
+/// Signature of each Photoshop image resource.
+const RESOURCE_SIGNATURE: &[u8] = b"8BIM";
+
+/// Image resource holding the IPTC-IIM datasets.
+pub const IPTC_RESOURCE: u16 = 0x0404;
+
+/// Image resource holding the MD5 digest of the IPTC-IIM datasets, which
+/// Photoshop uses to detect edits by other applications.
+pub const IPTC_DIGEST_RESOURCE: u16 = 0x0425;
+
+/// Marker starting every IPTC-IIM dataset.
+const DATASET_MARKER: u8 = 0x1C;
+
+/// Envelope record holding the coded character set.
+const ENVELOPE_RECORD: u8 = 1;
+
+/// Coded character set dataset of the envelope record.
+const CODED_CHARACTER_SET: u8 = 90;
+
+/// Escape sequence declaring UTF-8 as the coded character set.
+const UTF8_CHARACTER_SET: &[u8] = b"\x1b%G";
+
+/// Application record holding the descriptive datasets.
+pub const APPLICATION_RECORD: u8 = 2;
+
+/// Dataset numbers of the application record.
+pub mod dataset {
+  /// Version of the application record, required as its first dataset
+  pub const RECORD_VERSION: u8 = 0;
+  /// Keywords, repeated once per keyword
+  pub const KEYWORDS: u8 = 25;
+  /// Date the content was created (CCYYMMDD)
+  pub const DATE_CREATED: u8 = 55;
+  /// Time the content was created (HHMMSS±HHMM)
+  pub const TIME_CREATED: u8 = 60;
+  /// Date the digital representation was created
+  pub const DIGITAL_CREATION_DATE: u8 = 62;
+  /// Time the digital representation was created
+  pub const DIGITAL_CREATION_TIME: u8 = 63;
+  /// Creator of the content
+  pub const BY_LINE: u8 = 80;
+  /// Job title of the creator
+  pub const BY_LINE_TITLE: u8 = 85;
+  /// City of the content
+  pub const CITY: u8 = 90;
+  /// Location within the city
+  pub const SUBLOCATION: u8 = 92;
+  /// Province or state of the content
+  pub const PROVINCE_STATE: u8 = 95;
+  /// ISO country code of the content
+  pub const COUNTRY_CODE: u8 = 100;
+  /// Country name of the content
+  pub const COUNTRY_NAME: u8 = 101;
+  /// Provider of the content
+  pub const CREDIT: u8 = 110;
+  /// Copyright notice
+  pub const COPYRIGHT_NOTICE: u8 = 116;
+  /// Person or organisation to contact about the content
+  pub const CONTACT: u8 = 118;
+  /// Caption or abstract of the content
+  pub const CAPTION: u8 = 120;
+
+  /// Returns the maximum length in bytes the IIM specification allows for a
+  /// dataset of the application record.
+  #[must_use]
+  pub const fn max_length(number: u8) -> usize {
+    match number {
+      KEYWORDS => 64,
+      BY_LINE | BY_LINE_TITLE | CITY | SUBLOCATION | PROVINCE_STATE | CREDIT => 32,
+      COUNTRY_NAME => 64,
+      COPYRIGHT_NOTICE | CONTACT => 128,
+      CAPTION => 2000,
+      _ => 256,
+    }
+  }
+}
+
+/// A Photoshop image resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+  /// Resource ID, such as `IPTC_RESOURCE`
+  pub id: u16,
+  /// Name of the resource, usually empty
+  pub name: Vec<u8>,
+  /// Contents of the resource
+  pub data: Vec<u8>,
+}
+
+/// Parses a sequence of Photoshop image resources.
+///
+/// Fails if a resource does not start with the "8BIM" signature or runs past
+/// the end of the data.
+pub fn parse_resources(mut data: &[u8]) -> Result<Vec<Resource>, Box<dyn std::error::Error>> {
+  let mut resources = Vec::new();
+  // Writers may pad the last resource
+  while data.iter().any(|&b| b != 0) {
+    if !data.starts_with(RESOURCE_SIGNATURE) || data.len() < 7 {
+      return Err("Photoshop resource is missing its 8BIM signature".into());
+    }
+    let id = u16::from_be_bytes([data[4], data[5]]);
+    let name_length = usize::from(data[6]);
+    // The name is a Pascal string padded to an even length
+    let name_end = 7 + name_length + usize::from(name_length % 2 == 0);
+    let size_bytes = data
+      .get(name_end..name_end + 4)
+      .ok_or("Photoshop resource is truncated")?;
+    let size = usize::try_from(u32::from_be_bytes([
+      size_bytes[0],
+      size_bytes[1],
+      size_bytes[2],
+      size_bytes[3],
+    ]))?;
+    let start = name_end + 4;
+    let contents = start
+      .checked_add(size)
+      .and_then(|end| data.get(start..end))
+      .ok_or("Photoshop resource is truncated")?;
+    resources.push(Resource {
+      id,
+      name: data[7..7 + name_length].to_vec(),
+      data: contents.to_vec(),
+    });
+    // Resource data is padded to an even length as well
+    data = data.get(start + size + size % 2..).unwrap_or_default();
+  }
+  Ok(resources)
+}
+
+/// Serializes Photoshop image resources, padding names and data to even
+/// lengths.
+pub fn write_resources(resources: &[Resource]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+  let mut out = Vec::new();
+  for resource in resources {
+    out.extend_from_slice(RESOURCE_SIGNATURE);
+    out.extend_from_slice(&resource.id.to_be_bytes());
+    out.push(u8::try_from(resource.name.len())?);
+    out.extend_from_slice(&resource.name);
+    if resource.name.len() % 2 == 0 {
+      out.push(0);
+    }
+    out.extend_from_slice(&u32::try_from(resource.data.len())?.to_be_bytes());
+    out.extend_from_slice(&resource.data);
+    if resource.data.len() % 2 == 1 {
+      out.push(0);
+    }
+  }
+  Ok(out)
+}
+
+/// A single IPTC-IIM dataset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dataset {
+  /// Record number, such as `APPLICATION_RECORD`
+  pub record: u8,
+  /// Dataset number within the record, see [`dataset`]
+  pub number: u8,
+  /// Raw value
+  pub value: Vec<u8>,
+}
+
+/// The IPTC-IIM datasets of an image, in the order they are written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IptcData {
+  /// Every dataset, including repeated ones
+  pub datasets: Vec<Dataset>,
+}
+
+impl IptcData {
+  /// Parses a stream of IPTC-IIM datasets.
+  ///
+  /// Parsing stops at the first byte that does not start a dataset, as some
+  /// writers pad the stream. Fails if a dataset runs past the end of the data
+  /// or uses an extended length, which no descriptive dataset needs.
+  pub fn parse(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut datasets = Vec::new();
+    let mut i = 0;
+    while i + 5 <= data.len() && data[i] == DATASET_MARKER {
+      let length = u16::from_be_bytes([data[i + 3], data[i + 4]]);
+      if length & 0x8000 != 0 {
+        return Err("IPTC datasets with an extended length are not supported".into());
+      }
+      let end = i + 5 + usize::from(length);
+      let value = data.get(i + 5..end).ok_or("IPTC dataset is truncated")?;
+      datasets.push(Dataset {
+        record: data[i + 1],
+        number: data[i + 2],
+        value: value.to_vec(),
+      });
+      i = end;
+    }
+    Ok(Self { datasets })
+  }
+
+  /// Serializes the datasets into an IPTC-IIM stream.
+  pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut out = Vec::new();
+    for dataset in &self.datasets {
+      let length = u16::try_from(dataset.value.len())
+        .ok()
+        .filter(|length| length & 0x8000 == 0)
+        .ok_or("IPTC dataset is too long")?;
+      out.extend_from_slice(&[DATASET_MARKER, dataset.record, dataset.number]);
+      out.extend_from_slice(&length.to_be_bytes());
+      out.extend_from_slice(&dataset.value);
+    }
+    Ok(out)
+  }
+
+  /// Returns the values of an application record dataset as text.
+  ///
+  /// Repeated datasets such as keywords yield one value each. Values are
+  /// decoded as UTF-8, replacing invalid sequences.
+  #[must_use]
+  pub fn get(&self, number: u8) -> Vec<String> {
+    self
+      .datasets
+      .iter()
+      .filter(|d| d.record == APPLICATION_RECORD && d.number == number)
+      .map(|d| String::from_utf8_lossy(&d.value).into_owned())
+      .collect()
+  }
+
+  /// Replaces the values of an application record dataset.
+  ///
+  /// The new datasets take the place of the first existing one, or follow
+  /// the datasets with lower numbers. Values are written as UTF-8, truncated
+  /// to the length the IIM specification allows, and the coded character set
+  /// and record version are added if missing.
+  pub fn set(&mut self, number: u8, values: &[String]) {
+    self.remove(number);
+    if values.is_empty() {
+      return;
+    }
+    self.ensure_headers();
+
+    let position = self
+      .datasets
+      .iter()
+      .rposition(|d| (d.record, d.number) <= (APPLICATION_RECORD, number))
+      .map_or(self.datasets.len(), |index| index + 1);
+    let new = values.iter().map(|value| Dataset {
+      record: APPLICATION_RECORD,
+      number,
+      value: truncate(value, dataset::max_length(number))
+        .as_bytes()
+        .to_vec(),
+    });
+    self.datasets.splice(position..position, new);
+  }
+
+  /// Removes every value of an application record dataset, returning whether
+  /// any was present.
+  pub fn remove(&mut self, number: u8) -> bool {
+    let before = self.datasets.len();
+    self
+      .datasets
+      .retain(|d| d.record != APPLICATION_RECORD || d.number != number);
+    self.datasets.len() != before
+  }
+
+  /// Returns whether the datasets hold nothing but the coded character set
+  /// and record version.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.datasets.iter().all(|d| {
+      (d.record == ENVELOPE_RECORD && d.number == CODED_CHARACTER_SET)
+        || (d.record == APPLICATION_RECORD && d.number == dataset::RECORD_VERSION)
+    })
+  }
+
+  /// Declares UTF-8 as the coded character set and adds the record version
+  /// the application record must start with.
+  fn ensure_headers(&mut self) {
+    let declared = self
+      .datasets
+      .iter_mut()
+      .find(|d| d.record == ENVELOPE_RECORD && d.number == CODED_CHARACTER_SET);
+    if let Some(declared) = declared {
+      declared.value = UTF8_CHARACTER_SET.to_vec();
+    } else {
+      let position = self
+        .datasets
+        .iter()
+        .position(|d| d.record > ENVELOPE_RECORD)
+        .unwrap_or(self.datasets.len());
+      self.datasets.insert(
+        position,
+        Dataset {
+          record: ENVELOPE_RECORD,
+          number: CODED_CHARACTER_SET,
+          value: UTF8_CHARACTER_SET.to_vec(),
+        },
+      );
+    }
+
+    if !self
+      .datasets
+      .iter()
+      .any(|d| d.record == APPLICATION_RECORD && d.number == dataset::RECORD_VERSION)
+    {
+      let position = self
+        .datasets
+        .iter()
+        .position(|d| d.record >= APPLICATION_RECORD)
+        .unwrap_or(self.datasets.len());
+      self.datasets.insert(
+        position,
+        Dataset {
+          record: APPLICATION_RECORD,
+          number: dataset::RECORD_VERSION,
+          value: 4u16.to_be_bytes().to_vec(),
+        },
+      );
+    }
+  }
+}
+
+/// Returns the IPTC data stored in a list of image resources, if any.
+pub fn read_iptc(resources: &[Resource]) -> Result<Option<IptcData>, Box<dyn std::error::Error>> {
+  resources
+    .iter()
+    .find(|resource| resource.id == IPTC_RESOURCE)
+    .map(|resource| IptcData::parse(&resource.data))
+    .transpose()
+}
+
+/// Stores IPTC data in a list of image resources.
+///
+/// Replaces the existing IPTC resource or appends a new one, and removes it
+/// when `iptc` holds no datasets of its own. The IPTC digest is updated if
+/// present, so Photoshop does not flag the datasets as changed elsewhere.
+pub fn write_iptc(
+  resources: &mut Vec<Resource>,
+  iptc: &IptcData,
+) -> Result<(), Box<dyn std::error::Error>> {
+  if iptc.is_empty() {
+    resources.retain(|r| r.id != IPTC_RESOURCE && r.id != IPTC_DIGEST_RESOURCE);
+    return Ok(());
+  }
+
+  let data = iptc.to_bytes()?;
+  for resource in resources.iter_mut() {
+    if resource.id == IPTC_DIGEST_RESOURCE {
+      resource.data = Md5::digest(&data).to_vec();
+    }
+  }
+  if let Some(resource) = resources.iter_mut().find(|r| r.id == IPTC_RESOURCE) {
+    resource.data = data;
+  } else {
+    resources.push(Resource {
+      id: IPTC_RESOURCE,
+      name: Vec::new(),
+      data,
+    });
+  }
+  Ok(())
+}
+
+/// Cuts a value to at most `max` bytes at a character boundary.
+fn truncate(value: &str, max: usize) -> &str {
+  if value.len() <= max {
+    return value;
+  }
+  let mut end = max;
+  while !value.is_char_boundary(end) {
+    end -= 1;
+  }
+  &value[..end]
+}
@@ -0,0 +1,169 @@
+//! XMP packets embedded in JPEG files.
+//!
+//! JPEG files carry XMP in an APP1 segment of its own, told apart from the
+//! EXIF segment by the XMP namespace at its start. A segment holds less than
+//! 64 KB, so larger packets are split: the standard packet keeps what fits and
+//! names the GUID of the Extended XMP, which holds the remaining properties
+//! and is written in chunks to further APP1 segments. This module reassembles
+//! a packet from these segments and splits a packet into segment payloads.
+
+use crate::exif::ifd::MAX_APP1_PAYLOAD;
+use crate::exif::xmp::{prop, XmpDocument, XmpValue};
+use md5::{Digest, Md5};
+use std::fmt::Write;
+
+/// Identifier preceding the packet in a standard XMP APP1 segment.
+pub const XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// Identifier preceding each chunk of Extended XMP.
+pub const EXTENDED_XMP_HEADER: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+
+/// Length of the GUID, full length and offset that follow the identifier of
+/// an Extended XMP chunk.
+const CHUNK_HEADER_LEN: usize = 32 + 4 + 4;
+
+/// Bytes kept free in the standard packet for the `xmpNote:HasExtendedXMP`
+/// property and its namespace declaration.
+const EXTENDED_NOTE_ROOM: usize = 256;
+
+/// Packet used for JPEG files without XMP, with the packet wrapper the XMP
+/// specification recommends for embedded packets.
+const EMPTY_PACKET: &str = concat!(
+  "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+  "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\" x:xmptk=\"Adobe XMP Core\">\n",
+  "  <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+  "    <rdf:Description rdf:about=\"\">\n",
+  "    </rdf:Description>\n",
+  "  </rdf:RDF>\n",
+  "</x:xmpmeta>\n",
+  "<?xpacket end=\"w\"?>",
+);
+
+/// Returns whether an APP1 payload holds a standard XMP packet or a chunk
+/// of Extended XMP.
+#[must_use]
+pub fn is_xmp(payload: &[u8]) -> bool {
+  payload.starts_with(XMP_HEADER) || payload.starts_with(EXTENDED_XMP_HEADER)
+}
+
+/// Creates the packet for a JPEG file that has no XMP yet.
+pub fn empty_packet() -> Result<XmpDocument, Box<dyn std::error::Error>> {
+  XmpDocument::parse(EMPTY_PACKET)
+}
+
+/// Reads the XMP packet from the APP1 payloads of a JPEG file.
+///
+/// The properties of the Extended XMP named by the standard packet are
+/// merged into it, and the `xmpNote:HasExtendedXMP` link is removed, as
+/// `to_payloads` writes a new one when needed. Fails if the packet cannot be
+/// parsed or its Extended XMP is incomplete. Returns `None` if there is no
+/// standard packet.
+pub fn read_packet<'a>(
+  payloads: impl IntoIterator<Item = &'a [u8]>,
+) -> Result<Option<XmpDocument>, Box<dyn std::error::Error>> {
+  let mut standard = None;
+  let mut chunks = Vec::new();
+  for payload in payloads {
+    if let Some(packet) = payload.strip_prefix(XMP_HEADER) {
+      standard = standard.or(Some(packet));
+    } else if let Some(chunk) = payload.strip_prefix(EXTENDED_XMP_HEADER) {
+      chunks.push(chunk);
+    }
+  }
+  let Some(packet) = standard else {
+    return Ok(None);
+  };
+
+  let mut xmp = XmpDocument::parse(std::str::from_utf8(packet)?)?;
+  let Some(XmpValue::Text(guid)) = xmp.get(prop::HAS_EXTENDED_XMP) else {
+    return Ok(Some(xmp));
+  };
+  xmp.remove(prop::HAS_EXTENDED_XMP);
+  let extended = assemble_extended(&chunks, guid.trim().as_bytes())
+    .ok_or_else(|| format!("Extended XMP {} is incomplete", guid.trim()))?;
+  xmp.merge(&XmpDocument::parse(std::str::from_utf8(&extended)?)?);
+  Ok(Some(xmp))
+}
+
+/// Serializes a packet into APP1 payloads, starting with the standard packet.
+///
+/// Packets that do not fit into one segment are split into a standard
+/// packet and Extended XMP, moving the largest properties first, so the
+/// properties IFEX writes stay visible to applications that do not read
+/// Extended XMP.
+pub fn to_payloads(xmp: &XmpDocument) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>> {
+  let limit = MAX_APP1_PAYLOAD - XMP_HEADER.len();
+  let mut standard = xmp.clone();
+  standard.remove(prop::HAS_EXTENDED_XMP);
+  let Some(extended) = standard.split_off(limit - EXTENDED_NOTE_ROOM) else {
+    return Ok(vec![[XMP_HEADER, standard.to_xml().as_bytes()].concat()]);
+  };
+
+  let extended = extended.to_xml().into_bytes();
+  let guid = Md5::digest(&extended).iter().fold(String::new(), |mut guid, byte| {
+    let _ = write!(guid, "{byte:02X}");
+    guid
+  });
+  standard.set(prop::HAS_EXTENDED_XMP, &XmpValue::Text(guid.clone()));
+  let standard = standard.to_xml();
+  if standard.len() > limit {
+    return Err(
+      format!(
+        "XMP packet is {} bytes even without its properties, exceeding the {limit} byte limit of a JPEG APP1 segment",
+        standard.len()
+      )
+      .into(),
+    );
+  }
+
+  let full_length = u32::try_from(extended.len())?;
+  let chunk_size = MAX_APP1_PAYLOAD - EXTENDED_XMP_HEADER.len() - CHUNK_HEADER_LEN;
+  let mut payloads = vec![[XMP_HEADER, standard.as_bytes()].concat()];
+  for (index, chunk) in extended.chunks(chunk_size).enumerate() {
+    let offset = u32::try_from(index * chunk_size)?;
+    let mut payload = EXTENDED_XMP_HEADER.to_vec();
+    payload.extend_from_slice(guid.as_bytes());
+    payload.extend_from_slice(&full_length.to_be_bytes());
+    payload.extend_from_slice(&offset.to_be_bytes());
+    payload.extend_from_slice(chunk);
+    payloads.push(payload);
+  }
+  Ok(payloads)
+}
+
+/// Puts together the Extended XMP with the given GUID from its chunks.
+///
+/// Returns `None` unless the chunks cover the full length exactly once.
+fn assemble_extended(chunks: &[&[u8]], guid: &[u8]) -> Option<Vec<u8>> {
+  let parts: Vec<(usize, usize, &[u8])> = chunks
+    .iter()
+    .filter(|chunk| chunk.len() >= CHUNK_HEADER_LEN && chunk[..32] == *guid)
+    .filter_map(|chunk| {
+      let full_length = u32::from_be_bytes(chunk[32..36].try_into().ok()?);
+      let offset = u32::from_be_bytes(chunk[36..40].try_into().ok()?);
+      Some((
+        usize::try_from(full_length).ok()?,
+        usize::try_from(offset).ok()?,
+        &chunk[CHUNK_HEADER_LEN..],
+      ))
+    })
+    .collect();
+  let full_length = parts.first()?.0;
+  if parts.iter().any(|(length, ..)| *length != full_length)
+    || parts.iter().map(|(_, _, data)| data.len()).sum::<usize>() != full_length
+  {
+    return None;
+  }
+
+  let mut extended = vec![0; full_length];
+  let mut covered = vec![false; full_length];
+  for (_, offset, data) in parts {
+    let range = offset..offset.checked_add(data.len())?;
+    if covered.get(range.clone())?.iter().any(|&c| c) {
+      return None;
+    }
+    extended[range.clone()].copy_from_slice(data);
+    covered[range].fill(true);
+  }
+  Some(extended)
+}
@@ -5,12 +5,21 @@
 //! It handles different file types through specialized processors and provides
 //! a unified interface for EXIF operations.
 
+pub mod diff;
 pub mod exif_manager;
 pub mod file_types;
+pub mod ifd;
+pub mod iptc;
+pub mod jpeg_xmp;
 pub mod processors;
+pub mod raw;
 pub mod tags;
+pub mod xmp;
 
+pub use diff::{ChangeKind, ExifDiff, TagChange};
 pub use exif_manager::ExifManager;
 pub use file_types::*;
+pub use ifd::{ByteOrder, ExifValue, Ifd, IfdTree};
 pub use processors::*;
 pub use tags::*;
+pub use xmp::{XmpDocument, XmpEntry, XmpProperty, XmpValue};
@@ -4,13 +4,29 @@
 //! operations on different image file types. Each processor implements
 //! format-specific logic for applying, erasing, and reading EXIF data.
 
-use crate::exif::tags::ExifTags;
-use crate::models::Selection;
-use exif::{Reader, Value};
+use crate::exif::ifd::{tag, ByteOrder, ExifValue, Ifd, IfdTree, EXIF_HEADER, MAX_APP1_PAYLOAD};
+use crate::exif::iptc::{self, IptcData, Resource, PHOTOSHOP_HEADER};
+use crate::exif::jpeg_xmp;
+use crate::exif::raw;
+use crate::exif::tags::{ExifTags, TagGroup};
+use crate::exif::xmp::{ns, XmpDocument, XmpEntry, XmpValue};
+use crate::models::{GpsPosition, Selection};
+use crate::utils::write_atomic;
+use chrono::{Datelike, Local, NaiveDateTime};
+use exif::Value;
 use std::fs;
-use std::io::BufReader;
+use std::ops::Range;
 use std::path::Path;
 
+/// Marker and byte range of a JPEG segment.
+type JpegSegment = (u8, Range<usize>);
+
+/// Returns the year to put into copyright notices, falling back to the
+/// current year for images without a capture date.
+fn copyright_year(capture_year: Option<i32>) -> i32 {
+  capture_year.unwrap_or_else(|| Local::now().year())
+}
+
 /// JPEG file EXIF processor.
 ///
 /// Handles EXIF metadata operations for JPEG files by manipulating
@@ -19,10 +35,17 @@ pub struct JpegProcessor;
 
 /// TIFF file EXIF processor.
 ///
-/// Handles EXIF metadata operations for TIFF files using the image crate
-/// for file manipulation and the exif crate for metadata reading.
+/// Handles EXIF metadata operations for TIFF files by rewriting their IFDs
+/// in place and using the exif crate for metadata reading.
 pub struct TiffProcessor;
 
+/// DNG file EXIF processor.
+///
+/// Handles EXIF metadata operations for DNG files by rewriting IFD0 and the
+/// Exif sub-IFD in place, keeping the raw data, private DNG tags and previews
+/// intact. Falls back to an XMP sidecar when the file cannot be edited safely.
+pub struct DngProcessor;
+
 /// RAW file EXIF processor.
 ///
 /// Handles EXIF metadata operations for RAW camera files by creating
@@ -32,392 +55,321 @@ pub struct RawProcessor;
 impl JpegProcessor {
   /// Sets the creation date in a JPEG file's EXIF data.
   ///
-  /// Updates the `DateTimeOriginal`, `DateTime`, and `DateTimeDigitized` fields in the EXIF data.
+  /// Updates the `DateTimeOriginal`, `DateTime`, and `DateTimeDigitized` fields in the EXIF data,
+  /// keeping every other tag of the existing EXIF segment, and the matching
+  /// dates of the embedded XMP packet.
   pub fn set_creation_date(
     path: &Path,
     date_string: &str,
   ) -> Result<(), Box<dyn std::error::Error>> {
-    let file = fs::File::open(path)?;
-    let mut bufreader = BufReader::new(&file);
-
-    let exifreader = Reader::new();
-    let existing_exif = exifreader.read_from_container(&mut bufreader).ok();
-
     let original_data = fs::read(path)?;
-
-    let mut new_data = Vec::new();
-
-    if original_data.len() >= 2 && &original_data[0..2] == b"\xff\xd8" {
-      new_data.extend_from_slice(&original_data[0..2]);
-
-      // Create EXIF segment with updated date
-      let exif_data = Self::create_date_exif_segment(date_string, existing_exif.as_ref())?;
-      new_data.extend_from_slice(&exif_data);
-
-      let mut i = 2;
-      while i < original_data.len() - 1 {
-        if original_data[i] == 0xff {
-          let marker = original_data[i + 1];
-          if marker == 0xe1 {
-            let segment_length =
-              (u16::from(original_data[i + 2]) << 8) | u16::from(original_data[i + 3]);
-            i += 2 + segment_length as usize;
-            continue;
-          }
-        }
-        break;
-      }
-
-      new_data.extend_from_slice(&original_data[i..]);
-    } else {
-      return Err("Not a valid JPEG file".into());
-    }
-
-    fs::write(path, new_data)?;
+    let mut tree = Self::read_exif_tree(&original_data)?;
+    ExifTags::apply_creation_date(&mut tree, date_string);
+    let data = Self::replace_exif_segment(&original_data, &tree)?;
+    let data = Self::edit_xmp(&data, |xmp| {
+      ExifTags::apply_xmp_creation_date(xmp, date_string);
+    })?;
+    write_atomic(path, &data)?;
     Ok(())
   }
 
-  /// Creates an EXIF segment specifically for updating date fields
-  fn create_date_exif_segment(
-    date_string: &str,
-    existing_exif: Option<&exif::Exif>,
-  ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    use exif::Value;
-
-    let mut segment = Vec::new();
-    segment.extend_from_slice(b"\xff\xe1");
-
-    let mut data = Vec::new();
-    data.extend_from_slice(b"Exif\x00\x00");
-    data.extend_from_slice(b"II*\x00");
-    let ifd_offset = 8u32;
-    data.extend_from_slice(&ifd_offset.to_le_bytes());
-
-    // Date tags we want to update
-    let date_tag_numbers = [
-      0x0132, // DateTime
-      0x9003, // DateTimeOriginal
-      0x9004, // DateTimeDigitized
-    ];
-
-    // Collect preserved fields from existing EXIF
-    let mut preserved_fields = Vec::new();
-
-    if let Some(exif) = existing_exif {
-      for field in exif.fields() {
-        let tag_number = Self::tag_to_number(field.tag);
-
-        // Skip the date fields we're updating
-        if let Some(tag_num) = tag_number {
-          if date_tag_numbers.contains(&tag_num) {
-            continue;
-          }
-        }
-
-        // Preserve other fields
-        if let Value::Ascii(ascii_vec) = &field.value {
-          for ascii_bytes in ascii_vec {
-            if let Ok(string_value) = std::str::from_utf8(ascii_bytes) {
-              let clean_value = string_value.trim_end_matches('\0');
-              if !clean_value.is_empty() && clean_value.len() < 1000 {
-                if let Some(tag_number) = Self::tag_to_number(field.tag) {
-                  preserved_fields.push((tag_number, 0x02, clean_value.as_bytes().to_vec()));
-                }
-              }
-            }
-          }
-        } else {
-          // Preserve other field types using existing logic from the original implementation
-        }
-      }
-    }
-
-    // Calculate entry count
-    let entry_count = preserved_fields.len() + date_tag_numbers.len();
-    data.extend_from_slice(&(entry_count as u16).to_le_bytes());
-
-    // Calculate where string data will start
-    let string_data_start = 8 + 2 + (entry_count * 12) + 4;
-    let mut string_offset = string_data_start;
-    let mut string_data = Vec::new();
-
-    // Add preserved fields
-    for (tag_num, field_type, field_data) in preserved_fields {
-      let mut entry = Vec::new();
-      entry.extend_from_slice(&tag_num.to_le_bytes());
-      entry.extend_from_slice(&[field_type, 0x00]);
-
-      let count = field_data.len();
-      entry.extend_from_slice(&u32::try_from(count).unwrap_or(0).to_le_bytes());
-
-      if field_data.len() <= 4 {
-        let mut padded_data = field_data.clone();
-        while padded_data.len() < 4 {
-          padded_data.push(0);
-        }
-        entry.extend_from_slice(&padded_data[0..4]);
-      } else {
-        entry.extend_from_slice(&u32::try_from(string_offset).unwrap_or(0).to_le_bytes());
-        string_data.extend_from_slice(&field_data);
-        string_offset += field_data.len();
-      }
-
-      data.extend_from_slice(&entry);
-    }
-
-    // Add date entries
-    for &tag_num in &date_tag_numbers {
-      let mut entry = Vec::new();
-      entry.extend_from_slice(&tag_num.to_le_bytes());
-      entry.extend_from_slice(&[0x02, 0x00]); // ASCII type
-      let string_len = date_string.len() + 1; // Include null terminator
-      entry.extend_from_slice(&u32::try_from(string_len).unwrap_or(0).to_le_bytes());
-
-      if string_len <= 4 {
-        let mut padded_value = date_string.as_bytes().to_vec();
-        padded_value.push(0); // null terminator
-        while padded_value.len() < 4 {
-          padded_value.push(0);
-        }
-        entry.extend_from_slice(&padded_value[0..4]);
-      } else {
-        entry.extend_from_slice(&u32::try_from(string_offset).unwrap_or(0).to_le_bytes());
-        string_data.extend_from_slice(date_string.as_bytes());
-        string_data.push(0); // null terminator
-        string_offset += string_len;
-      }
-
-      data.extend_from_slice(&entry);
-    }
-
-    // Next IFD pointer (0 = no more IFDs)
-    data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
-
-    // Append string data
-    data.extend_from_slice(&string_data);
-
-    // Add length and data to segment
-    let length = u16::try_from(data.len() + 2).unwrap_or(0);
-    segment.push((length >> 8) as u8);
-    segment.push((length & 0xff) as u8);
-    segment.extend_from_slice(&data);
-
-    Ok(segment)
+  /// Sets the position a JPEG file was taken at.
+  ///
+  /// Replaces the GPS IFD of the EXIF data and the GPS properties of the
+  /// embedded XMP packet, keeping every other tag and property.
+  pub fn set_position(
+    path: &Path,
+    position: &GpsPosition,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    let original_data = fs::read(path)?;
+    let mut tree = Self::read_exif_tree(&original_data)?;
+    ExifTags::apply_gps(&mut tree, position);
+    let data = Self::replace_exif_segment(&original_data, &tree)?;
+    let data = Self::edit_xmp(&data, |xmp| ExifTags::apply_xmp_gps(xmp, position))?;
+    write_atomic(path, &data)?;
+    Ok(())
   }
 
   /// Applies EXIF metadata to a JPEG file.
   ///
-  /// Creates new EXIF segments containing equipment and photographer information
-  /// from the selection and embeds them into the JPEG file structure.
+  /// Equivalent to `apply_exif_with_iso` using the film's base ISO rating.
   /// This method preserves existing EXIF/IPTC data and only updates the specified fields.
   pub fn apply_exif(path: &Path, selection: &Selection) -> Result<(), Box<dyn std::error::Error>> {
-    let file = fs::File::open(path)?;
-    let mut bufreader = BufReader::new(&file);
+    Self::apply_exif_with_iso(path, selection, None)
+  }
 
-    let exifreader = Reader::new();
-    let existing_exif = exifreader.read_from_container(&mut bufreader).ok();
+  /// Erases EXIF metadata from a JPEG file.
+  ///
+  /// Equivalent to `erase_exif_groups` with `TagGroup::All`.
+  pub fn erase_exif(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    Self::erase_exif_groups(path, &[TagGroup::All])
+  }
 
+  /// Erases the given groups of metadata from a JPEG file.
+  ///
+  /// With `TagGroup::All` every APP1 segment other than XMP is removed, while
+  /// the JFIF header and all other segments are kept. Otherwise only the tags
+  /// of the selected groups are removed from the EXIF segment. In both cases
+  /// the properties of the selected groups are removed from the embedded XMP
+  /// packet, keeping ratings, keywords and other properties, and the packet
+  /// is dropped once nothing is left in it. The IPTC datasets of the groups
+  /// are removed likewise, keeping captions, keywords and the other
+  /// Photoshop image resources.
+  pub fn erase_exif_groups(
+    path: &Path,
+    groups: &[TagGroup],
+  ) -> Result<(), Box<dyn std::error::Error>> {
     let original_data = fs::read(path)?;
+    let erase_all = groups.contains(&TagGroup::All);
 
-    let mut new_data = Vec::new();
-
-    if original_data.len() >= 2 && &original_data[0..2] == b"\xff\xd8" {
-      new_data.extend_from_slice(&original_data[0..2]);
-
-      // Create merged EXIF segment that preserves existing data
-      let exif_data = Self::create_merged_exif_segment(selection, existing_exif.as_ref())?;
-      new_data.extend_from_slice(&exif_data);
-
-      let mut i = 2;
-      while i < original_data.len() - 1 {
-        if original_data[i] == 0xff {
-          let marker = original_data[i + 1];
-          if marker == 0xe1 {
-            let segment_length =
-              (u16::from(original_data[i + 2]) << 8) | u16::from(original_data[i + 3]);
-            i += 2 + segment_length as usize;
-            continue;
-          }
+    let data = if erase_all {
+      let mut new_data = original_data.clone();
+      for (marker, range) in Self::jpeg_segments(&original_data)?.into_iter().rev() {
+        if marker == 0xe1 && !jpeg_xmp::is_xmp(&original_data[range.start + 4..range.end]) {
+          new_data.drain(range);
         }
-        new_data.push(original_data[i]);
-        i += 1;
-      }
-      if i < original_data.len() {
-        new_data.push(original_data[i]);
       }
+      new_data
+    } else if Self::find_exif_segment(&original_data)?.is_some() {
+      let mut tree = Self::read_exif_tree(&original_data)?;
+      ExifTags::erase_groups(&mut tree, groups);
+      Self::replace_exif_segment(&original_data, &tree)?
     } else {
-      return Err("Not a valid JPEG file".into());
-    }
+      original_data.clone()
+    };
+
+    let data = match Self::read_xmp(&data) {
+      Ok(Some(mut xmp)) => {
+        ExifTags::erase_xmp_groups(&mut xmp, groups);
+        let keep = !xmp.properties().is_empty();
+        Self::replace_xmp_segments(&data, keep.then_some(&xmp))?
+      }
+      Ok(None) => data,
+      // A packet that cannot be read is dropped along with everything else
+      Err(_) if erase_all => Self::replace_xmp_segments(&data, None)?,
+      Err(e) => return Err(format!("Cannot update the XMP packet: {e}").into()),
+    };
+
+    let data = match Self::edit_iptc(&data, |iptc| ExifTags::erase_iptc_groups(iptc, groups)) {
+      Ok(data) => data,
+      // Unreadable image resources are dropped along with everything else
+      Err(_) if erase_all => Self::replace_iptc_segments(&data, &[])?,
+      Err(e) => return Err(e),
+    };
 
-    fs::write(path, new_data)?;
+    if data != original_data {
+      write_atomic(path, &data)?;
+    }
     Ok(())
   }
 
-  /// Erases EXIF metadata from a JPEG file.
+  /// Context added to the names of properties read from the embedded XMP
+  /// packet, as in "Film (XMP)".
+  pub const XMP_SOURCE: &'static str = "XMP";
+
+  /// Read EXIF data from a JPEG file and return as key-value pairs
   ///
-  /// Removes all EXIF and JFIF segments from the JPEG file,
-  /// leaving only the core image data.
-  pub fn erase_exif(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let original_data = fs::read(path)?;
+  /// The EXIF segment is parsed into the same IFD tree the writers use, so
+  /// reads and dry runs see exactly what an apply would start from.
+  /// The properties of the embedded XMP packet are included, flagged with
+  /// the `XMP_SOURCE` context and named as by `RawProcessor::read_sidecar`.
+  pub fn read_exif(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let data = fs::read(path)?;
 
-    if original_data.len() < 2 || &original_data[0..2] != b"\xff\xd8" {
-      return Err("Not a valid JPEG file".into());
-    }
+    let range = Self::find_exif_segment(&data)?.ok_or(exif::Error::NotFound("JPEG"))?;
+    let tree = IfdTree::parse(&data[range.start + 4 + EXIF_HEADER.len()..range.end])?;
 
-    let mut new_data = Vec::new();
-    new_data.extend_from_slice(&original_data[0..2]);
+    let mut results = Self::format_tree(&tree, None);
 
-    let mut i = 2;
-    while i < original_data.len() - 1 {
-      if original_data[i] == 0xff {
-        let marker = original_data[i + 1];
-        if marker == 0xe1 || marker == 0xe0 {
-          let segment_length =
-            (u16::from(original_data[i + 2]) << 8) | u16::from(original_data[i + 3]);
-          i += 2 + segment_length as usize;
-          continue;
-        }
-      }
-      new_data.push(original_data[i]);
-      i += 1;
-    }
-    if i < original_data.len() {
-      new_data.push(original_data[i]);
+    // Also read IPTC data from APP13 segments if present
+    let mut iptc_results = Self::read_iptc_data(&data)?;
+    results.append(&mut iptc_results);
+
+    if let Some(xmp) = Self::read_xmp(&data)? {
+      results.extend(RawProcessor::format_properties(
+        &xmp,
+        Some(Self::XMP_SOURCE),
+      ));
     }
 
-    fs::write(path, new_data)?;
-    Ok(())
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(results)
   }
 
-  /// Read EXIF data from a JPEG file and return as key-value pairs
-  pub fn read_exif(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
-    let file = fs::File::open(path)?;
-    let mut bufreader = BufReader::new(&file);
+  /// Formats every field read from all IFDs as a key-value pair.
+  ///
+  /// Fields outside IFD0 are named after their directory, such as
+  /// "Compression (Thumbnail)". When `source` is given, it is added to that
+  /// context for every field, as in "Make (Embedded)". The pairs are returned
+  /// in the order of the fields.
+  #[must_use]
+  pub fn format_fields(exif: &exif::Exif, source: Option<&str>) -> Vec<(String, String)> {
+    exif
+      .fields()
+      .map(|field| {
+        Self::format_field(
+          field.tag,
+          field.ifd_num,
+          &field.value,
+          exif.little_endian(),
+          source,
+        )
+      })
+      .collect()
+  }
 
-    let exifreader = Reader::new();
-    let exif = exifreader.read_from_container(&mut bufreader)?;
+  /// Formats every entry of an IFD tree as a key-value pair.
+  ///
+  /// Entries are named and formatted exactly like the fields of
+  /// `format_fields`: Exif, GPS and interoperability entries belong to the
+  /// primary image and IFD1 entries to the thumbnail. The pairs are returned
+  /// in directory order.
+  #[must_use]
+  pub fn format_tree(tree: &IfdTree, source: Option<&str>) -> Vec<(String, String)> {
+    let little_endian = tree.byte_order == ByteOrder::LittleEndian;
+    let directories = [
+      (Some(&tree.ifd0), exif::Context::Tiff, exif::In::PRIMARY),
+      (tree.exif.as_ref(), exif::Context::Exif, exif::In::PRIMARY),
+      (
+        tree.interop.as_ref(),
+        exif::Context::Interop,
+        exif::In::PRIMARY,
+      ),
+      (tree.gps.as_ref(), exif::Context::Gps, exif::In::PRIMARY),
+      (tree.ifd1.as_ref(), exif::Context::Tiff, exif::In::THUMBNAIL),
+    ];
 
     let mut results = Vec::new();
-
-    // Read all EXIF fields from all IFDs
-    for field in exif.fields() {
-      let tag_name = Self::format_tag_name(&field.tag);
-      let mut value = Self::format_exif_value(&field.value);
-
-      // Truncate long values (UTF-8 safe)
-      if value.len() > 50 {
-        // Ensure we truncate at a valid UTF-8 boundary
-        let mut truncate_at = 50;
-        while truncate_at > 0 && !value.is_char_boundary(truncate_at) {
-          truncate_at -= 1;
-        }
-        value.truncate(truncate_at);
-        value.push('…');
+    for (ifd, context, ifd_num) in directories {
+      for (number, value) in ifd.into_iter().flat_map(Ifd::iter) {
+        results.push(Self::format_field(
+          exif::Tag(context, number),
+          ifd_num,
+          &Self::to_exif_value(value),
+          little_endian,
+          source,
+        ));
       }
+    }
+    results
+  }
 
-      // Add IFD context to help identify the source
-      let ifd_name = match field.ifd_num {
-        exif::In::PRIMARY => "",
-        exif::In::THUMBNAIL => " (Thumbnail)",
-        _ => " (Sub-IFD)",
-      };
-      let full_tag_name = if ifd_name.is_empty() {
-        tag_name.clone()
-      } else {
-        format!("{tag_name}{ifd_name}")
-      };
-
-      // Also add raw tag info for debugging unknown tags
-      let raw_tag_info = format!("{:?}", field.tag);
-      if raw_tag_info.contains("Tag(") && !raw_tag_info.starts_with(&tag_name) {
-        results.push((format!("{full_tag_name} [{raw_tag_info}]"), value.clone()));
-      } else {
-        results.push((full_tag_name, value));
+  /// Formats a single field as a key-value pair.
+  fn format_field(
+    tag: exif::Tag,
+    ifd_num: exif::In,
+    value: &Value,
+    little_endian: bool,
+    source: Option<&str>,
+  ) -> (String, String) {
+    let tag_name = Self::format_tag_name(&tag);
+    let mut value = match value {
+      Value::Undefined(bytes, _) if tag == exif::Tag::UserComment => {
+        let byte_order = if little_endian {
+          ByteOrder::LittleEndian
+        } else {
+          ByteOrder::BigEndian
+        };
+        ExifTags::decode_user_comment(bytes, byte_order)
       }
+      value => Self::format_exif_value(value),
+    };
+
+    // Comments, such as the development summary, are shown in full
+    if tag != exif::Tag::UserComment {
+      Self::truncate_value(&mut value);
     }
 
-    // Also try to read IPTC data from APP13 segments if present
-    let mut iptc_results = Self::read_iptc_data(path)?;
-    results.append(&mut iptc_results);
+    // Add IFD context to help identify the source
+    let ifd_name = match ifd_num {
+      exif::In::PRIMARY => "",
+      exif::In::THUMBNAIL => "Thumbnail",
+      _ => "Sub-IFD",
+    };
+    let context = match (source, ifd_name) {
+      (None, "") => String::new(),
+      (None, ifd_name) => format!(" ({ifd_name})"),
+      (Some(source), "") => format!(" ({source})"),
+      (Some(source), ifd_name) => format!(" ({source} {ifd_name})"),
+    };
+    let full_tag_name = format!("{tag_name}{context}");
 
-    results.sort_by(|a, b| a.0.cmp(&b.0));
-    Ok(results)
+    // Also add raw tag info for debugging unknown tags
+    let raw_tag_info = format!("{tag:?}");
+    if raw_tag_info.contains("Tag(") && !raw_tag_info.starts_with(&tag_name) {
+      (format!("{full_tag_name} [{raw_tag_info}]"), value)
+    } else {
+      (full_tag_name, value)
+    }
   }
 
-  /// Reads IPTC data from APP13 segments in JPEG files.
+  /// Converts an IFD entry into the value type of the `exif` crate, which
+  /// `format_exif_value` formats.
   ///
-  /// Searches for APP13 (0xFFED) segments that contain IPTC metadata
-  /// and extracts common IPTC fields like keywords, caption, etc.
-  fn read_iptc_data(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
-    let data = fs::read(path)?;
-    let mut results = Vec::new();
-    let mut i = 0;
-
-    // Look for APP13 segments (0xFFED) that contain IPTC data
-    while i < data.len() - 1 {
-      if data[i] == 0xFF && data[i + 1] == 0xED {
-        // Found APP13 segment
-        if i + 4 < data.len() {
-          let segment_len = (u16::from(data[i + 2]) << 8) | u16::from(data[i + 3]);
-          let segment_end = i + 2 + segment_len as usize;
-
-          if segment_end <= data.len() {
-            // Look for Photoshop resource blocks within APP13
-            let segment_data = &data[i + 4..segment_end];
-            if segment_data.len() >= 14 && &segment_data[0..14] == b"Photoshop 3.0\0" {
-              // Parse IPTC records within the Photoshop resource
-              Self::parse_iptc_records(&segment_data[14..], &mut results);
-            }
-          }
-        }
-        i += 2;
-      } else {
-        i += 1;
-      }
+  /// ASCII values are split at their NUL terminators like the crate does.
+  fn to_exif_value(value: &ExifValue) -> Value {
+    let rationals = |values: &[(u32, u32)]| {
+      values
+        .iter()
+        .map(|&(num, denom)| exif::Rational { num, denom })
+        .collect()
+    };
+    match value {
+      ExifValue::Byte(bytes) => Value::Byte(bytes.clone()),
+      ExifValue::Ascii(bytes) => Value::Ascii(
+        bytes
+          .split(|&b| b == 0)
+          .filter(|part| !part.is_empty())
+          .map(<[u8]>::to_vec)
+          .collect(),
+      ),
+      ExifValue::Short(values) => Value::Short(values.clone()),
+      ExifValue::Long(values) | ExifValue::Ifd(values) => Value::Long(values.clone()),
+      ExifValue::Rational(values) => Value::Rational(rationals(values)),
+      ExifValue::SByte(values) => Value::SByte(values.clone()),
+      ExifValue::Undefined(bytes) => Value::Undefined(bytes.clone(), 0),
+      ExifValue::SShort(values) => Value::SShort(values.clone()),
+      ExifValue::SLong(values) => Value::SLong(values.clone()),
+      ExifValue::SRational(values) => Value::SRational(
+        values
+          .iter()
+          .map(|&(num, denom)| exif::SRational { num, denom })
+          .collect(),
+      ),
+      ExifValue::Float(values) => Value::Float(values.clone()),
+      ExifValue::Double(values) => Value::Double(values.clone()),
+      ExifValue::Unknown {
+        field_type, count, ..
+      } => Value::Unknown(*field_type, *count, 0),
     }
-
-    Ok(results)
   }
 
-  /// Parses IPTC records from Photoshop resource data.
-  fn parse_iptc_records(data: &[u8], results: &mut Vec<(String, String)>) {
-    let mut i = 0;
-
-    while i + 8 < data.len() {
-      // Look for IPTC record marker (0x1C)
-      if data[i] == 0x1C {
-        let record = data[i + 1];
-        let dataset = data[i + 2];
-        let length = (u16::from(data[i + 3]) << 8) | u16::from(data[i + 4]);
-
-        if i + 5 + length as usize <= data.len() {
-          let value_data = &data[i + 5..i + 5 + length as usize];
-          let value = String::from_utf8_lossy(value_data).trim().to_string();
-
-          if !value.is_empty() {
-            let tag_name = Self::format_iptc_tag(record, dataset);
-            let mut display_value = value;
-            // Truncate long values (UTF-8 safe)
-            if display_value.len() > 50 {
-              // Ensure we truncate at a valid UTF-8 boundary
-              let mut truncate_at = 50;
-              while truncate_at > 0 && !display_value.is_char_boundary(truncate_at) {
-                truncate_at -= 1;
-              }
-              display_value.truncate(truncate_at);
-              display_value.push('…');
-            }
-            results.push((format!("IPTC: {tag_name}"), display_value));
-          }
+  /// Reads IPTC data from APP13 segments in JPEG files.
+  ///
+  /// Decodes the IPTC-IIM datasets of the Photoshop image resources and
+  /// names them as by `format_iptc_tag`, such as "IPTC: Byline". Repeated
+  /// datasets like keywords yield one pair each.
+  fn read_iptc_data(data: &[u8]) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let Some(iptc) = Self::read_iptc(data)? else {
+      return Ok(Vec::new());
+    };
 
-          i += 5 + length as usize;
-        } else {
-          break;
-        }
-      } else {
-        i += 1;
+    let mut results = Vec::new();
+    for dataset in &iptc.datasets {
+      // The envelope and record version only describe the encoding
+      if dataset.record != iptc::APPLICATION_RECORD
+        || dataset.number == iptc::dataset::RECORD_VERSION
+      {
+        continue;
+      }
+      let mut value = String::from_utf8_lossy(&dataset.value).trim().to_string();
+      if !value.is_empty() {
+        Self::truncate_value(&mut value);
+        let tag_name = Self::format_iptc_tag(dataset.record, dataset.number);
+        results.push((format!("IPTC: {tag_name}"), value));
       }
     }
+    Ok(results)
   }
 
   /// Formats IPTC record and dataset numbers into readable names.
@@ -522,6 +474,15 @@ impl JpegProcessor {
       Tag::LensMake => "Lens Make".to_string(),
       Tag::LensModel => "Lens Model".to_string(),
       Tag::LensSerialNumber => "Lens Serial Number".to_string(),
+      Tag::GPSVersionID => "GPS Version ID".to_string(),
+      Tag::GPSLatitudeRef => "GPS Latitude Ref".to_string(),
+      Tag::GPSLatitude => "GPS Latitude".to_string(),
+      Tag::GPSLongitudeRef => "GPS Longitude Ref".to_string(),
+      Tag::GPSLongitude => "GPS Longitude".to_string(),
+      Tag::GPSAltitudeRef => "GPS Altitude Ref".to_string(),
+      Tag::GPSAltitude => "GPS Altitude".to_string(),
+      Tag::GPSTimeStamp => "GPS Time Stamp".to_string(),
+      Tag::GPSDateStamp => "GPS Date Stamp".to_string(),
       _ => {
         // For unknown tags, try to provide a cleaner format
         let tag_str = format!("{tag}");
@@ -583,6 +544,19 @@ impl JpegProcessor {
     }
   }
 
+  /// Truncates long values for display (UTF-8 safe).
+  pub fn truncate_value(value: &mut String) {
+    if value.len() > 50 {
+      // Ensure we truncate at a valid UTF-8 boundary
+      let mut truncate_at = 50;
+      while truncate_at > 0 && !value.is_char_boundary(truncate_at) {
+        truncate_at -= 1;
+      }
+      value.truncate(truncate_at);
+      value.push('…');
+    }
+  }
+
   /// Formats an EXIF value into a human-readable string.
   ///
   /// Handles different EXIF value types and converts them to displayable
@@ -596,10 +570,11 @@ impl JpegProcessor {
           .iter()
           .map(|bytes| {
             let s = String::from_utf8_lossy(bytes);
-            // Remove null terminators and non-printable characters
+            // Remove null terminators and control characters, keeping the
+            // UTF-8 text many writers store, such as the © of a copyright
             s.trim_end_matches('\0')
               .chars()
-              .filter(|c| c.is_ascii_graphic() || c.is_ascii_whitespace())
+              .filter(|c| !c.is_control() || c.is_ascii_whitespace())
               .collect::<String>()
               .trim()
               .to_string()
@@ -612,7 +587,7 @@ impl JpegProcessor {
         if result.is_empty()
           || result
             .chars()
-            .all(|c| !c.is_ascii_graphic() && !c.is_ascii_whitespace())
+            .all(|c| c == char::REPLACEMENT_CHARACTER || c.is_whitespace())
         {
           "<binary data>".to_string()
         } else {
@@ -671,490 +646,411 @@ impl JpegProcessor {
   ///
   /// Similar to `apply_exif` but allows overriding the ISO value for push/pull processing.
   /// If `shot_iso` is None, uses the film's base ISO rating.
-  /// This method preserves existing EXIF/IPTC data and only updates the specified fields.
+  /// The existing EXIF segment is parsed and only the fields IFEX owns are
+  /// replaced; orientation, GPS, the thumbnail and every other tag are kept.
+  /// The same properties as in RAW sidecars are written to the embedded XMP
+  /// packet, which is created if needed; other properties in it are kept.
+  /// Copyright notices use the capture year of the EXIF dates, or of the XMP
+  /// dates if there are none.
   pub fn apply_exif_with_iso(
     path: &Path,
     selection: &Selection,
     shot_iso: Option<u32>,
   ) -> Result<(), Box<dyn std::error::Error>> {
-    let file = fs::File::open(path)?;
-    let mut bufreader = BufReader::new(&file);
-
-    let exifreader = Reader::new();
-    let existing_exif = exifreader.read_from_container(&mut bufreader).ok();
-
     let original_data = fs::read(path)?;
+    let mut tree = Self::read_exif_tree(&original_data)?;
+    let year = copyright_year(ExifTags::capture_year(&tree).or_else(|| {
+      Self::read_xmp(&original_data)
+        .ok()
+        .flatten()
+        .and_then(|xmp| ExifTags::xmp_capture_year(&xmp))
+    }));
+    let photographer = &selection.photographer;
+    ExifTags::apply_selection(&mut tree, selection, shot_iso);
+    ExifTags::apply_rights(&mut tree, photographer, year);
+    let data = Self::replace_exif_segment(&original_data, &tree)?;
+    let data = Self::edit_xmp(&data, |xmp| {
+      ExifTags::apply_xmp_selection(xmp, selection, shot_iso);
+      ExifTags::apply_xmp_rights(xmp, photographer, year);
+    })?;
+    let data = Self::edit_iptc(&data, |iptc| {
+      ExifTags::apply_iptc_selection(iptc, selection, year);
+    })?;
+    write_atomic(path, &data)?;
+    Ok(())
+  }
 
-    let mut new_data = Vec::new();
-
-    if original_data.len() >= 2 && &original_data[0..2] == b"\xff\xd8" {
-      new_data.extend_from_slice(&original_data[0..2]);
-
-      // Create merged EXIF segment that preserves existing data
-      let exif_data =
-        Self::create_merged_exif_segment_with_iso(selection, shot_iso, existing_exif.as_ref())?;
-      new_data.extend_from_slice(&exif_data);
-
-      let mut i = 2;
-      while i < original_data.len() - 1 {
-        if original_data[i] == 0xff {
-          let marker = original_data[i + 1];
-          if marker == 0xe1 {
-            let segment_length =
-              (u16::from(original_data[i + 2]) << 8) | u16::from(original_data[i + 3]);
-            i += 2 + segment_length as usize;
-            continue;
+  /// Lists the marker segments preceding the image data of a JPEG file.
+  ///
+  /// Returns each segment's marker and its byte range, including the marker
+  /// and length fields. Scanning stops at the start-of-scan marker.
+  fn jpeg_segments(data: &[u8]) -> Result<Vec<JpegSegment>, Box<dyn std::error::Error>> {
+    if data.len() < 2 || &data[0..2] != b"\xff\xd8" {
+      return Err("Not a valid JPEG file".into());
+    }
+
+    let mut segments = Vec::new();
+    let mut i = 2;
+    while i + 1 < data.len() {
+      if data[i] != 0xff {
+        return Err("Corrupt JPEG marker structure".into());
+      }
+      let marker = data[i + 1];
+      match marker {
+        // Fill bytes before a marker
+        0xff => i += 1,
+        // Standalone markers without a length field
+        0x01 | 0xd0..=0xd7 => i += 2,
+        0xd9 | 0xda => break,
+        _ => {
+          let length = data
+            .get(i + 2..i + 4)
+            .map(|bytes| usize::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+            .ok_or("Truncated JPEG segment")?;
+          let end = i + 2 + length;
+          if length < 2 || end > data.len() {
+            return Err("Truncated JPEG segment".into());
           }
+          segments.push((marker, i..end));
+          i = end;
         }
-        break;
       }
-
-      new_data.extend_from_slice(&original_data[i..]);
-    } else {
-      return Err("Not a valid JPEG file".into());
     }
+    Ok(segments)
+  }
 
-    fs::write(path, new_data)?;
-    Ok(())
+  /// Returns the byte range of the APP1 segment carrying EXIF data, if any.
+  fn find_exif_segment(data: &[u8]) -> Result<Option<Range<usize>>, Box<dyn std::error::Error>> {
+    Ok(
+      Self::jpeg_segments(data)?
+        .into_iter()
+        .find(|(marker, range)| {
+          *marker == 0xe1 && data[range.start + 4..range.end].starts_with(EXIF_HEADER)
+        })
+        .map(|(_, range)| range),
+    )
   }
 
-  /// Creates an EXIF segment while preserving existing EXIF data.
-  fn create_merged_exif_segment(
-    selection: &Selection,
-    existing_exif: Option<&exif::Exif>,
-  ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    Self::create_merged_exif_segment_with_iso(selection, None, existing_exif)
+  /// Parses the EXIF segment of a JPEG file into an IFD tree.
+  ///
+  /// Returns an empty tree when the file has no EXIF segment or the existing
+  /// one cannot be parsed.
+  fn read_exif_tree(data: &[u8]) -> Result<IfdTree, Box<dyn std::error::Error>> {
+    let tree = Self::find_exif_segment(data)?
+      .and_then(|range| IfdTree::parse(&data[range.start + 4 + EXIF_HEADER.len()..range.end]).ok())
+      .unwrap_or_default();
+    Ok(tree)
   }
 
-  /// Creates an EXIF segment with optional custom shot ISO while preserving existing EXIF data.
-  /// This creates a properly formatted EXIF segment that Google Photos can read.
-  fn create_merged_exif_segment_with_iso(
-    selection: &Selection,
-    shot_iso: Option<u32>,
-    _existing_exif: Option<&exif::Exif>,
+  /// Serializes `tree` into an APP1 segment and writes it into the JPEG data.
+  ///
+  /// An existing EXIF segment is replaced in place. Otherwise the new segment
+  /// is inserted after any leading APP0 (JFIF) segments. All other segments
+  /// are copied unchanged.
+  fn replace_exif_segment(
+    data: &[u8],
+    tree: &IfdTree,
   ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    // Create the JPEG APP1 segment for EXIF
-    let mut segment = Vec::new();
-    segment.extend_from_slice(b"\xff\xe1");
-
-    // Create EXIF data with proper TIFF header
-    let mut exif_data = Vec::new();
-    exif_data.extend_from_slice(b"Exif\x00\x00");
-
-    // TIFF header (little endian)
-    exif_data.extend_from_slice(b"II"); // Byte order: little endian
-    exif_data.extend_from_slice(&42u16.to_le_bytes()); // TIFF magic number
-    exif_data.extend_from_slice(&8u32.to_le_bytes()); // Offset to first IFD (from TIFF header start)
-
-    // Define entry structure for EXIF entries
-    #[allow(clippy::items_after_statements)]
-    struct ExifEntry {
-      tag: u16,
-      field_type: u16,
-      count: u32,
-      value_or_offset: u32,
-    }
+    let segment = Self::marker_segment(0xe1, &tree.to_app1_payload()?)?;
 
-    // -------- IFD0 (primary) --------
-    let mut ifd0_entries: Vec<ExifEntry> = Vec::new();
-    let mut ifd0_external: Vec<u8> = Vec::new();
-
-    let mut add_ifd0_ascii = |tag: u16, text: &str| {
-      let bytes = text.as_bytes();
-      let count = (bytes.len() + 1) as u32;
-      if count <= 4 {
-        let mut v = [0u8; 4];
-        v[..bytes.len()].copy_from_slice(bytes);
-        ifd0_entries.push(ExifEntry {
-          tag,
-          field_type: 2,
-          count,
-          value_or_offset: u32::from_le_bytes(v),
-        });
-      } else {
-        let off = ifd0_external.len() as u32;
-        ifd0_external.extend_from_slice(bytes);
-        ifd0_external.push(0);
-        ifd0_entries.push(ExifEntry {
-          tag,
-          field_type: 2,
-          count,
-          value_or_offset: off,
-        });
-      }
+    let range = if let Some(range) = Self::find_exif_segment(data)? {
+      range
+    } else {
+      let insert_at = Self::after_app0(data)?;
+      insert_at..insert_at
     };
 
-    // Add equipment EXIF entries
-    add_ifd0_ascii(0x010F, &selection.camera.maker); // Make
-    add_ifd0_ascii(0x0110, &selection.camera.model); // Model
-    add_ifd0_ascii(0x013B, &selection.photographer.name); // Artist
-
-    // Add film information to Film field (0x0289)
-    let film_info = format!("{} {} (ISO {})", 
-      selection.film.maker, 
-      selection.film.name, 
-      selection.film.iso);
-    add_ifd0_ascii(0x0289, &film_info); // Film
-
-    // Placeholder ExifIFDPointer (0x8769), LONG
-    ifd0_entries.push(ExifEntry {
-      tag: 0x8769,
-      field_type: 4,
-      count: 1,
-      value_or_offset: 0,
-    });
+    let mut new_data = Vec::with_capacity(data.len() + segment.len());
+    new_data.extend_from_slice(&data[..range.start]);
+    new_data.extend_from_slice(&segment);
+    new_data.extend_from_slice(&data[range.end..]);
+    Ok(new_data)
+  }
 
-    // Sort entries
-    ifd0_entries.sort_by_key(|e| e.tag);
-
-    // Compute IFD0 external data start (from TIFF start)
-    let ifd0_external_offset = 8 + 2 + (ifd0_entries.len() * 12) + 4;
-
-    // We'll serialize IFD0 to a buffer so we can patch ExifIFDPointer later
-    let mut ifd0_buf = Vec::new();
-    ifd0_buf.extend_from_slice(&(ifd0_entries.len() as u16).to_le_bytes());
-    for e in &ifd0_entries {
-      ifd0_buf.extend_from_slice(&e.tag.to_le_bytes());
-      ifd0_buf.extend_from_slice(&e.field_type.to_le_bytes());
-      ifd0_buf.extend_from_slice(&e.count.to_le_bytes());
-      if e.field_type == 2 && e.count > 4 {
-        let adj = (ifd0_external_offset as u32) + e.value_or_offset;
-        ifd0_buf.extend_from_slice(&adj.to_le_bytes());
-      } else {
-        ifd0_buf.extend_from_slice(&e.value_or_offset.to_le_bytes());
-      }
-    }
-    // next IFD = 0
-    ifd0_buf.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
-    ifd0_buf.extend_from_slice(&ifd0_external);
-
-    // -------- Exif SubIFD --------
-    let mut exif_entries: Vec<ExifEntry> = Vec::new();
-    let mut exif_external: Vec<u8> = Vec::new();
-
-    #[allow(clippy::items_after_statements)]
-    fn add_exif_ascii(entries: &mut Vec<ExifEntry>, external: &mut Vec<u8>, tag: u16, text: &str) {
-      let bytes = text.as_bytes();
-      let count = (bytes.len() + 1) as u32;
-      if count <= 4 {
-        let mut v = [0u8; 4];
-        v[..bytes.len()].copy_from_slice(bytes);
-        entries.push(ExifEntry {
-          tag,
-          field_type: 2,
-          count,
-          value_or_offset: u32::from_le_bytes(v),
-        });
-      } else {
-        let off = external.len() as u32;
-        external.extend_from_slice(bytes);
-        external.push(0);
-        entries.push(ExifEntry {
-          tag,
-          field_type: 2,
-          count,
-          value_or_offset: off,
-        });
-      }
-    }
+  /// Returns the offset following the leading APP0 (JFIF) segments.
+  fn after_app0(data: &[u8]) -> Result<usize, Box<dyn std::error::Error>> {
+    Ok(
+      Self::jpeg_segments(data)?
+        .iter()
+        .take_while(|(marker, _)| *marker == 0xe0)
+        .last()
+        .map_or(2, |(_, range)| range.end),
+    )
+  }
 
-    // ExifVersion (Undefined, 4 bytes) set to "0232"
-    let ver = *b"0232";
-    exif_entries.push(ExifEntry {
-      tag: 0x9000,
-      field_type: 7,
-      count: 4,
-      value_or_offset: u32::from_le_bytes(ver),
-    });
+  /// Wraps a payload into a segment with the given marker and its length.
+  fn marker_segment(marker: u8, payload: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let length = u16::try_from(payload.len() + 2)?;
+    let mut segment = Vec::with_capacity(payload.len() + 4);
+    segment.extend_from_slice(&[0xff, marker]);
+    segment.extend_from_slice(&length.to_be_bytes());
+    segment.extend_from_slice(payload);
+    Ok(segment)
+  }
 
-    // ISO (SHORT)
-    let iso_value = shot_iso.unwrap_or(selection.film.iso);
-    let iso_u16 = if iso_value > 65535 {
-      65535
-    } else {
-      iso_value as u16
-    };
-    exif_entries.push(ExifEntry {
-      tag: 0x8827,
-      field_type: 3,
-      count: 1,
-      value_or_offset: u32::from(iso_u16),
-    });
+  /// Returns the byte ranges of the APP1 segments carrying XMP, including
+  /// the chunks of Extended XMP.
+  fn find_xmp_segments(data: &[u8]) -> Result<Vec<Range<usize>>, Box<dyn std::error::Error>> {
+    Ok(
+      Self::jpeg_segments(data)?
+        .into_iter()
+        .filter(|(marker, range)| {
+          *marker == 0xe1 && jpeg_xmp::is_xmp(&data[range.start + 4..range.end])
+        })
+        .map(|(_, range)| range)
+        .collect(),
+    )
+  }
 
-    // Lens info & focal length
-    if let Some(lens) = &selection.lens {
-      add_exif_ascii(&mut exif_entries, &mut exif_external, 0xA433, &lens.maker); // LensMake
-      let lens_model_string = lens.complete_lens_model();
-      add_exif_ascii(
-        &mut exif_entries,
-        &mut exif_external,
-        0xA434,
-        &lens_model_string,
-      ); // LensModel
-
-      if let Ok(focal_mm) = lens.focal_length.parse::<f32>() {
-        let num = (focal_mm * 1000.0) as u32;
-        let den = 1000u32;
-        let off = exif_external.len() as u32;
-        exif_external.extend_from_slice(&num.to_le_bytes());
-        exif_external.extend_from_slice(&den.to_le_bytes());
-        exif_entries.push(ExifEntry {
-          tag: 0x920A,
-          field_type: 5,
-          count: 1,
-          value_or_offset: off,
-        });
-      }
-    }
+  /// Parses the XMP packet embedded in a JPEG file, merging its Extended XMP.
+  ///
+  /// Returns `None` if the file has no XMP packet.
+  fn read_xmp(data: &[u8]) -> Result<Option<XmpDocument>, Box<dyn std::error::Error>> {
+    let segments = Self::find_xmp_segments(data)?;
+    jpeg_xmp::read_packet(
+      segments
+        .iter()
+        .map(|range| &data[range.start + 4..range.end]),
+    )
+  }
 
-    exif_entries.sort_by_key(|e| e.tag);
+  /// Applies `edit` to the XMP packet of the JPEG data and writes it back.
+  ///
+  /// Starts from an empty packet if there is none yet. A packet that cannot
+  /// be parsed is reported as an error.
+  fn edit_xmp(
+    data: &[u8],
+    edit: impl FnOnce(&mut XmpDocument),
+  ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut xmp = match Self::read_xmp(data) {
+      Ok(Some(xmp)) => xmp,
+      Ok(None) => jpeg_xmp::empty_packet()?,
+      Err(e) => return Err(format!("Cannot update the XMP packet: {e}").into()),
+    };
+    edit(&mut xmp);
+    Self::replace_xmp_segments(data, Some(&xmp))
+  }
 
-    // Offset where ExifIFD will be placed (from TIFF start)
-    let exif_ifd_offset_from_tiff_start = (8 + ifd0_buf.len()) as u32;
+  /// Writes `xmp` into the JPEG data, replacing all existing XMP segments.
+  ///
+  /// The new segments take the place of the first XMP segment. Otherwise they
+  /// follow the EXIF segment, or the leading APP0 segments if there is none.
+  /// With `None` the XMP segments are only removed.
+  fn replace_xmp_segments(
+    data: &[u8],
+    xmp: Option<&XmpDocument>,
+  ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let segments = Self::find_xmp_segments(data)?;
+    let insert_at = match (segments.first(), Self::find_exif_segment(data)?) {
+      (Some(first), _) => first.start,
+      (None, Some(exif)) => exif.end,
+      (None, None) => Self::after_app0(data)?,
+    };
 
-    // Patch ExifIFDPointer in IFD0 buffer
-    let mut pos = 2usize; // skip count
-    for e in &ifd0_entries {
-      if e.tag == 0x8769 {
-        let write_at = pos + 2 + 2 + 4; // tag + type + count
-        let bytes = exif_ifd_offset_from_tiff_start.to_le_bytes();
-        ifd0_buf[write_at..write_at + 4].copy_from_slice(&bytes);
-        break;
-      }
-      pos += 12;
+    let mut new_data = Vec::with_capacity(data.len());
+    new_data.extend_from_slice(&data[..insert_at]);
+    for payload in xmp
+      .map(jpeg_xmp::to_payloads)
+      .transpose()?
+      .unwrap_or_default()
+    {
+      new_data.extend_from_slice(&Self::marker_segment(0xe1, &payload)?);
+    }
+    let mut position = insert_at;
+    for range in segments {
+      new_data.extend_from_slice(&data[position..range.start]);
+      position = range.end;
     }
+    new_data.extend_from_slice(&data[position..]);
+    Ok(new_data)
+  }
 
-    // Serialize Exif SubIFD
-    let mut exif_ifd_buf = Vec::new();
-    exif_ifd_buf.extend_from_slice(&(exif_entries.len() as u16).to_le_bytes());
-    let exif_external_offset =
-      (exif_ifd_offset_from_tiff_start as usize) + 2 + (exif_entries.len() * 12) + 4;
-    for e in &exif_entries {
-      exif_ifd_buf.extend_from_slice(&e.tag.to_le_bytes());
-      exif_ifd_buf.extend_from_slice(&e.field_type.to_le_bytes());
-      exif_ifd_buf.extend_from_slice(&e.count.to_le_bytes());
-      let needs_external = (e.field_type == 2 && e.count > 4) || e.field_type == 5;
-      if needs_external {
-        let adj = (exif_external_offset as u32) + e.value_or_offset;
-        exif_ifd_buf.extend_from_slice(&adj.to_le_bytes());
-      } else {
-        exif_ifd_buf.extend_from_slice(&e.value_or_offset.to_le_bytes());
-      }
+  /// Returns the byte ranges of the APP13 segments carrying Photoshop image
+  /// resources.
+  fn find_iptc_segments(data: &[u8]) -> Result<Vec<Range<usize>>, Box<dyn std::error::Error>> {
+    Ok(
+      Self::jpeg_segments(data)?
+        .into_iter()
+        .filter(|(marker, range)| {
+          *marker == 0xed && data[range.start + 4..range.end].starts_with(PHOTOSHOP_HEADER)
+        })
+        .map(|(_, range)| range)
+        .collect(),
+    )
+  }
+
+  /// Parses the Photoshop image resources of a JPEG file.
+  ///
+  /// Resources too large for one segment continue in the following APP13
+  /// segments, so their contents are joined before parsing. Returns `None` if
+  /// the file has no Photoshop APP13 segment.
+  fn read_resources(data: &[u8]) -> Result<Option<Vec<Resource>>, Box<dyn std::error::Error>> {
+    let segments = Self::find_iptc_segments(data)?;
+    if segments.is_empty() {
+      return Ok(None);
     }
-    exif_ifd_buf.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // next IFD = 0
-    exif_ifd_buf.extend_from_slice(&exif_external);
+    let resources: Vec<u8> = segments
+      .iter()
+      .flat_map(|range| &data[range.start + 4 + PHOTOSHOP_HEADER.len()..range.end])
+      .copied()
+      .collect();
+    iptc::parse_resources(&resources).map(Some)
+  }
 
-    // Build final EXIF payload: header + IFD0 + ExifIFD
-    exif_data.extend_from_slice(&ifd0_buf);
-    exif_data.extend_from_slice(&exif_ifd_buf);
+  /// Parses the IPTC-IIM datasets of a JPEG file.
+  ///
+  /// Returns `None` if the file has no IPTC resource.
+  fn read_iptc(data: &[u8]) -> Result<Option<IptcData>, Box<dyn std::error::Error>> {
+    match Self::read_resources(data)? {
+      Some(resources) => iptc::read_iptc(&resources),
+      None => Ok(None),
+    }
+  }
 
-    // Create final APP1 segment
-    let segment_length = (exif_data.len() + 2) as u16; // +2 for length field itself
-    segment.extend_from_slice(&segment_length.to_be_bytes());
-    segment.extend_from_slice(&exif_data);
+  /// Applies `edit` to the IPTC-IIM datasets of the JPEG data and writes
+  /// them back.
+  ///
+  /// Starts from empty datasets if there are none yet, and leaves the data
+  /// untouched if `edit` changes nothing. Resources that cannot be parsed are
+  /// reported as an error.
+  fn edit_iptc(
+    data: &[u8],
+    edit: impl FnOnce(&mut IptcData),
+  ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let parsed = Self::read_resources(data).and_then(|resources| {
+      let resources = resources.unwrap_or_default();
+      let iptc = iptc::read_iptc(&resources)?.unwrap_or_default();
+      Ok((resources, iptc))
+    });
+    let (mut resources, mut iptc) =
+      parsed.map_err(|e| format!("Cannot update the IPTC data: {e}"))?;
 
-    Ok(segment)
+    let original = iptc.clone();
+    edit(&mut iptc);
+    if iptc == original {
+      return Ok(data.to_vec());
+    }
+    iptc::write_iptc(&mut resources, &iptc)?;
+    Self::replace_iptc_segments(data, &resources)
   }
 
-  /// Convert an EXIF tag to its numeric representation for field processing.
-  /// This is a helper function for the merged EXIF segment creation.
-  fn tag_to_number(tag: exif::Tag) -> Option<u16> {
-    use exif::Tag;
+  /// Writes Photoshop image resources into the JPEG data, replacing all
+  /// existing Photoshop APP13 segments.
+  ///
+  /// The new segments take the place of the first Photoshop segment.
+  /// Otherwise they follow the last APP1 segment, or the leading APP0
+  /// segments if there is none. Without resources the segments are only
+  /// removed.
+  fn replace_iptc_segments(
+    data: &[u8],
+    resources: &[Resource],
+  ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let segments = Self::find_iptc_segments(data)?;
+    let last_app1 = Self::jpeg_segments(data)?
+      .into_iter()
+      .rfind(|(marker, _)| *marker == 0xe1);
+    let insert_at = match (segments.first(), last_app1) {
+      (Some(first), _) => first.start,
+      (None, Some((_, app1))) => app1.end,
+      (None, None) => Self::after_app0(data)?,
+    };
 
-    match tag {
-      Tag::ImageWidth => Some(0x0100),
-      Tag::ImageLength => Some(0x0101),
-      Tag::Compression => Some(0x0103),
-      Tag::PhotometricInterpretation => Some(0x0106),
-      Tag::ImageDescription => Some(0x010e),
-      Tag::Make => Some(0x010f),
-      Tag::Model => Some(0x0110),
-      Tag::Orientation => Some(0x0112),
-      Tag::XResolution => Some(0x011a),
-      Tag::YResolution => Some(0x011b),
-      Tag::ResolutionUnit => Some(0x0128),
-      Tag::Software => Some(0x0131),
-      Tag::DateTime => Some(0x0132),
-      Tag::Artist => Some(0x013b),
-      Tag::Copyright => Some(0x8298),
-      // Add Film tag mapping
-      _ if format!("{tag:?}").contains("Tag(") && format!("{tag:?}").contains("649)") => Some(0x0289), // Film
-      Tag::ExposureTime => Some(0x829a),
-      Tag::FNumber => Some(0x829d),
-      Tag::ExposureProgram => Some(0x8822),
-      Tag::PhotographicSensitivity => Some(0x8827),
-      Tag::ExifVersion => Some(0x9000),
-      Tag::DateTimeOriginal => Some(0x9003),
-      Tag::DateTimeDigitized => Some(0x9004),
-      Tag::ShutterSpeedValue => Some(0x9201),
-      Tag::ApertureValue => Some(0x9202),
-      Tag::BrightnessValue => Some(0x9203),
-      Tag::ExposureBiasValue => Some(0x9204),
-      Tag::MaxApertureValue => Some(0x9205),
-      Tag::SubjectDistance => Some(0x9206),
-      Tag::MeteringMode => Some(0x9207),
-      Tag::LightSource => Some(0x9208),
-      Tag::Flash => Some(0x9209),
-      Tag::FocalLength => Some(0x920a),
-      Tag::ColorSpace => Some(0xa001),
-      Tag::LensSpecification => Some(0xa432),
-      Tag::LensMake => Some(0xa433),
-      Tag::LensModel => Some(0xa434),
-      // Add more commonly used tags that were missing
-      Tag::ComponentsConfiguration => Some(0x9101),
-      Tag::CompressedBitsPerPixel => Some(0x9102),
-      Tag::UserComment => Some(0x9286),
-      Tag::FlashpixVersion => Some(0xa000),
-      Tag::PixelXDimension => Some(0xa002),
-      Tag::PixelYDimension => Some(0xa003),
-      Tag::RelatedSoundFile => Some(0xa004),
-      Tag::FocalPlaneXResolution => Some(0xa20e),
-      Tag::FocalPlaneYResolution => Some(0xa20f),
-      Tag::FocalPlaneResolutionUnit => Some(0xa210),
-      Tag::SubjectLocation => Some(0xa214),
-      Tag::ExposureIndex => Some(0xa215),
-      Tag::SensingMethod => Some(0xa217),
-      Tag::FileSource => Some(0xa300),
-      Tag::SceneType => Some(0xa301),
-      Tag::CFAPattern => Some(0xa302),
-      Tag::CustomRendered => Some(0xa401),
-      Tag::ExposureMode => Some(0xa402),
-      Tag::WhiteBalance => Some(0xa403),
-      Tag::DigitalZoomRatio => Some(0xa404),
-      Tag::FocalLengthIn35mmFilm => Some(0xa405),
-      Tag::SceneCaptureType => Some(0xa406),
-      Tag::GainControl => Some(0xa407),
-      Tag::Contrast => Some(0xa408),
-      Tag::Saturation => Some(0xa409),
-      Tag::Sharpness => Some(0xa40a),
-      Tag::DeviceSettingDescription => Some(0xa40b),
-      Tag::SubjectDistanceRange => Some(0xa40c),
-      Tag::ImageUniqueID => Some(0xa420),
-      Tag::LensSerialNumber => Some(0xa435),
-      // Add missing standard tags that are commonly seen but not in the enum
-      // These will be handled by the fallback case, but we can add known ones here
-      _ => {
-        // For truly unknown tags, try to extract the numeric value from the debug format
-        let tag_str = format!("{tag:?}");
-        if tag_str.contains("Tag(") {
-          if let Some(comma_pos) = tag_str.rfind(", ") {
-            if let Some(end_pos) = tag_str.rfind(')') {
-              let tag_num_str = &tag_str[comma_pos + 2..end_pos];
-              if let Ok(tag_num) = tag_num_str.parse::<u16>() {
-                return Some(tag_num);
-              }
-            }
-          }
-        }
-        None
+    let mut new_data = Vec::with_capacity(data.len());
+    new_data.extend_from_slice(&data[..insert_at]);
+    if !resources.is_empty() {
+      let contents = iptc::write_resources(resources)?;
+      for chunk in contents.chunks(MAX_APP1_PAYLOAD - PHOTOSHOP_HEADER.len()) {
+        let payload = [PHOTOSHOP_HEADER, chunk].concat();
+        new_data.extend_from_slice(&Self::marker_segment(0xed, &payload)?);
       }
     }
+    let mut position = insert_at;
+    for range in segments {
+      new_data.extend_from_slice(&data[position..range.start]);
+      position = range.end;
+    }
+    new_data.extend_from_slice(&data[position..]);
+    Ok(new_data)
   }
 }
 
 impl TiffProcessor {
   /// Sets the creation date in a TIFF file's EXIF data.
   ///
-  /// Updates the `DateTimeOriginal`, `DateTime`, and `DateTimeDigitized` fields in the EXIF data.
-  /// Note: This is a basic implementation that will be enhanced in the future.
+  /// Updates the `DateTimeOriginal`, `DateTime`, and `DateTimeDigitized` fields in place,
+  /// leaving the image data and all other tags untouched.
   pub fn set_creation_date(
     path: &Path,
-    _date_string: &str,
+    date_string: &str,
   ) -> Result<(), Box<dyn std::error::Error>> {
-    // For now, we'll just re-save the TIFF file to preserve it
-    // A full implementation would need to properly modify TIFF EXIF data
-    let img = image::open(path)?;
-    let mut output_file = fs::File::create(path)?;
-    img.write_to(&mut output_file, image::ImageFormat::Tiff)?;
-
-    // TODO: Implement proper TIFF EXIF date modification
-    println!("Note: TIFF date modification is not fully implemented yet. File preserved.");
+    let original_data = fs::read(path)?;
+    let mut tree = IfdTree::parse(&original_data)?;
+    ExifTags::apply_creation_date(&mut tree, date_string);
+    write_atomic(path, &tree.append_to(&original_data)?)?;
     Ok(())
   }
 
-  /// Applies EXIF metadata to a TIFF file.
+  /// Sets the position a TIFF file was taken at.
   ///
-  /// Currently re-saves the TIFF file using the image crate.
-  /// Full EXIF application for TIFF files is not yet implemented.
-  pub fn apply_exif(path: &Path, _selection: &Selection) -> Result<(), Box<dyn std::error::Error>> {
-    let img = image::open(path)?;
-    let mut output_file = fs::File::create(path)?;
-
-    match img {
-      image::DynamicImage::ImageRgb8(rgb_img) => {
-        rgb_img.write_to(&mut output_file, image::ImageFormat::Tiff)?;
-      }
-      image::DynamicImage::ImageRgba8(rgba_img) => {
-        rgba_img.write_to(&mut output_file, image::ImageFormat::Tiff)?;
-      }
-      _ => {
-        img.write_to(&mut output_file, image::ImageFormat::Tiff)?;
-      }
-    }
-
+  /// Replaces the GPS IFD in place, leaving the image data and all other
+  /// tags untouched.
+  pub fn set_position(
+    path: &Path,
+    position: &GpsPosition,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    let original_data = fs::read(path)?;
+    let mut tree = IfdTree::parse(&original_data)?;
+    ExifTags::apply_gps(&mut tree, position);
+    write_atomic(path, &tree.append_to(&original_data)?)?;
     Ok(())
   }
 
+  /// Applies EXIF metadata to a TIFF file.
+  ///
+  /// Equivalent to `apply_exif_with_iso` using the film's base ISO rating.
+  pub fn apply_exif(path: &Path, selection: &Selection) -> Result<(), Box<dyn std::error::Error>> {
+    Self::apply_exif_with_iso(path, selection, None)
+  }
+
   /// Erases EXIF metadata from a TIFF file.
   ///
-  /// Re-saves the TIFF file which removes embedded metadata.
+  /// Equivalent to `erase_exif_groups` with `TagGroup::All`.
   pub fn erase_exif(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let img = image::open(path)?;
-    let mut output_file = fs::File::create(path)?;
-    img.write_to(&mut output_file, image::ImageFormat::Tiff)?;
+    Self::erase_exif_groups(path, &[TagGroup::All])
+  }
+
+  /// Erases the given groups of metadata from a TIFF file.
+  ///
+  /// With `TagGroup::All` the Exif, GPS and interoperability IFDs are dropped
+  /// along with the descriptive IFD0 tags. The IFDs are rewritten in place, so
+  /// the image data and the tags needed to decode it are preserved.
+  pub fn erase_exif_groups(
+    path: &Path,
+    groups: &[TagGroup],
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    let original_data = fs::read(path)?;
+    let mut tree = IfdTree::parse(&original_data)?;
+    ExifTags::erase_groups(&mut tree, groups);
+    if groups.contains(&TagGroup::All) {
+      tree.exif = None;
+      tree.interop = None;
+      tree.ifd0.remove(tag::IMAGE_DESCRIPTION);
+      tree.ifd0.remove(tag::SOFTWARE);
+    }
+    write_atomic(path, &tree.append_to(&original_data)?)?;
     Ok(())
   }
 
   /// Reads EXIF metadata from a TIFF file.
   ///
   /// Extracts all available EXIF fields and returns them as key-value pairs,
-  /// sorted alphabetically by tag name.
+  /// sorted alphabetically by tag name. The file is parsed into the same IFD
+  /// tree the writers use.
   /// Read EXIF data from a TIFF file and return as key-value pairs
   pub fn read_exif(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
-    let file = fs::File::open(path)?;
-    let mut bufreader = BufReader::new(&file);
+    let tree = IfdTree::parse(&fs::read(path)?)?;
 
-    let exifreader = Reader::new();
-    let exif = exifreader.read_from_container(&mut bufreader)?;
-
-    let mut results = Vec::new();
-
-    // Read all EXIF fields from all IFDs
-    for field in exif.fields() {
-      let tag_name = JpegProcessor::format_tag_name(&field.tag);
-      let mut value = JpegProcessor::format_exif_value(&field.value);
-
-      // Truncate long values (UTF-8 safe)
-      if value.len() > 50 {
-        // Ensure we truncate at a valid UTF-8 boundary
-        let mut truncate_at = 50;
-        while truncate_at > 0 && !value.is_char_boundary(truncate_at) {
-          truncate_at -= 1;
-        }
-        value.truncate(truncate_at);
-        value.push('…');
-      }
-
-      // Add IFD context to help identify the source
-      let ifd_name = match field.ifd_num {
-        exif::In::PRIMARY => "",
-        exif::In::THUMBNAIL => " (Thumbnail)",
-        _ => " (Sub-IFD)",
-      };
-      let full_tag_name = if ifd_name.is_empty() {
-        tag_name.clone()
-      } else {
-        format!("{tag_name}{ifd_name}")
-      };
-
-      // Also add raw tag info for debugging unknown tags
-      let raw_tag_info = format!("{:?}", field.tag);
-      if raw_tag_info.contains("Tag(") && !raw_tag_info.starts_with(&tag_name) {
-        results.push((format!("{full_tag_name} [{raw_tag_info}]"), value.clone()));
-      } else {
-        results.push((full_tag_name, value));
-      }
-    }
+    let mut results = JpegProcessor::format_tree(&tree, None);
 
     results.sort_by(|a, b| a.0.cmp(&b.0));
     Ok(results)
@@ -1164,100 +1060,471 @@ impl TiffProcessor {
   ///
   /// Similar to `apply_exif` but allows overriding the ISO value for push/pull processing.
   /// If `shot_iso` is None, uses the film's base ISO rating.
-  /// Currently re-saves the TIFF file using the image crate.
-  /// Full EXIF application for TIFF files is not yet implemented.
+  /// The IFD0 and Exif sub-IFD are rewritten at the end of the file with the
+  /// equipment tags inserted or updated; the image strips and every other tag
+  /// are preserved byte for byte. The copyright notice uses the capture year
+  /// of the existing dates.
   pub fn apply_exif_with_iso(
     path: &Path,
-    _selection: &Selection,
-    _shot_iso: Option<u32>,
+    selection: &Selection,
+    shot_iso: Option<u32>,
   ) -> Result<(), Box<dyn std::error::Error>> {
-    let img = image::open(path)?;
-    let mut output_file = fs::File::create(path)?;
-    img.write_to(&mut output_file, image::ImageFormat::Tiff)?;
+    let original_data = fs::read(path)?;
+    let mut tree = IfdTree::parse(&original_data)?;
+    let year = copyright_year(ExifTags::capture_year(&tree));
+    ExifTags::apply_selection(&mut tree, selection, shot_iso);
+    ExifTags::apply_rights(&mut tree, &selection.photographer, year);
+    write_atomic(path, &tree.append_to(&original_data)?)?;
     Ok(())
   }
 }
 
-impl RawProcessor {
-  /// Sets the creation date in a RAW file's XMP sidecar.
+impl DngProcessor {
+  /// Sets the creation date in a DNG file's EXIF data.
   ///
-  /// Updates or creates an XMP file with the new creation date.
+  /// Updates the date tags in place, or writes them to an XMP sidecar
+  /// if the file cannot be edited safely.
   pub fn set_creation_date(
     path: &Path,
     date_string: &str,
   ) -> Result<(), Box<dyn std::error::Error>> {
-    let xmp_path = path.with_extension("xmp");
+    if !Self::edit_in_place(path, |tree| {
+      ExifTags::apply_creation_date(tree, date_string);
+    })? {
+      RawProcessor::set_creation_date(path, date_string)?;
+    }
+    Ok(())
+  }
 
-    // Create basic XMP content with date information
-    let xmp_content = format!(
-      r#"<?xml version="1.0" encoding="UTF-8"?>
-<x:xmpmeta xmlns:x="adobe:ns:meta/" x:xmptk="Adobe XMP Core">
-  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
-    <rdf:Description rdf:about=""
-        xmlns:exif="http://ns.adobe.com/exif/1.0/">
-      <exif:DateTimeOriginal>{date_string}</exif:DateTimeOriginal>
-      <exif:DateTimeDigitized>{date_string}</exif:DateTimeDigitized>
-    </rdf:Description>
-  </rdf:RDF>
-</x:xmpmeta>"#
-    );
-
-    fs::write(&xmp_path, xmp_content)?;
+  /// Sets the position a DNG file was taken at.
+  ///
+  /// Replaces the GPS IFD in place, or writes the position to an XMP
+  /// sidecar if the file cannot be edited safely.
+  pub fn set_position(
+    path: &Path,
+    position: &GpsPosition,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if !Self::edit_in_place(path, |tree| ExifTags::apply_gps(tree, position))? {
+      RawProcessor::set_position(path, position)?;
+    }
     Ok(())
   }
 
-  /// Applies EXIF metadata to a RAW file by creating an XMP sidecar.
+  /// Applies EXIF metadata to a DNG file.
   ///
-  /// Creates an XMP metadata file alongside the RAW file containing
-  /// equipment and photographer information from the selection.
+  /// Equivalent to `apply_exif_with_iso` using the film's base ISO rating.
   pub fn apply_exif(path: &Path, selection: &Selection) -> Result<(), Box<dyn std::error::Error>> {
-    let xmp_content = ExifTags::create_xmp_metadata(selection);
-    let xmp_path = path.with_extension("xmp");
-    fs::write(&xmp_path, xmp_content)?;
+    Self::apply_exif_with_iso(path, selection, None)
+  }
+
+  /// Applies EXIF metadata to a DNG file with optional custom shot ISO.
+  ///
+  /// The equipment tags are written into IFD0 and the Exif sub-IFD in place.
+  /// If the file cannot be edited safely, an XMP sidecar is created instead.
+  pub fn apply_exif_with_iso(
+    path: &Path,
+    selection: &Selection,
+    shot_iso: Option<u32>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if !Self::edit_in_place(path, |tree| {
+      let year = copyright_year(ExifTags::capture_year(tree));
+      ExifTags::apply_selection(tree, selection, shot_iso);
+      ExifTags::apply_rights(tree, &selection.photographer, year);
+    })? {
+      RawProcessor::apply_exif_with_iso(path, selection, shot_iso)?;
+    }
     Ok(())
   }
 
-  /// Erases EXIF metadata from a RAW file by removing its XMP sidecar.
+  /// Erases EXIF metadata from a DNG file.
   ///
-  /// Deletes the associated XMP metadata file if it exists,
-  /// effectively removing all applied metadata.
+  /// Equivalent to `erase_exif_groups` with `TagGroup::All`.
   pub fn erase_exif(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let xmp_path = path.with_extension("xmp");
-    if xmp_path.exists() {
-      fs::remove_file(&xmp_path)?;
+    Self::erase_exif_groups(path, &[TagGroup::All])
+  }
+
+  /// Erases the given groups of metadata from a DNG file.
+  ///
+  /// Removes the tags of the selected groups in place while keeping every tag
+  /// required to render the raw data. If the file cannot be edited safely,
+  /// the groups are erased from its XMP sidecar instead.
+  pub fn erase_exif_groups(
+    path: &Path,
+    groups: &[TagGroup],
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if !Self::edit_in_place(path, |tree| ExifTags::erase_groups(tree, groups))? {
+      RawProcessor::erase_exif_groups(path, groups)?;
     }
     Ok(())
   }
 
-  /// Reads EXIF metadata from a RAW file's XMP sidecar.
+  /// Reads EXIF metadata from a DNG file and its XMP sidecar.
   ///
-  /// Returns the contents of the associated XMP file if it exists,
-  /// or an empty vector if no XMP file is found.
-  /// Read EXIF data from a JPEG file and return as key-value pairs
+  /// As for RAW files, the fields of the DNG itself are flagged with the
+  /// `RawProcessor::EMBEDDED_SOURCE` context, so they can be told apart from
+  /// the sidecar properties written when the file cannot be edited in place.
+  /// The pairs are sorted by name.
+  pub fn read_exif(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let tree = IfdTree::parse(&fs::read(path)?)?;
+    let mut results = JpegProcessor::format_tree(&tree, Some(RawProcessor::EMBEDDED_SOURCE));
+    results.extend(RawProcessor::read_sidecar(path)?);
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(results)
+  }
+
+  /// Applies `edit` to the DNG's IFD tree and writes the result back to disk.
+  ///
+  /// Returns `false` without touching the file when the in-place edit is not
+  /// safe: the file is not a parseable DNG, or the rewritten file would not
+  /// preserve its DNG version, sub-IFDs, private data, maker notes or previews.
+  fn edit_in_place(
+    path: &Path,
+    edit: impl FnOnce(&mut IfdTree),
+  ) -> Result<bool, Box<dyn std::error::Error>> {
+    let original_data = fs::read(path)?;
+    let Ok(original_tree) = IfdTree::parse(&original_data) else {
+      return Ok(false);
+    };
+    if !original_tree.ifd0.contains(tag::DNG_VERSION) {
+      return Ok(false);
+    }
+
+    let mut tree = original_tree.clone();
+    edit(&mut tree);
+    let Ok(updated_data) = tree.append_to(&original_data) else {
+      return Ok(false);
+    };
+    if !Self::preserves_structure(&original_tree, &updated_data) {
+      return Ok(false);
+    }
+
+    write_atomic(path, &updated_data)?;
+    Ok(true)
+  }
+
+  /// Checks that a rewritten DNG still references the original raw data.
+  fn preserves_structure(original: &IfdTree, updated_data: &[u8]) -> bool {
+    let Ok(updated) = IfdTree::parse(updated_data) else {
+      return false;
+    };
+    let maker_note = |tree: &IfdTree| {
+      tree
+        .exif
+        .as_ref()
+        .and_then(|exif| exif.get(tag::MAKER_NOTE).cloned())
+    };
+
+    [
+      tag::DNG_VERSION,
+      tag::DNG_PRIVATE_DATA,
+      tag::SUB_IFDS,
+      tag::STRIP_OFFSETS,
+    ]
+    .iter()
+    .all(|&number| updated.ifd0.get(number) == original.ifd0.get(number))
+      && maker_note(&updated) == maker_note(original)
+      && updated.ifd1 == original.ifd1
+  }
+}
+
+impl RawProcessor {
+  /// Sets the creation date in a RAW file's XMP sidecar.
+  ///
+  /// Updates the date properties of an existing sidecar, keeping everything
+  /// else in it, or creates a new sidecar.
+  pub fn set_creation_date(
+    path: &Path,
+    date_string: &str,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    Self::edit_sidecar(path, |xmp| {
+      ExifTags::apply_xmp_creation_date(xmp, date_string);
+    })
+  }
+
+  /// Sets the position a RAW file was taken at in its XMP sidecar.
+  ///
+  /// Replaces the GPS properties of an existing sidecar, keeping everything
+  /// else in it, or creates a new sidecar.
+  pub fn set_position(
+    path: &Path,
+    position: &GpsPosition,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    Self::edit_sidecar(path, |xmp| ExifTags::apply_xmp_gps(xmp, position))
+  }
+
+  /// Applies EXIF metadata to a RAW file through its XMP sidecar.
+  ///
+  /// Equivalent to `apply_exif_with_iso` using the film's base ISO rating.
+  pub fn apply_exif(path: &Path, selection: &Selection) -> Result<(), Box<dyn std::error::Error>> {
+    Self::apply_exif_with_iso(path, selection, None)
+  }
+
+  /// Erases EXIF metadata from a RAW file's XMP sidecar.
+  ///
+  /// Equivalent to `erase_exif_groups` with `TagGroup::All`.
+  pub fn erase_exif(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    Self::erase_exif_groups(path, &[TagGroup::All])
+  }
+
+  /// Erases the given groups of metadata from a RAW file's XMP sidecar.
+  ///
+  /// Only the properties of the selected groups are removed; develop
+  /// settings, ratings, keywords and every other property are kept, and so is
+  /// the sidecar itself, even when everything is erased. Nothing is done if
+  /// there is no sidecar.
+  pub fn erase_exif_groups(
+    path: &Path,
+    groups: &[TagGroup],
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if !path.with_extension("xmp").exists() {
+      return Ok(());
+    }
+    Self::edit_sidecar(path, |xmp| ExifTags::erase_xmp_groups(xmp, groups))
+  }
+
+  /// Context added to the names of fields embedded in a RAW file, as in
+  /// "Make (Embedded)".
+  pub const EMBEDDED_SOURCE: &'static str = "Embedded";
+
+  /// Reads EXIF metadata from a RAW file and its XMP sidecar.
+  ///
+  /// Sidecar properties are named as by `read_sidecar`, since the sidecar is
+  /// where IFEX and photo editors write and its values take precedence.
+  /// Fields the camera embedded in the RAW file itself are flagged with the
+  /// `EMBEDDED_SOURCE` context, so both sources can be told apart. Returns an
+  /// empty vector if neither holds any metadata.
+  ///
+  /// When the embedded metadata cannot be read, such as from a damaged
+  /// container, a file with a sidecar still reads from the sidecar, along with
+  /// a "Warning (Embedded)" field giving the reason.
   pub fn read_exif(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let mut results = match Self::read_embedded(path) {
+      Ok(embedded) => embedded,
+      Err(e) if path.with_extension("xmp").exists() => {
+        vec![(
+          format!("Warning ({})", Self::EMBEDDED_SOURCE),
+          e.to_string(),
+        )]
+      }
+      Err(e) => return Err(e),
+    };
+    results.extend(Self::read_sidecar(path)?);
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(results)
+  }
+
+  /// Reads the EXIF metadata embedded in a RAW file by the camera.
+  ///
+  /// TIFF-based formats (CR2, NEF, ARW, ORF, PEF, RW2, ...), Canon CR3 and
+  /// Fujifilm RAF files are supported. Returns an empty vector for other
+  /// containers.
+  pub fn read_embedded(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let exif = raw::read_embedded_exif(fs::read(path)?)
+      .map_err(|e| format!("Cannot read metadata embedded in {}: {e}", path.display()))?;
+    Ok(
+      exif
+        .map(|exif| JpegProcessor::format_fields(&exif, Some(Self::EMBEDDED_SOURCE)))
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Reads EXIF metadata from a RAW file's XMP sidecar.
+  ///
+  /// Every property of the sidecar becomes a key-value pair. EXIF and TIFF
+  /// properties are named like `JpegProcessor::format_tag_name` names the
+  /// equivalent tags, other properties keep their qualified XMP name, and
+  /// array values are joined with commas. Returns an empty vector if no XMP
+  /// file is found.
+  pub fn read_sidecar(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
     let xmp_path = path.with_extension("xmp");
-    if xmp_path.exists() {
-      let content = fs::read_to_string(&xmp_path)?;
-      Ok(vec![("XMP Content".to_string(), content)])
-    } else {
-      Ok(vec![])
+    if !xmp_path.exists() {
+      return Ok(vec![]);
     }
+    let content = fs::read_to_string(&xmp_path)?;
+    let xmp = XmpDocument::parse(&content)
+      .map_err(|e| format!("Cannot read XMP sidecar {}: {e}", xmp_path.display()))?;
+
+    Ok(Self::format_properties(&xmp, None))
   }
 
-  /// Applies EXIF metadata to a RAW file with optional custom shot ISO by creating an XMP sidecar.
+  /// Formats every property of an XMP packet as a key-value pair.
   ///
-  /// Similar to `apply_exif` but allows overriding the ISO value for push/pull processing.
-  /// If `shot_iso` is None, uses the film's base ISO rating.
-  /// Creates an XMP metadata file alongside the RAW file containing
-  /// equipment and photographer information from the selection.
+  /// Properties are named as by `format_property_name`, with `source` added
+  /// as context when given, as in "Film (XMP)". Array values are joined with
+  /// commas, each field of a structure gets its own pair, as in
+  /// "Iptc4xmpCore:CreatorContactInfo/CiAdrCity", and dates are converted to
+  /// the EXIF format. The pairs are sorted by name.
+  #[must_use]
+  pub fn format_properties(xmp: &XmpDocument, source: Option<&str>) -> Vec<(String, String)> {
+    let mut results = Vec::new();
+    for entry in xmp.properties() {
+      let name = Self::format_property_name(&entry);
+      let values = match &entry.value {
+        XmpValue::Text(text) | XmpValue::LangAlt(text) => vec![(name, text.trim().to_string())],
+        XmpValue::Bag(items) | XmpValue::Seq(items) => vec![(name, items.join(", "))],
+        XmpValue::Struct(fields) => fields
+          .iter()
+          .map(|(field, text)| (format!("{name}/{field}"), text.trim().to_string()))
+          .collect(),
+      };
+      for (mut name, mut value) in values {
+        // Structures of arrays have no text of their own
+        if value.is_empty() {
+          continue;
+        }
+        if name.starts_with("Date/Time") {
+          value = Self::format_xmp_date(&value).unwrap_or(value);
+        }
+        if name != "User Comment" {
+          JpegProcessor::truncate_value(&mut value);
+        }
+        if let Some(source) = source {
+          name = format!("{name} ({source})");
+        }
+        results.push((name, value));
+      }
+    }
+
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+  }
+
+  /// Converts an XMP date such as "2024-03-01T12:00:00.00+01:00" to the EXIF
+  /// "YYYY:MM:DD HH:MM:SS" form, dropping fractions and the time zone.
+  ///
+  /// Returns `None` for dates without a time or in any other format.
+  fn format_xmp_date(value: &str) -> Option<String> {
+    let parsed = value
+      .get(..19)
+      .and_then(|date_time| NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S").ok())
+      .or_else(|| {
+        let date_time = value.get(..16)?;
+        NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M").ok()
+      })?;
+    Some(parsed.format("%Y:%m:%d %H:%M:%S").to_string())
+  }
+
+  /// Names an XMP property after the EXIF tag it mirrors.
+  ///
+  /// Falls back to the qualified name as written in the sidecar, such as
+  /// "xmp:Rating", for properties without an EXIF equivalent.
+  #[must_use]
+  pub fn format_property_name(entry: &XmpEntry) -> String {
+    let name = match (entry.namespace.as_str(), entry.local_name()) {
+      (ns::TIFF, "Make") => "Make",
+      (ns::TIFF, "Model") => "Model",
+      (ns::TIFF, "Artist") | (ns::DC, "creator") => "Artist",
+      (ns::TIFF, "Copyright") | (ns::DC, "rights") => "Copyright",
+      (ns::TIFF, "DateTime") | (ns::XMP, "ModifyDate") => "Date/Time",
+      (ns::TIFF, "Software") | (ns::XMP, "CreatorTool") => "Software",
+      (ns::TIFF, "ImageDescription") => "Image Description",
+      (ns::IFEX, "Film") => "Film",
+      (ns::IFEX, "Roll") => "Roll",
+      (ns::IFEX, "Developer") => "Developer",
+      (ns::IFEX, "Dilution") => "Dilution",
+      (ns::IFEX, "DevelopmentTime") => "Development Time",
+      (ns::IFEX, "DevelopmentTemperature") => "Development Temperature",
+      (ns::IFEX, "Lab") => "Lab",
+      (ns::IFEX, "ScanDate") => "Scan Date",
+      (ns::IFEX, "PushPull") => "Push/Pull",
+      (ns::TIFF, "Orientation") => "Orientation",
+      (ns::TIFF, "XResolution") => "X Resolution",
+      (ns::TIFF, "YResolution") => "Y Resolution",
+      (ns::TIFF, "ResolutionUnit") => "Resolution Unit",
+      (ns::EXIF, "DateTimeOriginal") => "Date/Time Original",
+      (ns::EXIF, "DateTimeDigitized") | (ns::XMP, "CreateDate") => "Date/Time Digitized",
+      (ns::EXIF, "ExposureTime") => "Exposure Time",
+      (ns::EXIF, "FNumber") => "F-Number",
+      (ns::EXIF, "ExposureProgram") => "Exposure Program",
+      (ns::EXIF, "ISOSpeedRatings") | (ns::EXIF_EX, "PhotographicSensitivity") => "ISO Speed",
+      (ns::EXIF, "ShutterSpeedValue") => "Shutter Speed Value",
+      (ns::EXIF, "ApertureValue") => "Aperture Value",
+      (ns::EXIF, "BrightnessValue") => "Brightness Value",
+      (ns::EXIF, "ExposureBiasValue") => "Exposure Bias Value",
+      (ns::EXIF, "MaxApertureValue") => "Max Aperture Value",
+      (ns::EXIF, "SubjectDistance") => "Subject Distance",
+      (ns::EXIF, "MeteringMode") => "Metering Mode",
+      (ns::EXIF, "LightSource") => "Light Source",
+      (ns::EXIF, "FocalLength") => "Focal Length",
+      (ns::EXIF, "UserComment") => "User Comment",
+      (ns::EXIF, "GPSVersionID") => "GPS Version ID",
+      (ns::EXIF, "GPSLatitude") => "GPS Latitude",
+      (ns::EXIF, "GPSLongitude") => "GPS Longitude",
+      (ns::EXIF, "GPSAltitudeRef") => "GPS Altitude Ref",
+      (ns::EXIF, "GPSAltitude") => "GPS Altitude",
+      (ns::EXIF, "GPSTimeStamp") => "GPS Time Stamp",
+      (ns::EXIF, "ColorSpace") => "Color Space",
+      (ns::EXIF, "PixelXDimension") => "Pixel X Dimension",
+      (ns::EXIF, "PixelYDimension") => "Pixel Y Dimension",
+      (ns::EXIF, "ExposureMode") => "Exposure Mode",
+      (ns::EXIF, "WhiteBalance") => "White Balance",
+      (ns::EXIF, "FocalLengthIn35mmFilm") => "Focal Length (35mm equiv)",
+      (ns::EXIF, "SceneCaptureType") => "Scene Capture Type",
+      (ns::EXIF_EX, "LensSpecification") | (ns::AUX, "LensInfo") => "Lens Specification",
+      (ns::EXIF_EX, "LensMake") => "Lens Make",
+      (ns::EXIF_EX | ns::AUX, "LensModel") | (ns::AUX, "Lens") => "Lens Model",
+      (ns::EXIF_EX | ns::AUX, "LensSerialNumber") => "Lens Serial Number",
+      _ => return entry.qualified_name.clone(),
+    };
+    name.to_string()
+  }
+
+  /// Applies EXIF metadata to a RAW file with optional custom shot ISO through its XMP sidecar.
+  ///
+  /// If `shot_iso` is None, uses the film's base ISO rating. Only the
+  /// properties owned by IFEX are updated in an existing sidecar, so develop
+  /// settings, ratings and any other namespaces written by tools such as
+  /// Lightroom or darktable are preserved. Copyright notices use the capture
+  /// year of the sidecar dates, or of the metadata embedded in the RAW file.
   pub fn apply_exif_with_iso(
     path: &Path,
     selection: &Selection,
     shot_iso: Option<u32>,
   ) -> Result<(), Box<dyn std::error::Error>> {
-    let xmp_content = ExifTags::create_xmp_metadata_with_iso(selection, shot_iso);
+    let embedded_year = fs::read(path)
+      .ok()
+      .and_then(|data| raw::read_embedded_exif(data).ok().flatten())
+      .and_then(|exif| Self::embedded_capture_year(&exif));
+    Self::edit_sidecar(path, |xmp| {
+      let year = copyright_year(ExifTags::xmp_capture_year(xmp).or(embedded_year));
+      ExifTags::apply_xmp_selection(xmp, selection, shot_iso);
+      ExifTags::apply_xmp_rights(xmp, &selection.photographer, year);
+    })
+  }
+
+  /// Returns the capture year from the EXIF metadata embedded in a RAW file.
+  ///
+  /// Tries `DateTimeOriginal`, then `DateTimeDigitized` and `DateTime`.
+  fn embedded_capture_year(exif: &exif::Exif) -> Option<i32> {
+    [
+      exif::Tag::DateTimeOriginal,
+      exif::Tag::DateTimeDigitized,
+      exif::Tag::DateTime,
+    ]
+    .into_iter()
+    .find_map(|tag| match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+      Value::Ascii(values) => ExifTags::year_of(&String::from_utf8_lossy(values.first()?)),
+      _ => None,
+    })
+  }
+
+  /// Applies `edit` to the XMP sidecar of a RAW file and writes it back.
+  ///
+  /// Starts from an empty packet if there is no sidecar yet. An existing
+  /// sidecar that cannot be parsed is left untouched and reported as an error.
+  fn edit_sidecar(
+    path: &Path,
+    edit: impl FnOnce(&mut XmpDocument),
+  ) -> Result<(), Box<dyn std::error::Error>> {
     let xmp_path = path.with_extension("xmp");
-    fs::write(&xmp_path, xmp_content)?;
+    let mut xmp = if xmp_path.exists() {
+      XmpDocument::parse(&fs::read_to_string(&xmp_path)?)
+        .map_err(|e| format!("Cannot update XMP sidecar {}: {e}", xmp_path.display()))?
+    } else {
+      XmpDocument::new()
+    };
+    edit(&mut xmp);
+    write_atomic(&xmp_path, xmp.to_xml().as_bytes())?;
     Ok(())
   }
 }
@@ -0,0 +1,169 @@
+//! Embedded metadata in RAW camera files.
+//!
+//! Cameras write their EXIF metadata into the RAW file itself. Most RAW
+//! formats are TIFF structures, some with a magic number of their own. Canon
+//! CR3 files are ISO base media files that keep each TIFF directory in a
+//! separate box, and Fujifilm RAF files carry a JPEG preview with a regular
+//! EXIF segment. This module locates that metadata and reads it like the EXIF
+//! data of a TIFF or JPEG file.
+
+use crate::exif::ifd::IfdTree;
+use exif::{Exif, Reader};
+use std::io::Cursor;
+
+/// Magic numbers that TIFF-based RAW formats use instead of 42.
+const RAW_TIFF_MAGIC: &[u16] = &[
+  0x4f52, // Olympus ORF ("RO")
+  0x5352, // Olympus ORF ("RS")
+  0x0055, // Panasonic RW2 and Leica RWL
+];
+
+/// User type of the box holding the metadata of a Canon CR3 file.
+const CANON_UUID: [u8; 16] = [
+  0x85, 0xc0, 0xb6, 0x87, 0x82, 0x0f, 0x11, 0xe0, 0x81, 0x11, 0xf4, 0xce, 0x46, 0x2b, 0x6a, 0x48,
+];
+
+/// Signature at the start of a Fujifilm RAF file.
+const RAF_SIGNATURE: &[u8] = b"FUJIFILMCCD-RAW ";
+
+/// Reads the EXIF metadata embedded in the contents of a RAW file.
+///
+/// Directories that cannot be parsed are skipped, so damaged maker data does
+/// not hide the rest of the metadata. Returns `None` if the file does not use
+/// a supported container, such as Sigma X3F, or carries no metadata.
+pub fn read_embedded_exif(data: Vec<u8>) -> Result<Option<Exif>, Box<dyn std::error::Error>> {
+  if data.starts_with(RAF_SIGNATURE) {
+    return read_raf(&data).map(Some);
+  }
+  if data.get(4..8) == Some(b"ftyp") {
+    return read_cr3(&data);
+  }
+  match normalize_tiff(data) {
+    Some(tiff) => read_tiff(tiff).map(Some),
+    None => Ok(None),
+  }
+}
+
+/// Parses a TIFF structure, keeping whatever could be read.
+fn read_tiff(tiff: Vec<u8>) -> Result<Exif, Box<dyn std::error::Error>> {
+  let exif = Reader::new()
+    .continue_on_error(true)
+    .read_raw(tiff)
+    .or_else(|e| e.distill_partial_result(|_| {}))?;
+  Ok(exif)
+}
+
+/// Rewrites the magic number of TIFF-based RAW formats to 42.
+///
+/// Returns `None` if the data is not a TIFF structure.
+fn normalize_tiff(mut data: Vec<u8>) -> Option<Vec<u8>> {
+  let magic = match data.get(0..4)? {
+    [b'I', b'I', low, high] => u16::from_le_bytes([*low, *high]),
+    [b'M', b'M', high, low] => u16::from_be_bytes([*high, *low]),
+    _ => return None,
+  };
+  if magic == 42 {
+    return Some(data);
+  }
+  if !RAW_TIFF_MAGIC.contains(&magic) {
+    return None;
+  }
+  let magic = if data[0] == b'I' {
+    42u16.to_le_bytes()
+  } else {
+    42u16.to_be_bytes()
+  };
+  data[2..4].copy_from_slice(&magic);
+  Some(data)
+}
+
+/// Reassembles the TIFF directories stored in the boxes of a Canon CR3 file.
+///
+/// CMT1 holds IFD0, CMT2 the Exif sub-IFD and CMT4 the GPS sub-IFD, each
+/// as a TIFF structure of its own.
+fn read_cr3(data: &[u8]) -> Result<Option<Exif>, Box<dyn std::error::Error>> {
+  let canon = iso_boxes(data)
+    .into_iter()
+    .filter(|(kind, _)| kind == b"moov")
+    .flat_map(|(_, moov)| iso_boxes(moov))
+    .find_map(|(kind, contents)| {
+      (kind == *b"uuid" && contents.starts_with(&CANON_UUID)).then(|| &contents[CANON_UUID.len()..])
+    });
+  let Some(canon) = canon else {
+    return Ok(None);
+  };
+
+  let directory = |name: &[u8; 4]| -> Result<Option<IfdTree>, Box<dyn std::error::Error>> {
+    iso_boxes(canon)
+      .into_iter()
+      .find(|(kind, _)| kind == name)
+      .map(|(_, contents)| IfdTree::parse(contents))
+      .transpose()
+  };
+  let Some(mut tree) = directory(b"CMT1")? else {
+    return Ok(None);
+  };
+  if let Some(exif) = directory(b"CMT2")? {
+    tree.exif = Some(exif.ifd0);
+    tree.interop = None;
+  }
+  if let Some(gps) = directory(b"CMT4")? {
+    tree.gps = Some(gps.ifd0);
+  }
+  read_tiff(tree.to_bytes()).map(Some)
+}
+
+/// Splits the contents of an ISO base media box into its child boxes.
+///
+/// Returns the type and contents of each box; a truncated box ends the list.
+fn iso_boxes(mut data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+  let mut boxes = Vec::new();
+  while let (Some(size), Some(kind)) = (be_u32(data, 0), data.get(4..8)) {
+    let (header, size) = match size {
+      0 => (8, data.len()),
+      1 => {
+        let Some(size) = data
+          .get(8..16)
+          .and_then(|b| b.try_into().ok())
+          .and_then(|b| usize::try_from(u64::from_be_bytes(b)).ok())
+        else {
+          break;
+        };
+        (16, size)
+      }
+      size => (8, size as usize),
+    };
+    if size < header || size > data.len() {
+      break;
+    }
+    let kind = [kind[0], kind[1], kind[2], kind[3]];
+    boxes.push((kind, &data[header..size]));
+    data = &data[size..];
+  }
+  boxes
+}
+
+/// Reads the EXIF segment of the JPEG preview embedded in a Fujifilm RAF file.
+///
+/// The RAF header stores the offset and length of the preview at bytes 84
+/// and 88.
+fn read_raf(data: &[u8]) -> Result<Exif, Box<dyn std::error::Error>> {
+  let (Some(offset), Some(length)) = (be_u32(data, 84), be_u32(data, 88)) else {
+    return Err("RAF header is truncated".into());
+  };
+  let preview = (offset as usize)
+    .checked_add(length as usize)
+    .and_then(|end| data.get(offset as usize..end))
+    .ok_or("RAF preview lies outside the file")?;
+  let exif = Reader::new()
+    .continue_on_error(true)
+    .read_from_container(&mut Cursor::new(preview))
+    .or_else(|e| e.distill_partial_result(|_| {}))?;
+  Ok(exif)
+}
+
+/// Reads a big-endian `u32` at `offset`, if the data is long enough.
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+  let bytes = data.get(offset..offset.checked_add(4)?)?;
+  Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -3,9 +3,17 @@
 //! This module provides functionality for converting equipment selections into
 //! various metadata formats including EXIF tag mappings and XMP metadata structures.
 
-use crate::models::Selection;
+use crate::exif::ifd::ByteOrder;
+use crate::exif::ifd::{tag, ExifValue, IfdTree};
+use crate::exif::iptc::{dataset, IptcData};
+use crate::exif::xmp::{ns, prop, XmpDocument, XmpProperty, XmpValue};
+use crate::models::{Development, Exposure, GpsPosition, Lens, Photographer, Selection};
+use chrono::{NaiveDateTime, Timelike};
 use std::collections::HashMap;
 
+/// Lens model property written by earlier versions of IFEX.
+const LEGACY_LENS_MODEL: XmpProperty = XmpProperty::new("aux", ns::AUX, "LensModel");
+
 /// Utility struct for converting equipment selections to EXIF metadata formats.
 ///
 /// Provides methods for generating EXIF tag mappings, XMP metadata, and
@@ -15,38 +23,22 @@ pub struct ExifTags;
 impl ExifTags {
   /// Creates a `HashMap` of EXIF tags from an equipment selection.
   ///
-  /// Converts the equipment selection into standard EXIF tag names and values,
-  /// including camera make/model, lens information, film ISO, and photographer name.
+  /// Equivalent to `create_exif_object_with_iso` using the film's base ISO rating.
   ///
   /// Returns a `HashMap` where keys are EXIF tag names and values are the corresponding data.
   #[must_use]
   pub fn create_exif_object(selection: &Selection) -> HashMap<String, String> {
-    let mut exif_data = HashMap::new();
-
-    exif_data.insert("Make".to_string(), selection.camera.maker.clone());
-    exif_data.insert("Model".to_string(), selection.camera.model.clone());
-
-    // Only add lens data if a lens is present
-    if let Some(lens) = &selection.lens {
-      exif_data.insert("LensMake".to_string(), lens.maker.clone());
-      exif_data.insert("LensModel".to_string(), lens.lens_model_with_aperture());
-      exif_data.insert("FocalLength".to_string(), lens.focal_length.clone());
-      exif_data.insert("FNumber".to_string(), lens.aperture.clone());
-    }
-
-    exif_data.insert(
-      "ISOSpeedRatings".to_string(),
-      selection.film.iso.to_string(),
-    );
-    exif_data.insert("ISOSpeed".to_string(), selection.film.iso.to_string());
-    exif_data.insert("Artist".to_string(), selection.photographer.name.clone());
-
-    exif_data
+    Self::create_exif_object_with_iso(selection, None)
   }
 
   /// Creates a `HashMap` of EXIF tags from an equipment selection with custom shot ISO.
   ///
-  /// Similar to `create_exif_object` but allows overriding the ISO value for push/pull processing.
+  /// Describes exactly the tags `apply_selection` writes: camera make/model,
+  /// photographer, film, the ISO the film was shot at, the exposure settings
+  /// that are known, the roll when applying by roll, the development summary
+  /// when there is one and, when the setup has a
+  /// lens, its make, model, specification and focal length. A focal length
+  /// logged for the frame replaces the lens's. Rationals are given as decimals.
   /// If `shot_iso` is None, uses the film's base ISO rating.
   ///
   /// Returns a `HashMap` where keys are EXIF tag names and values are the corresponding data.
@@ -59,24 +51,45 @@ impl ExifTags {
 
     exif_data.insert("Make".to_string(), selection.camera.maker.clone());
     exif_data.insert("Model".to_string(), selection.camera.model.clone());
+    exif_data.insert("Artist".to_string(), selection.photographer.name.clone());
+    exif_data.insert("Film".to_string(), selection.film.display_name());
+
+    let photographed_iso = shot_iso.unwrap_or(selection.film.iso);
+    exif_data.insert("ISOSpeedRatings".to_string(), photographed_iso.to_string());
+    exif_data.extend(Self::exposure_values(selection.exposure));
+    if let Some(roll) = &selection.roll {
+      exif_data.insert("ImageUniqueID".to_string(), roll.identifier());
+    }
+    if let Some(comment) = Self::development_comment(selection, shot_iso) {
+      exif_data.insert("UserComment".to_string(), comment);
+    }
 
     // Only add lens data if a lens is present
     if let Some(lens) = &selection.lens {
       exif_data.insert("LensMake".to_string(), lens.maker.clone());
-      exif_data.insert("LensModel".to_string(), lens.lens_model_with_aperture());
-      exif_data.insert("FocalLength".to_string(), lens.focal_length.clone());
-      exif_data.insert("FNumber".to_string(), lens.aperture.clone());
+      exif_data.insert("LensModel".to_string(), lens.complete_lens_model());
+      let specification = Self::lens_specification(lens)
+        .iter()
+        .map(|&(num, denom)| {
+          if denom == 0 {
+            format!("{num}/{denom}")
+          } else {
+            (f64::from(num) / f64::from(denom)).to_string()
+          }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+      exif_data.insert("LensSpecification".to_string(), specification);
+      if lens.focal_length.parse::<f32>().is_ok() {
+        exif_data.insert("FocalLength".to_string(), lens.focal_length.clone());
+      }
+    }
+    if let Some((num, denom)) = selection.focal_length {
+      exif_data.insert(
+        "FocalLength".to_string(),
+        (f64::from(num) / f64::from(denom)).to_string(),
+      );
     }
-
-    // ISOSpeedRatings always uses the film's base ISO rating
-    exif_data.insert(
-      "ISOSpeedRatings".to_string(),
-      selection.film.iso.to_string(),
-    );
-    // ISOSpeed uses the actual photographed ISO (shot_iso if provided, otherwise film ISO)
-    let photographed_iso = shot_iso.unwrap_or(selection.film.iso);
-    exif_data.insert("ISOSpeed".to_string(), photographed_iso.to_string());
-    exif_data.insert("Artist".to_string(), selection.photographer.name.clone());
 
     exif_data
   }
@@ -87,22 +100,17 @@ impl ExifTags {
   /// from the equipment selection, or None if the tag is not supported.
   ///
   /// Supported tags include Make, Model, `LensMake`, `LensModel`, `FocalLength`,
-  /// `FNumber`, `ISOSpeedRatings`, `ISOSpeed`, and Artist.
+  /// `FNumber` (the aperture the frame was shot at, not the lens's maximum
+  /// aperture), `ISOSpeedRatings`, `ISOSpeed`, and Artist.
   #[must_use]
   pub fn get_tag_value(tag: &str, selection: &Selection) -> Option<String> {
     match tag {
       "Make" => Some(selection.camera.maker.clone()),
       "Model" => Some(selection.camera.model.clone()),
       "LensMake" => selection.lens.as_ref().map(|lens| lens.maker.clone()),
-      "LensModel" => selection
-        .lens
-        .as_ref()
-        .map(super::super::models::Lens::lens_model_with_aperture),
-      "FocalLength" => selection
-        .lens
-        .as_ref()
-        .map(|lens| lens.focal_length.clone()),
-      "FNumber" => selection.lens.as_ref().map(|lens| lens.aperture.clone()),
+      "LensModel" => selection.lens.as_ref().map(Lens::lens_model_with_aperture),
+      "FocalLength" => Self::create_exif_object(selection).remove("FocalLength"),
+      "FNumber" => Self::exposure_values(selection.exposure).remove("FNumber"),
       "ISOSpeedRatings" | "ISOSpeed" => Some(selection.film.iso.to_string()),
       "Artist" => Some(selection.photographer.name.clone()),
       _ => None,
@@ -123,15 +131,9 @@ impl ExifTags {
       "Make" => Some(selection.camera.maker.clone()),
       "Model" => Some(selection.camera.model.clone()),
       "LensMake" => selection.lens.as_ref().map(|lens| lens.maker.clone()),
-      "LensModel" => selection
-        .lens
-        .as_ref()
-        .map(super::super::models::Lens::lens_model_with_aperture),
-      "FocalLength" => selection
-        .lens
-        .as_ref()
-        .map(|lens| lens.focal_length.clone()),
-      "FNumber" => selection.lens.as_ref().map(|lens| lens.aperture.clone()),
+      "LensModel" => selection.lens.as_ref().map(Lens::lens_model_with_aperture),
+      "FocalLength" => Self::create_exif_object(selection).remove("FocalLength"),
+      "FNumber" => Self::exposure_values(selection.exposure).remove("FNumber"),
       "ISOSpeedRatings" | "ISOSpeed" => {
         let iso_value = shot_iso.unwrap_or(selection.film.iso);
         Some(iso_value.to_string())
@@ -141,126 +143,1123 @@ impl ExifTags {
     }
   }
 
+  /// Returns the known exposure settings as EXIF tag names and values, with
+  /// rationals given as decimals.
+  fn exposure_values(exposure: Exposure) -> HashMap<String, String> {
+    let unsigned = |(num, denom): (u32, u32)| (f64::from(num) / f64::from(denom)).to_string();
+    let signed = |(num, denom): (i32, i32)| (f64::from(num) / f64::from(denom)).to_string();
+    [
+      ("ExposureTime", exposure.exposure_time.map(unsigned)),
+      ("FNumber", exposure.f_number.map(unsigned)),
+      ("ExposureBiasValue", exposure.compensation.map(signed)),
+      (
+        "MeteringMode",
+        exposure
+          .metering_mode
+          .map(|mode| mode.exif_value().to_string()),
+      ),
+      (
+        "ExposureProgram",
+        exposure
+          .program
+          .map(|program| program.exif_value().to_string()),
+      ),
+    ]
+    .into_iter()
+    .filter_map(|(tag, value)| Some((tag.to_string(), value?)))
+    .collect()
+  }
+
   /// Creates XMP metadata XML from an equipment selection.
   ///
-  /// Generates a complete XMP metadata structure containing camera, lens,
-  /// film, and photographer information formatted as XML for use with
-  /// RAW image files as sidecar metadata.
-  ///
-  /// The XMP follows Adobe's metadata standards and includes appropriate
-  /// namespaces for TIFF, EXIF, Dublin Core, and auxiliary data.
+  /// Generates a complete XMP packet containing camera, lens, film, and
+  /// photographer information for use with RAW image files as sidecar
+  /// metadata. See `create_xmp_metadata_with_iso`.
   #[must_use]
   pub fn create_xmp_metadata(selection: &Selection) -> String {
-    let lens_metadata = if let Some(lens) = &selection.lens {
-      format!(
-        r"      <aux:LensModel>{}</aux:LensModel>
-      <exif:FocalLength>{}</exif:FocalLength>
-      <exif:FNumber>{}</exif:FNumber>",
-        lens.lens_model_with_aperture(),
-        lens.focal_length,
-        lens.aperture
-      )
-    } else {
-      String::new()
-    };
-
-    let film_info = format!("{} {} (ISO {})", 
-      selection.film.maker, 
-      selection.film.name, 
-      selection.film.iso);
-
-    format!(
-      r#"<?xml version="1.0" encoding="UTF-8"?>
-<x:xmpmeta xmlns:x="adobe:ns:meta/" x:xmptk="Adobe XMP Core">
-  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
-    <rdf:Description rdf:about=""
-        xmlns:tiff="http://ns.adobe.com/tiff/1.0/"
-        xmlns:exif="http://ns.adobe.com/exif/1.0/"
-        xmlns:dc="http://purl.org/dc/elements/1.1/"
-        xmlns:aux="http://ns.adobe.com/exif/1.0/aux/">
-      <tiff:Make>{}</tiff:Make>
-      <tiff:Model>{}</tiff:Model>
-      <tiff:ImageDescription>{}</tiff:ImageDescription>
-{}
-      <exif:ISOSpeedRatings>
-        <rdf:Bag>
-          <rdf:li>{}</rdf:li>
-        </rdf:Bag>
-      </exif:ISOSpeedRatings>
-      <dc:creator>
-        <rdf:Bag>
-          <rdf:li>{}</rdf:li>
-        </rdf:Bag>
-      </dc:creator>
-    </rdf:Description>
-  </rdf:RDF>
-</x:xmpmeta>"#,
-      selection.camera.maker,
-      selection.camera.model,
-      film_info,
-      lens_metadata,
-      selection.film.iso,
-      selection.photographer.name
-    )
+    Self::create_xmp_metadata_with_iso(selection, None)
   }
 
   /// Creates XMP metadata XML from an equipment selection with custom shot ISO.
   ///
   /// Similar to `create_xmp_metadata` but allows overriding the ISO value for push/pull processing.
-  /// If `shot_iso` is None, uses the film's base ISO rating.
+  /// If `shot_iso` is None, uses the film's base ISO rating. The packet is
+  /// built with `XmpDocument`, so every value is escaped and array properties
+  /// use the container type the XMP specification defines for them.
   #[must_use]
   pub fn create_xmp_metadata_with_iso(selection: &Selection, shot_iso: Option<u32>) -> String {
+    let mut xmp = XmpDocument::new();
+    Self::apply_xmp_selection(&mut xmp, selection, shot_iso);
+    xmp.to_xml()
+  }
+
+  /// Returns the focal length to write: the one logged for the frame, else
+  /// the lens's.
+  fn focal_length(selection: &Selection) -> Option<(u32, u32)> {
+    selection.focal_length.or_else(|| {
+      selection
+        .lens
+        .as_ref()
+        .and_then(|lens| Self::rational(&lens.focal_length))
+    })
+  }
+
+  /// Converts a decimal such as "50" or "1.4" into a rational in thousandths.
+  fn rational(value: &str) -> Option<(u32, u32)> {
+    let value = value.trim().parse::<f64>().ok()?;
+    (value.is_finite() && value >= 0.0 && value < f64::from(u32::MAX) / 1000.0)
+      .then(|| ((value * 1000.0).round() as u32, 1000))
+  }
+
+  /// Returns the lens specification as four rationals: the minimum and
+  /// maximum focal length, then the maximum aperture at each of them.
+  ///
+  /// Ranges such as "24-70" and "3.5-5.6" are supported, and an "f/" prefix
+  /// on the aperture is ignored. Values that cannot be parsed are written as
+  /// 0/0, which EXIF defines as unknown.
+  #[must_use]
+  pub fn lens_specification(lens: &Lens) -> [(u32, u32); 4] {
+    let range = |value: &str| {
+      let value = value.trim();
+      let value = value
+        .strip_prefix("f/")
+        .or_else(|| value.strip_prefix("F/"))
+        .unwrap_or(value);
+      let value = value.strip_suffix("mm").unwrap_or(value);
+      let (min, max) = value.split_once('-').unwrap_or((value, value));
+      Self::rational(min)
+        .zip(Self::rational(max))
+        .unwrap_or(((0, 0), (0, 0)))
+    };
+    let (min_focal, max_focal) = range(&lens.focal_length);
+    let (min_aperture, max_aperture) = range(&lens.aperture);
+    [min_focal, max_focal, min_aperture, max_aperture]
+  }
+
+  /// Writes the IFEX-owned equipment tags from a selection into an IFD tree.
+  ///
+  /// Make, Model, Artist and Film go into IFD0; ISO, lens make/model/specification
+  /// and focal length go into the Exif sub-IFD. Lens tags left over from a previous
+  /// application are removed when the selection has no lens, unless a focal
+  /// length was logged for the frame. The exposure
+  /// settings that are known go into the Exif sub-IFD as well, as RATIONAL,
+  /// SRATIONAL and SHORT values; unknown ones leave existing tags untouched.
+  /// When applying by roll, its identifier goes into `ImageUniqueID`, and
+  /// the development summary goes into `UserComment` when there is one,
+  /// replacing a summary written earlier, which is removed otherwise. The
+  /// position of a saved location replaces the GPS IFD.
+  /// Every other tag in the tree is left untouched.
+  pub fn apply_selection(tree: &mut IfdTree, selection: &Selection, shot_iso: Option<u32>) {
+    tree
+      .ifd0
+      .set(tag::MAKE, ExifValue::ascii(&selection.camera.maker));
+    tree
+      .ifd0
+      .set(tag::MODEL, ExifValue::ascii(&selection.camera.model));
+    tree
+      .ifd0
+      .set(tag::ARTIST, ExifValue::ascii(&selection.photographer.name));
+
+    tree
+      .ifd0
+      .set(tag::FILM, ExifValue::ascii(&selection.film.display_name()));
+
+    let byte_order = tree.byte_order;
+    let exif = tree.exif_mut();
+    if !exif.contains(tag::EXIF_VERSION) {
+      exif.set(tag::EXIF_VERSION, ExifValue::Undefined(b"0232".to_vec()));
+    }
+
     let iso_value = shot_iso.unwrap_or(selection.film.iso);
-    let lens_metadata = if let Some(lens) = &selection.lens {
-      format!(
-        r"      <aux:LensModel>{}</aux:LensModel>
-      <exif:FocalLength>{}</exif:FocalLength>
-      <exif:FNumber>{}</exif:FNumber>",
-        lens.lens_model_with_aperture(),
-        lens.focal_length,
-        lens.aperture
-      )
+    let iso_u16 = u16::try_from(iso_value).unwrap_or(u16::MAX);
+    exif.set(tag::ISO_SPEED_RATINGS, ExifValue::Short(vec![iso_u16]));
+
+    if let Some(roll) = &selection.roll {
+      exif.set(tag::IMAGE_UNIQUE_ID, ExifValue::ascii(&roll.identifier()));
+    }
+    if let Some(comment) = Self::development_comment(selection, shot_iso) {
+      exif.set(
+        tag::USER_COMMENT,
+        ExifValue::Undefined(Self::user_comment(&comment, byte_order)),
+      );
+    } else if matches!(
+      exif.get(tag::USER_COMMENT),
+      Some(ExifValue::Undefined(bytes))
+        if Self::is_development_comment(&Self::decode_user_comment(bytes, byte_order))
+    ) {
+      exif.remove(tag::USER_COMMENT);
+    }
+
+    let exposure = &selection.exposure;
+    if let Some(exposure_time) = exposure.exposure_time {
+      exif.set(tag::EXPOSURE_TIME, ExifValue::Rational(vec![exposure_time]));
+    }
+    if let Some(f_number) = exposure.f_number {
+      exif.set(tag::F_NUMBER, ExifValue::Rational(vec![f_number]));
+    }
+    if let Some(compensation) = exposure.compensation {
+      exif.set(
+        tag::EXPOSURE_BIAS_VALUE,
+        ExifValue::SRational(vec![compensation]),
+      );
+    }
+    if let Some(mode) = exposure.metering_mode {
+      exif.set(
+        tag::METERING_MODE,
+        ExifValue::Short(vec![mode.exif_value()]),
+      );
+    }
+    if let Some(program) = exposure.program {
+      exif.set(
+        tag::EXPOSURE_PROGRAM,
+        ExifValue::Short(vec![program.exif_value()]),
+      );
+    }
+
+    exif.remove(tag::LENS_MAKE);
+    exif.remove(tag::LENS_MODEL);
+    exif.remove(tag::LENS_SPECIFICATION);
+    exif.remove(tag::FOCAL_LENGTH);
+    if let Some(lens) = &selection.lens {
+      exif.set(tag::LENS_MAKE, ExifValue::ascii(&lens.maker));
+      exif.set(
+        tag::LENS_MODEL,
+        ExifValue::ascii(&lens.complete_lens_model()),
+      );
+      exif.set(
+        tag::LENS_SPECIFICATION,
+        ExifValue::Rational(Self::lens_specification(lens).to_vec()),
+      );
+    }
+    if let Some(focal_length) = Self::focal_length(selection) {
+      exif.set(tag::FOCAL_LENGTH, ExifValue::Rational(vec![focal_length]));
+    }
+
+    if let Some(location) = &selection.saved_location {
+      Self::apply_gps(tree, &location.position());
+    }
+  }
+
+  /// Writes the IFEX-owned equipment properties from a selection into an XMP packet.
+  ///
+  /// This is the sidecar counterpart of `apply_selection` and writes the same
+  /// content: the film goes into its own property rather than the image
+  /// description, and the lens model, specification and focal length use the
+  /// same values and rationals as the EXIF tags. Lens properties left over
+  /// from a previous application are removed when the selection has no lens,
+  /// and the exposure settings that are known are written as rationals and
+  /// EXIF values; the roll, when applying by roll, goes into `ifex:Roll`, and
+  /// the known development details, the push or pull and the development
+  /// summary go into `ifex:` properties and `exif:UserComment`, where an
+  /// earlier summary is removed when there is no new one. The
+  /// photographer is credited in `dc:creator` and `photoshop:Credit`, and
+  /// their email address and contact details go into the IPTC Core
+  /// `Iptc4xmpCore:CreatorContactInfo` when they have any. The caption and
+  /// keywords go into `dc:description` and `dc:subject` when given, as does
+  /// the location into `Iptc4xmpCore:Location`. A saved location replaces
+  /// the GPS properties and the address in `photoshop:City`,
+  /// `photoshop:State`, `photoshop:Country` and `Iptc4xmpCore:CountryCode`,
+  /// and every property owned by other applications is left untouched.
+  pub fn apply_xmp_selection(xmp: &mut XmpDocument, selection: &Selection, shot_iso: Option<u32>) {
+    let text = |value: &str| XmpValue::Text(value.to_string());
+    let rational = |(num, denom): (u32, u32)| format!("{num}/{denom}");
+
+    xmp.set(prop::MAKE, &text(&selection.camera.maker));
+    xmp.set(prop::MODEL, &text(&selection.camera.model));
+
+    // Earlier versions wrote the film into the image description
+    let film = text(&selection.film.display_name());
+    let description = xmp.get(prop::IMAGE_DESCRIPTION);
+    if description.is_some()
+      && (description == xmp.get(prop::FILM) || description == Some(film.clone()))
+    {
+      xmp.remove(prop::IMAGE_DESCRIPTION);
+    }
+    xmp.set(prop::FILM, &film);
+
+    for property in [
+      prop::LENS_MAKE,
+      prop::LENS_MODEL,
+      prop::LENS_SPECIFICATION,
+      prop::LENS,
+      prop::FOCAL_LENGTH,
+      LEGACY_LENS_MODEL,
+    ] {
+      xmp.remove(property);
+    }
+    if let Some(lens) = &selection.lens {
+      let model = text(&lens.complete_lens_model());
+      xmp.set(prop::LENS_MAKE, &text(&lens.maker));
+      xmp.set(prop::LENS_MODEL, &model);
+      xmp.set(prop::LENS, &model);
+      xmp.set(
+        prop::LENS_SPECIFICATION,
+        &XmpValue::Seq(Self::lens_specification(lens).map(rational).to_vec()),
+      );
+    }
+    if let Some(focal_length) = Self::focal_length(selection) {
+      xmp.set(prop::FOCAL_LENGTH, &text(&rational(focal_length)));
+    }
+
+    if let Some(roll) = &selection.roll {
+      xmp.set(prop::ROLL, &text(&roll.identifier()));
+    }
+
+    let exposure = &selection.exposure;
+    if let Some(exposure_time) = exposure.exposure_time {
+      xmp.set(prop::EXPOSURE_TIME, &text(&rational(exposure_time)));
+    }
+    if let Some(f_number) = exposure.f_number {
+      xmp.set(prop::F_NUMBER, &text(&rational(f_number)));
+    }
+    if let Some((num, denom)) = exposure.compensation {
+      xmp.set(prop::EXPOSURE_BIAS_VALUE, &text(&format!("{num}/{denom}")));
+    }
+    if let Some(mode) = exposure.metering_mode {
+      xmp.set(prop::METERING_MODE, &text(&mode.exif_value().to_string()));
+    }
+    if let Some(program) = exposure.program {
+      xmp.set(
+        prop::EXPOSURE_PROGRAM,
+        &text(&program.exif_value().to_string()),
+      );
+    }
+
+    // Both are ordered arrays in the XMP specification
+    let iso_value = shot_iso.unwrap_or(selection.film.iso);
+    xmp.set(
+      prop::ISO_SPEED_RATINGS,
+      &XmpValue::Seq(vec![iso_value.to_string()]),
+    );
+    xmp.set(
+      prop::CREATOR,
+      &XmpValue::Seq(vec![selection.photographer.name.clone()]),
+    );
+    xmp.set(prop::CREDIT, &text(&selection.photographer.name));
+
+    let development = &selection.development;
+    for (property, value) in [
+      (prop::DEVELOPER, development.developer.clone()),
+      (prop::DILUTION, development.dilution.clone()),
+      (
+        prop::DEVELOPMENT_TIME,
+        development.time.map(Development::format_time),
+      ),
+      (
+        prop::DEVELOPMENT_TEMPERATURE,
+        development.temperature.map(|degrees| degrees.to_string()),
+      ),
+      (prop::LAB, development.lab.clone()),
+      (
+        prop::SCAN_DATE,
+        development.scanned_on.map(|date| date.to_string()),
+      ),
+    ] {
+      if let Some(value) = value {
+        xmp.set(property, &text(&value));
+      }
+    }
+    match Development::push_pull_thirds(selection.film.iso, iso_value) {
+      0 => {
+        xmp.remove(prop::PUSH_PULL);
+      }
+      thirds => xmp.set(prop::PUSH_PULL, &text(&Development::format_stops(thirds))),
+    }
+    if let Some(comment) = Self::development_comment(selection, shot_iso) {
+      xmp.set(prop::USER_COMMENT, &XmpValue::LangAlt(comment));
+    } else if matches!(
+      xmp.get(prop::USER_COMMENT),
+      Some(XmpValue::LangAlt(comment) | XmpValue::Text(comment))
+        if Self::is_development_comment(&comment)
+    ) {
+      xmp.remove(prop::USER_COMMENT);
+    }
+    if let Some(contact_info) = Self::creator_contact_info(&selection.photographer) {
+      xmp.set(prop::CREATOR_CONTACT_INFO, &contact_info);
+    }
+
+    if let Some(caption) = &selection.caption {
+      xmp.set(prop::DESCRIPTION, &XmpValue::LangAlt(caption.clone()));
+    }
+    if !selection.keywords.is_empty() {
+      xmp.set(prop::SUBJECT, &XmpValue::Bag(selection.keywords.clone()));
+    }
+    if let Some(location) = &selection.saved_location {
+      Self::apply_xmp_gps(xmp, &location.position());
+      for (property, value) in [
+        (prop::LOCATION, Self::sublocation(selection)),
+        (prop::CITY, location.city.as_ref()),
+        (prop::STATE, location.state.as_ref()),
+        (prop::COUNTRY, location.country.as_ref()),
+        (prop::COUNTRY_CODE, location.country_code.as_ref()),
+      ] {
+        match value {
+          Some(value) => xmp.set(property, &text(value)),
+          None => {
+            xmp.remove(property);
+          }
+        }
+      }
+    } else if let Some(sublocation) = &selection.sublocation {
+      xmp.set(prop::LOCATION, &text(sublocation));
+    }
+  }
+
+  /// Returns the sublocation of a selection: its own, such as one logged for
+  /// the frame, or else that of its saved location.
+  fn sublocation(selection: &Selection) -> Option<&String> {
+    selection
+      .sublocation
+      .as_ref()
+      .or_else(|| selection.saved_location.as_ref()?.sublocation.as_ref())
+  }
+
+  /// Summarizes how the film was shot, developed and scanned, such as "Kodak
+  /// Tri-X (ISO 400) shot at ISO 1600, pushed 2 stops. Developed in HC-110
+  /// 1+31 for 9:30 at 20°C by Carmencita. Scanned 2024-08-01."
+  ///
+  /// Returns `None` if no development detail is known and the film was shot
+  /// at its box speed.
+  fn development_comment(selection: &Selection, shot_iso: Option<u32>) -> Option<String> {
+    let development = &selection.development;
+    let shot_iso = shot_iso.unwrap_or(selection.film.iso);
+    let thirds = Development::push_pull_thirds(selection.film.iso, shot_iso);
+    if development.is_empty() && thirds == 0 {
+      return None;
+    }
+
+    let push_pull = match thirds {
+      0 => String::new(),
+      _ => format!(
+        ", {} {} {}",
+        if thirds > 0 { "pushed" } else { "pulled" },
+        Development::format_stops(thirds.abs()).trim_start_matches('+'),
+        if thirds.abs() > 3 { "stops" } else { "stop" }
+      ),
+    };
+    let shot = format!(
+      "{} shot at ISO {shot_iso}{push_pull}",
+      selection.film.display_name()
+    );
+    let developer = [&development.developer, &development.dilution]
+      .into_iter()
+      .filter_map(Option::as_deref)
+      .collect::<Vec<_>>()
+      .join(" ");
+    let process = [
+      Some(developer)
+        .filter(|developer| !developer.is_empty())
+        .map(|developer| format!("in {developer}")),
+      development
+        .time
+        .map(|time| format!("for {}", Development::format_time(time))),
+      development
+        .temperature
+        .map(|degrees| format!("at {degrees}°C")),
+      development.lab.as_ref().map(|lab| format!("by {lab}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    let mut sentences = vec![format!("{shot}.")];
+    if !process.is_empty() {
+      sentences.push(format!("Developed {}.", process.join(" ")));
+    }
+    if let Some(date) = development.scanned_on {
+      sentences.push(format!("Scanned {date}."));
+    }
+    Some(sentences.join(" "))
+  }
+
+  /// Returns whether a comment is a development summary written by
+  /// `development_comment`, which starts with the film and the ISO it was
+  /// shot at, as in "Kodak Tri-X (ISO 400) shot at ISO 1600".
+  #[must_use]
+  pub fn is_development_comment(comment: &str) -> bool {
+    comment
+      .split_once(") shot at ISO ")
+      .is_some_and(|(film, rest)| {
+        film.contains(" (ISO ") && rest.starts_with(|c: char| c.is_ascii_digit())
+      })
+  }
+
+  /// Encodes a `UserComment` value: ASCII text with the ASCII character
+  /// code, and any other text as UCS-2 in the byte order of the tree with the
+  /// Unicode character code.
+  fn user_comment(comment: &str, byte_order: ByteOrder) -> Vec<u8> {
+    if comment.is_ascii() {
+      return [b"ASCII\0\0\0".as_slice(), comment.as_bytes()].concat();
+    }
+    let mut bytes = b"UNICODE\0".to_vec();
+    for unit in comment.encode_utf16() {
+      match byte_order {
+        ByteOrder::LittleEndian => bytes.extend(unit.to_le_bytes()),
+        ByteOrder::BigEndian => bytes.extend(unit.to_be_bytes()),
+      }
+    }
+    bytes
+  }
+
+  /// Decodes a `UserComment` value, whose first 8 bytes name its character
+  /// code: ASCII, Unicode (UCS-2 in the given byte order) or undefined, which
+  /// is read as UTF-8.
+  #[must_use]
+  pub fn decode_user_comment(bytes: &[u8], byte_order: ByteOrder) -> String {
+    let (code, text) = bytes.split_at(bytes.len().min(8));
+    let comment = if code == b"UNICODE\0" {
+      let units: Vec<u16> = text
+        .chunks_exact(2)
+        .map(|pair| {
+          let pair = [pair[0], pair[1]];
+          match byte_order {
+            ByteOrder::LittleEndian => u16::from_le_bytes(pair),
+            ByteOrder::BigEndian => u16::from_be_bytes(pair),
+          }
+        })
+        .collect();
+      String::from_utf16_lossy(&units)
+    } else {
+      String::from_utf8_lossy(text).to_string()
+    };
+    comment
+      .trim_end_matches(['\0', ' '])
+      .trim_start()
+      .to_string()
+  }
+
+  /// Builds the IPTC Core creator contact info of a photographer.
+  ///
+  /// Returns `None` if the photographer has neither an email address nor
+  /// contact details.
+  fn creator_contact_info(photographer: &Photographer) -> Option<XmpValue> {
+    let contact = &photographer.contact;
+    let fields: Vec<(String, String)> = [
+      ("CiAdrExtadr", &contact.address),
+      ("CiAdrCity", &contact.city),
+      ("CiAdrRegion", &contact.region),
+      ("CiAdrPcode", &contact.postal_code),
+      ("CiAdrCtry", &contact.country),
+      ("CiTelWork", &contact.phone),
+      ("CiEmailWork", &photographer.email),
+      ("CiUrlWork", &contact.website),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| Some((field.to_string(), value.clone()?)))
+    .collect();
+    (!fields.is_empty()).then_some(XmpValue::Struct(fields))
+  }
+
+  /// Writes the photographer's copyright notice into an IFD tree.
+  ///
+  /// The notice goes into the IFD0 `Copyright` tag, with `year` filled in as
+  /// the capture year.
+  pub fn apply_rights(tree: &mut IfdTree, photographer: &Photographer, year: i32) {
+    tree.ifd0.set(
+      tag::COPYRIGHT,
+      ExifValue::ascii(&photographer.copyright_notice(year)),
+    );
+  }
+
+  /// Writes the photographer's copyright notice and licence into an XMP packet.
+  ///
+  /// This is the XMP counterpart of `apply_rights`: the notice goes into
+  /// `dc:rights`, and `xmpRights:Marked` flags the image as rights-managed
+  /// unless the licence is a public domain dedication. The licence URL and
+  /// usage terms go into `xmpRights:WebStatement` and `xmpRights:UsageTerms`
+  /// when the photographer has them, and are left untouched otherwise.
+  pub fn apply_xmp_rights(xmp: &mut XmpDocument, photographer: &Photographer, year: i32) {
+    let rights = &photographer.rights;
+    xmp.set(
+      prop::RIGHTS,
+      &XmpValue::LangAlt(photographer.copyright_notice(year)),
+    );
+    let marked = if rights.is_public_domain() {
+      "False"
     } else {
-      String::new()
+      "True"
     };
+    xmp.set(prop::MARKED, &XmpValue::Text(marked.to_string()));
+    if let Some(url) = rights.license_url() {
+      xmp.set(prop::WEB_STATEMENT, &XmpValue::Text(url));
+    }
+    if let Some(terms) = &rights.usage_terms {
+      xmp.set(prop::USAGE_TERMS, &XmpValue::LangAlt(terms.clone()));
+    }
+  }
+
+  /// Returns the year an image was captured from the dates of an IFD tree.
+  ///
+  /// Tries `DateTimeOriginal`, then `DateTimeDigitized` and `DateTime`.
+  #[must_use]
+  pub fn capture_year(tree: &IfdTree) -> Option<i32> {
+    [
+      (tree.exif.as_ref(), tag::DATE_TIME_ORIGINAL),
+      (tree.exif.as_ref(), tag::DATE_TIME_DIGITIZED),
+      (Some(&tree.ifd0), tag::DATE_TIME),
+    ]
+    .into_iter()
+    .find_map(|(ifd, number)| Self::year_of(&ifd?.get(number)?.as_ascii()?))
+  }
+
+  /// Returns the year an image was captured from the dates of an XMP packet.
+  ///
+  /// Tries `exif:DateTimeOriginal`, then `photoshop:DateCreated` and
+  /// `xmp:CreateDate`.
+  #[must_use]
+  pub fn xmp_capture_year(xmp: &XmpDocument) -> Option<i32> {
+    [
+      prop::DATE_TIME_ORIGINAL,
+      prop::DATE_CREATED,
+      prop::CREATE_DATE,
+    ]
+    .into_iter()
+    .find_map(|property| match xmp.get(property)? {
+      XmpValue::Text(date) => Self::year_of(&date),
+      _ => None,
+    })
+  }
+
+  /// Returns the year of an EXIF ("YYYY:MM:DD HH:MM:SS") or ISO 8601 date.
+  ///
+  /// Returns `None` for the blank or zeroed dates cameras write when their
+  /// clock was not set.
+  #[must_use]
+  pub fn year_of(date: &str) -> Option<i32> {
+    let year = date.trim().get(..4)?;
+    if !year.bytes().all(|b| b.is_ascii_digit()) {
+      return None;
+    }
+    year.parse().ok().filter(|&year| year > 0)
+  }
+
+  /// Writes the photographer, caption, keywords and location from a selection
+  /// into IPTC-IIM datasets.
+  ///
+  /// The photographer goes into By-line and Credit, and their copyright
+  /// notice for the capture `year` into Copyright Notice. Their email
+  /// address, phone number and website go into Contact when they have any.
+  /// The caption, keywords and location (as Sub-location) are only written
+  /// when given, so existing ones are kept otherwise. A saved location
+  /// replaces Sub-location, City, Province/State and the country code and
+  /// name. Every other dataset is left untouched.
+  pub fn apply_iptc_selection(iptc: &mut IptcData, selection: &Selection, year: i32) {
+    let photographer = &selection.photographer;
+    iptc.set(dataset::BY_LINE, std::slice::from_ref(&photographer.name));
+    iptc.set(dataset::CREDIT, std::slice::from_ref(&photographer.name));
+    iptc.set(
+      dataset::COPYRIGHT_NOTICE,
+      &[photographer.copyright_notice(year)],
+    );
+    let contact: Vec<&str> = [
+      &photographer.email,
+      &photographer.contact.phone,
+      &photographer.contact.website,
+    ]
+    .into_iter()
+    .filter_map(Option::as_deref)
+    .collect();
+    if !contact.is_empty() {
+      iptc.set(dataset::CONTACT, &[contact.join(", ")]);
+    }
+    if let Some(caption) = &selection.caption {
+      iptc.set(dataset::CAPTION, std::slice::from_ref(caption));
+    }
+    if !selection.keywords.is_empty() {
+      iptc.set(dataset::KEYWORDS, &selection.keywords);
+    }
+    if let Some(location) = &selection.saved_location {
+      for (number, value) in [
+        (dataset::SUBLOCATION, Self::sublocation(selection)),
+        (dataset::CITY, location.city.as_ref()),
+        (dataset::PROVINCE_STATE, location.state.as_ref()),
+        (dataset::COUNTRY_CODE, location.country_code.as_ref()),
+        (dataset::COUNTRY_NAME, location.country.as_ref()),
+      ] {
+        match value {
+          Some(value) => iptc.set(number, std::slice::from_ref(value)),
+          None => {
+            iptc.remove(number);
+          }
+        }
+      }
+    } else if let Some(sublocation) = &selection.sublocation {
+      iptc.set(dataset::SUBLOCATION, std::slice::from_ref(sublocation));
+    }
+  }
+
+  /// Writes a creation date into an XMP packet.
+  ///
+  /// This is the sidecar counterpart of `apply_creation_date`: the EXIF
+  /// dates go into `exif:DateTimeOriginal`, `exif:DateTimeDigitized`,
+  /// `xmp:CreateDate` and `xmp:ModifyDate`, and the capture date is also
+  /// written to `photoshop:DateCreated`, which photo editors show. The date
+  /// is given in EXIF format and written in the ISO 8601 format XMP uses.
+  /// Every other property is left untouched.
+  pub fn apply_xmp_creation_date(xmp: &mut XmpDocument, date_string: &str) {
+    let date = NaiveDateTime::parse_from_str(date_string, "%Y:%m:%d %H:%M:%S").map_or_else(
+      |_| date_string.to_string(),
+      |date| date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    );
+    let date = XmpValue::Text(date);
+    for property in [
+      prop::DATE_TIME_ORIGINAL,
+      prop::DATE_TIME_DIGITIZED,
+      prop::DATE_CREATED,
+      prop::CREATE_DATE,
+      prop::MODIFY_DATE,
+    ] {
+      xmp.set(property, &date);
+    }
+  }
+
+  /// Writes a creation date into an IFD tree.
+  ///
+  /// Sets `DateTime` in IFD0 and `DateTimeOriginal` and `DateTimeDigitized`
+  /// in the Exif sub-IFD. The date must already be in EXIF format
+  /// ("YYYY:MM:DD HH:MM:SS").
+  pub fn apply_creation_date(tree: &mut IfdTree, date_string: &str) {
+    tree.ifd0.set(tag::DATE_TIME, ExifValue::ascii(date_string));
+    let exif = tree.exif_mut();
+    exif.set(tag::DATE_TIME_ORIGINAL, ExifValue::ascii(date_string));
+    exif.set(tag::DATE_TIME_DIGITIZED, ExifValue::ascii(date_string));
+  }
+
+  /// Describes the GPS tags `apply_gps` writes for a position, named as
+  /// reported by `ExifManager::read_exif_data`, with coordinates in decimal
+  /// degrees.
+  #[must_use]
+  pub fn create_gps_object(position: &GpsPosition) -> Vec<(String, String)> {
+    let mut gps_data = vec![
+      (
+        "GPS Latitude".to_string(),
+        format!(
+          "{:.6} {}",
+          position.latitude.abs(),
+          Self::hemisphere(position.latitude, 'N', 'S')
+        ),
+      ),
+      (
+        "GPS Longitude".to_string(),
+        format!(
+          "{:.6} {}",
+          position.longitude.abs(),
+          Self::hemisphere(position.longitude, 'E', 'W')
+        ),
+      ),
+    ];
+    if let Some(altitude) = position.altitude {
+      gps_data.push(("GPS Altitude".to_string(), format!("{altitude:.2} m")));
+    }
+    if let Some(time) = position.time {
+      gps_data.push((
+        "GPS Time Stamp".to_string(),
+        time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+      ));
+    }
+    gps_data
+  }
+
+  /// Writes a position into the GPS IFD of an IFD tree.
+  ///
+  /// The GPS IFD is replaced as a whole, so that no tag of an earlier
+  /// position is left behind. Coordinates are written as degrees, minutes and
+  /// seconds to the millisecond of arc, the altitude to the centimetre, and
+  /// the UTC time of the position, when known, as `GPSTimeStamp` and
+  /// `GPSDateStamp`.
+  pub fn apply_gps(tree: &mut IfdTree, position: &GpsPosition) {
+    tree.gps = None;
+    let gps = tree.gps_mut();
+    gps.set(tag::GPS_VERSION_ID, ExifValue::Byte(vec![2, 3, 0, 0]));
+    gps.set(
+      tag::GPS_LATITUDE_REF,
+      ExifValue::ascii(&Self::hemisphere(position.latitude, 'N', 'S').to_string()),
+    );
+    gps.set(
+      tag::GPS_LATITUDE,
+      ExifValue::Rational(Self::degrees_minutes_seconds(position.latitude)),
+    );
+    gps.set(
+      tag::GPS_LONGITUDE_REF,
+      ExifValue::ascii(&Self::hemisphere(position.longitude, 'E', 'W').to_string()),
+    );
+    gps.set(
+      tag::GPS_LONGITUDE,
+      ExifValue::Rational(Self::degrees_minutes_seconds(position.longitude)),
+    );
+    if let Some(altitude) = position.altitude {
+      gps.set(
+        tag::GPS_ALTITUDE_REF,
+        ExifValue::Byte(vec![u8::from(altitude < 0.0)]),
+      );
+      gps.set(
+        tag::GPS_ALTITUDE,
+        ExifValue::Rational(vec![((altitude.abs() * 100.0).round() as u32, 100)]),
+      );
+    }
+    if let Some(time) = position.time {
+      gps.set(
+        tag::GPS_TIME_STAMP,
+        ExifValue::Rational(vec![
+          (time.hour(), 1),
+          (time.minute(), 1),
+          (time.second(), 1),
+        ]),
+      );
+      gps.set(
+        tag::GPS_DATE_STAMP,
+        ExifValue::ascii(&time.format("%Y:%m:%d").to_string()),
+      );
+    }
+  }
 
-    let film_info = format!("{} {} (ISO {})", 
-      selection.film.maker, 
-      selection.film.name, 
-      selection.film.iso);
+  /// Writes a position into an XMP packet.
+  ///
+  /// This is the sidecar counterpart of `apply_gps`: every `exif:GPS*`
+  /// property is replaced, with coordinates given as degrees and decimal
+  /// minutes ("38,42.834000N") and the time as an ISO 8601 UTC date.
+  pub fn apply_xmp_gps(xmp: &mut XmpDocument, position: &GpsPosition) {
+    let text = |value: String| XmpValue::Text(value);
+    xmp.remove_where(|namespace, name| namespace == ns::EXIF && name.starts_with("GPS"));
+    xmp.set(prop::GPS_VERSION_ID, &text("2.3.0.0".to_string()));
+    xmp.set(
+      prop::GPS_LATITUDE,
+      &text(Self::xmp_coordinate(position.latitude, 'N', 'S')),
+    );
+    xmp.set(
+      prop::GPS_LONGITUDE,
+      &text(Self::xmp_coordinate(position.longitude, 'E', 'W')),
+    );
+    if let Some(altitude) = position.altitude {
+      xmp.set(
+        prop::GPS_ALTITUDE_REF,
+        &text(u8::from(altitude < 0.0).to_string()),
+      );
+      xmp.set(
+        prop::GPS_ALTITUDE,
+        &text(format!("{}/100", (altitude.abs() * 100.0).round() as u32)),
+      );
+    }
+    if let Some(time) = position.time {
+      xmp.set(
+        prop::GPS_TIME_STAMP,
+        &text(time.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+      );
+    }
+  }
 
+  /// Returns `positive` for coordinates of zero and above, `negative` below.
+  fn hemisphere(coordinate: f64, positive: char, negative: char) -> char {
+    if coordinate < 0.0 {
+      negative
+    } else {
+      positive
+    }
+  }
+
+  /// Splits a coordinate into degrees, minutes and seconds rationals, the
+  /// seconds in thousandths.
+  fn degrees_minutes_seconds(coordinate: f64) -> Vec<(u32, u32)> {
+    let milliseconds = (coordinate.abs() * 3_600_000.0).round() as u64;
+    let degrees = milliseconds / 3_600_000;
+    let minutes = milliseconds / 60_000 % 60;
+    let seconds = milliseconds % 60_000;
+    vec![
+      (degrees as u32, 1),
+      (minutes as u32, 1),
+      (seconds as u32, 1000),
+    ]
+  }
+
+  /// Formats a coordinate as degrees and decimal minutes followed by its
+  /// hemisphere, as XMP stores GPS coordinates.
+  fn xmp_coordinate(coordinate: f64, positive: char, negative: char) -> String {
+    let micro_minutes = (coordinate.abs() * 60_000_000.0).round() as u64;
     format!(
-      r#"<?xml version="1.0" encoding="UTF-8"?>
-<x:xmpmeta xmlns:x="adobe:ns:meta/" x:xmptk="Adobe XMP Core">
-  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
-    <rdf:Description rdf:about=""
-        xmlns:tiff="http://ns.adobe.com/tiff/1.0/"
-        xmlns:exif="http://ns.adobe.com/exif/1.0/"
-        xmlns:dc="http://purl.org/dc/elements/1.1/"
-        xmlns:aux="http://ns.adobe.com/exif/1.0/aux/">
-      <tiff:Make>{}</tiff:Make>
-      <tiff:Model>{}</tiff:Model>
-      <tiff:ImageDescription>{}</tiff:ImageDescription>
-{}
-      <exif:ISOSpeedRatings>
-        <rdf:Bag>
-          <rdf:li>{}</rdf:li>
-        </rdf:Bag>
-      </exif:ISOSpeedRatings>
-      <dc:creator>
-        <rdf:Bag>
-          <rdf:li>{}</rdf:li>
-        </rdf:Bag>
-      </dc:creator>
-    </rdf:Description>
-  </rdf:RDF>
-</x:xmpmeta>"#,
-      selection.camera.maker,
-      selection.camera.model,
-      film_info,
-      lens_metadata,
-      iso_value,
-      selection.photographer.name
+      "{},{}.{:06}{}",
+      micro_minutes / 60_000_000,
+      micro_minutes / 1_000_000 % 60,
+      micro_minutes % 1_000_000,
+      Self::hemisphere(coordinate, positive, negative)
     )
   }
+
+  /// Removes the tags belonging to the given groups from an IFD tree.
+  ///
+  /// `TagGroup::All` expands to every individual group. Structural tags,
+  /// maker notes and any metadata outside the groups are left untouched,
+  /// which makes this suitable for formats where the EXIF data cannot simply
+  /// be dropped as a whole.
+  pub fn erase_groups(tree: &mut IfdTree, groups: &[TagGroup]) {
+    for group in TagGroup::expand(groups) {
+      if group == TagGroup::Gps {
+        tree.gps = None;
+      }
+      for &number in group.ifd0_tags() {
+        tree.ifd0.remove(number);
+      }
+      if let Some(exif) = tree.exif.as_mut() {
+        for &number in group.exif_tags() {
+          exif.remove(number);
+        }
+      }
+    }
+  }
+
+  /// Removes the properties belonging to the given groups from an XMP packet.
+  ///
+  /// This is the XMP counterpart of `erase_groups`: properties outside the
+  /// groups, such as ratings, keywords or develop settings, are kept.
+  pub fn erase_xmp_groups(xmp: &mut XmpDocument, groups: &[TagGroup]) {
+    let groups = TagGroup::expand(groups);
+    xmp.remove_where(|namespace, name| {
+      groups
+        .iter()
+        .any(|group| group.contains_xmp_property(namespace, name))
+    });
+  }
+
+  /// Removes the datasets belonging to the given groups from IPTC-IIM data.
+  ///
+  /// This is the IPTC counterpart of `erase_groups`: captions, keywords and
+  /// locations are kept.
+  pub fn erase_iptc_groups(iptc: &mut IptcData, groups: &[TagGroup]) {
+    for group in TagGroup::expand(groups) {
+      for &number in group.iptc_datasets() {
+        iptc.remove(number);
+      }
+    }
+  }
+}
+
+/// A group of related metadata fields that can be erased on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, clap::ValueEnum)]
+pub enum TagGroup {
+  /// GPS position, altitude, timestamps and location
+  Gps,
+  /// Camera, lens, film, exposure and development details
+  Equipment,
+  /// Photographer, owner and copyright
+  People,
+  /// Capture, digitization and modification dates
+  Dates,
+  /// All metadata
+  All,
+}
+
+impl TagGroup {
+  /// Every individual group, i.e. what `All` stands for.
+  pub const INDIVIDUAL: [Self; 4] = [Self::Gps, Self::Equipment, Self::People, Self::Dates];
+
+  /// Returns the lowercase name used on the command line.
+  #[must_use]
+  pub const fn as_str(self) -> &'static str {
+    match self {
+      Self::Gps => "gps",
+      Self::Equipment => "equipment",
+      Self::People => "people",
+      Self::Dates => "dates",
+      Self::All => "all",
+    }
+  }
+
+  /// Returns the IFD0 tags that belong to this group.
+  #[must_use]
+  pub const fn ifd0_tags(self) -> &'static [u16] {
+    match self {
+      Self::Gps | Self::All => &[],
+      Self::Equipment => &[tag::MAKE, tag::MODEL, tag::FILM],
+      Self::People => &[tag::ARTIST, tag::COPYRIGHT, tag::XP_AUTHOR],
+      Self::Dates => &[tag::DATE_TIME],
+    }
+  }
+
+  /// Returns the Exif sub-IFD tags that belong to this group.
+  #[must_use]
+  pub const fn exif_tags(self) -> &'static [u16] {
+    match self {
+      Self::Gps | Self::All => &[],
+      Self::Equipment => &[
+        tag::EXPOSURE_TIME,
+        tag::F_NUMBER,
+        tag::EXPOSURE_PROGRAM,
+        tag::ISO_SPEED_RATINGS,
+        tag::EXPOSURE_BIAS_VALUE,
+        tag::METERING_MODE,
+        tag::MAX_APERTURE_VALUE,
+        tag::FOCAL_LENGTH,
+        tag::IMAGE_UNIQUE_ID,
+        tag::FOCAL_LENGTH_IN_35MM_FILM,
+        tag::BODY_SERIAL_NUMBER,
+        tag::LENS_SPECIFICATION,
+        tag::LENS_MAKE,
+        tag::LENS_MODEL,
+        tag::LENS_SERIAL_NUMBER,
+        tag::USER_COMMENT,
+      ],
+      Self::People => &[tag::CAMERA_OWNER_NAME],
+      Self::Dates => &[
+        tag::DATE_TIME_ORIGINAL,
+        tag::DATE_TIME_DIGITIZED,
+        tag::OFFSET_TIME,
+        tag::OFFSET_TIME_ORIGINAL,
+        tag::OFFSET_TIME_DIGITIZED,
+        tag::SUB_SEC_TIME,
+        tag::SUB_SEC_TIME_ORIGINAL,
+        tag::SUB_SEC_TIME_DIGITIZED,
+      ],
+    }
+  }
+
+  /// Returns the IPTC-IIM application record datasets that belong to this
+  /// group.
+  #[must_use]
+  pub const fn iptc_datasets(self) -> &'static [u8] {
+    match self {
+      Self::Gps => &[
+        dataset::CITY,
+        dataset::SUBLOCATION,
+        dataset::PROVINCE_STATE,
+        dataset::COUNTRY_CODE,
+        dataset::COUNTRY_NAME,
+      ],
+      Self::Equipment | Self::All => &[],
+      Self::People => &[
+        dataset::BY_LINE,
+        dataset::BY_LINE_TITLE,
+        dataset::CREDIT,
+        dataset::COPYRIGHT_NOTICE,
+        dataset::CONTACT,
+      ],
+      Self::Dates => &[
+        dataset::DATE_CREATED,
+        dataset::TIME_CREATED,
+        dataset::DIGITAL_CREATION_DATE,
+        dataset::DIGITAL_CREATION_TIME,
+      ],
+    }
+  }
+
+  /// Returns whether the XMP property with the given namespace URI and local
+  /// name belongs to this group.
+  #[must_use]
+  pub fn contains_xmp_property(self, namespace: &str, name: &str) -> bool {
+    match self {
+      Self::All => Self::INDIVIDUAL
+        .iter()
+        .any(|group| group.contains_xmp_property(namespace, name)),
+      Self::Gps => {
+        (namespace == ns::EXIF && name.starts_with("GPS"))
+          || matches!(
+            (namespace, name),
+            (ns::IPTC_CORE, "Location" | "CountryCode")
+              | (ns::PHOTOSHOP, "City" | "State" | "Country")
+          )
+      }
+      Self::Equipment => matches!(
+        (namespace, name),
+        (ns::TIFF, "Make" | "Model")
+          | (
+            ns::IFEX,
+            "Film"
+              | "Roll"
+              | "Developer"
+              | "Dilution"
+              | "DevelopmentTime"
+              | "DevelopmentTemperature"
+              | "Lab"
+              | "ScanDate"
+              | "PushPull"
+          )
+          | (
+            ns::EXIF,
+            "ExposureTime"
+              | "FNumber"
+              | "ExposureProgram"
+              | "ISOSpeedRatings"
+              | "ExposureBiasValue"
+              | "MeteringMode"
+              | "MaxApertureValue"
+              | "FocalLength"
+              | "FocalLengthIn35mmFilm"
+              | "UserComment"
+          )
+          | (
+            ns::EXIF_EX,
+            "PhotographicSensitivity"
+              | "BodySerialNumber"
+              | "LensSpecification"
+              | "LensMake"
+              | "LensModel"
+              | "LensSerialNumber"
+          )
+          | (
+            ns::AUX,
+            "Lens" | "LensInfo" | "LensModel" | "LensSerialNumber" | "SerialNumber"
+          )
+      ),
+      Self::People => matches!(
+        (namespace, name),
+        (ns::TIFF, "Artist" | "Copyright")
+          | (ns::DC, "creator" | "rights")
+          | (ns::XMP_RIGHTS, "Marked" | "WebStatement" | "UsageTerms")
+          | (ns::PHOTOSHOP, "Credit")
+          | (ns::IPTC_CORE, "CreatorContactInfo")
+          | (ns::EXIF_EX, "CameraOwnerName")
+      ),
+      Self::Dates => matches!(
+        (namespace, name),
+        (ns::TIFF, "DateTime")
+          | (ns::EXIF, "DateTimeOriginal" | "DateTimeDigitized")
+          | (ns::XMP, "CreateDate" | "ModifyDate")
+          | (ns::PHOTOSHOP, "DateCreated")
+      ),
+    }
+  }
+
+  /// Expands `All` into the individual groups and removes duplicates.
+  #[must_use]
+  pub fn expand(groups: &[Self]) -> Vec<Self> {
+    let mut expanded: Vec<Self> = groups
+      .iter()
+      .flat_map(|&group| {
+        if group == Self::All {
+          Self::INDIVIDUAL.to_vec()
+        } else {
+          vec![group]
+        }
+      })
+      .collect();
+    expanded.sort_unstable();
+    expanded.dedup();
+    expanded
+  }
+
+  /// Works out which groups to erase from `--only` and `--keep` selections.
+  ///
+  /// Returns `[All]` when everything is selected and nothing is kept, so
+  /// processors can drop metadata wholesale; otherwise returns the individual
+  /// groups that remain, which may be empty.
+  #[must_use]
+  pub fn resolve(only: &[Self], keep: &[Self]) -> Vec<Self> {
+    if keep.is_empty() && (only.is_empty() || only.contains(&Self::All)) {
+      return vec![Self::All];
+    }
+    let kept = Self::expand(keep);
+    let only = if only.is_empty() {
+      &[Self::All][..]
+    } else {
+      only
+    };
+    Self::expand(only)
+      .into_iter()
+      .filter(|group| !kept.contains(group))
+      .collect()
+  }
+}
+
+impl std::fmt::Display for TagGroup {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
 }
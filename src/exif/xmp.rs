@@ -0,0 +1,1078 @@
+//! Editable model of XMP packets.
+//!
+//! This module parses an XMP packet (such as a RAW sidecar) into an
+//! [`XmpDocument`] that keeps every node of the original XML, lets callers
+//! read, set and remove individual properties by namespace, and serializes the
+//! document back. Elements that are not edited are written out exactly as they
+//! were read, so properties owned by other applications survive a round trip.
+
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// XMP namespace URIs.
+pub mod ns {
+  /// XMP packet wrapper (`x:`)
+  pub const X: &str = "adobe:ns:meta/";
+  /// RDF syntax (`rdf:`)
+  pub const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+  /// XML itself (`xml:`), always bound
+  pub const XML: &str = "http://www.w3.org/XML/1998/namespace";
+  /// TIFF properties (`tiff:`)
+  pub const TIFF: &str = "http://ns.adobe.com/tiff/1.0/";
+  /// EXIF properties (`exif:`)
+  pub const EXIF: &str = "http://ns.adobe.com/exif/1.0/";
+  /// Dublin Core (`dc:`)
+  pub const DC: &str = "http://purl.org/dc/elements/1.1/";
+  /// Adobe auxiliary EXIF properties (`aux:`)
+  pub const AUX: &str = "http://ns.adobe.com/exif/1.0/aux/";
+  /// EXIF 2.3 properties (`exifEX:`)
+  pub const EXIF_EX: &str = "http://cipa.jp/exif/1.0/";
+  /// XMP basic properties (`xmp:`)
+  pub const XMP: &str = "http://ns.adobe.com/xap/1.0/";
+  /// Photoshop properties (`photoshop:`)
+  pub const PHOTOSHOP: &str = "http://ns.adobe.com/photoshop/1.0/";
+  /// IPTC Core properties (`Iptc4xmpCore:`)
+  pub const IPTC_CORE: &str = "http://iptc.org/std/Iptc4xmpCore/1.0/xmlns/";
+  /// XMP rights management (`xmpRights:`)
+  pub const XMP_RIGHTS: &str = "http://ns.adobe.com/xap/1.0/rights/";
+  /// XMP notes (`xmpNote:`), used to link Extended XMP in JPEG files
+  pub const XMP_NOTE: &str = "http://ns.adobe.com/xmp/note/";
+  /// IFEX properties without a standard equivalent (`ifex:`)
+  pub const IFEX: &str = "https://github.com/danielfilho/ifex/ns/1.0/";
+}
+
+/// A property identified by namespace URI and local name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XmpProperty {
+  /// Namespace URI
+  pub namespace: &'static str,
+  /// Prefix used when the namespace has to be declared
+  pub prefix: &'static str,
+  /// Local name of the property
+  pub name: &'static str,
+}
+
+impl XmpProperty {
+  /// Creates a property from its preferred prefix, namespace URI and local name.
+  #[must_use]
+  pub const fn new(prefix: &'static str, namespace: &'static str, name: &'static str) -> Self {
+    Self {
+      namespace,
+      prefix,
+      name,
+    }
+  }
+}
+
+/// Well-known properties used by the IFEX writers.
+pub mod prop {
+  use super::{ns, XmpProperty};
+
+  /// Camera manufacturer
+  pub const MAKE: XmpProperty = XmpProperty::new("tiff", ns::TIFF, "Make");
+  /// Camera model
+  pub const MODEL: XmpProperty = XmpProperty::new("tiff", ns::TIFF, "Model");
+  /// Image description
+  pub const IMAGE_DESCRIPTION: XmpProperty = XmpProperty::new("tiff", ns::TIFF, "ImageDescription");
+  /// Original capture date and time
+  pub const DATE_TIME_ORIGINAL: XmpProperty =
+    XmpProperty::new("exif", ns::EXIF, "DateTimeOriginal");
+  /// Digitization date and time
+  pub const DATE_TIME_DIGITIZED: XmpProperty =
+    XmpProperty::new("exif", ns::EXIF, "DateTimeDigitized");
+  /// Date the image was created, as shown by photo editors
+  pub const DATE_CREATED: XmpProperty = XmpProperty::new("photoshop", ns::PHOTOSHOP, "DateCreated");
+  /// Date the digital file was created
+  pub const CREATE_DATE: XmpProperty = XmpProperty::new("xmp", ns::XMP, "CreateDate");
+  /// Date the file was last modified
+  pub const MODIFY_DATE: XmpProperty = XmpProperty::new("xmp", ns::XMP, "ModifyDate");
+  /// Lens focal length, a rational
+  pub const FOCAL_LENGTH: XmpProperty = XmpProperty::new("exif", ns::EXIF, "FocalLength");
+  /// Lens f-number, a rational
+  pub const F_NUMBER: XmpProperty = XmpProperty::new("exif", ns::EXIF, "FNumber");
+  /// Exposure time in seconds, as a rational
+  pub const EXPOSURE_TIME: XmpProperty = XmpProperty::new("exif", ns::EXIF, "ExposureTime");
+  /// Exposure compensation in EV, as a signed rational
+  pub const EXPOSURE_BIAS_VALUE: XmpProperty =
+    XmpProperty::new("exif", ns::EXIF, "ExposureBiasValue");
+  /// Metering mode, with the values of the EXIF tag
+  pub const METERING_MODE: XmpProperty = XmpProperty::new("exif", ns::EXIF, "MeteringMode");
+  /// Exposure program, with the values of the EXIF tag
+  pub const EXPOSURE_PROGRAM: XmpProperty = XmpProperty::new("exif", ns::EXIF, "ExposureProgram");
+  /// ISO speed, an ordered array (`rdf:Seq`)
+  pub const ISO_SPEED_RATINGS: XmpProperty = XmpProperty::new("exif", ns::EXIF, "ISOSpeedRatings");
+  /// Lens manufacturer
+  pub const LENS_MAKE: XmpProperty = XmpProperty::new("exifEX", ns::EXIF_EX, "LensMake");
+  /// Lens model
+  pub const LENS_MODEL: XmpProperty = XmpProperty::new("exifEX", ns::EXIF_EX, "LensModel");
+  /// Focal length and aperture range of the lens, an ordered array (`rdf:Seq`) of four rationals
+  pub const LENS_SPECIFICATION: XmpProperty =
+    XmpProperty::new("exifEX", ns::EXIF_EX, "LensSpecification");
+  /// Lens description, as shown by Lightroom
+  pub const LENS: XmpProperty = XmpProperty::new("aux", ns::AUX, "Lens");
+  /// Film stock, the counterpart of the EXIF Film tag
+  pub const FILM: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "Film");
+  /// Roll the image was shot on, the counterpart of the EXIF `ImageUniqueID`
+  pub const ROLL: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "Roll");
+  /// Developer the film was developed in
+  pub const DEVELOPER: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "Developer");
+  /// Dilution of the developer, such as "1+31"
+  pub const DILUTION: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "Dilution");
+  /// Development time as minutes and seconds, such as "9:30"
+  pub const DEVELOPMENT_TIME: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "DevelopmentTime");
+  /// Temperature of the developer in degrees Celsius
+  pub const DEVELOPMENT_TEMPERATURE: XmpProperty =
+    XmpProperty::new("ifex", ns::IFEX, "DevelopmentTemperature");
+  /// Lab or person who developed the film
+  pub const LAB: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "Lab");
+  /// Date the negative was scanned, as YYYY-MM-DD
+  pub const SCAN_DATE: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "ScanDate");
+  /// Stops the film was pushed (positive) or pulled (negative), such as "+1"
+  /// or "-2/3"
+  pub const PUSH_PULL: XmpProperty = XmpProperty::new("ifex", ns::IFEX, "PushPull");
+  /// Comment on the image, a language alternative (`rdf:Alt`)
+  pub const USER_COMMENT: XmpProperty = XmpProperty::new("exif", ns::EXIF, "UserComment");
+  /// GPS tag version, as "2.3.0.0"
+  pub const GPS_VERSION_ID: XmpProperty = XmpProperty::new("exif", ns::EXIF, "GPSVersionID");
+  /// Latitude, as "DDD,MM.mmmmmmK"
+  pub const GPS_LATITUDE: XmpProperty = XmpProperty::new("exif", ns::EXIF, "GPSLatitude");
+  /// Longitude, as "DDD,MM.mmmmmmK"
+  pub const GPS_LONGITUDE: XmpProperty = XmpProperty::new("exif", ns::EXIF, "GPSLongitude");
+  /// "0" above sea level, "1" below
+  pub const GPS_ALTITUDE_REF: XmpProperty = XmpProperty::new("exif", ns::EXIF, "GPSAltitudeRef");
+  /// Altitude in metres, as a rational
+  pub const GPS_ALTITUDE: XmpProperty = XmpProperty::new("exif", ns::EXIF, "GPSAltitude");
+  /// UTC date and time of the position
+  pub const GPS_TIME_STAMP: XmpProperty = XmpProperty::new("exif", ns::EXIF, "GPSTimeStamp");
+  /// Creators of the image, an ordered array (`rdf:Seq`)
+  pub const CREATOR: XmpProperty = XmpProperty::new("dc", ns::DC, "creator");
+  /// Credit line of the image
+  pub const CREDIT: XmpProperty = XmpProperty::new("photoshop", ns::PHOTOSHOP, "Credit");
+  /// Contact details of the creator, a structure of address, phone, email
+  /// and website fields
+  pub const CREATOR_CONTACT_INFO: XmpProperty =
+    XmpProperty::new("Iptc4xmpCore", ns::IPTC_CORE, "CreatorContactInfo");
+  /// Name of the place the image was taken, the IPTC sublocation
+  pub const LOCATION: XmpProperty = XmpProperty::new("Iptc4xmpCore", ns::IPTC_CORE, "Location");
+  /// City the image was taken in
+  pub const CITY: XmpProperty = XmpProperty::new("photoshop", ns::PHOTOSHOP, "City");
+  /// State, province or region the image was taken in
+  pub const STATE: XmpProperty = XmpProperty::new("photoshop", ns::PHOTOSHOP, "State");
+  /// Country the image was taken in
+  pub const COUNTRY: XmpProperty = XmpProperty::new("photoshop", ns::PHOTOSHOP, "Country");
+  /// ISO 3166 code of the country the image was taken in
+  pub const COUNTRY_CODE: XmpProperty =
+    XmpProperty::new("Iptc4xmpCore", ns::IPTC_CORE, "CountryCode");
+  /// Copyright notice, a language alternative (`rdf:Alt`)
+  pub const RIGHTS: XmpProperty = XmpProperty::new("dc", ns::DC, "rights");
+  /// Whether the image is rights-managed ("True") or public domain ("False")
+  pub const MARKED: XmpProperty = XmpProperty::new("xmpRights", ns::XMP_RIGHTS, "Marked");
+  /// URL of the licence or a statement of the rights
+  pub const WEB_STATEMENT: XmpProperty =
+    XmpProperty::new("xmpRights", ns::XMP_RIGHTS, "WebStatement");
+  /// Terms under which the image may be used, a language alternative
+  pub const USAGE_TERMS: XmpProperty = XmpProperty::new("xmpRights", ns::XMP_RIGHTS, "UsageTerms");
+  /// Caption, a language alternative (`rdf:Alt`)
+  pub const DESCRIPTION: XmpProperty = XmpProperty::new("dc", ns::DC, "description");
+  /// Keywords, an unordered array (`rdf:Bag`)
+  pub const SUBJECT: XmpProperty = XmpProperty::new("dc", ns::DC, "subject");
+  /// GUID of the Extended XMP belonging to a packet embedded in a JPEG file
+  pub const HAS_EXTENDED_XMP: XmpProperty =
+    XmpProperty::new("xmpNote", ns::XMP_NOTE, "HasExtendedXMP");
+}
+
+/// Value of an XMP property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmpValue {
+  /// Simple text value
+  Text(String),
+  /// Unordered array (`rdf:Bag`)
+  Bag(Vec<String>),
+  /// Ordered array (`rdf:Seq`)
+  Seq(Vec<String>),
+  /// Language alternative (`rdf:Alt`) with only a default language entry
+  LangAlt(String),
+  /// Structure of simple text fields, given by local name, in the namespace
+  /// of the property
+  Struct(Vec<(String, String)>),
+}
+
+/// A property as found in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmpEntry {
+  /// Namespace URI of the property
+  pub namespace: String,
+  /// Name as written in the document, such as "crs:Exposure2012"
+  pub qualified_name: String,
+  /// Value of the property
+  pub value: XmpValue,
+}
+
+impl XmpEntry {
+  /// Returns the name of the property without its prefix.
+  #[must_use]
+  pub fn local_name(&self) -> &str {
+    self
+      .qualified_name
+      .split_once(':')
+      .map_or(self.qualified_name.as_str(), |(_, local)| local)
+  }
+}
+
+/// A node of the XML tree.
+#[derive(Debug, Clone)]
+enum Node {
+  /// Element with its children
+  Element(Element),
+  /// Character data, kept escaped exactly as read
+  Text(String),
+  /// Comments, CDATA, processing instructions and declarations, kept verbatim
+  Raw(String),
+}
+
+/// An XML element.
+#[derive(Debug, Clone)]
+struct Element {
+  /// Qualified name as written, such as "tiff:Make"
+  name: String,
+  /// Start tag contents between the angle brackets, kept as written
+  start: String,
+  /// Whether the element was written as `<name/>`
+  self_closing: bool,
+  children: Vec<Node>,
+}
+
+/// An attribute in the start tag of an element.
+struct Attribute<'a> {
+  key: &'a str,
+  /// Value kept escaped as written
+  value: &'a str,
+  /// Bytes of the start tag covered by the attribute, including the whitespace before it
+  span: Range<usize>,
+}
+
+impl Element {
+  fn new(name: &str) -> Self {
+    Self {
+      name: name.to_string(),
+      start: name.to_string(),
+      self_closing: false,
+      children: Vec::new(),
+    }
+  }
+
+  fn from_start(
+    start: &BytesStart,
+    self_closing: bool,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    // Reject malformed or duplicate attributes up front
+    for attribute in start.attributes() {
+      attribute?;
+    }
+    Ok(Self {
+      name: String::from_utf8(start.name().as_ref().to_vec())?,
+      start: String::from_utf8(start.to_vec())?,
+      self_closing,
+      children: Vec::new(),
+    })
+  }
+
+  /// Lists the attributes of the start tag.
+  fn attributes(&self) -> Vec<Attribute<'_>> {
+    let bytes = self.start.as_bytes();
+    let skip_whitespace = |mut i: usize| {
+      while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+      }
+      i
+    };
+
+    let mut attributes = Vec::new();
+    let mut i = self.name.len();
+    loop {
+      let span_start = i;
+      let key_start = skip_whitespace(i);
+      let mut key_end = key_start;
+      while key_end < bytes.len() && bytes[key_end] != b'=' && !bytes[key_end].is_ascii_whitespace()
+      {
+        key_end += 1;
+      }
+      let equals = skip_whitespace(key_end);
+      if key_start == key_end || bytes.get(equals) != Some(&b'=') {
+        break;
+      }
+      let quote_start = skip_whitespace(equals + 1);
+      let Some(&quote) = bytes.get(quote_start).filter(|q| matches!(q, b'"' | b'\'')) else {
+        break;
+      };
+      let Some(length) = bytes[quote_start + 1..].iter().position(|&b| b == quote) else {
+        break;
+      };
+      i = quote_start + 1 + length + 1;
+      attributes.push(Attribute {
+        key: &self.start[key_start..key_end],
+        value: &self.start[quote_start + 1..i - 1],
+        span: span_start..i,
+      });
+    }
+    attributes
+  }
+
+  /// Adds an attribute, keeping namespace declarations together and reusing
+  /// the whitespace that precedes the neighbouring attribute.
+  fn push_attribute(&mut self, key: &str, raw_value: &str) {
+    let (position, separator) = {
+      let attributes = self.attributes();
+      let is_declaration = |key: &str| key.starts_with("xmlns");
+      let anchor = attributes
+        .iter()
+        .rev()
+        .find(|a| is_declaration(a.key) == is_declaration(key))
+        .or_else(|| attributes.last());
+      anchor.map_or_else(
+        || (self.name.len(), " ".to_string()),
+        |a| {
+          let written = &self.start[a.span.clone()];
+          let separator = &written[..written.len() - written.trim_start().len()];
+          (a.span.end, separator.to_string())
+        },
+      )
+    };
+    let quote = if raw_value.contains('"') { '\'' } else { '"' };
+    self.start.insert_str(
+      position,
+      &format!("{separator}{key}={quote}{raw_value}{quote}"),
+    );
+  }
+
+  /// Removes the attributes whose key matches, returning whether any was removed.
+  fn remove_attributes(&mut self, matches: impl Fn(&str) -> bool) -> bool {
+    let spans: Vec<Range<usize>> = self
+      .attributes()
+      .into_iter()
+      .filter(|a| matches(a.key))
+      .map(|a| a.span)
+      .collect();
+    for span in spans.iter().rev() {
+      self.start.replace_range(span.clone(), "");
+    }
+    !spans.is_empty()
+  }
+
+  /// Adds the prefixes used by the names of this element, its attributes and
+  /// its descendants to `prefixes`.
+  fn collect_prefixes(&self, prefixes: &mut Vec<String>) {
+    let names = std::iter::once(self.name.as_str()).chain(
+      self
+        .attributes()
+        .into_iter()
+        .map(|a| a.key)
+        .filter(|key| *key != "xmlns" && !key.starts_with("xmlns:")),
+    );
+    for name in names {
+      if let Some((prefix, _)) = name.split_once(':') {
+        if !prefixes.iter().any(|p| p == prefix) {
+          prefixes.push(prefix.to_string());
+        }
+      }
+    }
+    for child in self.child_elements() {
+      child.collect_prefixes(prefixes);
+    }
+  }
+
+  fn text(&self) -> String {
+    self
+      .children
+      .iter()
+      .filter_map(|node| match node {
+        Node::Text(raw) => Some(unescape_lossy(raw)),
+        _ => None,
+      })
+      .collect()
+  }
+
+  fn child_elements(&self) -> impl Iterator<Item = &Self> {
+    self.children.iter().filter_map(|node| match node {
+      Node::Element(element) => Some(element),
+      _ => None,
+    })
+  }
+
+  /// Returns the line break and indentation preceding the last child element.
+  fn child_indent(&self) -> Option<String> {
+    self.children.windows(2).rev().find_map(|pair| match pair {
+      [Node::Text(raw), Node::Element(_)] if raw.trim().is_empty() => {
+        raw.rfind('\n').map(|newline| raw[newline..].to_string())
+      }
+      _ => None,
+    })
+  }
+
+  fn write(&self, out: &mut String) {
+    out.push('<');
+    out.push_str(&self.start);
+    if self.self_closing && self.children.is_empty() {
+      out.push_str("/>");
+      return;
+    }
+    out.push('>');
+    for child in &self.children {
+      match child {
+        Node::Element(element) => element.write(out),
+        Node::Text(raw) | Node::Raw(raw) => out.push_str(raw),
+      }
+    }
+    out.push_str("</");
+    out.push_str(&self.name);
+    out.push('>');
+  }
+}
+
+/// Namespace prefixes in scope at an element.
+#[derive(Debug, Clone)]
+struct Scope(HashMap<String, String>);
+
+impl Scope {
+  fn root() -> Self {
+    Self(HashMap::from([("xml".to_string(), ns::XML.to_string())]))
+  }
+
+  /// Returns the scope inside `element`, including its own declarations.
+  fn enter(&self, element: &Element) -> Self {
+    let mut scope = self.clone();
+    for attribute in element.attributes() {
+      if let Some(prefix) = attribute.key.strip_prefix("xmlns:") {
+        scope
+          .0
+          .insert(prefix.to_string(), unescape_lossy(attribute.value));
+      }
+    }
+    scope
+  }
+
+  /// Resolves a qualified name into its namespace URI and local name.
+  fn resolve<'a>(&'a self, name: &'a str) -> Option<(&'a str, &'a str)> {
+    let (prefix, local) = name.split_once(':')?;
+    Some((self.0.get(prefix)?.as_str(), local))
+  }
+
+  fn is(&self, name: &str, namespace: &str, local: &str) -> bool {
+    self.resolve(name) == Some((namespace, local))
+  }
+
+  fn prefix_for(&self, namespace: &str) -> Option<&str> {
+    let mut prefixes: Vec<&str> = self
+      .0
+      .iter()
+      .filter(|(_, uri)| uri.as_str() == namespace)
+      .map(|(prefix, _)| prefix.as_str())
+      .collect();
+    prefixes.sort_unstable();
+    prefixes.first().copied()
+  }
+}
+
+/// An XMP packet that can be edited property by property.
+#[derive(Debug, Clone)]
+pub struct XmpDocument {
+  nodes: Vec<Node>,
+}
+
+impl Default for XmpDocument {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl XmpDocument {
+  /// Creates a packet with a single empty `rdf:Description`.
+  #[must_use]
+  pub fn new() -> Self {
+    let mut description = Element::new("rdf:Description");
+    description.push_attribute("rdf:about", "");
+    description.children.push(Node::Text("\n    ".to_string()));
+
+    let mut rdf = Element::new("rdf:RDF");
+    rdf.push_attribute("xmlns:rdf", ns::RDF);
+    rdf.children = vec![
+      Node::Text("\n    ".to_string()),
+      Node::Element(description),
+      Node::Text("\n  ".to_string()),
+    ];
+
+    let mut xmpmeta = Element::new("x:xmpmeta");
+    xmpmeta.push_attribute("xmlns:x", ns::X);
+    xmpmeta.push_attribute("x:xmptk", "Adobe XMP Core");
+    xmpmeta.children = vec![
+      Node::Text("\n  ".to_string()),
+      Node::Element(rdf),
+      Node::Text("\n".to_string()),
+    ];
+
+    Self {
+      nodes: vec![
+        Node::Raw(r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string()),
+        Node::Text("\n".to_string()),
+        Node::Element(xmpmeta),
+      ],
+    }
+  }
+
+  /// Parses an XMP packet.
+  ///
+  /// Fails if the XML is not well formed or contains no `rdf:RDF` element.
+  pub fn parse(xml: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<Element> = Vec::new();
+    let mut nodes = Vec::new();
+
+    loop {
+      let node = match reader.read_event()? {
+        Event::Start(start) => {
+          stack.push(Element::from_start(&start, false)?);
+          continue;
+        }
+        Event::End(_) => Node::Element(stack.pop().ok_or("Unexpected closing tag in XMP")?),
+        Event::Empty(start) => Node::Element(Element::from_start(&start, true)?),
+        Event::Text(text) => Node::Text(String::from_utf8(text.into_inner().to_vec())?),
+        Event::CData(data) => Node::Raw(format!(
+          "<![CDATA[{}]]>",
+          String::from_utf8(data.into_inner().to_vec())?
+        )),
+        Event::Comment(comment) => Node::Raw(format!(
+          "<!--{}-->",
+          String::from_utf8(comment.into_inner().to_vec())?
+        )),
+        Event::Decl(decl) => Node::Raw(format!("<?{}?>", String::from_utf8(decl.to_vec())?)),
+        Event::PI(pi) => Node::Raw(format!("<?{}?>", String::from_utf8(pi.to_vec())?)),
+        Event::DocType(doctype) => Node::Raw(format!(
+          "<!DOCTYPE{}>",
+          String::from_utf8(doctype.into_inner().to_vec())?
+        )),
+        Event::Eof => break,
+      };
+      match stack.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => nodes.push(node),
+      }
+    }
+    if !stack.is_empty() {
+      return Err("Unclosed element in XMP".into());
+    }
+
+    let document = Self { nodes };
+    if document.rdf_path().is_none() {
+      return Err("XMP packet has no rdf:RDF element".into());
+    }
+    Ok(document)
+  }
+
+  /// Serializes the packet back to XML.
+  #[must_use]
+  pub fn to_xml(&self) -> String {
+    let mut out = String::new();
+    for node in &self.nodes {
+      match node {
+        Node::Element(element) => element.write(&mut out),
+        Node::Text(raw) | Node::Raw(raw) => out.push_str(raw),
+      }
+    }
+    out
+  }
+
+  /// Returns the value of a property, looking at every `rdf:Description`.
+  ///
+  /// Properties written as attributes of the description are returned as
+  /// text. Structures are returned with their simple fields only.
+  #[must_use]
+  pub fn get(&self, property: XmpProperty) -> Option<XmpValue> {
+    self.descriptions().into_iter().find_map(|(path, scope)| {
+      let description = self.element(&path)?;
+      let scope = scope.enter(description);
+      let attribute = description
+        .attributes()
+        .into_iter()
+        .find(|a| scope.is(a.key, property.namespace, property.name))
+        .map(|a| XmpValue::Text(unescape_lossy(a.value)));
+      attribute.or_else(|| {
+        description
+          .child_elements()
+          .find(|child| {
+            scope
+              .enter(child)
+              .is(&child.name, property.namespace, property.name)
+          })
+          .map(|child| Self::value_of(child, &scope.enter(child)))
+      })
+    })
+  }
+
+  /// Lists the properties of every `rdf:Description` in document order.
+  ///
+  /// Attributes come before elements within each description. Namespace
+  /// declarations, RDF attributes and properties whose prefix is not bound
+  /// are skipped.
+  #[must_use]
+  pub fn properties(&self) -> Vec<XmpEntry> {
+    let mut entries = Vec::new();
+    for (path, scope) in self.descriptions() {
+      let Some(description) = self.element(&path) else {
+        continue;
+      };
+      let scope = scope.enter(description);
+      let entry = |scope: &Scope, name: &str, value: XmpValue| {
+        let (namespace, _) = scope.resolve(name)?;
+        (namespace != ns::RDF && namespace != ns::XML).then(|| XmpEntry {
+          namespace: namespace.to_string(),
+          qualified_name: name.to_string(),
+          value,
+        })
+      };
+
+      for attribute in description.attributes() {
+        if attribute.key == "xmlns" || attribute.key.starts_with("xmlns:") {
+          continue;
+        }
+        entries.extend(entry(
+          &scope,
+          attribute.key,
+          XmpValue::Text(unescape_lossy(attribute.value)),
+        ));
+      }
+      for child in description.child_elements() {
+        let scope = scope.enter(child);
+        entries.extend(entry(&scope, &child.name, Self::value_of(child, &scope)));
+      }
+    }
+    entries
+  }
+
+  /// Sets a property, replacing any existing value.
+  ///
+  /// The property is written as an element of the first `rdf:Description`,
+  /// declaring its namespace there if needed.
+  pub fn set(&mut self, property: XmpProperty, value: &XmpValue) {
+    self.remove(property);
+
+    let Some((path, scope)) = self.descriptions().into_iter().next() else {
+      return;
+    };
+    let Some(description) = self.element_mut(&path) else {
+      return;
+    };
+    let scope = scope.enter(description);
+
+    let prefix = if let Some(prefix) = scope.prefix_for(property.namespace) {
+      prefix.to_string()
+    } else {
+      let prefix = Self::free_prefix(&scope, property.prefix);
+      description.push_attribute(&format!("xmlns:{prefix}"), &escape(property.namespace));
+      prefix
+    };
+    let rdf = scope.prefix_for(ns::RDF).unwrap_or("rdf").to_string();
+
+    Self::append_property(description, path.len(), |indent| {
+      Self::property_element(&format!("{prefix}:{}", property.name), value, &rdf, indent)
+    });
+  }
+
+  /// Removes a property from every `rdf:Description`.
+  ///
+  /// Returns whether the property was present.
+  pub fn remove(&mut self, property: XmpProperty) -> bool {
+    self.remove_where(|namespace, name| namespace == property.namespace && name == property.name)
+  }
+
+  /// Removes every property for which `matches` returns true, given the
+  /// namespace URI and local name of the property.
+  ///
+  /// Returns whether any property was removed.
+  pub fn remove_where(&mut self, matches: impl Fn(&str, &str) -> bool) -> bool {
+    let is_property = |scope: &Scope, name: &str| {
+      scope.resolve(name).is_some_and(|(namespace, local)| {
+        namespace != ns::RDF && namespace != ns::XML && matches(namespace, local)
+      })
+    };
+
+    let mut removed = false;
+    for (path, scope) in self.descriptions() {
+      let Some(description) = self.element_mut(&path) else {
+        continue;
+      };
+      let scope = scope.enter(description);
+
+      if description.remove_attributes(|key| is_property(&scope, key)) {
+        removed = true;
+      }
+
+      let mut index = 0;
+      while index < description.children.len() {
+        let matches = matches!(
+          &description.children[index],
+          Node::Element(child) if is_property(&scope.enter(child), &child.name)
+        );
+        if !matches {
+          index += 1;
+          continue;
+        }
+        description.children.remove(index);
+        // Drop the indentation that preceded the element as well
+        if index > 0
+          && matches!(&description.children[index - 1], Node::Text(raw) if raw.trim().is_empty())
+        {
+          description.children.remove(index - 1);
+          index -= 1;
+        }
+        removed = true;
+      }
+    }
+    removed
+  }
+
+  /// Copies every property of `other` into this packet, replacing properties
+  /// of the same name.
+  ///
+  /// Properties are copied with the namespace declarations they need, so
+  /// prefixes bound differently in both packets do not clash.
+  pub fn merge(&mut self, other: &Self) {
+    for (namespace, name, element) in other.detached_properties() {
+      self.remove_where(|n, l| n == namespace && l == name);
+      self.insert(element);
+    }
+  }
+
+  /// Moves properties into a new packet until this one serializes to at
+  /// most `limit` bytes, largest properties first.
+  ///
+  /// Returns `None` if the packet fits already. The packet may still exceed
+  /// the limit if everything but its skeleton has been moved.
+  pub fn split_off(&mut self, limit: usize) -> Option<Self> {
+    if self.to_xml().len() <= limit {
+      return None;
+    }
+    let mut properties = self.detached_properties();
+    properties.sort_by_key(|(_, _, element)| {
+      let mut written = String::new();
+      element.write(&mut written);
+      std::cmp::Reverse(written.len())
+    });
+
+    let mut moved = Self::new();
+    for (namespace, name, element) in properties {
+      if self.to_xml().len() <= limit {
+        break;
+      }
+      self.remove_where(|n, l| n == namespace && l == name);
+      moved.insert(element);
+    }
+    Some(moved)
+  }
+
+  /// Lists every property as a standalone element with its namespace URI
+  /// and local name.
+  ///
+  /// Properties written as attributes become simple elements. Each element
+  /// declares the prefixes it uses that were bound outside of it.
+  fn detached_properties(&self) -> Vec<(String, String, Element)> {
+    let mut properties = Vec::new();
+    for (path, scope) in self.descriptions() {
+      let Some(description) = self.element(&path) else {
+        continue;
+      };
+      let scope = scope.enter(description);
+      let mut detach = |mut element: Element, inner: &Scope| {
+        let Some((namespace, local)) = inner.resolve(&element.name) else {
+          return;
+        };
+        if namespace == ns::RDF || namespace == ns::XML {
+          return;
+        }
+        let (namespace, local) = (namespace.to_string(), local.to_string());
+        let mut prefixes = Vec::new();
+        element.collect_prefixes(&mut prefixes);
+        let declared: Vec<String> = element
+          .attributes()
+          .iter()
+          .filter_map(|a| a.key.strip_prefix("xmlns:").map(str::to_string))
+          .collect();
+        for prefix in prefixes {
+          if declared.contains(&prefix) {
+            continue;
+          }
+          if let Some(uri) = scope.0.get(&prefix).filter(|_| prefix != "xml") {
+            element.push_attribute(&format!("xmlns:{prefix}"), &escape(uri.as_str()));
+          }
+        }
+        properties.push((namespace, local, element));
+      };
+
+      for attribute in description.attributes() {
+        if attribute.key == "xmlns" || attribute.key.starts_with("xmlns:") {
+          continue;
+        }
+        let mut element = Element::new(attribute.key);
+        element
+          .children
+          .push(Node::Text(attribute.value.to_string()));
+        detach(element, &scope);
+      }
+      for child in description.child_elements() {
+        detach(child.clone(), &scope.enter(child));
+      }
+    }
+    properties
+  }
+
+  /// Appends a standalone property element to the first `rdf:Description`,
+  /// dropping namespace declarations that are already in scope there.
+  fn insert(&mut self, mut element: Element) {
+    let Some((path, scope)) = self.descriptions().into_iter().next() else {
+      return;
+    };
+    let Some(description) = self.element_mut(&path) else {
+      return;
+    };
+    let scope = scope.enter(description);
+    let redundant: Vec<String> = element
+      .attributes()
+      .iter()
+      .filter(|a| {
+        a.key.strip_prefix("xmlns:").is_some_and(|prefix| {
+          scope.0.get(prefix).map(String::as_str) == Some(unescape_lossy(a.value).as_str())
+        })
+      })
+      .map(|a| a.key.to_string())
+      .collect();
+    element.remove_attributes(|key| redundant.iter().any(|r| r == key));
+
+    Self::append_property(description, path.len(), |_| element);
+  }
+
+  /// Appends the element built by `build` as the last property of a
+  /// description at `depth`, passing it the indentation to use.
+  fn append_property(description: &mut Element, depth: usize, build: impl FnOnce(&str) -> Element) {
+    // Follow the indentation of the existing properties when there are any
+    let indent = description
+      .child_indent()
+      .unwrap_or_else(|| format!("\n{}", "  ".repeat(depth)));
+    let element = build(&indent);
+
+    description.self_closing = false;
+    // Keep the whitespace before the closing tag at the end
+    let trailing = match description.children.last() {
+      Some(Node::Text(raw)) if raw.trim().is_empty() => description.children.pop(),
+      _ => None,
+    };
+    description.children.push(Node::Text(indent));
+    description.children.push(Node::Element(element));
+    description.children.push(
+      trailing.unwrap_or_else(|| Node::Text(format!("\n{}", "  ".repeat(depth.saturating_sub(1))))),
+    );
+  }
+
+  /// Extracts the value of a property element.
+  fn value_of(element: &Element, scope: &Scope) -> XmpValue {
+    let container = element.child_elements().find_map(|child| {
+      let (namespace, local) = scope.resolve(&child.name)?;
+      (namespace == ns::RDF && ["Bag", "Seq", "Alt"].contains(&local)).then_some((child, local))
+    });
+    let Some((container, kind)) = container else {
+      let fields = Self::struct_fields(element, scope);
+      return if fields.is_empty() {
+        XmpValue::Text(element.text())
+      } else {
+        XmpValue::Struct(fields)
+      };
+    };
+
+    let items: Vec<String> = container
+      .child_elements()
+      .filter(|item| scope.is(&item.name, ns::RDF, "li"))
+      .map(Element::text)
+      .collect();
+    match kind {
+      "Bag" => XmpValue::Bag(items),
+      "Seq" => XmpValue::Seq(items),
+      _ => XmpValue::LangAlt(items.into_iter().next().unwrap_or_default()),
+    }
+  }
+
+  /// Extracts the simple fields of a structure, whether they are written as
+  /// attributes of the property, as elements of an `rdf:parseType="Resource"`
+  /// property or inside a nested `rdf:Description`.
+  fn struct_fields(element: &Element, scope: &Scope) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = element
+      .attributes()
+      .into_iter()
+      .filter(|a| a.key != "xmlns" && !a.key.starts_with("xmlns:"))
+      .filter_map(|a| {
+        let (namespace, local) = scope.resolve(a.key)?;
+        (namespace != ns::RDF && namespace != ns::XML)
+          .then(|| (local.to_string(), unescape_lossy(a.value)))
+      })
+      .collect();
+    for child in element.child_elements() {
+      let inner = scope.enter(child);
+      let Some((namespace, local)) = inner.resolve(&child.name) else {
+        continue;
+      };
+      if namespace == ns::RDF && local == "Description" {
+        fields.extend(Self::struct_fields(child, &inner));
+      } else if namespace != ns::RDF && child.child_elements().next().is_none() {
+        fields.push((local.to_string(), child.text()));
+      }
+    }
+    fields
+  }
+
+  /// Builds the element for a property value, indented for its position.
+  fn property_element(name: &str, value: &XmpValue, rdf: &str, indent: &str) -> Element {
+    let mut element = Element::new(name);
+    let (container, items, lang) = match value {
+      XmpValue::Text(text) => {
+        element
+          .children
+          .push(Node::Text(escape(text.as_str()).into()));
+        return element;
+      }
+      XmpValue::Struct(fields) => {
+        // Fields share the prefix of the property
+        let prefix = name.split_once(':').map_or("", |(prefix, _)| prefix);
+        element.push_attribute(&format!("{rdf}:parseType"), "Resource");
+        for (field, text) in fields {
+          let mut child = Element::new(&format!("{prefix}:{field}"));
+          child
+            .children
+            .push(Node::Text(escape(text.as_str()).into()));
+          element.children.push(Node::Text(format!("{indent}  ")));
+          element.children.push(Node::Element(child));
+        }
+        element.children.push(Node::Text(indent.to_string()));
+        return element;
+      }
+      XmpValue::Bag(items) => ("Bag", items.as_slice(), false),
+      XmpValue::Seq(items) => ("Seq", items.as_slice(), false),
+      XmpValue::LangAlt(text) => ("Alt", std::slice::from_ref(text), true),
+    };
+
+    let mut array = Element::new(&format!("{rdf}:{container}"));
+    for item in items {
+      let mut li = Element::new(&format!("{rdf}:li"));
+      if lang {
+        li.push_attribute("xml:lang", "x-default");
+      }
+      li.children.push(Node::Text(escape(item.as_str()).into()));
+      array.children.push(Node::Text(format!("{indent}    ")));
+      array.children.push(Node::Element(li));
+    }
+    array.children.push(Node::Text(format!("{indent}  ")));
+
+    element.children = vec![
+      Node::Text(format!("{indent}  ")),
+      Node::Element(array),
+      Node::Text(indent.to_string()),
+    ];
+    element
+  }
+
+  /// Picks a prefix for a new namespace declaration that is not bound yet.
+  fn free_prefix(scope: &Scope, preferred: &str) -> String {
+    // At most one candidate per bound prefix can be taken
+    std::iter::once(preferred.to_string())
+      .chain((1..=scope.0.len()).map(|n| format!("{preferred}{n}")))
+      .find(|prefix| !scope.0.contains_key(prefix))
+      .unwrap_or_else(|| preferred.to_string())
+  }
+
+  /// Finds the path of child indices leading to the `rdf:RDF` element.
+  fn rdf_path(&self) -> Option<(Vec<usize>, Scope)> {
+    fn find(nodes: &[Node], scope: &Scope, path: &mut Vec<usize>) -> Option<Scope> {
+      for (index, node) in nodes.iter().enumerate() {
+        let Node::Element(element) = node else {
+          continue;
+        };
+        path.push(index);
+        let inner = scope.enter(element);
+        if inner.is(&element.name, ns::RDF, "RDF") {
+          return Some(scope.clone());
+        }
+        if let Some(found) = find(&element.children, &inner, path) {
+          return Some(found);
+        }
+        path.pop();
+      }
+      None
+    }
+
+    let mut path = Vec::new();
+    find(&self.nodes, &Scope::root(), &mut path).map(|scope| (path, scope))
+  }
+
+  /// Lists the `rdf:Description` elements with the scope of their parent.
+  fn descriptions(&self) -> Vec<(Vec<usize>, Scope)> {
+    let Some((rdf_path, scope)) = self.rdf_path() else {
+      return Vec::new();
+    };
+    let Some(rdf) = self.element(&rdf_path) else {
+      return Vec::new();
+    };
+    let scope = scope.enter(rdf);
+
+    rdf
+      .children
+      .iter()
+      .enumerate()
+      .filter_map(|(index, node)| match node {
+        Node::Element(element)
+          if scope
+            .enter(element)
+            .is(&element.name, ns::RDF, "Description") =>
+        {
+          let mut path = rdf_path.clone();
+          path.push(index);
+          Some((path, scope.clone()))
+        }
+        _ => None,
+      })
+      .collect()
+  }
+
+  fn element(&self, path: &[usize]) -> Option<&Element> {
+    let (&first, rest) = path.split_first()?;
+    let Node::Element(root) = self.nodes.get(first)? else {
+      return None;
+    };
+    let mut element = root;
+    for &index in rest {
+      let Node::Element(child) = element.children.get(index)? else {
+        return None;
+      };
+      element = child;
+    }
+    Some(element)
+  }
+
+  fn element_mut(&mut self, path: &[usize]) -> Option<&mut Element> {
+    let (&first, rest) = path.split_first()?;
+    let Node::Element(root) = self.nodes.get_mut(first)? else {
+      return None;
+    };
+    let mut element = root;
+    for &index in rest {
+      let Node::Element(child) = element.children.get_mut(index)? else {
+        return None;
+      };
+      element = child;
+    }
+    Some(element)
+  }
+}
+
+/// Unescapes XML character data, falling back to the raw text if it is malformed.
+fn unescape_lossy(raw: &str) -> String {
+  unescape(raw).map_or_else(|_| raw.to_string(), std::borrow::Cow::into_owned)
+}
@@ -36,9 +36,36 @@ impl FileSelector {
   /// A vector of `PathBuf` objects representing all supported image files found.
   #[must_use]
   pub fn scan_directory(folder_path: &Path) -> Vec<PathBuf> {
+    Self::scan_directory_to_depth(folder_path, usize::MAX)
+  }
+
+  /// Expands a list of command-line paths into the files to process.
+  ///
+  /// Files are kept as given, even if they do not exist or are not supported,
+  /// so that the caller can report them. Directories are replaced by the
+  /// supported image files they contain, descending into subdirectories only
+  /// when `recursive` is set.
+  #[must_use]
+  pub fn collect_files(paths: &[PathBuf], recursive: bool) -> Vec<PathBuf> {
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    let mut files = Vec::new();
+
+    for path in paths {
+      if path.is_dir() {
+        files.extend(Self::scan_directory_to_depth(path, max_depth));
+      } else {
+        files.push(path.clone());
+      }
+    }
+
+    files
+  }
+
+  /// Scans a directory for supported image files up to `max_depth` levels deep.
+  fn scan_directory_to_depth(folder_path: &Path, max_depth: usize) -> Vec<PathBuf> {
     let mut files = Vec::new();
 
-    let walker = WalkDir::new(folder_path);
+    let walker = WalkDir::new(folder_path).max_depth(max_depth);
 
     for entry in walker.into_iter().flatten() {
       let path = entry.path();
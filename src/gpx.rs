@@ -0,0 +1,246 @@
+//! GPX track log import.
+//!
+//! A GPX track, such as one logged by a phone while shooting, records where
+//! its recorder was over time. The timestamped points of all its tracks and
+//! segments are read in time order, and the position at the capture time of
+//! a photograph is interpolated between the two surrounding points.
+
+use crate::models::GpsPosition;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::path::Path;
+
+/// A timestamped point of a GPX track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPoint {
+  /// Time the point was recorded at
+  pub time: DateTime<Utc>,
+  /// Latitude in decimal degrees
+  pub latitude: f64,
+  /// Longitude in decimal degrees
+  pub longitude: f64,
+  /// Elevation in metres, if recorded
+  pub elevation: Option<f64>,
+}
+
+impl TrackPoint {
+  /// Returns the position of this point, recorded at `time`.
+  const fn position(self, time: DateTime<Utc>) -> GpsPosition {
+    GpsPosition {
+      latitude: self.latitude,
+      longitude: self.longitude,
+      altitude: self.elevation,
+      time: Some(time),
+    }
+  }
+}
+
+/// Child element of a track point whose text is being read.
+#[derive(Clone, Copy)]
+enum PointField {
+  Elevation,
+  Time,
+}
+
+/// Track point being read, before it is known to have a time.
+#[derive(Default)]
+struct PartialPoint {
+  latitude: f64,
+  longitude: f64,
+  elevation: Option<f64>,
+  time: Option<DateTime<Utc>>,
+}
+
+/// Points of a GPX track log, ordered by time.
+#[derive(Debug, Clone, Default)]
+pub struct Track {
+  /// Timestamped track points
+  pub points: Vec<TrackPoint>,
+}
+
+impl Track {
+  /// Reads a GPX file.
+  pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)
+      .map_err(|e| format!("Failed to read GPX track {}: {e}", path.display()))?;
+    Self::parse(&content).map_err(|e| format!("Invalid GPX track {}: {e}", path.display()).into())
+  }
+
+  /// Parses the track points (`trkpt`) of a GPX document.
+  ///
+  /// Points without a time cannot be matched to photographs and are
+  /// skipped; a track without any timestamped point is an error.
+  pub fn parse(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut reader = Reader::from_str(content);
+    let mut points = Vec::new();
+    let mut point: Option<PartialPoint> = None;
+    let mut field = None;
+
+    loop {
+      match reader.read_event()? {
+        Event::Start(start) => match start.local_name().as_ref() {
+          b"trkpt" => point = Some(Self::read_point(&start)?),
+          b"ele" => field = Some(PointField::Elevation),
+          b"time" => field = Some(PointField::Time),
+          _ => field = None,
+        },
+        Event::Empty(start) if start.local_name().as_ref() == b"trkpt" => {
+          // A point without children has no time and is skipped
+          Self::read_point(&start)?;
+        }
+        Event::Text(text) => {
+          if let (Some(point), Some(field)) = (point.as_mut(), field) {
+            let text = text.unescape()?;
+            let text = text.trim();
+            match field {
+              PointField::Elevation => {
+                point.elevation = Some(
+                  text
+                    .parse()
+                    .map_err(|_| format!("Invalid elevation \"{text}\""))?,
+                );
+              }
+              PointField::Time => {
+                let time = DateTime::parse_from_rfc3339(text)
+                  .map_err(|_| format!("Invalid time \"{text}\""))?;
+                point.time = Some(time.with_timezone(&Utc));
+              }
+            }
+          }
+        }
+        Event::End(end) => {
+          field = None;
+          if end.local_name().as_ref() == b"trkpt" {
+            if let Some(PartialPoint {
+              latitude,
+              longitude,
+              elevation,
+              time: Some(time),
+            }) = point.take()
+            {
+              points.push(TrackPoint {
+                time,
+                latitude,
+                longitude,
+                elevation,
+              });
+            }
+          }
+        }
+        Event::Eof => break,
+        _ => {}
+      }
+    }
+
+    if points.is_empty() {
+      return Err("No track points with a time".into());
+    }
+    points.sort_by_key(|point| point.time);
+    Ok(Self { points })
+  }
+
+  /// Reads the coordinates of a `trkpt` element.
+  fn read_point(start: &BytesStart) -> Result<PartialPoint, Box<dyn std::error::Error>> {
+    let mut latitude = None;
+    let mut longitude = None;
+    for attribute in start.attributes() {
+      let attribute = attribute?;
+      let value = attribute.unescape_value()?;
+      let coordinate = || {
+        value
+          .trim()
+          .parse::<f64>()
+          .map_err(|_| format!("Invalid coordinate \"{value}\""))
+      };
+      match attribute.key.local_name().as_ref() {
+        b"lat" => latitude = Some(coordinate()?),
+        b"lon" => longitude = Some(coordinate()?),
+        _ => {}
+      }
+    }
+    match (latitude, longitude) {
+      (Some(latitude), Some(longitude))
+        if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) =>
+      {
+        Ok(PartialPoint {
+          latitude,
+          longitude,
+          ..PartialPoint::default()
+        })
+      }
+      (Some(_), Some(_)) => Err("Track point coordinates out of range".into()),
+      _ => Err("Track point without lat and lon".into()),
+    }
+  }
+
+  /// Returns the position of the track at `time`.
+  ///
+  /// Between two points at most `max_gap` apart, the position is
+  /// interpolated linearly. Otherwise the nearest point is used if it was
+  /// recorded within `max_gap` of `time`, which also covers photographs
+  /// taken shortly before the track starts or after it ends. Returns `None`
+  /// when no point is close enough. The position carries `time` itself.
+  #[must_use]
+  #[allow(clippy::cast_precision_loss)]
+  pub fn position_at(&self, time: DateTime<Utc>, max_gap: Duration) -> Option<GpsPosition> {
+    let next = self.points.partition_point(|point| point.time < time);
+    let after = self.points.get(next);
+    let before = next.checked_sub(1).and_then(|index| self.points.get(index));
+
+    match (before, after) {
+      (_, Some(after)) if after.time == time => Some(after.position(time)),
+      (Some(before), Some(after)) if after.time - before.time <= max_gap => {
+        let span = (after.time - before.time).num_milliseconds() as f64;
+        let fraction = (time - before.time).num_milliseconds() as f64 / span;
+        let between = |from: f64, to: f64| (to - from).mul_add(fraction, from);
+        Some(GpsPosition {
+          latitude: between(before.latitude, after.latitude),
+          longitude: between(before.longitude, after.longitude),
+          altitude: before
+            .elevation
+            .zip(after.elevation)
+            .map(|(from, to)| between(from, to)),
+          time: Some(time),
+        })
+      }
+      _ => [before, after]
+        .into_iter()
+        .flatten()
+        .filter(|point| (point.time - time).abs() <= max_gap)
+        .min_by_key(|point| (point.time - time).abs())
+        .map(|point| point.position(time)),
+    }
+  }
+}
+
+/// Parses a UTC offset such as "+01:00", "-0530", "+2" or "Z".
+///
+/// Used for the offset of camera clocks, which record local time without a
+/// time zone.
+pub fn parse_utc_offset(value: &str) -> Result<FixedOffset, String> {
+  let invalid = || format!("Invalid UTC offset \"{value}\": use ±HH:MM, such as +01:00");
+  let trimmed = value.trim();
+  if trimmed.eq_ignore_ascii_case("z") {
+    return FixedOffset::east_opt(0).ok_or_else(invalid);
+  }
+  let (sign, rest) = if let Some(rest) = trimmed.strip_prefix('+') {
+    (1, rest)
+  } else if let Some(rest) = trimmed.strip_prefix(['-', '−']) {
+    (-1, rest)
+  } else {
+    return Err(invalid());
+  };
+  let (hours, minutes) = match rest.split_once(':') {
+    Some((hours, minutes)) => (hours, minutes),
+    None if rest.len() == 4 => rest.split_at(2),
+    None => (rest, "0"),
+  };
+  let hours: u16 = hours.parse().map_err(|_| invalid())?;
+  let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+  if hours > 14 || minutes >= 60 {
+    return Err(invalid());
+  }
+  FixedOffset::east_opt(sign * (i32::from(hours) * 3600 + i32::from(minutes) * 60))
+    .ok_or_else(invalid)
+}
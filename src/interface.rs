@@ -6,12 +6,18 @@
 
 use crate::{
   data::DataManager,
-  models::{Camera, Film, Lens, Photographer, Selection, Setup},
+  journal::BackupMode,
+  models::{
+    Camera, Contact, Development, Exposure, ExposureProgram, Film, Frame, Lens, Location,
+    MeteringMode, Photographer, Rights, Roll, Selection, Setup,
+  },
   prompts::PromptUtils,
-  utils::clean_path,
+  shot_log::ShotLog,
+  utils::{clean_path, split_keywords},
 };
+use clap::ValueEnum;
 use colored::Colorize;
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 /// Main application interface providing interactive menu systems.
 ///
@@ -21,6 +27,7 @@ use std::path::PathBuf;
 pub struct Interface {
   data_manager: DataManager,
   one_sec: bool,
+  backup_mode: BackupMode,
 }
 
 impl Interface {
@@ -33,9 +40,17 @@ impl Interface {
     Ok(Self {
       data_manager,
       one_sec,
+      backup_mode: BackupMode::Off,
     })
   }
 
+  /// Sets how original files are backed up before they are modified.
+  #[must_use]
+  pub const fn with_backup_mode(mut self, backup_mode: BackupMode) -> Self {
+    self.backup_mode = backup_mode;
+    self
+  }
+
   /// Runs the main application menu loop.
   ///
   /// Displays the primary menu with options to apply EXIF data, erase EXIF data,
@@ -93,8 +108,12 @@ impl Interface {
       );
       return Ok(());
     }
-    let selection = selection.unwrap();
+    let mut selection = selection.unwrap();
     let shot_iso = shot_iso.unwrap();
+    let Some(saved_location) = self.select_location()? else {
+      return Ok(());
+    };
+    selection.saved_location = saved_location;
 
     PromptUtils::display_selection(&selection);
 
@@ -111,10 +130,34 @@ impl Interface {
       return Ok(());
     }
     let selected_files = selected_files.unwrap();
+    let Some(enter) = PromptUtils::prompt_confirm(
+      "Enter development details (developer, time, lab, scan date)?",
+      false,
+    )?
+    else {
+      return Ok(());
+    };
+    if enter {
+      let Some(development) = Self::prompt_development(&selection.development)? else {
+        return Ok(());
+      };
+      selection.development = development;
+    }
+    let Some(mut frames) = self.prompt_shot_log(&selected_files)? else {
+      return Ok(());
+    };
+    if frames.is_empty() {
+      let Some(exposures) = Self::prompt_exposures(&mut selection, &selected_files)? else {
+        return Ok(());
+      };
+      frames = exposures;
+    }
 
     println!("{}", "\n📝 Applying EXIF data...\n".blue());
 
-    let exif_manager = crate::ExifManager::new();
+    let exif_manager = crate::ExifManager::new()
+      .with_backups(self.backup_mode)
+      .with_frames(frames);
     let result = exif_manager.process_selected_files_with_one_sec(
       &selected_files,
       Some(&selection),
@@ -123,48 +166,213 @@ impl Interface {
       self.one_sec,
     );
 
-    if result.success {
-      println!(
-        "{}",
-        format!(
-          "✅ Successfully processed {} files",
-          result.results.processed
-        )
-        .green()
-      );
-      if result.results.failed > 0 {
+    PromptUtils::display_processing_result(&result);
+    Ok(())
+  }
+
+  /// Offers to import a shot log for the selected files.
+  ///
+  /// Reads the log, matches its frames to the files by sort order or by a
+  /// frame number pattern, reports frames without files and files without
+  /// frames, and returns the frame of each matched file. Returns an empty map
+  /// if no log is imported or it cannot be used, and None if the user cancels.
+  fn prompt_shot_log(
+    &self,
+    files: &[PathBuf],
+  ) -> Result<Option<HashMap<PathBuf, Frame>>, Box<dyn std::error::Error>> {
+    let Some(import) = PromptUtils::prompt_confirm("Import a shot log (CSV or JSON)?", false)?
+    else {
+      return Ok(None);
+    };
+    if !import {
+      return Ok(Some(HashMap::new()));
+    }
+    let Some(path) = PromptUtils::prompt_path("Enter the shot log path:")? else {
+      return Ok(None);
+    };
+    let log = match ShotLog::load(&PathBuf::from(clean_path(&path))) {
+      Ok(log) => log,
+      Err(e) => {
+        println!("{}", format!("❌ {e}").red());
+        return Ok(Some(HashMap::new()));
+      }
+    };
+    let Some(pattern) = Self::prompt_parsed(
+      "Frame number pattern for file names (e.g. _(\\d+)\\., empty to match by order):",
+      "",
+      |value| regex::Regex::new(value).map_err(|e| e.to_string()),
+    )?
+    else {
+      return Ok(None);
+    };
+
+    let matches = log.match_files(files, pattern.as_ref());
+    for warning in matches.warnings() {
+      println!("{}", format!("⚠️  {warning}").yellow());
+    }
+    match matches.frames(&self.data_manager) {
+      Ok(frames) => {
         println!(
           "{}",
-          format!("❌ Failed to process {} files", result.results.failed).red()
+          format!("✅ Matched {} file(s) to logged frames", frames.len()).green()
         );
+        Ok(Some(frames))
+      }
+      Err(e) => {
+        println!("{}", format!("❌ {e}").red());
+        Ok(Some(HashMap::new()))
       }
+    }
+  }
 
-      println!("\n📊 Processing Results:");
-      for file in &result.results.files {
-        let status = if file.success {
-          "✓".green()
-        } else {
-          "✗".red()
-        };
-        let type_label = file
-          .file_type
-          .as_ref()
-          .map(|t| format!("[{}]", t.to_uppercase()))
-          .unwrap_or_default();
-        println!(
-          "  {} {} {}",
-          status,
-          file.name,
-          type_label.as_str().bright_black()
+  /// Asks for the exposure settings of the selected files.
+  ///
+  /// Settings shared by all files are stored in the selection; if the user
+  /// wants to enter them frame by frame, the shutter speed, aperture and
+  /// compensation of each file are returned as its frame, falling back to the
+  /// shared ones when skipped. Returns None if the user cancels.
+  fn prompt_exposures(
+    selection: &mut Selection,
+    files: &[PathBuf],
+  ) -> Result<Option<HashMap<PathBuf, Frame>>, Box<dyn std::error::Error>> {
+    let Some(enter) = PromptUtils::prompt_confirm(
+      "Enter exposure settings (shutter speed, aperture, compensation)?",
+      false,
+    )?
+    else {
+      return Ok(None);
+    };
+    if !enter {
+      return Ok(Some(HashMap::new()));
+    }
+
+    println!("{}", "Settings for all files (press Enter to skip):".cyan());
+    let Some(exposure) = Self::prompt_exposure("")? else {
+      return Ok(None);
+    };
+    let Some(metering_mode) =
+      Self::select_optional("Metering mode:", MeteringMode::value_variants())?
+    else {
+      return Ok(None);
+    };
+    let Some(program) =
+      Self::select_optional("Exposure program:", ExposureProgram::value_variants())?
+    else {
+      return Ok(None);
+    };
+    selection.exposure = Exposure {
+      metering_mode,
+      program,
+      ..exposure
+    };
+
+    let per_file = files.len() > 1
+      && PromptUtils::prompt_confirm("Enter shutter speed and aperture for each file?", false)?
+        .unwrap_or(false);
+    let mut frames = HashMap::new();
+    if per_file {
+      for file in files {
+        let name = file.file_name().map_or_else(
+          || file.display().to_string(),
+          |name| name.to_string_lossy().to_string(),
         );
-        if let Some(error) = &file.error {
-          println!("    {}", format!("Error: {error}").red());
-        }
+        let Some(exposure) = Self::prompt_exposure(&format!(" of {name}"))? else {
+          return Ok(None);
+        };
+        let frame = Frame {
+          exposure,
+          ..Frame::default()
+        };
+        frames.insert(file.clone(), frame);
       }
-    } else {
-      println!("{}", format!("❌ Error: {}", result.message).red());
     }
-    Ok(())
+    Ok(Some(frames))
+  }
+
+  /// Prompts for a shutter speed, aperture and exposure compensation, asking
+  /// again until each one is empty or valid. Returns None if the user cancels.
+  fn prompt_exposure(subject: &str) -> Result<Option<Exposure>, Box<dyn std::error::Error>> {
+    let Some(exposure_time) = Self::prompt_parsed(
+      &format!("Shutter speed{subject} (e.g. 1/125 or 2s):"),
+      "",
+      Exposure::parse_shutter_speed,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(f_number) = Self::prompt_parsed(
+      &format!("Aperture{subject} (e.g. 5.6):"),
+      "",
+      Exposure::parse_aperture,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(compensation) = Self::prompt_parsed(
+      &format!("Exposure compensation{subject} (e.g. +1/3 or -0.7):"),
+      "",
+      Exposure::parse_compensation,
+    )?
+    else {
+      return Ok(None);
+    };
+    Ok(Some(Exposure {
+      exposure_time,
+      f_number,
+      compensation,
+      ..Exposure::default()
+    }))
+  }
+
+  /// Prompts for an optional value, starting from `default`, and asks again
+  /// while it cannot be parsed.
+  ///
+  /// Returns `Some(None)` if the input is empty, and None if the user cancels.
+  #[allow(clippy::option_option)]
+  fn prompt_parsed<T>(
+    message: &str,
+    default: &str,
+    parse: fn(&str) -> Result<T, String>,
+  ) -> Result<Option<Option<T>>, Box<dyn std::error::Error>> {
+    loop {
+      let Some(input) = PromptUtils::prompt_text_with_default(message, default)? else {
+        return Ok(None);
+      };
+      if input.trim().is_empty() {
+        return Ok(Some(None));
+      }
+      match parse(&input) {
+        Ok(value) => return Ok(Some(Some(value))),
+        Err(e) => println!("{}", format!("❌ {e}").red()),
+      }
+    }
+  }
+
+  /// Lets the user pick one of `variants` or "Unknown".
+  ///
+  /// Returns `Some(None)` for "Unknown", and None if the user cancels.
+  #[allow(clippy::option_option)]
+  fn select_optional<T: ValueEnum + Copy>(
+    message: &str,
+    variants: &[T],
+  ) -> Result<Option<Option<T>>, Box<dyn std::error::Error>> {
+    let names = |variant: &T| {
+      variant
+        .to_possible_value()
+        .map(|value| value.get_name().replace('-', " "))
+        .unwrap_or_default()
+    };
+    let mut options = vec!["Unknown".to_string()];
+    options.extend(variants.iter().map(names));
+    let Some(choice) = PromptUtils::select_from_list(message, options)? else {
+      return Ok(None);
+    };
+    Ok(Some(
+      variants
+        .iter()
+        .copied()
+        .find(|variant| names(variant) == choice),
+    ))
   }
 
   /// Handles the EXIF erasure workflow.
@@ -186,61 +394,38 @@ impl Interface {
 
     println!("{}", "\n🗑️  Erasing EXIF data...\n".blue());
 
-    let exif_manager = crate::ExifManager::new();
+    let exif_manager = crate::ExifManager::new().with_backups(self.backup_mode);
     let result = exif_manager.process_folder(&_folder_path, None, "erase");
 
-    if result.success {
-      println!(
-        "{}",
-        format!(
-          "✅ Successfully processed {} files",
-          result.results.processed
-        )
-        .green()
-      );
-      if result.results.failed > 0 {
-        println!(
-          "{}",
-          format!("❌ Failed to process {} files", result.results.failed).red()
-        );
-      }
-
-      println!("\n📊 Processing Results:");
-      for file in &result.results.files {
-        let status = if file.success {
-          "✓".green()
-        } else {
-          "✗".red()
-        };
-        let type_label = file
-          .file_type
-          .as_ref()
-          .map(|t| format!("[{}]", t.to_uppercase()))
-          .unwrap_or_default();
-        println!(
-          "  {} {} {}",
-          status,
-          file.name,
-          type_label.as_str().bright_black()
-        );
-        if let Some(error) = &file.error {
-          println!("    {}", format!("Error: {error}").red());
-        }
-      }
-    } else {
-      println!("{}", format!("❌ Error: {}", result.message).red());
-    }
+    PromptUtils::display_processing_result(&result);
     Ok(())
   }
 
   /// Guides the user through selecting a complete equipment configuration.
   ///
-  /// Prompts the user to select a setup (camera + lens), film, photographer, and ISO,
-  /// then creates a Selection object containing all the necessary information
-  /// for EXIF metadata application.
+  /// Offers the rolls first, and otherwise prompts the user to select a setup
+  /// (camera + lens), film, photographer, and ISO, then creates a Selection
+  /// object containing all the necessary information for EXIF metadata
+  /// application.
   fn select_setup_film_and_iso(
     &self,
   ) -> Result<(Option<Selection>, Option<u32>), Box<dyn std::error::Error>> {
+    match self.select_roll()? {
+      None => return Ok((None, None)),
+      Some(Some(roll)) => {
+        let mut selection = self
+          .data_manager
+          .create_roll_selection(roll.id)
+          .map_err(|e| format!("Error creating selection: {e}"))?;
+        let shot_iso = roll.shot_iso.unwrap_or(selection.film.iso);
+        if !Self::prompt_caption_and_keywords(&mut selection)? {
+          return Ok((None, None));
+        }
+        return Ok((Some(selection), Some(shot_iso)));
+      }
+      Some(None) => {}
+    }
+
     let setups = self.data_manager.get_setups();
     if setups.is_empty() {
       println!(
@@ -312,7 +497,7 @@ impl Interface {
       .find(|p| p.display_name() == selected_photographer_name)
       .unwrap();
 
-    let selection = self
+    let mut selection = self
       .data_manager
       .create_selection(
         selected_setup.id,
@@ -321,13 +506,84 @@ impl Interface {
       )
       .map_err(|e| format!("Error creating selection: {e}"))?;
 
+    if !Self::prompt_caption_and_keywords(&mut selection)? {
+      return Ok((None, None));
+    }
     Ok((Some(selection), Some(shot_iso)))
   }
 
+  /// Offers the rolls to apply instead of selecting the equipment.
+  ///
+  /// Returns the chosen roll, `Some(None)` if the user wants to select the
+  /// equipment or there are no rolls, and None if the user cancels.
+  #[allow(clippy::option_option)]
+  fn select_roll(&self) -> Result<Option<Option<Roll>>, Box<dyn std::error::Error>> {
+    const SELECT_EQUIPMENT: &str = "Select setup, film and photographer";
+
+    let rolls = self.data_manager.get_rolls();
+    if rolls.is_empty() {
+      return Ok(Some(None));
+    }
+    let mut options = vec![SELECT_EQUIPMENT.to_string()];
+    options.extend(rolls.iter().map(Roll::display_name));
+    let Some(choice) = PromptUtils::select_from_list("Apply a roll:", options)? else {
+      return Ok(None);
+    };
+    Ok(Some(
+      rolls.iter().find(|r| r.display_name() == choice).cloned(),
+    ))
+  }
+
+  /// Offers the saved locations to attach to the selection.
+  ///
+  /// Returns the chosen location, `Some(None)` if the user picks none or
+  /// there are no locations, and None if the user cancels.
+  #[allow(clippy::option_option)]
+  fn select_location(&self) -> Result<Option<Option<Location>>, Box<dyn std::error::Error>> {
+    const NO_LOCATION: &str = "No location";
+
+    let locations = self.data_manager.get_locations();
+    if locations.is_empty() {
+      return Ok(Some(None));
+    }
+    let mut options = vec![NO_LOCATION.to_string()];
+    options.extend(locations.iter().map(Location::display_name));
+    let Some(choice) = PromptUtils::select_from_list("Attach a location:", options)? else {
+      return Ok(None);
+    };
+    Ok(Some(
+      locations
+        .iter()
+        .find(|l| l.display_name() == choice)
+        .cloned(),
+    ))
+  }
+
+  /// Asks for the caption and keywords of a selection, which may be skipped.
+  ///
+  /// Returns false if the user cancels.
+  fn prompt_caption_and_keywords(
+    selection: &mut Selection,
+  ) -> Result<bool, Box<dyn std::error::Error>> {
+    let Some(caption) = PromptUtils::prompt_text("Enter a caption (press Enter to skip): ")? else {
+      return Ok(false);
+    };
+    let Some(keywords) =
+      PromptUtils::prompt_text("Enter comma-separated keywords (press Enter to skip): ")?
+    else {
+      return Ok(false);
+    };
+    let caption = caption.trim();
+    selection.caption = (!caption.is_empty()).then(|| caption.to_string());
+    selection.keywords = split_keywords(&keywords);
+    Ok(true)
+  }
+
   /// Prompts the user to enter the ISO speed at which the film was shot.
   ///
   /// Defaults to the film's base ISO rating. Useful for push/pull processing.
   /// Returns None if the user cancels the operation.
+  #[allow(clippy::self_only_used_in_recursion)]
   fn prompt_shot_iso(&self, default_iso: u32) -> Result<Option<u32>, Box<dyn std::error::Error>> {
     println!("{}", format!("📸 Film base ISO: {default_iso}").cyan());
 
@@ -368,18 +624,22 @@ impl Interface {
 
   /// Prompts the user to confirm EXIF data erasure.
   ///
-  /// Shows a warning that the operation cannot be undone.
+  /// Warns that the operation cannot be undone when backups are off.
   /// Defaults to false (do not erase). Returns None if the user cancels.
   fn confirm_erase_exif(&self) -> Result<Option<bool>, Box<dyn std::error::Error>> {
     PromptUtils::prompt_confirm(
-      "Are you sure you want to erase EXIF data? This cannot be undone.",
+      &format!(
+        "Are you sure you want to erase EXIF data? {}",
+        PromptUtils::undo_hint(self.backup_mode)
+      ),
       false,
     )
   }
 
   /// Runs the equipment management menu loop.
   ///
-  /// Provides options to manage cameras, lenses, films, photographers, and setups.
+  /// Provides options to manage cameras, lenses, films, photographers, setups,
+  /// rolls and locations.
   /// Returns to the main menu when the user selects "Back to main menu".
   ///
   /// # Errors
@@ -393,6 +653,8 @@ impl Interface {
         "Manage Films",
         "Manage Photographers",
         "Manage Setups",
+        "Manage Rolls",
+        "Manage Locations",
         "Back to main menu",
       ];
 
@@ -403,6 +665,8 @@ impl Interface {
           "Manage Films" => self.manage_films()?,
           "Manage Photographers" => self.manage_photographers()?,
           "Manage Setups" => self.manage_setups()?,
+          "Manage Rolls" => self.manage_rolls()?,
+          "Manage Locations" => self.manage_locations()?,
           "Back to main menu" => break,
           _ => {}
         }
@@ -728,9 +992,15 @@ impl Interface {
                 if let Some(film) = films.iter().find(|f| f.display_name() == selected_name) {
                   let film_id = film.id;
                   let film_name = film.display_name();
-                  self.data_manager.delete_film(film_id);
-                  self.data_manager.save()?;
-                  println!("{}", format!("✅ Deleted film: {film_name}").green());
+                  match self.data_manager.delete_film(film_id) {
+                    Ok(()) => {
+                      self.data_manager.save()?;
+                      println!("{}", format!("✅ Deleted film: {film_name}").green());
+                    }
+                    Err(e) => {
+                      println!("{}", format!("❌ Error: {e}").red());
+                    }
+                  }
                 }
               }
             }
@@ -783,12 +1053,23 @@ impl Interface {
                 email
               };
 
-              let photographer = self.data_manager.add_photographer(name, email);
-              self.data_manager.save()?;
-              println!(
-                "{}",
-                format!("✅ Added photographer: {}", photographer.display_name()).green()
-              );
+              let Some(rights) = Self::prompt_rights(&Rights::default())? else {
+                continue;
+              };
+              if let Some(contact) = Self::prompt_contact(&Contact::default())? {
+                let photographer = self.data_manager.add_photographer(name, email);
+                self
+                  .data_manager
+                  .edit_photographer_rights(photographer.id, rights);
+                self
+                  .data_manager
+                  .edit_photographer_contact(photographer.id, contact);
+                self.data_manager.save()?;
+                println!(
+                  "{}",
+                  format!("✅ Added photographer: {}", photographer.display_name()).green()
+                );
+              }
             }
           }
           "Edit photographer" => {
@@ -819,10 +1100,17 @@ impl Interface {
                     } else {
                       email
                     };
+                    let Some(rights) = Self::prompt_rights(&photographer.rights)? else {
+                      continue;
+                    };
+                    let Some(contact) = Self::prompt_contact(&photographer.contact)? else {
+                      continue;
+                    };
+                    let id = photographer.id;
 
-                    if self
-                      .data_manager
-                      .edit_photographer(photographer.id, name, email)
+                    if self.data_manager.edit_photographer(id, name, email)
+                      && self.data_manager.edit_photographer_rights(id, rights)
+                      && self.data_manager.edit_photographer_contact(id, contact)
                     {
                       self.data_manager.save()?;
                       println!("{}", format!("✅ Updated photographer: {old_name}").green());
@@ -853,12 +1141,18 @@ impl Interface {
                 {
                   let photographer_id = photographer.id;
                   let photographer_name = photographer.display_name();
-                  self.data_manager.delete_photographer(photographer_id);
-                  self.data_manager.save()?;
-                  println!(
-                    "{}",
-                    format!("✅ Deleted photographer: {photographer_name}").green()
-                  );
+                  match self.data_manager.delete_photographer(photographer_id) {
+                    Ok(()) => {
+                      self.data_manager.save()?;
+                      println!(
+                        "{}",
+                        format!("✅ Deleted photographer: {photographer_name}").green()
+                      );
+                    }
+                    Err(e) => {
+                      println!("{}", format!("❌ Error: {e}").red());
+                    }
+                  }
                 }
               }
             }
@@ -873,6 +1167,94 @@ impl Interface {
     Ok(())
   }
 
+  /// Prompts for the copyright template, licence and usage terms of a
+  /// photographer, starting from `current`.
+  ///
+  /// Empty answers clear a field, and a licence that is not recognized is
+  /// asked for again. Returns None if the user cancels the operation.
+  fn prompt_rights(current: &Rights) -> Result<Option<Rights>, Box<dyn std::error::Error>> {
+    let filled = |value: String| {
+      let value = value.trim();
+      (!value.is_empty()).then(|| value.to_string())
+    };
+
+    let Some(copyright) = PromptUtils::prompt_text_with_default(
+      &format!(
+        "Copyright template ({{year}} and {{name}} are filled in, empty for \"{}\"):",
+        Rights::DEFAULT_COPYRIGHT_TEMPLATE
+      ),
+      current.copyright_template.as_deref().unwrap_or(""),
+    )?
+    else {
+      return Ok(None);
+    };
+    let mut rights = Rights {
+      copyright_template: filled(copyright),
+      ..current.clone()
+    };
+
+    loop {
+      let Some(license) = PromptUtils::prompt_text_with_default(
+        "License URL or Creative Commons identifier, such as CC BY 4.0 (optional):",
+        rights.license.as_deref().unwrap_or(""),
+      )?
+      else {
+        return Ok(None);
+      };
+      rights.license = filled(license);
+      match rights.validate() {
+        Ok(()) => break,
+        Err(e) => println!("{}", format!("❌ {e}").red()),
+      }
+    }
+
+    let Some(usage_terms) = PromptUtils::prompt_text_with_default(
+      "Usage terms (optional):",
+      current.usage_terms.as_deref().unwrap_or(""),
+    )?
+    else {
+      return Ok(None);
+    };
+    rights.usage_terms = filled(usage_terms);
+    Ok(Some(rights))
+  }
+
+  /// Prompts for the address, phone number and website of a photographer,
+  /// starting from `current`.
+  ///
+  /// The fields are only asked for if the user wants to edit them, and empty
+  /// answers clear a field. Returns None if the user cancels the operation.
+  fn prompt_contact(current: &Contact) -> Result<Option<Contact>, Box<dyn std::error::Error>> {
+    let Some(edit) =
+      PromptUtils::prompt_confirm("Edit contact details (address, phone, website)?", false)?
+    else {
+      return Ok(None);
+    };
+    if !edit {
+      return Ok(Some(current.clone()));
+    }
+
+    let mut contact = current.clone();
+    for (message, field) in [
+      ("Street address (optional):", &mut contact.address),
+      ("City (optional):", &mut contact.city),
+      ("State, province or region (optional):", &mut contact.region),
+      ("Postal code (optional):", &mut contact.postal_code),
+      ("Country (optional):", &mut contact.country),
+      ("Phone (optional):", &mut contact.phone),
+      ("Website (optional):", &mut contact.website),
+    ] {
+      let Some(value) =
+        PromptUtils::prompt_text_with_default(message, field.as_deref().unwrap_or(""))?
+      else {
+        return Ok(None);
+      };
+      let value = value.trim();
+      *field = (!value.is_empty()).then(|| value.to_string());
+    }
+    Ok(Some(contact))
+  }
+
   /// Handles equipment setup management operations.
   ///
   /// Provides options to view, add, and delete equipment setups (camera + lens combinations).
@@ -1082,9 +1464,394 @@ impl Interface {
                 if let Some(setup) = setups.iter().find(|s| s.display_name() == selected_name) {
                   let setup_id = setup.id;
                   let setup_name = setup.display_name();
-                  self.data_manager.delete_setup(setup_id);
+                  match self.data_manager.delete_setup(setup_id) {
+                    Ok(()) => {
+                      self.data_manager.save()?;
+                      println!("{}", format!("✅ Deleted setup: {setup_name}").green());
+                    }
+                    Err(e) => {
+                      println!("{}", format!("❌ Error: {e}").red());
+                    }
+                  }
+                }
+              }
+            }
+          }
+          "Back" => break,
+          _ => {}
+        }
+      } else {
+        break;
+      }
+    }
+    Ok(())
+  }
+
+  /// Handles roll management operations.
+  ///
+  /// Provides options to view, add, edit and delete rolls. Rolls can be
+  /// deleted without restriction as they are not referenced by other entities.
+  fn manage_rolls(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "\n🎞️ Roll Management\n".blue().bold());
+
+    loop {
+      let options = vec![
+        "View all rolls",
+        "Add new roll",
+        "Edit roll",
+        "Delete roll",
+        "Back",
+      ];
+
+      if let Some(choice) = PromptUtils::select_from_list("Roll Management", options)? {
+        match choice {
+          "View all rolls" => {
+            let rolls = self.data_manager.get_rolls();
+            if rolls.is_empty() {
+              println!("{}", "No rolls found.".yellow());
+            } else {
+              println!("{}", "🎞️ Rolls:".cyan().bold());
+              for roll in rolls {
+                let film = self
+                  .data_manager
+                  .get_film_by_id(roll.film_id)
+                  .map(Film::display_name)
+                  .unwrap_or_default();
+                let setup = self
+                  .data_manager
+                  .get_setup_by_id(roll.setup_id)
+                  .map(Setup::display_name)
+                  .unwrap_or_default();
+                println!("  • {} ({film}, {setup})", roll.display_name());
+              }
+            }
+          }
+          "Add new roll" => {
+            if let Some(roll) = self.prompt_roll(None)? {
+              match self.data_manager.add_roll(roll) {
+                Ok(roll) => {
+                  self.data_manager.save()?;
+                  println!(
+                    "{}",
+                    format!("✅ Added roll: {}", roll.display_name()).green()
+                  );
+                }
+                Err(e) => {
+                  println!("{}", format!("❌ Error: {e}").red());
+                }
+              }
+            }
+          }
+          "Edit roll" => {
+            let rolls = self.data_manager.get_rolls();
+            if rolls.is_empty() {
+              println!("{}", "No rolls to edit.".yellow());
+            } else {
+              let roll_options: Vec<String> = rolls.iter().map(Roll::display_name).collect();
+              if let Some(selected_name) =
+                PromptUtils::select_from_list("Select roll to edit:", roll_options)?
+              {
+                if let Some(roll) = rolls
+                  .iter()
+                  .find(|r| r.display_name() == selected_name)
+                  .cloned()
+                {
+                  if let Some(roll) = self.prompt_roll(Some(&roll))? {
+                    match self.data_manager.edit_roll(roll) {
+                      Ok(true) => {
+                        self.data_manager.save()?;
+                        println!("{}", format!("✅ Updated roll: {selected_name}").green());
+                      }
+                      Ok(false) => {
+                        println!("{}", "❌ Failed to update roll.".red());
+                      }
+                      Err(e) => {
+                        println!("{}", format!("❌ Error: {e}").red());
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+          "Delete roll" => {
+            let rolls = self.data_manager.get_rolls();
+            if rolls.is_empty() {
+              println!("{}", "No rolls to delete.".yellow());
+            } else {
+              let roll_options: Vec<String> = rolls.iter().map(Roll::display_name).collect();
+              if let Some(selected_name) =
+                PromptUtils::select_from_list("Select roll to delete:", roll_options)?
+              {
+                if let Some(roll) = rolls.iter().find(|r| r.display_name() == selected_name) {
+                  let roll_id = roll.id;
+                  self.data_manager.delete_roll(roll_id);
+                  self.data_manager.save()?;
+                  println!("{}", format!("✅ Deleted roll: {selected_name}").green());
+                }
+              }
+            }
+          }
+          "Back" => break,
+          _ => {}
+        }
+      } else {
+        break;
+      }
+    }
+    Ok(())
+  }
+
+  /// Prompts for the details of a roll, starting from `current` when editing.
+  ///
+  /// The film, setup and photographer are selected from the saved ones, and
+  /// the ISO, dates, frame count, lab and notes may be left empty. Returns
+  /// None if the user cancels or an entity is missing.
+  fn prompt_roll(
+    &self,
+    current: Option<&Roll>,
+  ) -> Result<Option<Roll>, Box<dyn std::error::Error>> {
+    let films = self.data_manager.get_films();
+    let setups = self.data_manager.get_setups();
+    let photographers = self.data_manager.get_photographers();
+    if films.is_empty() || setups.is_empty() || photographers.is_empty() {
+      println!(
+        "{}",
+        "A roll needs a film, a setup and a photographer. Please add them first.".yellow()
+      );
+      return Ok(None);
+    }
+    let filled = |value: String| {
+      let value = value.trim();
+      (!value.is_empty()).then(|| value.to_string())
+    };
+    let optional = |value: Option<String>| value.unwrap_or_default();
+
+    let Some(name) =
+      PromptUtils::prompt_text_with_default("Roll name:", current.map_or("", |r| r.name.as_str()))?
+    else {
+      return Ok(None);
+    };
+    let Some(film_name) = PromptUtils::select_from_list(
+      "Select film:",
+      films.iter().map(Film::display_name).collect(),
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(setup_name) = PromptUtils::select_from_list(
+      "Select setup:",
+      setups.iter().map(Setup::display_name).collect(),
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(photographer_name) = PromptUtils::select_from_list(
+      "Select photographer:",
+      photographers
+        .iter()
+        .map(Photographer::display_name)
+        .collect(),
+    )?
+    else {
+      return Ok(None);
+    };
+    let (Some(film), Some(setup), Some(photographer)) = (
+      films.iter().find(|f| f.display_name() == film_name),
+      setups.iter().find(|s| s.display_name() == setup_name),
+      photographers
+        .iter()
+        .find(|p| p.display_name() == photographer_name),
+    ) else {
+      return Ok(None);
+    };
+
+    let Some(shot_iso) = Self::prompt_parsed(
+      &format!(
+        "ISO the roll was shot at (empty for the box speed, {}):",
+        film.iso
+      ),
+      &optional(current.and_then(|r| r.shot_iso).map(|iso| iso.to_string())),
+      |value| {
+        value
+          .trim()
+          .parse::<u32>()
+          .map_err(|_| format!("Invalid ISO \"{value}\""))
+      },
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(loaded_on) = Self::prompt_parsed(
+      "Date loaded (YYYY-MM-DD, optional):",
+      &optional(current.and_then(|r| r.loaded_on).map(|d| d.to_string())),
+      Roll::parse_date,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(finished_on) = Self::prompt_parsed(
+      "Date finished (YYYY-MM-DD, optional):",
+      &optional(current.and_then(|r| r.finished_on).map(|d| d.to_string())),
+      Roll::parse_date,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(frame_count) = Self::prompt_parsed(
+      "Number of frames (optional):",
+      &optional(current.and_then(|r| r.frame_count).map(|n| n.to_string())),
+      |value| {
+        value
+          .trim()
+          .parse::<u32>()
+          .map_err(|_| format!("Invalid number of frames \"{value}\""))
+      },
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(notes) = PromptUtils::prompt_text_with_default(
+      "Notes (optional):",
+      current.and_then(|r| r.notes.as_deref()).unwrap_or(""),
+    )?
+    else {
+      return Ok(None);
+    };
+    let mut development = current.map(|r| r.development.clone()).unwrap_or_default();
+    let Some(enter) =
+      PromptUtils::prompt_confirm("Enter development details?", !development.is_empty())?
+    else {
+      return Ok(None);
+    };
+    if enter {
+      let Some(entered) = Self::prompt_development(&development)? else {
+        return Ok(None);
+      };
+      development = entered;
+    }
+
+    let mut roll = current
+      .cloned()
+      .unwrap_or_else(|| Roll::new(name.clone(), film.id, setup.id, photographer.id));
+    roll.name = name;
+    roll.film_id = film.id;
+    roll.setup_id = setup.id;
+    roll.photographer_id = photographer.id;
+    roll.shot_iso = shot_iso;
+    roll.loaded_on = loaded_on.flatten();
+    roll.finished_on = finished_on.flatten();
+    roll.frame_count = frame_count;
+    roll.notes = filled(notes);
+    roll.development = development;
+    Ok(Some(roll))
+  }
+
+  /// Handles location management operations.
+  ///
+  /// Provides options to view, add, edit and delete locations. Locations can
+  /// be deleted without restriction as they are not referenced by other
+  /// entities.
+  fn manage_locations(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "\n📍 Location Management\n".blue().bold());
+
+    loop {
+      let options = vec![
+        "View all locations",
+        "Add new location",
+        "Edit location",
+        "Delete location",
+        "Back",
+      ];
+
+      if let Some(choice) = PromptUtils::select_from_list("Location Management", options)? {
+        match choice {
+          "View all locations" => {
+            let locations = self.data_manager.get_locations();
+            if locations.is_empty() {
+              println!("{}", "No locations found.".yellow());
+            } else {
+              println!("{}", "📍 Locations:".cyan().bold());
+              for location in locations {
+                println!(
+                  "  • {} ({})",
+                  location.display_name(),
+                  location.position().display_name()
+                );
+              }
+            }
+          }
+          "Add new location" => {
+            if let Some(location) = Self::prompt_location(None)? {
+              match self.data_manager.add_location(location) {
+                Ok(location) => {
+                  self.data_manager.save()?;
+                  println!(
+                    "{}",
+                    format!("✅ Added location: {}", location.display_name()).green()
+                  );
+                }
+                Err(e) => {
+                  println!("{}", format!("❌ Error: {e}").red());
+                }
+              }
+            }
+          }
+          "Edit location" => {
+            let locations = self.data_manager.get_locations();
+            if locations.is_empty() {
+              println!("{}", "No locations to edit.".yellow());
+            } else {
+              let location_options: Vec<String> =
+                locations.iter().map(Location::display_name).collect();
+              if let Some(selected_name) =
+                PromptUtils::select_from_list("Select location to edit:", location_options)?
+              {
+                if let Some(location) = locations
+                  .iter()
+                  .find(|l| l.display_name() == selected_name)
+                  .cloned()
+                {
+                  if let Some(location) = Self::prompt_location(Some(&location))? {
+                    match self.data_manager.edit_location(location) {
+                      Ok(true) => {
+                        self.data_manager.save()?;
+                        println!(
+                          "{}",
+                          format!("✅ Updated location: {selected_name}").green()
+                        );
+                      }
+                      Ok(false) => {
+                        println!("{}", "❌ Failed to update location.".red());
+                      }
+                      Err(e) => {
+                        println!("{}", format!("❌ Error: {e}").red());
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+          "Delete location" => {
+            let locations = self.data_manager.get_locations();
+            if locations.is_empty() {
+              println!("{}", "No locations to delete.".yellow());
+            } else {
+              let location_options: Vec<String> =
+                locations.iter().map(Location::display_name).collect();
+              if let Some(selected_name) =
+                PromptUtils::select_from_list("Select location to delete:", location_options)?
+              {
+                if let Some(location) = locations.iter().find(|l| l.display_name() == selected_name)
+                {
+                  let location_id = location.id;
+                  self.data_manager.delete_location(location_id);
                   self.data_manager.save()?;
-                  println!("{}", format!("✅ Deleted setup: {setup_name}").green());
+                  println!(
+                    "{}",
+                    format!("✅ Deleted location: {selected_name}").green()
+                  );
                 }
               }
             }
@@ -1098,4 +1865,213 @@ impl Interface {
     }
     Ok(())
   }
+
+  /// Prompts for the details of a location, starting from `current` when
+  /// editing.
+  ///
+  /// The coordinates are asked again until they are valid; the altitude and
+  /// address may be left empty. Returns None if the user cancels.
+  fn prompt_location(
+    current: Option<&Location>,
+  ) -> Result<Option<Location>, Box<dyn std::error::Error>> {
+    let text = |value: &str| Ok(value.trim().to_string());
+    let current_text = |field: fn(&Location) -> &Option<String>| {
+      current.and_then(|l| field(l).clone()).unwrap_or_default()
+    };
+
+    let Some(name) = PromptUtils::prompt_text_with_default(
+      "Location name:",
+      current.map_or("", |l| l.name.as_str()),
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(latitude) = Self::prompt_coordinate(
+      "Latitude in decimal degrees (e.g. 38.7139, negative south):",
+      current.map(|l| l.latitude),
+      |value| {
+        value
+          .trim()
+          .parse::<f64>()
+          .ok()
+          .filter(|latitude| (-90.0..=90.0).contains(latitude))
+          .ok_or_else(|| format!("Invalid latitude \"{value}\": use -90 to 90 degrees"))
+      },
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(longitude) = Self::prompt_coordinate(
+      "Longitude in decimal degrees (e.g. -9.1394, negative west):",
+      current.map(|l| l.longitude),
+      |value| {
+        value
+          .trim()
+          .parse::<f64>()
+          .ok()
+          .filter(|longitude| (-180.0..=180.0).contains(longitude))
+          .ok_or_else(|| format!("Invalid longitude \"{value}\": use -180 to 180 degrees"))
+      },
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(altitude) = Self::prompt_parsed(
+      "Altitude in metres (optional):",
+      &current
+        .and_then(|l| l.altitude)
+        .map(|altitude| altitude.to_string())
+        .unwrap_or_default(),
+      |value| {
+        value
+          .trim()
+          .parse::<f64>()
+          .map_err(|_| format!("Invalid altitude \"{value}\""))
+      },
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(sublocation) = Self::prompt_parsed(
+      "Sublocation, such as a district or landmark (optional):",
+      &current_text(|l| &l.sublocation),
+      text,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(city) = Self::prompt_parsed("City (optional):", &current_text(|l| &l.city), text)?
+    else {
+      return Ok(None);
+    };
+    let Some(state) = Self::prompt_parsed(
+      "State or province (optional):",
+      &current_text(|l| &l.state),
+      text,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(country) =
+      Self::prompt_parsed("Country (optional):", &current_text(|l| &l.country), text)?
+    else {
+      return Ok(None);
+    };
+    let Some(country_code) = Self::prompt_parsed(
+      "Country code (e.g. PT, optional):",
+      &current_text(|l| &l.country_code),
+      |value| Ok(value.trim().to_ascii_uppercase()),
+    )?
+    else {
+      return Ok(None);
+    };
+
+    let mut location = current
+      .cloned()
+      .unwrap_or_else(|| Location::new(name.clone(), latitude, longitude));
+    location.name = name;
+    location.latitude = latitude;
+    location.longitude = longitude;
+    location.altitude = altitude;
+    location.sublocation = sublocation;
+    location.city = city;
+    location.state = state;
+    location.country = country;
+    location.country_code = country_code;
+    Ok(Some(location))
+  }
+
+  /// Prompts for a required coordinate, starting from `current`, and asks
+  /// again while it is empty or cannot be parsed.
+  ///
+  /// Returns None if the user cancels.
+  fn prompt_coordinate(
+    message: &str,
+    current: Option<f64>,
+    parse: fn(&str) -> Result<f64, String>,
+  ) -> Result<Option<f64>, Box<dyn std::error::Error>> {
+    let default = current.map(|value| value.to_string()).unwrap_or_default();
+    loop {
+      match Self::prompt_parsed(message, &default, parse)? {
+        None => return Ok(None),
+        Some(Some(value)) => return Ok(Some(value)),
+        Some(None) => println!("{}", "❌ A coordinate is required".red()),
+      }
+    }
+  }
+
+  /// Prompts for the development details of a roll, starting from `current`.
+  ///
+  /// Every detail is optional and asked again until it is empty or valid.
+  /// Returns None if the user cancels.
+  fn prompt_development(
+    current: &Development,
+  ) -> Result<Option<Development>, Box<dyn std::error::Error>> {
+    let text = |value: &str| Ok(value.trim().to_string());
+    let Some(developer) = Self::prompt_parsed(
+      "Developer (e.g. HC-110, optional):",
+      current.developer.as_deref().unwrap_or(""),
+      text,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(dilution) = Self::prompt_parsed(
+      "Dilution (e.g. 1+31, optional):",
+      current.dilution.as_deref().unwrap_or(""),
+      text,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(time) = Self::prompt_parsed(
+      "Development time (e.g. 9:30, optional):",
+      &current
+        .time
+        .map(Development::format_time)
+        .unwrap_or_default(),
+      Development::parse_time,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(temperature) = Self::prompt_parsed(
+      "Temperature in °C (e.g. 20, optional):",
+      &current
+        .temperature
+        .map(|temperature| temperature.to_string())
+        .unwrap_or_default(),
+      Development::parse_temperature,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(lab) = Self::prompt_parsed(
+      "Developed by (lab or person, optional):",
+      current.lab.as_deref().unwrap_or(""),
+      text,
+    )?
+    else {
+      return Ok(None);
+    };
+    let Some(scanned_on) = Self::prompt_parsed(
+      "Date scanned (YYYY-MM-DD, optional):",
+      &current
+        .scanned_on
+        .map(|date| date.to_string())
+        .unwrap_or_default(),
+      Roll::parse_date,
+    )?
+    else {
+      return Ok(None);
+    };
+    Ok(Some(Development {
+      developer,
+      dilution,
+      time,
+      temperature,
+      lab,
+      scanned_on: scanned_on.flatten(),
+    }))
+  }
 }
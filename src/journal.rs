@@ -0,0 +1,564 @@
+//! Backup journal for undoing batch operations.
+//!
+//! Right before a batch run writes to a file, the original of every file the
+//! write may touch (the image and its XMP sidecar) is snapshotted into a run
+//! directory under the configuration directory, keyed by a run ID. Right
+//! after, the snapshot is compared with the result and the manifest of the
+//! run is saved, so an interrupted run can be undone up to its last file.
+//! Depending on the `BackupMode`, a snapshot is either a full copy of the
+//! original or a patch holding only the bytes the run changed. Undoing a run
+//! first checks, by size and SHA-256 hash, that no file has been modified
+//! since the run.
+
+use crate::{utils::write_atomic, Result};
+use chrono::{DateTime, Local, Utc};
+use dirs::config_dir;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Name of the manifest file inside each run directory
+const MANIFEST: &str = "journal.json";
+
+/// Unchanged bytes tolerated between two changed ranges before a patch splits them
+const MERGE_GAP: usize = 32;
+
+/// What the journal stores for each file a run modifies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupMode {
+  /// Store only the bytes the run changed, which is usually just the metadata
+  #[default]
+  Metadata,
+  /// Store a full copy of every original file
+  Full,
+  /// Do not back up anything; runs cannot be undone
+  Off,
+}
+
+/// Size and SHA-256 hash identifying the contents of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileState {
+  /// Size in bytes
+  pub size: u64,
+  /// Lowercase hexadecimal SHA-256 digest
+  pub sha256: String,
+}
+
+impl FileState {
+  /// Computes the state of the given file contents.
+  #[must_use]
+  pub fn of(bytes: &[u8]) -> Self {
+    Self {
+      size: bytes.len() as u64,
+      sha256: format!("{:x}", Sha256::digest(bytes)),
+    }
+  }
+}
+
+/// Snapshot of the original contents of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "file", rename_all = "lowercase")]
+pub enum Snapshot {
+  /// Full copy of the original file
+  Full(String),
+  /// Patch that turns the modified file back into the original
+  Patch(String),
+}
+
+/// A file changed by a run.
+///
+/// A file written more than once by a run, such as a date adjustment followed
+/// by an apply, has a record for each write.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRecord {
+  /// Absolute path of the file
+  pub path: PathBuf,
+  /// State before the run, or `None` if the run created the file
+  pub before: Option<FileState>,
+  /// State after the run, or `None` if the run deleted the file
+  pub after: Option<FileState>,
+  /// Snapshot of the original, stored inside the run directory
+  pub snapshot: Option<Snapshot>,
+  /// Whether the file has already been restored by an undo
+  #[serde(default)]
+  pub restored: bool,
+}
+
+/// A recorded batch run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+  /// Unique run ID, also the name of the run directory
+  pub id: String,
+  /// Operation carried out by the run ("apply" or "erase")
+  pub operation: String,
+  /// How the originals were backed up
+  pub backup_mode: BackupMode,
+  /// When the run started
+  pub created_at: DateTime<Utc>,
+  /// When the run was undone, if it has been
+  pub undone_at: Option<DateTime<Utc>>,
+  /// Files changed by the run, in the order they were written
+  pub files: Vec<FileRecord>,
+}
+
+impl RunRecord {
+  /// Returns "undone", "partially undone" or "recorded".
+  #[must_use]
+  pub fn status(&self) -> &'static str {
+    if self.undone_at.is_some() {
+      "undone"
+    } else if self.files.iter().any(|f| f.restored) {
+      "partially undone"
+    } else {
+      "recorded"
+    }
+  }
+
+  /// Formats the start time of the run in local time.
+  #[must_use]
+  pub fn local_time(&self) -> String {
+    self
+      .created_at
+      .with_timezone(&Local)
+      .format("%Y-%m-%d %H:%M:%S")
+      .to_string()
+  }
+}
+
+/// A file that could not be restored.
+#[derive(Debug, Serialize)]
+pub struct UndoFailure {
+  /// Path of the file
+  pub path: PathBuf,
+  /// Why the file was not restored
+  pub error: String,
+}
+
+/// Outcome of undoing a run.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoReport {
+  /// ID of the undone run
+  pub run_id: String,
+  /// Files restored to their original contents
+  pub restored: Vec<PathBuf>,
+  /// Files left untouched
+  pub failed: Vec<UndoFailure>,
+}
+
+/// A file captured before a write, waiting to be compared with its new state.
+struct Pending {
+  path: PathBuf,
+  before: Option<FileState>,
+  copy: String,
+}
+
+/// Journal of the batch run in progress.
+pub struct Journal {
+  dir: PathBuf,
+  run: RunRecord,
+  pending: Vec<Pending>,
+  captured: usize,
+}
+
+impl Journal {
+  /// Returns the default journal directory, `ifex/journal` under the config directory.
+  pub fn default_root() -> Result<PathBuf> {
+    let config_dir = config_dir().ok_or("Could not find config directory")?;
+    Ok(config_dir.join("ifex").join("journal"))
+  }
+
+  /// Starts journaling a new run under `root`.
+  pub fn start(root: &Path, operation: &str, backup_mode: BackupMode) -> Result<Self> {
+    let created_at = Utc::now();
+    let suffix = Uuid::new_v4().simple().to_string();
+    let id = format!(
+      "{}-{}",
+      created_at.with_timezone(&Local).format("%Y%m%d-%H%M%S"),
+      &suffix[..6]
+    );
+    let dir = root.join(&id);
+    fs::create_dir_all(&dir)?;
+
+    Ok(Self {
+      dir,
+      run: RunRecord {
+        id,
+        operation: operation.to_string(),
+        backup_mode,
+        created_at,
+        undone_at: None,
+        files: Vec::new(),
+      },
+      pending: Vec::new(),
+      captured: 0,
+    })
+  }
+
+  /// Returns the ID of the run.
+  #[must_use]
+  pub fn id(&self) -> &str {
+    &self.run.id
+  }
+
+  /// Snapshots an image and its XMP sidecar right before they are written.
+  ///
+  /// Call `record` once the write is done. Files that do not exist yet are
+  /// recorded as well, so that undo can remove a sidecar created by the run.
+  /// Capturing a file that is already waiting to be recorded is a no-op.
+  pub fn capture(&mut self, path: &Path) -> Result<()> {
+    for path in [path.to_path_buf(), path.with_extension("xmp")] {
+      let path = if path.is_absolute() {
+        path
+      } else {
+        std::env::current_dir()?.join(path)
+      };
+      if self.pending.iter().any(|p| p.path == path) {
+        continue;
+      }
+
+      let copy = format!("{}.orig", self.captured);
+      self.captured += 1;
+      let before = if path.is_file() {
+        let bytes = fs::read(&path)?;
+        fs::write(self.dir.join(&copy), &bytes)?;
+        Some(FileState::of(&bytes))
+      } else {
+        None
+      };
+      self.pending.push(Pending { path, before, copy });
+    }
+    Ok(())
+  }
+
+  /// Records how the captured files changed since `capture` and saves the
+  /// manifest.
+  ///
+  /// Unchanged files are dropped from the journal.
+  pub fn record(&mut self) -> Result<()> {
+    let before = self.run.files.len();
+    for pending in std::mem::take(&mut self.pending) {
+      self.record_file(pending)?;
+    }
+    if self.run.files.len() > before {
+      self.save()?;
+    }
+    Ok(())
+  }
+
+  /// Records any files still captured and completes the run.
+  ///
+  /// Returns the run ID, or `None` (removing the run directory) if the run
+  /// changed nothing.
+  pub fn finish(mut self) -> Result<Option<String>> {
+    self.record()?;
+
+    if self.run.files.is_empty() {
+      self.discard();
+      return Ok(None);
+    }
+    Ok(Some(self.run.id))
+  }
+
+  /// Abandons the run, removing everything captured so far.
+  pub fn discard(self) {
+    let _ = fs::remove_dir_all(&self.dir);
+  }
+
+  /// Compares a captured file with its current state and stores its snapshot.
+  fn record_file(&mut self, pending: Pending) -> Result<()> {
+    let copy_path = self.dir.join(&pending.copy);
+    let current = read_state(&pending.path)?;
+    let after = current.as_ref().map(|(state, _)| state.clone());
+
+    if after == pending.before {
+      if pending.before.is_some() {
+        fs::remove_file(&copy_path)?;
+      }
+      return Ok(());
+    }
+
+    let snapshot = match (&pending.before, current) {
+      (None, _) => None,
+      (Some(_), Some((_, modified))) if self.run.backup_mode == BackupMode::Metadata => {
+        let original = fs::read(&copy_path)?;
+        let name = pending.copy.replace(".orig", ".patch");
+        fs::write(
+          self.dir.join(&name),
+          encode_patch(&diff(&original, &modified)),
+        )?;
+        fs::remove_file(&copy_path)?;
+        Some(Snapshot::Patch(name))
+      }
+      (Some(_), _) => Some(Snapshot::Full(pending.copy)),
+    };
+
+    self.run.files.push(FileRecord {
+      path: pending.path,
+      before: pending.before,
+      after,
+      snapshot,
+      restored: false,
+    });
+    Ok(())
+  }
+
+  /// Writes the manifest of the run.
+  fn save(&self) -> Result<()> {
+    save_manifest(&self.dir, &self.run)
+  }
+
+  /// Lists the runs recorded under `root`, oldest first.
+  pub fn history(root: &Path) -> Result<Vec<RunRecord>> {
+    if !root.is_dir() {
+      return Ok(Vec::new());
+    }
+
+    let mut runs = Vec::new();
+    for entry in fs::read_dir(root)? {
+      let manifest = entry?.path().join(MANIFEST);
+      if manifest.is_file() {
+        runs.push(serde_json::from_str::<RunRecord>(&fs::read_to_string(
+          manifest,
+        )?)?);
+      }
+    }
+    runs.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+    Ok(runs)
+  }
+
+  /// Restores the files changed by a run, by default the latest one not yet undone.
+  ///
+  /// Files are restored in reverse order. A file whose size or hash no longer
+  /// matches the state left by the run is reported and left untouched; once
+  /// the conflict is resolved, undoing the run again retries only the files
+  /// that were not restored.
+  pub fn undo(root: &Path, run_id: Option<&str>) -> Result<UndoReport> {
+    let history = Self::history(root)?;
+    let mut run = match run_id {
+      Some(id) => history
+        .into_iter()
+        .find(|run| run.id == id)
+        .ok_or_else(|| format!("Run not found: {id}"))?,
+      None => history
+        .into_iter()
+        .rev()
+        .find(|run| run.undone_at.is_none())
+        .ok_or("No run to undo")?,
+    };
+    if run.undone_at.is_some() {
+      return Err(format!("Run {} has already been undone", run.id).into());
+    }
+
+    let dir = root.join(&run.id);
+    let mut report = UndoReport {
+      run_id: run.id.clone(),
+      restored: Vec::new(),
+      failed: Vec::new(),
+    };
+
+    for file in run.files.iter_mut().rev().filter(|f| !f.restored) {
+      match restore(&dir, file) {
+        Ok(()) => {
+          file.restored = true;
+          report.restored.push(file.path.clone());
+        }
+        Err(e) => report.failed.push(UndoFailure {
+          path: file.path.clone(),
+          error: e.to_string(),
+        }),
+      }
+    }
+
+    if report.failed.is_empty() {
+      run.undone_at = Some(Utc::now());
+    }
+    save_manifest(&dir, &run)?;
+    Ok(report)
+  }
+}
+
+/// Writes a run manifest into its run directory.
+fn save_manifest(dir: &Path, run: &RunRecord) -> Result<()> {
+  write_atomic(
+    &dir.join(MANIFEST),
+    serde_json::to_string_pretty(run)?.as_bytes(),
+  )?;
+  Ok(())
+}
+
+/// Reads the state and contents of a file, or `None` if it does not exist.
+fn read_state(path: &Path) -> Result<Option<(FileState, Vec<u8>)>> {
+  if !path.exists() {
+    return Ok(None);
+  }
+  let bytes = fs::read(path)?;
+  Ok(Some((FileState::of(&bytes), bytes)))
+}
+
+/// Restores one file from its snapshot after verifying it is unchanged since the run.
+fn restore(dir: &Path, file: &FileRecord) -> Result<()> {
+  let current = read_state(&file.path)?;
+  match (&current, &file.after) {
+    (Some((state, _)), Some(after)) if state == after => {}
+    (None, None) => {}
+    (None, Some(_)) => return Err("File has been removed since the run".into()),
+    _ => return Err("File has been modified since the run".into()),
+  }
+
+  let original = match (&file.snapshot, current) {
+    (None, _) => {
+      // The run created this file
+      fs::remove_file(&file.path)?;
+      return Ok(());
+    }
+    (Some(Snapshot::Full(name)), _) => fs::read(dir.join(name))?,
+    (Some(Snapshot::Patch(name)), Some((_, modified))) => {
+      apply_patch(&modified, &decode_patch(&fs::read(dir.join(name))?)?)?
+    }
+    (Some(Snapshot::Patch(_)), None) => return Err("Backup patch has no file to apply to".into()),
+  };
+
+  if Some(FileState::of(&original)) != file.before {
+    return Err("Backup does not match the original file".into());
+  }
+  write_atomic(&file.path, &original)?;
+  Ok(())
+}
+
+/// A range of the modified file to replace with bytes of the original.
+struct Hunk {
+  start: usize,
+  end: usize,
+  bytes: Vec<u8>,
+}
+
+/// Computes the hunks turning `modified` back into `original`.
+///
+/// Picks the smaller of a single hunk spanning everything between the common
+/// prefix and suffix, which suits inserted or removed segments (JPEG), and a
+/// hunk per changed run of bytes at the same offsets, which suits in-place
+/// edits followed by appended data (TIFF and DNG).
+fn diff(original: &[u8], modified: &[u8]) -> Vec<Hunk> {
+  let prefix = original
+    .iter()
+    .zip(modified)
+    .take_while(|(a, b)| a == b)
+    .count();
+  let suffix = original[prefix..]
+    .iter()
+    .rev()
+    .zip(modified[prefix..].iter().rev())
+    .take_while(|(a, b)| a == b)
+    .count();
+  let single = vec![Hunk {
+    start: prefix,
+    end: modified.len() - suffix,
+    bytes: original[prefix..original.len() - suffix].to_vec(),
+  }];
+  if modified.len() < original.len() {
+    return single;
+  }
+
+  let mut aligned = Vec::new();
+  let mut i = prefix;
+  while i < original.len() {
+    if original[i] == modified[i] {
+      i += 1;
+      continue;
+    }
+    let start = i;
+    let mut end = i + 1;
+    let mut j = end;
+    while j < original.len() && j - end < MERGE_GAP {
+      if original[j] != modified[j] {
+        end = j + 1;
+      }
+      j += 1;
+    }
+    aligned.push(Hunk {
+      start,
+      end,
+      bytes: original[start..end].to_vec(),
+    });
+    i = end;
+  }
+  if modified.len() > original.len() {
+    aligned.push(Hunk {
+      start: original.len(),
+      end: modified.len(),
+      bytes: Vec::new(),
+    });
+  }
+
+  let stored = |hunks: &[Hunk]| hunks.iter().map(|h| h.bytes.len() + 24).sum::<usize>();
+  if stored(&aligned) < stored(&single) {
+    aligned
+  } else {
+    single
+  }
+}
+
+/// Applies hunks computed by `diff` to the modified file.
+fn apply_patch(modified: &[u8], hunks: &[Hunk]) -> Result<Vec<u8>> {
+  let mut original = Vec::with_capacity(modified.len());
+  let mut pos = 0;
+  for hunk in hunks {
+    if hunk.start < pos || hunk.end < hunk.start || hunk.end > modified.len() {
+      return Err("Backup patch is corrupt".into());
+    }
+    original.extend_from_slice(&modified[pos..hunk.start]);
+    original.extend_from_slice(&hunk.bytes);
+    pos = hunk.end;
+  }
+  original.extend_from_slice(&modified[pos..]);
+  Ok(original)
+}
+
+/// Serializes hunks as little-endian start, end and length fields followed by the bytes.
+fn encode_patch(hunks: &[Hunk]) -> Vec<u8> {
+  let mut data = Vec::new();
+  for hunk in hunks {
+    for field in [hunk.start, hunk.end, hunk.bytes.len()] {
+      data.extend_from_slice(&(field as u64).to_le_bytes());
+    }
+    data.extend_from_slice(&hunk.bytes);
+  }
+  data
+}
+
+/// Parses hunks serialized by `encode_patch`.
+fn decode_patch(data: &[u8]) -> Result<Vec<Hunk>> {
+  let mut hunks = Vec::new();
+  let mut pos = 0;
+  while pos < data.len() {
+    let start = read_field(data, &mut pos)?;
+    let end = read_field(data, &mut pos)?;
+    let len = read_field(data, &mut pos)?;
+    let bytes = data
+      .get(pos..pos.saturating_add(len))
+      .ok_or("Backup patch is truncated")?;
+    pos += len;
+    hunks.push(Hunk {
+      start,
+      end,
+      bytes: bytes.to_vec(),
+    });
+  }
+  Ok(hunks)
+}
+
+/// Reads one little-endian 64-bit field of a patch.
+fn read_field(data: &[u8], pos: &mut usize) -> Result<usize> {
+  let bytes: [u8; 8] = data
+    .get(*pos..*pos + 8)
+    .and_then(|b| b.try_into().ok())
+    .ok_or("Backup patch is truncated")?;
+  *pos += 8;
+  Ok(usize::try_from(u64::from_le_bytes(bytes))?)
+}
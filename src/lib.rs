@@ -27,16 +27,24 @@ pub mod cli;
 pub mod config;
 /// Data management and persistence module
 pub mod data;
+/// Non-interactive equipment management commands module
+pub mod equipment;
 /// EXIF processing and manipulation module
 pub mod exif;
 /// Interactive file selection module
 pub mod file_selector;
+/// GPX track log import module
+pub mod gpx;
 /// Interactive user interface module
 pub mod interface;
+/// Backup journal and undo module
+pub mod journal;
 /// Data model definitions module
 pub mod models;
 /// User prompt utilities module
 pub mod prompts;
+/// Shot log import module
+pub mod shot_log;
 /// Utility functions and helpers module
 pub mod utils;
 
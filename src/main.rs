@@ -3,8 +3,11 @@
 
 use colored::Colorize;
 use ifex::{
-  cli::{Cli, Commands},
+  cli::{ApplyArgs, Cli, Commands, EraseArgs, GeotagArgs},
+  equipment,
+  exif::exif_manager::ProcessingResult,
   interface::Interface,
+  journal::{BackupMode, Journal},
   Result,
 };
 use std::process;
@@ -14,9 +17,21 @@ fn main() {
   let cli = Cli::parse_args();
 
   let result = match &cli.command {
-    Some(Commands::Manage) => run_management(),
+    Some(Commands::Manage) => run_management(cli.backup),
     Some(Commands::Read { paths, json }) => check_exif_data(paths, *json),
-    Some(Commands::Run) | None => run_interactive(cli.one_sec),
+    Some(Commands::Apply(args)) => apply_exif_data(args, cli.one_sec, cli.backup),
+    Some(Commands::Erase(args)) => erase_exif_data(args, cli.backup),
+    Some(Commands::Geotag(args)) => geotag_files(args, cli.backup),
+    Some(Commands::Camera(args)) => equipment::run_camera(args),
+    Some(Commands::Lens(args)) => equipment::run_lens(args),
+    Some(Commands::Film(args)) => equipment::run_film(args),
+    Some(Commands::Photographer(args)) => equipment::run_photographer(args),
+    Some(Commands::Setup(args)) => equipment::run_setup(args),
+    Some(Commands::Roll(args)) => equipment::run_roll(args),
+    Some(Commands::Location(args)) => equipment::run_location(args),
+    Some(Commands::Undo { run_id, json }) => undo_run(run_id.as_deref(), *json),
+    Some(Commands::History { json }) => show_history(*json),
+    Some(Commands::Run) | None => run_interactive(cli.one_sec, cli.backup),
   };
 
   if let Err(e) = result {
@@ -26,23 +41,269 @@ fn main() {
 }
 
 /// Run the interactive main menu interface
-fn run_interactive(one_sec: bool) -> Result<()> {
+fn run_interactive(one_sec: bool, backup_mode: BackupMode) -> Result<()> {
   println!("{}", "🏷️  IFEX - EXIF Data Manager\n".blue());
 
-  let mut interface = Interface::new(one_sec)?;
+  let mut interface = Interface::new(one_sec)?.with_backup_mode(backup_mode);
   interface.run_main_menu()?;
   Ok(())
 }
 
 /// Run the equipment management interface
-fn run_management() -> Result<()> {
+fn run_management(backup_mode: BackupMode) -> Result<()> {
   println!("{}", "🏷️  IFEX - Equipment Manager\n".blue());
 
-  let mut interface = Interface::new(false)?;
+  let mut interface = Interface::new(false)?.with_backup_mode(backup_mode);
   interface.run_management_menu()?;
   Ok(())
 }
 
+/// Apply a setup, film and photographer, or a roll, to image files without prompting
+///
+/// With `--dry-run`, only reports the tag changes for each file. Exits with an
+/// error if an entity cannot be resolved or any file fails.
+fn apply_exif_data(args: &ApplyArgs, one_sec: bool, backup_mode: BackupMode) -> Result<()> {
+  use ifex::{
+    equipment::update_development, file_selector::FileSelector, models::Exposure,
+    prompts::PromptUtils, shot_log::ShotLog, utils::split_keywords, DataManager, ExifManager,
+  };
+  use std::collections::HashMap;
+
+  let data_manager = DataManager::new()?;
+  let (mut selection, shot_iso) = if let Some(roll) = &args.roll {
+    let roll = data_manager.find_roll(roll)?;
+    let selection = data_manager.create_roll_selection(roll.id)?;
+    (selection, args.iso.or(roll.shot_iso))
+  } else {
+    let key = |value: &Option<String>| value.clone().unwrap_or_default();
+    let setup_id = data_manager.find_setup(&key(&args.setup))?.id;
+    let film_id = data_manager.find_film(&key(&args.film))?.id;
+    let photographer_id = data_manager.find_photographer(&key(&args.photographer))?.id;
+    let selection = data_manager.create_selection(setup_id, film_id, photographer_id)?;
+    (selection, args.iso)
+  };
+  selection.caption.clone_from(&args.caption);
+  selection.keywords = args
+    .keywords
+    .as_deref()
+    .map(split_keywords)
+    .unwrap_or_default();
+  selection.exposure = Exposure {
+    exposure_time: args.shutter,
+    f_number: args.aperture,
+    compensation: args.ev,
+    metering_mode: args.metering,
+    program: args.program,
+  };
+  selection.development = update_development(&args.development, selection.development)?;
+  if let Some(location) = &args.saved_location {
+    selection.saved_location = Some(data_manager.find_location(location)?.clone());
+  }
+
+  let files = FileSelector::collect_files(&args.paths, args.recursive);
+  let frames = match &args.shot_log {
+    Some(path) => {
+      let matches = ShotLog::load(path)?.match_files(&files, args.frame_pattern.as_ref());
+      for warning in matches.warnings() {
+        if args.json {
+          eprintln!("{warning}");
+        } else {
+          println!("{}", format!("⚠️  {warning}").yellow());
+        }
+      }
+      matches.frames(&data_manager)?
+    }
+    None => HashMap::new(),
+  };
+  if !args.json {
+    PromptUtils::display_selection(&selection);
+    if args.dry_run {
+      println!("{}", "🔍 Dry run: no files will be modified\n".blue());
+    } else {
+      println!("{}", "📝 Applying EXIF data...\n".blue());
+    }
+  }
+
+  let result = ExifManager::new()
+    .with_dry_run(args.dry_run)
+    .with_backups(backup_mode)
+    .with_frames(frames)
+    .process_selected_files_with_one_sec(&files, Some(&selection), "apply", shot_iso, one_sec);
+
+  report_processing_result(&result, args.json)
+}
+
+/// Erase groups of metadata from image files
+///
+/// Asks for confirmation unless `--yes` is given, and exits with an error if
+/// any file fails.
+fn erase_exif_data(args: &EraseArgs, backup_mode: BackupMode) -> Result<()> {
+  use ifex::{exif::TagGroup, file_selector::FileSelector, prompts::PromptUtils, ExifManager};
+
+  let groups = TagGroup::resolve(&args.only, &args.keep);
+  if groups.is_empty() {
+    return Err("Nothing to erase: every selected group is kept".into());
+  }
+  let group_names = groups
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<_>>()
+    .join(", ");
+
+  let files = FileSelector::collect_files(&args.paths, args.recursive);
+  if !args.yes {
+    let confirmed = PromptUtils::prompt_confirm(
+      &format!(
+        "Erase {group_names} metadata from {} file(s)? {}",
+        files.len(),
+        PromptUtils::undo_hint(backup_mode)
+      ),
+      false,
+    )?;
+    if confirmed != Some(true) {
+      println!("{}", "Operation cancelled.".yellow());
+      return Ok(());
+    }
+  }
+  if !args.json {
+    println!(
+      "{}",
+      format!("🗑️  Erasing {group_names} metadata...\n").blue()
+    );
+  }
+
+  let result = ExifManager::new()
+    .with_backups(backup_mode)
+    .erase_selected_files(&files, &groups);
+  report_processing_result(&result, args.json)
+}
+
+/// Write the positions of a GPX track into image files by their capture time
+///
+/// With `--dry-run`, only reports the GPS tags for each file. Exits with an
+/// error if the track cannot be read or any file fails, such as a file taken
+/// away from the track.
+fn geotag_files(args: &GeotagArgs, backup_mode: BackupMode) -> Result<()> {
+  use ifex::{file_selector::FileSelector, gpx::Track, ExifManager};
+
+  let track = Track::load(&args.gpx)?;
+  let files = FileSelector::collect_files(&args.paths, args.recursive);
+  if !args.json {
+    let action = if args.dry_run {
+      "🔍 Dry run: no files will be modified"
+    } else {
+      "🌍 Geotagging"
+    };
+    println!(
+      "{}",
+      format!(
+        "{action} ({} track points, {} file(s))\n",
+        track.points.len(),
+        files.len()
+      )
+      .blue()
+    );
+  }
+
+  let result = ExifManager::new()
+    .with_dry_run(args.dry_run)
+    .with_backups(backup_mode)
+    .geotag_files(
+      &files,
+      &track,
+      args.offset,
+      chrono::Duration::seconds(i64::from(args.max_gap)),
+    );
+  report_processing_result(&result, args.json)
+}
+
+/// Print the outcome of a batch operation as a summary or JSON report
+///
+/// Returns an error if nothing could be processed or any file failed.
+fn report_processing_result(result: &ProcessingResult, json: bool) -> Result<()> {
+  use ifex::prompts::PromptUtils;
+
+  if json {
+    println!("{}", serde_json::to_string_pretty(&result.results)?);
+  } else {
+    PromptUtils::display_processing_result(result);
+  }
+
+  if !result.success {
+    return Err(result.message.clone().into());
+  }
+  if result.results.failed > 0 {
+    return Err(format!("Failed to process {} files", result.results.failed).into());
+  }
+  Ok(())
+}
+
+/// Restore the files changed by a run from the backup journal
+///
+/// Exits with an error if any file could not be restored.
+fn undo_run(run_id: Option<&str>, json: bool) -> Result<()> {
+  let report = Journal::undo(&Journal::default_root()?, run_id)?;
+
+  if json {
+    println!("{}", serde_json::to_string_pretty(&report)?);
+  } else {
+    println!(
+      "{}",
+      format!(
+        "↩️  Restored {} files from run {}",
+        report.restored.len(),
+        report.run_id
+      )
+      .green()
+    );
+    for path in &report.restored {
+      println!("  {} {}", "✓".green(), path.display());
+    }
+    for failure in &report.failed {
+      println!("  {} {}", "✗".red(), failure.path.display());
+      println!("    {}", format!("Error: {}", failure.error).red());
+    }
+  }
+
+  if report.failed.is_empty() {
+    Ok(())
+  } else {
+    Err(format!("Failed to restore {} files", report.failed.len()).into())
+  }
+}
+
+/// List the runs recorded in the backup journal
+fn show_history(json: bool) -> Result<()> {
+  use ifex::prompts::PromptUtils;
+
+  let runs = Journal::history(&Journal::default_root()?)?;
+  if json {
+    println!("{}", serde_json::to_string_pretty(&runs)?);
+  } else if runs.is_empty() {
+    println!("{}", "No runs recorded.".yellow());
+  } else {
+    let rows: Vec<Vec<String>> = runs
+      .iter()
+      .rev()
+      .map(|run| {
+        vec![
+          run.id.clone(),
+          run.local_time(),
+          run.operation.clone(),
+          run.files.len().to_string(),
+          format!("{:?}", run.backup_mode).to_lowercase(),
+          run.status().to_string(),
+        ]
+      })
+      .collect();
+    PromptUtils::display_table(
+      &["Run ID", "Date", "Operation", "Files", "Backup", "Status"],
+      &rows,
+    );
+  }
+  Ok(())
+}
+
 /// Check and display EXIF data from image files or directories
 #[allow(clippy::unnecessary_wraps, clippy::too_many_lines)]
 fn check_exif_data(paths: &[std::path::PathBuf], json: bool) -> Result<()> {
@@ -194,4 +455,4 @@ fn check_exif_data(paths: &[std::path::PathBuf], json: bool) -> Result<()> {
   }
 
   Ok(())
-}
\ No newline at end of file
+}
@@ -1,6 +1,7 @@
 //! Data model definitions for IFEX equipment and selections
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -166,6 +167,12 @@ pub struct Photographer {
   /// Timestamp when the photographer was added to the system
   #[serde(rename = "createdAt")]
   pub created_at: DateTime<Utc>,
+  /// Copyright and licensing of the photographer's images
+  #[serde(default)]
+  pub rights: Rights,
+  /// Postal address, phone number and website of the photographer
+  #[serde(default)]
+  pub contact: Contact,
 }
 
 impl Photographer {
@@ -179,6 +186,8 @@ impl Photographer {
       name,
       email,
       created_at: Utc::now(),
+      rights: Rights::default(),
+      contact: Contact::default(),
     }
   }
 
@@ -193,6 +202,135 @@ impl Photographer {
       |email| format!("{} <{}>", self.name, email),
     )
   }
+
+  /// Returns the copyright notice for an image captured in `year`.
+  ///
+  /// Fills in the photographer's copyright template, or
+  /// `Rights::DEFAULT_COPYRIGHT_TEMPLATE` if they have none.
+  #[must_use]
+  #[allow(clippy::literal_string_with_formatting_args)] // template placeholders
+  pub fn copyright_notice(&self, year: i32) -> String {
+    self
+      .rights
+      .copyright_template
+      .as_deref()
+      .unwrap_or(Rights::DEFAULT_COPYRIGHT_TEMPLATE)
+      .replace("{year}", &year.to_string())
+      .replace("{name}", &self.name)
+  }
+}
+
+/// Contact details of a photographer, written as the IPTC Core creator
+/// contact info
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+  /// Street address, including the building and apartment
+  #[serde(default)]
+  pub address: Option<String>,
+  /// City
+  #[serde(default)]
+  pub city: Option<String>,
+  /// State, province or region
+  #[serde(default)]
+  pub region: Option<String>,
+  /// Postal code
+  #[serde(default, rename = "postalCode")]
+  pub postal_code: Option<String>,
+  /// Country
+  #[serde(default)]
+  pub country: Option<String>,
+  /// Phone number
+  #[serde(default)]
+  pub phone: Option<String>,
+  /// Website URL
+  #[serde(default)]
+  pub website: Option<String>,
+}
+
+impl Contact {
+  /// Returns whether no contact detail is set.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self == &Self::default()
+  }
+}
+
+/// Copyright and licensing terms of a photographer's images
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rights {
+  /// Copyright notice, where "{year}" is replaced by the capture year and
+  /// "{name}" by the photographer's name
+  #[serde(default, rename = "copyrightTemplate")]
+  pub copyright_template: Option<String>,
+  /// Licence, as a URL or a Creative Commons identifier such as "CC BY-SA 4.0"
+  #[serde(default)]
+  pub license: Option<String>,
+  /// Terms under which the images may be used
+  #[serde(default, rename = "usageTerms")]
+  pub usage_terms: Option<String>,
+}
+
+impl Rights {
+  /// Copyright notice used when the photographer has no template of their own.
+  pub const DEFAULT_COPYRIGHT_TEMPLATE: &'static str = "© {year} {name}";
+
+  /// Returns the URL of the licence.
+  ///
+  /// URLs are returned as given. Creative Commons identifiers such as
+  /// "CC BY-NC 4.0", "cc-by-sa" or "CC0" are resolved to the URL of their
+  /// legal code, defaulting to version 4.0. Returns `None` without a licence
+  /// or for an identifier that is not recognized.
+  #[must_use]
+  pub fn license_url(&self) -> Option<String> {
+    let license = self.license.as_deref()?.trim();
+    if license.starts_with("https://") || license.starts_with("http://") {
+      return Some(license.to_string());
+    }
+
+    let normalized = license.to_ascii_lowercase().replace([' ', '_'], "-");
+    match normalized.as_str() {
+      "cc0" | "cc0-1.0" => {
+        return Some("https://creativecommons.org/publicdomain/zero/1.0/".to_string());
+      }
+      "pdm" | "pdm-1.0" | "public-domain" => {
+        return Some("https://creativecommons.org/publicdomain/mark/1.0/".to_string());
+      }
+      _ => {}
+    }
+
+    let terms = normalized.strip_prefix("cc-")?;
+    let (elements, version) = match terms.rsplit_once('-') {
+      Some((elements, version)) if version.contains('.') => (elements, version),
+      _ => (terms, "4.0"),
+    };
+    let known_version = matches!(version, "1.0" | "2.0" | "2.5" | "3.0" | "4.0");
+    let known_elements = matches!(
+      elements,
+      "by" | "by-sa" | "by-nd" | "by-nc" | "by-nc-sa" | "by-nc-nd"
+    );
+    (known_version && known_elements)
+      .then(|| format!("https://creativecommons.org/licenses/{elements}/{version}/"))
+  }
+
+  /// Checks that the licence is a URL or a known Creative Commons identifier,
+  /// so typos do not end up in the written metadata.
+  pub fn validate(&self) -> Result<(), String> {
+    match &self.license {
+      Some(license) if self.license_url().is_none() => Err(format!(
+        "Unknown license \"{license}\": use a URL or a Creative Commons identifier such as \"CC BY 4.0\""
+      )),
+      _ => Ok(()),
+    }
+  }
+
+  /// Returns whether the licence places the images in the public domain,
+  /// meaning they are not rights-managed.
+  #[must_use]
+  pub fn is_public_domain(&self) -> bool {
+    self
+      .license_url()
+      .is_some_and(|url| url.starts_with("https://creativecommons.org/publicdomain/"))
+  }
 }
 
 /// Equipment setup combining a camera and optionally a lens
@@ -238,6 +376,459 @@ impl Setup {
   }
 }
 
+/// A roll of film that was shot with a setup and scanned
+///
+/// Ties a film stock to the setup it was loaded in, the photographer and the
+/// ISO it was shot at, so that its scans can be tagged without selecting each
+/// of them again. Its ID is written to the images as the roll identifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Roll {
+  /// Unique identifier for the roll, written to its images
+  pub id: Uuid,
+  /// User-defined name for the roll, such as "2024-07 Lisbon"
+  pub name: String,
+  /// Reference to the film stock of the roll
+  #[serde(rename = "filmId")]
+  pub film_id: Uuid,
+  /// Reference to the setup the roll was shot with
+  #[serde(rename = "setupId")]
+  pub setup_id: Uuid,
+  /// Reference to the photographer who shot the roll
+  #[serde(rename = "photographerId")]
+  pub photographer_id: Uuid,
+  /// ISO the roll was shot at, if pushed or pulled (defaults to the film's
+  /// box speed)
+  #[serde(rename = "shotIso", default)]
+  pub shot_iso: Option<u32>,
+  /// Date the roll was loaded into the camera
+  #[serde(rename = "loadedOn", default)]
+  pub loaded_on: Option<NaiveDate>,
+  /// Date the last frame was shot
+  #[serde(rename = "finishedOn", default)]
+  pub finished_on: Option<NaiveDate>,
+  /// Number of frames shot
+  #[serde(rename = "frameCount", default)]
+  pub frame_count: Option<u32>,
+  /// How the roll was developed and scanned
+  #[serde(default)]
+  pub development: Development,
+  /// Free-form notes
+  #[serde(default)]
+  pub notes: Option<String>,
+  /// Timestamp when the roll was added
+  #[serde(rename = "createdAt")]
+  pub created_at: DateTime<Utc>,
+}
+
+impl Roll {
+  /// Creates a new roll of a film stock shot with a setup by a photographer.
+  ///
+  /// Automatically generates a unique ID and sets the creation timestamp; the
+  /// other details are left empty.
+  #[must_use]
+  pub fn new(name: String, film_id: Uuid, setup_id: Uuid, photographer_id: Uuid) -> Self {
+    Self {
+      id: Uuid::new_v4(),
+      name,
+      film_id,
+      setup_id,
+      photographer_id,
+      shot_iso: None,
+      loaded_on: None,
+      finished_on: None,
+      frame_count: None,
+      development: Development::default(),
+      notes: None,
+      created_at: Utc::now(),
+    }
+  }
+
+  /// Returns the display name for the roll.
+  ///
+  /// Format: "Name (loaded - finished)", leaving out the dates that are not
+  /// known.
+  #[must_use]
+  pub fn display_name(&self) -> String {
+    match (self.loaded_on, self.finished_on) {
+      (Some(loaded), Some(finished)) => format!("{} ({loaded} - {finished})", self.name),
+      (Some(loaded), None) => format!("{} (loaded {loaded})", self.name),
+      (None, Some(finished)) => format!("{} (finished {finished})", self.name),
+      (None, None) => self.name.clone(),
+    }
+  }
+
+  /// Returns the identifier written to the images of the roll: its ID as 32
+  /// hexadecimal digits, the format of the EXIF `ImageUniqueID` tag.
+  #[must_use]
+  pub fn identifier(&self) -> String {
+    self.id.simple().to_string()
+  }
+
+  /// Parses a date such as "2024-07-14", where an empty value means the date
+  /// is not known.
+  pub fn parse_date(value: &str) -> Result<Option<NaiveDate>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+      return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+      .map(Some)
+      .map_err(|_| format!("Invalid date \"{value}\": use YYYY-MM-DD"))
+  }
+}
+
+/// Development and scanning of a roll of film
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Development {
+  /// Developer, such as "HC-110"
+  #[serde(default)]
+  pub developer: Option<String>,
+  /// Dilution of the developer, such as "1+31"
+  #[serde(default)]
+  pub dilution: Option<String>,
+  /// Development time in seconds
+  #[serde(default, rename = "timeSeconds")]
+  pub time: Option<u32>,
+  /// Temperature of the developer in degrees Celsius
+  #[serde(default)]
+  pub temperature: Option<f32>,
+  /// Lab or person who developed the roll
+  #[serde(default)]
+  pub lab: Option<String>,
+  /// Date the negatives were scanned
+  #[serde(default, rename = "scannedOn")]
+  pub scanned_on: Option<NaiveDate>,
+}
+
+impl Development {
+  /// Returns whether no development detail is set.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self == &Self::default()
+  }
+
+  /// Returns a short description of the known details, such as
+  /// "HC-110 1+31 9:30 at 20°C, lab Carmencita, scanned 2024-08-01".
+  #[must_use]
+  pub fn display_name(&self) -> String {
+    let process = [
+      self.developer.clone(),
+      self.dilution.clone(),
+      self.time.map(Self::format_time),
+      self
+        .temperature
+        .map(|temperature| format!("at {temperature}°C")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ");
+    [
+      Some(process).filter(|process| !process.is_empty()),
+      self.lab.as_ref().map(|lab| format!("lab {lab}")),
+      self.scanned_on.map(|date| format!("scanned {date}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ")
+  }
+
+  /// Parses a development time given as minutes and seconds such as "9:30"
+  /// or "9m30s", or as minutes such as "9" or "9.5", into seconds.
+  pub fn parse_time(value: &str) -> Result<u32, String> {
+    let invalid =
+      || format!("Invalid development time \"{value}\": use minutes such as 9:30 or 9.5");
+    let trimmed = value.trim();
+    let (minutes, seconds) = if let Some((minutes, seconds)) = trimmed.split_once([':', 'm']) {
+      let seconds = seconds.trim().strip_suffix('s').unwrap_or(seconds).trim();
+      let seconds: u32 = if seconds.is_empty() {
+        0
+      } else {
+        seconds.parse().map_err(|_| invalid())?
+      };
+      if seconds >= 60 {
+        return Err(invalid());
+      }
+      (
+        minutes.trim().parse::<u32>().map_err(|_| invalid())?,
+        seconds,
+      )
+    } else {
+      let (num, denom) = decimal_rational(trimmed).ok_or_else(invalid)?;
+      let seconds = u64::from(num) * 60 / u64::from(denom);
+      let seconds = u32::try_from(seconds).map_err(|_| invalid())?;
+      (seconds / 60, seconds % 60)
+    };
+    minutes
+      .checked_mul(60)
+      .and_then(|minutes| minutes.checked_add(seconds))
+      .filter(|&time| time > 0)
+      .ok_or_else(invalid)
+  }
+
+  /// Formats a development time in seconds as minutes and seconds, such as
+  /// "9:30".
+  #[must_use]
+  pub fn format_time(seconds: u32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+  }
+
+  /// Parses a developer temperature in degrees Celsius such as "20", "20C"
+  /// or "20.5°C".
+  pub fn parse_temperature(value: &str) -> Result<f32, String> {
+    let trimmed = value.trim();
+    let degrees = trimmed
+      .strip_suffix(['C', 'c'])
+      .unwrap_or(trimmed)
+      .trim_end()
+      .trim_end_matches('°')
+      .trim_end();
+    degrees
+      .parse::<f32>()
+      .ok()
+      .filter(|degrees| (0.0..100.0).contains(degrees))
+      .ok_or_else(|| format!("Invalid temperature \"{value}\": use degrees Celsius such as 20"))
+  }
+
+  /// Returns how far a film shot at `shot_iso` was pushed (positive) or
+  /// pulled (negative) from its box speed, in thirds of a stop.
+  #[must_use]
+  pub fn push_pull_thirds(box_iso: u32, shot_iso: u32) -> i32 {
+    if box_iso == 0 || shot_iso == 0 {
+      return 0;
+    }
+    let stops = (f64::from(shot_iso) / f64::from(box_iso)).log2();
+    (stops * 3.0).round() as i32
+  }
+
+  /// Formats a number of thirds of a stop, such as "+1", "-2/3" or
+  /// "+1 1/3".
+  #[must_use]
+  pub fn format_stops(thirds: i32) -> String {
+    let sign = match thirds {
+      0 => "",
+      _ if thirds < 0 => "-",
+      _ => "+",
+    };
+    let (whole, third) = (thirds.abs() / 3, thirds.abs() % 3);
+    match (whole, third) {
+      (_, 0) => format!("{sign}{whole}"),
+      (0, _) => format!("{sign}{third}/3"),
+      _ => format!("{sign}{whole} {third}/3"),
+    }
+  }
+}
+
+/// Exposure settings of a frame, which film cameras do not record.
+///
+/// Every setting is optional: settings that are not known leave the
+/// corresponding tags of an image untouched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Exposure {
+  /// Shutter speed in seconds, such as 1/125
+  pub exposure_time: Option<(u32, u32)>,
+  /// Aperture the frame was shot at, such as 28/5 for f/5.6
+  pub f_number: Option<(u32, u32)>,
+  /// Exposure compensation in EV, such as -1/3
+  pub compensation: Option<(i32, i32)>,
+  /// Metering mode of the camera
+  pub metering_mode: Option<MeteringMode>,
+  /// Exposure program of the camera
+  pub program: Option<ExposureProgram>,
+}
+
+impl Exposure {
+  /// Returns whether no exposure setting is known.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self == &Self::default()
+  }
+
+  /// Returns these settings, taking the ones that are not known from
+  /// `fallback`.
+  #[must_use]
+  pub fn or(self, fallback: Self) -> Self {
+    Self {
+      exposure_time: self.exposure_time.or(fallback.exposure_time),
+      f_number: self.f_number.or(fallback.f_number),
+      compensation: self.compensation.or(fallback.compensation),
+      metering_mode: self.metering_mode.or(fallback.metering_mode),
+      program: self.program.or(fallback.program),
+    }
+  }
+
+  /// Returns a short description of the known settings, such as
+  /// "1/125 s, f/5.6, -1/3 EV, spot metering, manual".
+  #[must_use]
+  pub fn display_name(&self) -> String {
+    let value_name = |value: Option<clap::builder::PossibleValue>| {
+      value.map(|value| value.get_name().replace('-', " "))
+    };
+    [
+      self.exposure_time.map(|(num, denom)| {
+        if num % denom == 0 {
+          format!("{} s", num / denom)
+        } else if num == 1 {
+          format!("1/{denom} s")
+        } else {
+          format!("{} s", f64::from(num) / f64::from(denom))
+        }
+      }),
+      self
+        .f_number
+        .map(|(num, denom)| format!("f/{}", f64::from(num) / f64::from(denom))),
+      self.compensation.map(|(num, denom)| match num {
+        0 => "0 EV".to_string(),
+        _ if denom == 1 => format!("{num:+} EV"),
+        _ => format!("{num:+}/{denom} EV"),
+      }),
+      self
+        .metering_mode
+        .and_then(|mode| value_name(mode.to_possible_value()))
+        .map(|name| format!("{name} metering")),
+      self
+        .program
+        .and_then(|program| value_name(program.to_possible_value())),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ")
+  }
+
+  /// Parses a shutter speed given as a fraction of a second such as "1/125",
+  /// or as seconds such as "2", "0.5" or "4s".
+  pub fn parse_shutter_speed(value: &str) -> Result<(u32, u32), String> {
+    let trimmed = value.trim();
+    let seconds = trimmed
+      .strip_suffix(['s', '"'])
+      .unwrap_or(trimmed)
+      .trim_end();
+    unsigned_rational(seconds).ok_or_else(|| {
+      format!(
+        "Invalid shutter speed \"{value}\": use a fraction such as 1/125 or seconds such as 2"
+      )
+    })
+  }
+
+  /// Parses an aperture such as "5.6", "f/5.6" or "f8".
+  pub fn parse_aperture(value: &str) -> Result<(u32, u32), String> {
+    let trimmed = value.trim();
+    let number = trimmed
+      .strip_prefix("f/")
+      .or_else(|| trimmed.strip_prefix("F/"))
+      .or_else(|| trimmed.strip_prefix(['f', 'F']))
+      .unwrap_or(trimmed);
+    decimal_rational(number)
+      .filter(|&(num, _)| num > 0)
+      .ok_or_else(|| format!("Invalid aperture \"{value}\": use an f-number such as 5.6 or f/8"))
+  }
+
+  /// Parses an exposure compensation in EV, given as a signed fraction such
+  /// as "+1/3" or "-2/3" or as a decimal such as "-0.7". "0" is accepted.
+  pub fn parse_compensation(value: &str) -> Result<(i32, i32), String> {
+    let trimmed = value.trim();
+    let (negative, magnitude) = match trimmed.strip_prefix('-') {
+      Some(magnitude) => (true, magnitude),
+      None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let invalid =
+      || format!("Invalid exposure compensation \"{value}\": use EV such as +1/3, -2/3 or -0.7");
+    let (num, denom) = match magnitude.split_once('/') {
+      Some(_) => unsigned_rational(magnitude),
+      None => decimal_rational(magnitude),
+    }
+    .ok_or_else(invalid)?;
+    let num = i32::try_from(num).map_err(|_| invalid())?;
+    let denom = i32::try_from(denom).map_err(|_| invalid())?;
+    Ok((if negative { -num } else { num }, denom))
+  }
+}
+
+/// Parses a positive fraction such as "1/125" or a positive decimal.
+fn unsigned_rational(value: &str) -> Option<(u32, u32)> {
+  match value.split_once('/') {
+    Some((num, denom)) => {
+      let num: u32 = num.trim().parse().ok()?;
+      let denom: u32 = denom.trim().parse().ok()?;
+      (num > 0 && denom > 0).then(|| reduce(num, denom))
+    }
+    None => decimal_rational(value).filter(|&(num, _)| num > 0),
+  }
+}
+
+/// Parses a non-negative decimal such as "5.6" into a rational with at most
+/// three decimal places.
+fn decimal_rational(value: &str) -> Option<(u32, u32)> {
+  let value = value.trim();
+  if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+    return None;
+  }
+  let value = value.parse::<f64>().ok()?;
+  (value.is_finite() && value < f64::from(u32::MAX) / 1000.0)
+    .then(|| reduce((value * 1000.0).round() as u32, 1000))
+}
+
+/// Reduces a fraction to its lowest terms.
+const fn reduce(num: u32, denom: u32) -> (u32, u32) {
+  let (mut a, mut b) = (num, denom);
+  while b != 0 {
+    (a, b) = (b, a % b);
+  }
+  match (num.checked_div(a), denom.checked_div(a)) {
+    (Some(num), Some(denom)) => (num, denom),
+    _ => (num, denom),
+  }
+}
+
+/// Metering mode of a camera, with the values of the EXIF `MeteringMode` tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MeteringMode {
+  /// Average of the whole frame
+  Average = 1,
+  /// Average weighted towards the centre
+  CenterWeighted = 2,
+  /// A small spot
+  Spot = 3,
+  /// Several spots
+  MultiSpot = 4,
+  /// Matrix or evaluative metering
+  Pattern = 5,
+  /// A larger spot
+  Partial = 6,
+}
+
+impl MeteringMode {
+  /// Returns the value of the EXIF `MeteringMode` tag.
+  #[must_use]
+  pub const fn exif_value(self) -> u16 {
+    self as u16
+  }
+}
+
+/// Exposure program of a camera, with the values of the EXIF
+/// `ExposureProgram` tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExposureProgram {
+  /// Shutter speed and aperture set by hand
+  Manual = 1,
+  /// Program auto exposure
+  Program = 2,
+  /// Aperture set by hand, shutter speed by the camera
+  AperturePriority = 3,
+  /// Shutter speed set by hand, aperture by the camera
+  ShutterPriority = 4,
+}
+
+impl ExposureProgram {
+  /// Returns the value of the EXIF `ExposureProgram` tag.
+  #[must_use]
+  pub const fn exif_value(self) -> u16 {
+    self as u16
+  }
+}
+
 /// Complete equipment selection for EXIF metadata application.
 ///
 /// This struct combines all the necessary equipment and photographer information
@@ -255,4 +846,250 @@ pub struct Selection {
   pub film: Film,
   /// The photographer who took the photographs
   pub photographer: Photographer,
+  /// Caption describing the photographs, if any
+  pub caption: Option<String>,
+  /// Keywords for the photographs, which replace existing ones unless empty
+  pub keywords: Vec<String>,
+  /// Exposure settings the photographs were shot with, if known
+  pub exposure: Exposure,
+  /// Roll the photographs were shot on, if applied by roll
+  pub roll: Option<Roll>,
+  /// Focal length the photographs were shot at, overriding the lens's, such
+  /// as 35/1 for a zoom lens set to 35mm
+  pub focal_length: Option<(u32, u32)>,
+  /// Name of the spot the photographs were taken at, such as a street or
+  /// landmark, which takes precedence over the sublocation of the saved
+  /// location when both are set
+  pub sublocation: Option<String>,
+  /// How the film was developed and scanned
+  pub development: Development,
+  /// Saved location the photographs were taken at, whose position and
+  /// address are written to the images along with `sublocation`
+  pub saved_location: Option<Location>,
+}
+
+impl Selection {
+  /// Creates a selection of the specified setup, equipment and photographer.
+  ///
+  /// Everything else, such as the caption, exposure and roll, starts out
+  /// unknown and can be filled in afterwards.
+  #[must_use]
+  pub fn new(
+    setup: Setup,
+    camera: Camera,
+    lens: Option<Lens>,
+    film: Film,
+    photographer: Photographer,
+  ) -> Self {
+    Self {
+      setup,
+      camera,
+      lens,
+      film,
+      photographer,
+      caption: None,
+      keywords: Vec::new(),
+      exposure: Exposure::default(),
+      roll: None,
+      focal_length: None,
+      sublocation: None,
+      development: Development::default(),
+      saved_location: None,
+    }
+  }
+}
+
+/// Values recorded for a single frame, such as in a shot log, which take
+/// precedence over those of the selection they are applied with
+#[derive(Debug, Clone, Default)]
+pub struct Frame {
+  /// Exposure settings of the frame
+  pub exposure: Exposure,
+  /// Lens the frame was shot with, if it differs from the setup's
+  pub lens: Option<Lens>,
+  /// Focal length the frame was shot at
+  pub focal_length: Option<(u32, u32)>,
+  /// Notes about the frame, written as its caption
+  pub notes: Option<String>,
+  /// Name of the spot the frame was taken at, written as its sublocation
+  pub sublocation: Option<String>,
+}
+
+impl Frame {
+  /// Returns `selection` with the values of this frame applied.
+  ///
+  /// Exposure settings that are not known for the frame fall back to the
+  /// selection's, and the notes of the frame replace the caption.
+  #[must_use]
+  pub fn apply_to(&self, selection: &Selection) -> Selection {
+    let mut selection = selection.clone();
+    selection.exposure = self.exposure.or(selection.exposure);
+    if self.lens.is_some() {
+      selection.lens.clone_from(&self.lens);
+    }
+    selection.focal_length = self.focal_length.or(selection.focal_length);
+    if self.notes.is_some() {
+      selection.caption.clone_from(&self.notes);
+    }
+    if self.sublocation.is_some() {
+      selection.sublocation.clone_from(&self.sublocation);
+    }
+    selection
+  }
+
+  /// Parses a focal length in millimetres such as "35" or "35mm".
+  pub fn parse_focal_length(value: &str) -> Result<(u32, u32), String> {
+    let trimmed = value.trim();
+    let millimetres = trimmed.strip_suffix("mm").unwrap_or(trimmed).trim_end();
+    decimal_rational(millimetres)
+      .filter(|&(num, _)| num > 0)
+      .ok_or_else(|| format!("Invalid focal length \"{value}\": use millimetres such as 35"))
+  }
+}
+
+/// Position a photograph was taken at, as written to the GPS tags
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsPosition {
+  /// Latitude in decimal degrees, positive north of the equator
+  pub latitude: f64,
+  /// Longitude in decimal degrees, positive east of Greenwich
+  pub longitude: f64,
+  /// Altitude in metres above sea level, if known
+  pub altitude: Option<f64>,
+  /// Time the position was recorded at, if known
+  pub time: Option<DateTime<Utc>>,
+}
+
+impl GpsPosition {
+  /// Formats the position as decimal degrees, such as "38.713900 N,
+  /// 9.139400 W", followed by the altitude when known.
+  #[must_use]
+  pub fn display_name(&self) -> String {
+    let latitude_ref = if self.latitude < 0.0 { 'S' } else { 'N' };
+    let longitude_ref = if self.longitude < 0.0 { 'W' } else { 'E' };
+    let mut name = format!(
+      "{:.6} {latitude_ref}, {:.6} {longitude_ref}",
+      self.latitude.abs(),
+      self.longitude.abs()
+    );
+    if let Some(altitude) = self.altitude {
+      name = format!("{name}, {altitude:.1} m");
+    }
+    name
+  }
+}
+
+/// A place photographs are taken at, saved to tag whole rolls shot there
+///
+/// Its coordinates are written to the GPS tags, and its name and address to
+/// the IPTC location fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Location {
+  /// Unique identifier for the location
+  pub id: Uuid,
+  /// User-defined name for the location, such as "Alfama"
+  pub name: String,
+  /// Latitude in decimal degrees, positive north of the equator
+  pub latitude: f64,
+  /// Longitude in decimal degrees, positive east of Greenwich
+  pub longitude: f64,
+  /// Altitude in metres above sea level, if known
+  #[serde(default)]
+  pub altitude: Option<f64>,
+  /// Place within the city, such as a district or landmark
+  #[serde(default)]
+  pub sublocation: Option<String>,
+  /// City
+  #[serde(default)]
+  pub city: Option<String>,
+  /// State, province or region
+  #[serde(default)]
+  pub state: Option<String>,
+  /// Country name
+  #[serde(default)]
+  pub country: Option<String>,
+  /// ISO 3166 country code, such as "PT"
+  #[serde(rename = "countryCode", default)]
+  pub country_code: Option<String>,
+  /// Timestamp when the location was added
+  #[serde(rename = "createdAt")]
+  pub created_at: DateTime<Utc>,
+}
+
+impl Location {
+  /// Creates a new location with the specified name and coordinates.
+  ///
+  /// Automatically generates a unique ID and sets the creation timestamp; the
+  /// altitude and address are left empty.
+  #[must_use]
+  pub fn new(name: String, latitude: f64, longitude: f64) -> Self {
+    Self {
+      id: Uuid::new_v4(),
+      name,
+      latitude,
+      longitude,
+      altitude: None,
+      sublocation: None,
+      city: None,
+      state: None,
+      country: None,
+      country_code: None,
+      created_at: Utc::now(),
+    }
+  }
+
+  /// Returns the display name for the location.
+  ///
+  /// Format: "Name (City, Country)", leaving out the parts of the address
+  /// that are not known.
+  #[must_use]
+  pub fn display_name(&self) -> String {
+    let place: Vec<&str> = [&self.city, &self.country]
+      .into_iter()
+      .filter_map(Option::as_deref)
+      .collect();
+    if place.is_empty() {
+      self.name.clone()
+    } else {
+      format!("{} ({})", self.name, place.join(", "))
+    }
+  }
+
+  /// Returns the position of the location, without a time.
+  #[must_use]
+  pub const fn position(&self) -> GpsPosition {
+    GpsPosition {
+      latitude: self.latitude,
+      longitude: self.longitude,
+      altitude: self.altitude,
+      time: None,
+    }
+  }
+
+  /// Checks that the coordinates are in range and that the country code has
+  /// two or three letters, as IPTC requires.
+  pub fn validate(&self) -> Result<(), String> {
+    if !(-90.0..=90.0).contains(&self.latitude) {
+      return Err(format!(
+        "Invalid latitude {}: use -90 to 90 degrees",
+        self.latitude
+      ));
+    }
+    if !(-180.0..=180.0).contains(&self.longitude) {
+      return Err(format!(
+        "Invalid longitude {}: use -180 to 180 degrees",
+        self.longitude
+      ));
+    }
+    match &self.country_code {
+      Some(code)
+        if !(2..=3).contains(&code.len()) || !code.bytes().all(|b| b.is_ascii_alphabetic()) =>
+      {
+        Err(format!(
+          "Invalid country code \"{code}\": use an ISO 3166 code such as \"PT\""
+        ))
+      }
+      _ => Ok(()),
+    }
+  }
 }
@@ -4,7 +4,12 @@
 //! interactive prompts, as well as formatting functions for displaying
 //! information in a user-friendly manner.
 
-use crate::{file_selector::FileSelector, models::Selection};
+use crate::{
+  exif::{exif_manager::ProcessingResult, ChangeKind, TagChange},
+  file_selector::FileSelector,
+  journal::BackupMode,
+  models::Selection,
+};
 use colored::Colorize;
 use inquire::{
   autocompletion::Autocomplete, Confirm, CustomType, InquireError, MultiSelect, Select, Text,
@@ -622,9 +627,166 @@ impl PromptUtils {
       "Photographer:".cyan(),
       selection.photographer.display_name()
     );
+    if let Some(caption) = &selection.caption {
+      println!("  {} {}", "Caption:".cyan(), caption);
+    }
+    if !selection.keywords.is_empty() {
+      println!("  {} {}", "Keywords:".cyan(), selection.keywords.join(", "));
+    }
+    if let Some(roll) = &selection.roll {
+      println!("  {} {}", "Roll:".cyan(), roll.display_name());
+    }
+    if let Some(location) = &selection.saved_location {
+      println!("  {} {}", "Location:".cyan(), location.display_name());
+    }
+    if !selection.exposure.is_empty() {
+      println!(
+        "  {} {}",
+        "Exposure:".cyan(),
+        selection.exposure.display_name()
+      );
+    }
+    if !selection.development.is_empty() {
+      println!(
+        "  {} {}",
+        "Development:".cyan(),
+        selection.development.display_name()
+      );
+    }
     println!();
   }
 
+  /// Displays the outcome of a batch EXIF operation.
+  ///
+  /// Prints the number of processed and failed files followed by
+  /// a per-file status line including any error message and, for dry runs,
+  /// the tag changes that would be made.
+  pub fn display_processing_result(result: &ProcessingResult) {
+    if !result.success {
+      println!("{}", format!("❌ Error: {}", result.message).red());
+      return;
+    }
+
+    println!(
+      "{}",
+      format!(
+        "✅ Successfully processed {} files",
+        result.results.processed
+      )
+      .green()
+    );
+    if result.results.failed > 0 {
+      println!(
+        "{}",
+        format!("❌ Failed to process {} files", result.results.failed).red()
+      );
+    }
+
+    println!("\n📊 Processing Results:");
+    for file in &result.results.files {
+      let status = if file.success {
+        "✓".green()
+      } else {
+        "✗".red()
+      };
+      let type_label = file
+        .file_type
+        .as_ref()
+        .map(|t| format!("[{}]", t.to_uppercase()))
+        .unwrap_or_default();
+      println!(
+        "  {} {} {}",
+        status,
+        file.name,
+        type_label.as_str().bright_black()
+      );
+      if let Some(error) = &file.error {
+        println!("    {}", format!("Error: {error}").red());
+      }
+      if let Some(changes) = &file.changes {
+        Self::display_tag_changes(changes);
+      }
+    }
+
+    if let Some(run_id) = &result.results.run_id {
+      println!(
+        "\n{}",
+        format!("↩️  Originals backed up as run {run_id} (revert with `ifex undo {run_id}`)")
+          .bright_black()
+      );
+    }
+  }
+
+  /// Describes whether an operation can be reverted, for confirmation prompts.
+  #[must_use]
+  pub const fn undo_hint(backup_mode: BackupMode) -> &'static str {
+    match backup_mode {
+      BackupMode::Off => "This cannot be undone.",
+      BackupMode::Metadata | BackupMode::Full => "Originals are backed up for `ifex undo`.",
+    }
+  }
+
+  /// Displays planned tag changes as a coloured diff.
+  ///
+  /// Added tags are shown in green, changed tags in yellow with their old
+  /// and new values, and removed tags in red.
+  pub fn display_tag_changes(changes: &[TagChange]) {
+    if changes.is_empty() {
+      println!("    {}", "No changes".bright_black());
+    }
+    for change in changes {
+      let old = change.old.as_deref().unwrap_or_default();
+      let new = change.new.as_deref().unwrap_or_default();
+      let line = match change.kind {
+        ChangeKind::Added => format!("+ {}: {new}", change.tag).green(),
+        ChangeKind::Changed => format!("~ {}: {old} → {new}", change.tag).yellow(),
+        ChangeKind::Removed => format!("- {}: {old}", change.tag).red(),
+      };
+      println!("    {line}");
+    }
+  }
+
+  /// Displays rows of text as a table with aligned columns.
+  ///
+  /// Each column is as wide as its longest cell or header.
+  pub fn display_table(headers: &[&str], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = headers
+      .iter()
+      .enumerate()
+      .map(|(i, header)| {
+        rows
+          .iter()
+          .filter_map(|row| row.get(i))
+          .map(|cell| cell.chars().count())
+          .chain(std::iter::once(header.chars().count()))
+          .max()
+          .unwrap_or(0)
+      })
+      .collect();
+    let format_row = |cells: Vec<String>| {
+      cells
+        .iter()
+        .zip(&widths)
+        .map(|(cell, width)| format!(" {cell:width$} "))
+        .collect::<Vec<_>>()
+        .join("│")
+    };
+
+    println!(
+      "{}",
+      format_row(headers.iter().map(ToString::to_string).collect()).cyan()
+    );
+    println!(
+      "{}",
+      "─"
+        .repeat(widths.iter().sum::<usize>() + widths.len() * 3 - 1)
+        .cyan()
+    );
+    for row in rows {
+      println!("{}", format_row(row.clone()));
+    }
+  }
+
   /// Formats a table header for EXIF data display.
   ///
   /// Creates a formatted table header with specified column widths
@@ -0,0 +1,308 @@
+//! Shot log import.
+//!
+//! A shot log records the values of each frame of a roll, such as its
+//! shutter speed, aperture, focal length, lens, notes and location, keyed by
+//! frame number. Logs are read from CSV files with a header row or from JSON
+//! files, and their frames are matched to scan files either by sort order or
+//! by a frame number captured from the file names.
+
+use crate::data::DataManager;
+use crate::models::{Exposure, Frame};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Values logged for a single frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoggedFrame {
+  /// Frame number on the roll
+  pub number: u32,
+  /// Shutter speed, aperture and exposure compensation of the frame
+  pub exposure: Exposure,
+  /// Focal length the frame was shot at
+  pub focal_length: Option<(u32, u32)>,
+  /// Name or ID of the lens the frame was shot with, if swapped
+  pub lens: Option<String>,
+  /// Notes about the frame
+  pub notes: Option<String>,
+  /// Name of the spot the frame was taken at
+  pub sublocation: Option<String>,
+}
+
+impl LoggedFrame {
+  /// Builds a frame from the fields of a log entry, given as column names
+  /// and values. Unknown columns and empty values are ignored.
+  fn from_fields<'a>(fields: impl IntoIterator<Item = (&'a str, String)>) -> Result<Self, String> {
+    let mut frame = Self::default();
+    let mut number = None;
+    for (name, value) in fields {
+      let value = value.trim();
+      if value.is_empty() {
+        continue;
+      }
+      match normalize_column(name).as_str() {
+        "frame" | "frameno" | "framenumber" | "number" | "no" => {
+          number = Some(
+            value
+              .parse()
+              .map_err(|_| format!("Invalid frame number \"{value}\""))?,
+          );
+        }
+        "shutter" | "shutterspeed" | "speed" | "exposuretime" => {
+          frame.exposure.exposure_time = Some(Exposure::parse_shutter_speed(value)?);
+        }
+        "aperture" | "fnumber" | "fstop" | "f" => {
+          frame.exposure.f_number = Some(Exposure::parse_aperture(value)?);
+        }
+        "ev" | "compensation" | "exposurecompensation" => {
+          frame.exposure.compensation = Some(Exposure::parse_compensation(value)?);
+        }
+        "focallength" | "focal" => {
+          frame.focal_length = Some(Frame::parse_focal_length(value)?);
+        }
+        "lens" => frame.lens = Some(value.to_string()),
+        "notes" | "note" | "caption" | "comment" => frame.notes = Some(value.to_string()),
+        "sublocation" | "location" | "place" => frame.sublocation = Some(value.to_string()),
+        _ => {}
+      }
+    }
+    frame.number = number.ok_or("Missing frame number")?;
+    Ok(frame)
+  }
+}
+
+/// Lowercases a column name and strips spaces, underscores and hyphens, so
+/// that "Focal Length", `focal_length` and "focal-length" are the same.
+fn normalize_column(name: &str) -> String {
+  name
+    .chars()
+    .filter(|c| !matches!(c, ' ' | '_' | '-'))
+    .flat_map(char::to_lowercase)
+    .collect()
+}
+
+/// Frames of a shot log, ordered by frame number.
+#[derive(Debug, Clone, Default)]
+pub struct ShotLog {
+  /// Logged frames
+  pub frames: Vec<LoggedFrame>,
+}
+
+impl ShotLog {
+  /// Reads a shot log, as JSON if the file has a `.json` extension and as
+  /// CSV otherwise.
+  pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+    let is_json = path
+      .extension()
+      .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let content = std::fs::read_to_string(path)
+      .map_err(|e| format!("Failed to read shot log {}: {e}", path.display()))?;
+    if is_json {
+      Self::from_json(&content)
+    } else {
+      Self::from_csv(&content)
+    }
+  }
+
+  /// Parses a CSV shot log whose first row names the columns.
+  ///
+  /// Recognized columns are the frame number ("Frame"), "Shutter",
+  /// "Aperture", "EV", "Focal Length", "Lens", "Notes" and "Location";
+  /// other columns are ignored.
+  pub fn from_csv(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+      .flexible(true)
+      .trim(csv::Trim::All)
+      .from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    let mut frames = Vec::new();
+    for record in reader.records() {
+      let record = record?;
+      let line = record.position().map_or(0, csv::Position::line);
+      let fields = headers
+        .iter()
+        .zip(record.iter())
+        .map(|(name, value)| (name, value.to_string()));
+      let frame =
+        LoggedFrame::from_fields(fields).map_err(|e| format!("Shot log line {line}: {e}"))?;
+      frames.push(frame);
+    }
+    Self::from_frames(frames)
+  }
+
+  /// Parses a JSON shot log: an array of frame objects, or an object with
+  /// such an array under "frames". Keys are the CSV column names.
+  pub fn from_json(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let value: serde_json::Value = serde_json::from_str(content)?;
+    let entries = match &value {
+      serde_json::Value::Array(entries) => entries,
+      serde_json::Value::Object(object) => match object.get("frames") {
+        Some(serde_json::Value::Array(entries)) => entries,
+        _ => return Err("Shot log must contain a \"frames\" array".into()),
+      },
+      _ => return Err("Shot log must be an array of frames".into()),
+    };
+    let mut frames = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+      let entry_error = |e: String| format!("Shot log entry {}: {e}", index + 1);
+      let object = entry
+        .as_object()
+        .ok_or_else(|| entry_error("Expected an object".to_string()))?;
+      let fields = object.iter().filter_map(|(name, value)| match value {
+        serde_json::Value::String(text) => Some((name.as_str(), text.clone())),
+        serde_json::Value::Null => None,
+        other => Some((name.as_str(), other.to_string())),
+      });
+      frames.push(LoggedFrame::from_fields(fields).map_err(entry_error)?);
+    }
+    Self::from_frames(frames)
+  }
+
+  /// Orders frames by number, rejecting frames that are logged twice.
+  fn from_frames(mut frames: Vec<LoggedFrame>) -> Result<Self, Box<dyn std::error::Error>> {
+    frames.sort_by_key(|frame| frame.number);
+    if let Some(pair) = frames
+      .windows(2)
+      .find(|pair| pair[0].number == pair[1].number)
+    {
+      return Err(format!("Shot log lists frame {} more than once", pair[0].number).into());
+    }
+    Ok(Self { frames })
+  }
+
+  /// Matches the logged frames to `files`.
+  ///
+  /// Without a pattern, files sorted by name are paired with frames in
+  /// order. With a pattern, the frame number of each file is captured from
+  /// its file name: by the group named `frame`, else the first group, else
+  /// the whole match. Several files may share a frame, such as a JPEG and a
+  /// TIFF scan of it.
+  #[must_use]
+  pub fn match_files(&self, files: &[PathBuf], pattern: Option<&Regex>) -> FrameMatches {
+    let mut files = files.to_vec();
+    files.sort_by_key(|path| file_name(path));
+
+    let mut matches = FrameMatches::default();
+    match pattern {
+      None => {
+        for (index, path) in files.into_iter().enumerate() {
+          match self.frames.get(index) {
+            Some(frame) => matches.matched.push((path, frame.clone())),
+            None => matches.files_without_frames.push(path),
+          }
+        }
+      }
+      Some(pattern) => {
+        let frames: BTreeMap<u32, &LoggedFrame> = self
+          .frames
+          .iter()
+          .map(|frame| (frame.number, frame))
+          .collect();
+        for path in files {
+          let frame = frame_number(pattern, &file_name(&path))
+            .and_then(|number| frames.get(&number).copied());
+          match frame {
+            Some(frame) => matches.matched.push((path, frame.clone())),
+            None => matches.files_without_frames.push(path),
+          }
+        }
+      }
+    }
+    matches.frames_without_files = self
+      .frames
+      .iter()
+      .map(|frame| frame.number)
+      .filter(|number| {
+        !matches
+          .matched
+          .iter()
+          .any(|(_, frame)| frame.number == *number)
+      })
+      .collect();
+    matches
+  }
+}
+
+/// Returns the file name of a path, or the whole path if it has none.
+fn file_name(path: &Path) -> String {
+  path.file_name().map_or_else(
+    || path.display().to_string(),
+    |name| name.to_string_lossy().to_string(),
+  )
+}
+
+/// Captures a frame number from a file name.
+fn frame_number(pattern: &Regex, name: &str) -> Option<u32> {
+  let captures = pattern.captures(name)?;
+  let number = captures
+    .name("frame")
+    .or_else(|| captures.get(1))
+    .or_else(|| captures.get(0))?;
+  number.as_str().parse().ok()
+}
+
+/// Result of matching the frames of a shot log to files.
+#[derive(Debug, Clone, Default)]
+pub struct FrameMatches {
+  /// Files paired with their logged frame, sorted by file name
+  pub matched: Vec<(PathBuf, LoggedFrame)>,
+  /// Numbers of logged frames that no file was matched to
+  pub frames_without_files: Vec<u32>,
+  /// Files that no logged frame was matched to
+  pub files_without_frames: Vec<PathBuf>,
+}
+
+impl FrameMatches {
+  /// Returns the frame to apply to each matched file, resolving the lens
+  /// names of the log against the saved lenses.
+  pub fn frames(&self, data_manager: &DataManager) -> Result<HashMap<PathBuf, Frame>, String> {
+    self
+      .matched
+      .iter()
+      .map(|(path, logged)| {
+        let lens = logged
+          .lens
+          .as_deref()
+          .map(|key| data_manager.find_lens(key).cloned())
+          .transpose()
+          .map_err(|e| format!("Frame {}: {e}", logged.number))?;
+        let frame = Frame {
+          exposure: logged.exposure,
+          lens,
+          focal_length: logged.focal_length,
+          notes: logged.notes.clone(),
+          sublocation: logged.sublocation.clone(),
+        };
+        Ok((path.clone(), frame))
+      })
+      .collect()
+  }
+
+  /// Describes the frames without files and the files without frames, one
+  /// line each; empty when every frame and file was matched.
+  #[must_use]
+  pub fn warnings(&self) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if !self.frames_without_files.is_empty() {
+      let numbers = self
+        .frames_without_files
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+      warnings.push(format!("Logged frames without a file: {numbers}"));
+    }
+    if !self.files_without_frames.is_empty() {
+      let names = self
+        .files_without_frames
+        .iter()
+        .map(|path| file_name(path))
+        .collect::<Vec<_>>()
+        .join(", ");
+      warnings.push(format!(
+        "Files without a logged frame (applied without frame values): {names}"
+      ));
+    }
+    warnings
+  }
+}
@@ -1,10 +1,14 @@
 //! Utility functions for file handling and path processing.
 //!
-//! This module provides helper functions for cleaning user input paths,
-//! determining supported image file formats, and extracting file type
-//! information from file extensions.
+//! This module provides helper functions for cleaning user input paths and
+//! keyword lists, determining supported image file formats, extracting file type
+//! information from file extensions, and replacing files atomically.
 
-use std::path::Path;
+use filetime::FileTime;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
 /// Cleans user-provided path input by removing quotes and handling escape sequences.
 ///
@@ -38,6 +42,20 @@ pub fn clean_path(input: &str) -> String {
   }
 }
 
+/// Splits comma-separated keyword input into keywords.
+///
+/// Surrounding whitespace is trimmed and empty entries are dropped, so
+/// "film, portrait,," yields "film" and "portrait".
+#[must_use]
+pub fn split_keywords(input: &str) -> Vec<String> {
+  input
+    .split(',')
+    .map(str::trim)
+    .filter(|keyword| !keyword.is_empty())
+    .map(str::to_string)
+    .collect()
+}
+
 /// Checks if a file path has a supported image format extension.
 ///
 /// Returns true if the file extension matches any of the supported formats
@@ -110,3 +128,84 @@ pub fn get_file_type(path: &Path) -> Option<String> {
     })
   })
 }
+
+/// Options for `write_atomic_with`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+  /// Keep the modification time of the file being replaced
+  pub preserve_mtime: bool,
+}
+
+/// Replaces the contents of a file without ever leaving it half-written.
+///
+/// Equivalent to `write_atomic_with` with the default options.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+  write_atomic_with(path, WriteOptions::default(), |file| file.write_all(data))
+}
+
+/// Replaces the contents of a file through a temporary file in the same directory.
+///
+/// The `write` callback fills the temporary file, which is then synced to disk,
+/// given the permissions (and optionally the modification time) of the file it
+/// replaces, and renamed over it. If anything fails before the rename, the
+/// temporary file is removed and the original is left untouched. Symbolic links
+/// are followed, so the file they point to is replaced rather than the link.
+pub fn write_atomic_with(
+  path: &Path,
+  options: WriteOptions,
+  write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+  let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+  let file_name = path.file_name().ok_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!("Not a file path: {}", path.display()),
+    )
+  })?;
+  let dir = path
+    .parent()
+    .filter(|dir| !dir.as_os_str().is_empty())
+    .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+  let temp_path = dir.join(format!(
+    ".{}.{}.tmp",
+    file_name.to_string_lossy(),
+    Uuid::new_v4().simple()
+  ));
+
+  let result = write_temp_file(&temp_path, fs::metadata(&path).ok(), options, write)
+    .and_then(|()| fs::rename(&temp_path, &path));
+  if result.is_err() {
+    let _ = fs::remove_file(&temp_path);
+    return result;
+  }
+
+  // Persist the rename itself
+  #[cfg(unix)]
+  File::open(&dir)?.sync_all()?;
+  Ok(())
+}
+
+/// Writes and syncs the temporary file backing `write_atomic_with`.
+fn write_temp_file(
+  temp_path: &Path,
+  original: Option<fs::Metadata>,
+  options: WriteOptions,
+  write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+  let mut file = OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .open(temp_path)?;
+  write(&mut file)?;
+  file.flush()?;
+  if let Some(original) = &original {
+    file.set_permissions(original.permissions())?;
+  }
+  file.sync_all()?;
+  drop(file);
+
+  if let Some(original) = original.filter(|_| options.preserve_mtime) {
+    filetime::set_file_mtime(temp_path, FileTime::from_last_modification_time(&original))?;
+  }
+  Ok(())
+}
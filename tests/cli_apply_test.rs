@@ -0,0 +1,611 @@
+//! Tests for the non-interactive `ifex apply` command.
+
+use assert_cmd::Command;
+use ifex::config::Config;
+use ifex::exif::processors::JpegProcessor;
+use ifex::models::*;
+use predicates::str::contains;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_minimal_jpeg() -> Vec<u8> {
+  vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+    0x00, 0x00, // APP0
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]
+}
+
+/// Writes an `ifex.json` with one setup, three films and a photographer.
+fn write_config(config_dir: &Path) -> Result<Setup, Box<dyn std::error::Error>> {
+  let camera = Camera::new("Olympus".to_string(), "OM-1".to_string());
+  let lens = Lens::new(
+    "Zuiko".to_string(),
+    "Auto-S".to_string(),
+    "50".to_string(),
+    "1.8".to_string(),
+    "OM".to_string(),
+  );
+  let setup = Setup::new("OM kit".to_string(), camera.id, Some(lens.id));
+  let config = Config {
+    cameras: vec![camera],
+    lenses: vec![lens],
+    films: vec![
+      Film::new("Ilford".to_string(), "HP5 Plus".to_string(), 400),
+      Film::new("Kodak".to_string(), "Gold".to_string(), 200),
+      Film::new("Kodak".to_string(), "Gold".to_string(), 400),
+    ],
+    photographers: vec![Photographer::new("Test User".to_string(), None)],
+    setups: vec![setup.clone()],
+    rolls: Vec::new(),
+    locations: Vec::new(),
+  };
+  fs::write(
+    config_dir.join("ifex.json"),
+    serde_json::to_string(&config)?,
+  )?;
+  Ok(setup)
+}
+
+fn ifex(config_dir: &Path) -> Command {
+  let mut command = Command::new(env!("CARGO_BIN_EXE_ifex"));
+  command
+    .env("XDG_CONFIG_HOME", config_dir)
+    .env("HOME", config_dir);
+  command
+}
+
+#[test]
+fn test_apply_by_name_and_id_with_json_report() {
+  let temp_dir = TempDir::new().unwrap();
+  let setup = write_config(temp_dir.path()).unwrap();
+  let photos = temp_dir.path().join("photos");
+  fs::create_dir_all(photos.join("nested")).unwrap();
+  fs::write(photos.join("a.jpg"), create_minimal_jpeg()).unwrap();
+  fs::write(photos.join("nested").join("b.jpg"), create_minimal_jpeg()).unwrap();
+
+  let output = ifex(temp_dir.path())
+    .args(["apply", "--setup", &setup.id.to_string()])
+    .args(["--film", "hp5 plus", "--photographer", "Test User"])
+    .args(["--iso", "1600", "--json"])
+    .arg(&photos)
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+
+  let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  assert_eq!(report["processed"], 1);
+  assert_eq!(report["failed"], 0);
+  assert_eq!(report["files"][0]["name"], "a.jpg");
+  assert_eq!(report["files"][0]["fileType"], "jpeg");
+
+  let exif_data = JpegProcessor::read_exif(&photos.join("a.jpg")).unwrap();
+  assert!(exif_data
+    .iter()
+    .any(|(key, value)| key.starts_with("Make") && value.contains("Olympus")));
+  assert!(exif_data
+    .iter()
+    .any(|(key, value)| key.starts_with("ISO") && value == "1600"));
+  assert!(JpegProcessor::read_exif(&photos.join("nested").join("b.jpg")).is_err());
+
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "Ilford HP5 Plus"])
+    .args(["--photographer", "test user", "--recursive", "--json"])
+    .arg(&photos)
+    .assert()
+    .success()
+    .stdout(contains("\"processed\": 2"));
+}
+
+#[test]
+fn test_apply_fails_for_unknown_or_ambiguous_entities() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  fs::write(&photo, create_minimal_jpeg()).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "Portra"])
+    .args(["--photographer", "Test User"])
+    .arg(&photo)
+    .assert()
+    .failure()
+    .stderr(contains("Film not found: Portra"));
+
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "Kodak Gold"])
+    .args(["--photographer", "Test User"])
+    .arg(&photo)
+    .assert()
+    .failure()
+    .stderr(contains("ambiguous"));
+
+  assert_eq!(fs::read(&photo).unwrap(), create_minimal_jpeg());
+}
+
+#[test]
+fn test_apply_exits_non_zero_when_a_file_fails() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  let broken = temp_dir.path().join("broken.jpg");
+  fs::write(&photo, create_minimal_jpeg()).unwrap();
+  fs::write(&broken, b"not a jpeg").unwrap();
+
+  let output = ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--json"])
+    .arg(&photo)
+    .arg(&broken)
+    .arg(temp_dir.path().join("missing.jpg"))
+    .output()
+    .unwrap();
+  assert!(!output.status.success());
+
+  let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  assert_eq!(report["processed"], 1);
+  assert_eq!(report["failed"], 2);
+  assert_eq!(report["files"][2]["error"], "File does not exist");
+}
+
+#[test]
+fn test_dry_run_reports_changes_without_writing() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let tagged = temp_dir.path().join("tagged.jpg");
+  let fresh = temp_dir.path().join("fresh.jpg");
+  fs::write(&tagged, create_minimal_jpeg()).unwrap();
+  fs::write(&fresh, create_minimal_jpeg()).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User"])
+    .arg(&tagged)
+    .assert()
+    .success();
+  let tagged_bytes = fs::read(&tagged).unwrap();
+
+  let output = ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--iso", "1600"])
+    .args(["--dry-run", "--json"])
+    .arg(&tagged)
+    .arg(&fresh)
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(fs::read(&tagged).unwrap(), tagged_bytes);
+  assert_eq!(fs::read(&fresh).unwrap(), create_minimal_jpeg());
+
+  let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  let changes = report["files"][0]["changes"].as_array().unwrap();
+  assert_eq!(changes.len(), 2);
+  assert_eq!(changes[0]["tag"], "ISOSpeedRatings");
+  assert_eq!(changes[0]["kind"], "changed");
+  assert_eq!(changes[0]["old"], "400");
+  assert_eq!(changes[0]["new"], "1600");
+  assert_eq!(changes[1]["tag"], "UserComment");
+  assert_eq!(changes[1]["kind"], "added");
+  assert_eq!(
+    changes[1]["new"],
+    "Ilford HP5 Plus (ISO 400) shot at ISO 1600, pushed 2 stops."
+  );
+
+  let changes = report["files"][1]["changes"].as_array().unwrap();
+  assert!(changes.iter().all(|change| change["kind"] == "added"));
+  assert!(changes
+    .iter()
+    .any(|change| change["tag"] == "FocalLength" && change["new"] == "50"));
+
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--dry-run"])
+    .arg(&tagged)
+    .assert()
+    .success()
+    .stdout(contains("No changes"));
+}
+
+#[test]
+fn test_apply_writes_exposure_settings() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let test_file = temp_dir.path().join("frame.jpg");
+  fs::write(&test_file, create_minimal_jpeg()).unwrap();
+
+  let apply = |extra: &[&str]| {
+    let mut command = ifex(temp_dir.path());
+    command
+      .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+      .args(["--photographer", "Test User"])
+      .args(extra)
+      .arg(&test_file);
+    command
+  };
+  let exposure = [
+    "--shutter",
+    "1/125",
+    "--aperture",
+    "f/5.6",
+    "--ev",
+    "-1/3",
+    "--metering",
+    "spot",
+    "--program",
+    "manual",
+  ];
+  apply(&exposure)
+    .assert()
+    .success()
+    .stdout(contains("1/125 s, f/5.6, -1/3 EV, spot metering, manual"));
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  let value = |tag: &str| {
+    exif_data
+      .iter()
+      .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+      .map(|(_, value)| value.clone())
+      .unwrap_or_default()
+  };
+  assert_eq!(value("Exposure Time"), "1/125");
+  assert_eq!(value("F-Number"), "28/5");
+  assert_eq!(value("Exposure Bias Value"), "-1/3");
+  assert_eq!(value("Metering Mode"), "3");
+  assert_eq!(value("Exposure Program"), "1");
+  assert_eq!(value("Exposure Time (XMP)"), "1/125");
+  assert_eq!(value("F-Number (XMP)"), "28/5");
+  assert_eq!(value("Exposure Bias Value (XMP)"), "-1/3");
+
+  apply(&["--dry-run"])
+    .assert()
+    .success()
+    .stdout(contains("No changes"));
+  let output = apply(&["--dry-run", "--json", "--aperture", "8"])
+    .output()
+    .unwrap();
+  let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  let changes = report["files"][0]["changes"].as_array().unwrap();
+  assert_eq!(changes.len(), 1);
+  assert_eq!(changes[0]["tag"], "FNumber");
+  assert_eq!(changes[0]["old"], "5.6");
+  assert_eq!(changes[0]["new"], "8");
+
+  apply(&["--shutter", "fast"])
+    .assert()
+    .failure()
+    .stderr(contains("Invalid shutter speed"));
+}
+
+#[test]
+fn test_apply_by_roll() {
+  let temp_dir = TempDir::new().unwrap();
+  let setup = write_config(temp_dir.path()).unwrap();
+  let test_file = temp_dir.path().join("frame.jpg");
+  fs::write(&test_file, create_minimal_jpeg()).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["roll", "add", "--name", "Alfama", "--setup", "OM kit"])
+    .args([
+      "--film",
+      "HP5 Plus",
+      "--photographer",
+      "Test User",
+      "--iso",
+      "800",
+    ])
+    .assert()
+    .success();
+  let output = ifex(temp_dir.path())
+    .args(["roll", "list", "--json"])
+    .output()
+    .unwrap();
+  let rolls: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  assert_eq!(rolls[0]["setupId"], setup.id.to_string());
+  let identifier = rolls[0]["id"].as_str().unwrap_or_default().replace('-', "");
+
+  ifex(temp_dir.path())
+    .args(["apply", "--roll", "alfama"])
+    .arg(&test_file)
+    .assert()
+    .success()
+    .stdout(contains("Roll: Alfama"));
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  let value = |tag: &str| {
+    exif_data
+      .iter()
+      .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+      .map(|(_, value)| value.clone())
+      .unwrap_or_default()
+  };
+  assert_eq!(value("Image Unique ID"), identifier);
+  assert_eq!(value("Roll (XMP)"), identifier);
+  assert_eq!(value("Model"), "OM-1");
+  assert_eq!(value("ISO Speed"), "800");
+
+  ifex(temp_dir.path())
+    .args(["apply", "--roll", "Alfama", "--iso", "1600", "--dry-run"])
+    .arg(&test_file)
+    .assert()
+    .success()
+    .stdout(contains("1600"));
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--iso", "800", "--dry-run"])
+    .arg(&test_file)
+    .assert()
+    .success()
+    .stdout(contains("No changes"));
+  ifex(temp_dir.path())
+    .args(["apply", "--roll", "Alfama", "--setup", "OM kit"])
+    .arg(&test_file)
+    .assert()
+    .failure();
+  ifex(temp_dir.path())
+    .args(["apply", "--film", "HP5 Plus"])
+    .arg(&test_file)
+    .assert()
+    .failure();
+}
+
+#[test]
+fn test_apply_shot_log() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let scans = temp_dir.path().join("scans");
+  fs::create_dir(&scans).unwrap();
+  for name in ["roll_01.jpg", "roll_02.jpg", "roll_07.jpg"] {
+    fs::write(scans.join(name), create_minimal_jpeg()).unwrap();
+  }
+  let shot_log = temp_dir.path().join("log.csv");
+  fs::write(
+    &shot_log,
+    "Frame,Shutter,Aperture,Focal Length,Lens,Notes,Location\n\
+     1,1/250,8,35,,Tram 28,Alfama\n\
+     2,1/60,2.8,,Zuiko Auto-S,,\n\
+     5,1/30,2,,,,\n",
+  )
+  .unwrap();
+
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--caption", "Lisbon"])
+    .args(["--aperture", "4", "--shot-log"])
+    .arg(&shot_log)
+    .args(["--frame-pattern", r"roll_(\d+)"])
+    .arg(&scans)
+    .assert()
+    .success()
+    .stdout(contains("Logged frames without a file: 5"))
+    .stdout(contains("Files without a logged frame"))
+    .stdout(contains("roll_07.jpg"));
+
+  let value = |name: &str, tag: &str| {
+    JpegProcessor::read_exif(&scans.join(name))
+      .unwrap_or_default()
+      .into_iter()
+      .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+      .map(|(_, value)| value)
+      .unwrap_or_default()
+  };
+  assert_eq!(value("roll_01.jpg", "Exposure Time"), "1/250");
+  assert_eq!(value("roll_01.jpg", "F-Number"), "8/1");
+  assert_eq!(value("roll_01.jpg", "Focal Length"), "35/1");
+  assert_eq!(
+    value("roll_01.jpg", "Iptc4xmpCore:Location (XMP)"),
+    "Alfama"
+  );
+  assert_eq!(value("roll_01.jpg", "IPTC: Sublocation"), "Alfama");
+  assert_eq!(value("roll_01.jpg", "IPTC: Caption/Abstract"), "Tram 28");
+  assert_eq!(value("roll_02.jpg", "Exposure Time"), "1/60");
+  assert_eq!(value("roll_02.jpg", "F-Number"), "14/5");
+  assert_eq!(value("roll_02.jpg", "Focal Length"), "50000/1000");
+  assert_eq!(value("roll_02.jpg", "IPTC: Caption/Abstract"), "Lisbon");
+  assert_eq!(value("roll_07.jpg", "Exposure Time"), "");
+  assert_eq!(value("roll_07.jpg", "F-Number"), "4/1");
+
+  fs::write(&shot_log, "Frame,Lens\n1,Summicron\n").unwrap();
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--shot-log"])
+    .arg(&shot_log)
+    .arg(&scans)
+    .assert()
+    .failure()
+    .stderr(contains("Frame 1"));
+}
+
+#[test]
+fn test_apply_writes_development_record() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let test_file = temp_dir.path().join("frame.jpg");
+  fs::write(&test_file, create_minimal_jpeg()).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["roll", "add", "--name", "Porto", "--setup", "OM kit"])
+    .args(["--film", "HP5 Plus", "--photographer", "Test User"])
+    .args([
+      "--iso",
+      "1600",
+      "--developer",
+      "HC-110",
+      "--dilution",
+      "1+31",
+    ])
+    .args(["--dev-time", "9:30", "--lab", "Carmencita"])
+    .assert()
+    .success();
+  ifex(temp_dir.path())
+    .args(["apply", "--roll", "Porto", "--scanned", "2024-08-01"])
+    .arg(&test_file)
+    .assert()
+    .success()
+    .stdout(contains("Development: HC-110 1+31 9:30, lab Carmencita"));
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  let value = |tag: &str| {
+    exif_data
+      .iter()
+      .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+      .map(|(_, value)| value.clone())
+      .unwrap_or_default()
+  };
+  assert_eq!(value("Developer (XMP)"), "HC-110");
+  assert_eq!(value("Dilution (XMP)"), "1+31");
+  assert_eq!(value("Development Time (XMP)"), "9:30");
+  assert_eq!(value("Lab (XMP)"), "Carmencita");
+  assert_eq!(value("Scan Date (XMP)"), "2024-08-01");
+  assert_eq!(value("Push/Pull (XMP)"), "+2");
+  assert!(value("User Comment").starts_with("Ilford HP5 Plus (ISO 400) shot at ISO 1600"));
+  assert_eq!(
+    value("User Comment (XMP)"),
+    "Ilford HP5 Plus (ISO 400) shot at ISO 1600, pushed 2 stops. Developed in HC-110 1+31 \
+     for 9:30 by Carmencita. Scanned 2024-08-01."
+  );
+
+  ifex(temp_dir.path())
+    .args([
+      "apply",
+      "--roll",
+      "Porto",
+      "--scanned",
+      "2024-08-01",
+      "--dry-run",
+    ])
+    .arg(&test_file)
+    .assert()
+    .success()
+    .stdout(contains("No changes"));
+  ifex(temp_dir.path())
+    .args(["apply", "--roll", "Porto", "--dev-temp", "20°C"])
+    .arg(&test_file)
+    .assert()
+    .success();
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  assert!(exif_data
+    .iter()
+    .any(|(key, value)| key == "Development Temperature (XMP)" && value == "20"));
+  assert!(exif_data
+    .iter()
+    .any(|(key, value)| key.starts_with("User Comment [") && value.contains("at 20°C")));
+
+  ifex(temp_dir.path())
+    .args(["apply", "--roll", "Porto", "--dev-time", "long"])
+    .arg(&test_file)
+    .assert()
+    .failure()
+    .stderr(contains("Invalid development time"));
+}
+
+#[test]
+fn test_reapply_at_box_speed_removes_the_development_summary() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let test_file = temp_dir.path().join("frame.jpg");
+  fs::write(&test_file, create_minimal_jpeg()).unwrap();
+  let apply = |iso: &[&str]| {
+    let mut command = ifex(temp_dir.path());
+    command
+      .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+      .args(["--photographer", "Test User"])
+      .args(iso)
+      .arg(&test_file);
+    command
+  };
+  let comments = || {
+    JpegProcessor::read_exif(&test_file)
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|(key, _)| key.starts_with("User Comment") || key.starts_with("Push/Pull"))
+      .count()
+  };
+
+  apply(&["--iso", "1600"]).assert().success();
+  assert_eq!(comments(), 3);
+
+  apply(&["--dry-run"])
+    .assert()
+    .success()
+    .stdout(contains("UserComment"));
+  apply(&[]).assert().success();
+  assert_eq!(comments(), 0);
+}
+
+#[test]
+fn test_apply_with_location() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let test_file = temp_dir.path().join("frame.jpg");
+  fs::write(&test_file, create_minimal_jpeg()).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["location", "add", "--name", "Santa Luzia"])
+    .args(["--latitude", "38.7115", "--longitude", "-9.1305"])
+    .args([
+      "--sublocation",
+      "Miradouro de Santa Luzia",
+      "--city",
+      "Lisbon",
+    ])
+    .args(["--country", "Portugal", "--country-code", "PT"])
+    .assert()
+    .success();
+  let apply = |extra: &[&str]| {
+    let mut command = ifex(temp_dir.path());
+    command
+      .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+      .args(["--photographer", "Test User", "--location", "santa luzia"])
+      .args(extra)
+      .arg(&test_file);
+    command
+  };
+
+  apply(&["--dry-run"])
+    .assert()
+    .success()
+    .stdout(contains("Location: Santa Luzia (Lisbon, Portugal)"))
+    .stdout(contains("38.711500 N"));
+  apply(&[]).assert().success();
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  let value = |tag: &str| {
+    exif_data
+      .iter()
+      .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+      .map(|(_, value)| value.clone())
+      .unwrap_or_default()
+  };
+  // 38.7115° is 38° 42' 41.4", and 9.1305° is 9° 7' 49.8"
+  assert_eq!(value("GPS Latitude"), "38/1, 42/1, 41400/1000");
+  assert_eq!(value("GPS Longitude Ref"), "W");
+  assert_eq!(value("GPS Longitude"), "9/1, 7/1, 49800/1000");
+  assert_eq!(value("IPTC: Sublocation"), "Miradouro de Santa Luzia");
+  assert_eq!(value("IPTC: City"), "Lisbon");
+  assert_eq!(value("IPTC: Country/Primary Location Code"), "PT");
+  assert_eq!(value("IPTC: Country/Primary Location Name"), "Portugal");
+  assert_eq!(value("photoshop:City (XMP)"), "Lisbon");
+  assert_eq!(value("photoshop:Country (XMP)"), "Portugal");
+  assert_eq!(value("Iptc4xmpCore:CountryCode (XMP)"), "PT");
+  assert_eq!(
+    value("Iptc4xmpCore:Location (XMP)"),
+    "Miradouro de Santa Luzia"
+  );
+  assert_eq!(value("GPS Latitude (XMP)"), "38,42.690000N");
+
+  apply(&["--dry-run"])
+    .assert()
+    .success()
+    .stdout(contains("No changes"));
+  ifex(temp_dir.path())
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--location", "Belém"])
+    .arg(&test_file)
+    .assert()
+    .failure()
+    .stderr(contains("Location not found: Belém"));
+}
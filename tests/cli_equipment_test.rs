@@ -0,0 +1,485 @@
+//! Tests for the scriptable `camera`, `lens`, `film`, `photographer`, `setup`, `roll` and
+//! `location` commands.
+
+use assert_cmd::Command;
+use predicates::str::contains;
+use serde_json::Value;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn ifex(config_dir: &Path) -> Command {
+  let mut command = Command::new(env!("CARGO_BIN_EXE_ifex"));
+  command
+    .env("XDG_CONFIG_HOME", config_dir)
+    .env("HOME", config_dir);
+  command
+}
+
+fn run_json(config_dir: &Path, args: &[&str]) -> Result<Value, Box<dyn std::error::Error>> {
+  let output = ifex(config_dir).args(args).arg("--json").output()?;
+  if !output.status.success() {
+    return Err(String::from_utf8_lossy(&output.stderr).into_owned().into());
+  }
+  Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[test]
+fn test_add_list_and_edit_equipment() {
+  let temp_dir = TempDir::new().unwrap();
+  let config = temp_dir.path();
+
+  let camera = run_json(
+    config,
+    &["camera", "add", "--maker", "Leica", "--model", "M6"],
+  )
+  .unwrap();
+  let lens = run_json(
+    config,
+    &[
+      "lens",
+      "add",
+      "--maker",
+      "Leica",
+      "--model",
+      "Summicron",
+      "--focal-length",
+      "35",
+      "--aperture",
+      "2",
+      "--mount",
+      "M",
+    ],
+  )
+  .unwrap();
+  let setup = run_json(
+    config,
+    &[
+      "setup",
+      "add",
+      "--name",
+      "Street",
+      "--camera",
+      camera["id"].as_str().unwrap(),
+      "--lens",
+      "Leica Summicron",
+    ],
+  )
+  .unwrap();
+  assert_eq!(setup["cameraId"], camera["id"]);
+  assert_eq!(setup["lensId"], lens["id"]);
+
+  let lenses = run_json(config, &["lens", "list"]).unwrap();
+  assert_eq!(lenses.as_array().map(Vec::len), Some(1));
+  assert_eq!(lenses[0]["focalLength"], "35");
+
+  let edited = run_json(config, &["camera", "edit", "m6", "--model", "M6 TTL"]).unwrap();
+  assert_eq!(edited["maker"], "Leica");
+  assert_eq!(edited["model"], "M6 TTL");
+
+  let edited = run_json(config, &["setup", "edit", "Street", "--no-lens"]).unwrap();
+  assert_eq!(edited["lensId"], Value::Null);
+  assert_eq!(edited["cameraId"], camera["id"]);
+
+  ifex(config)
+    .args(["setup", "list"])
+    .assert()
+    .success()
+    .stdout(contains("Leica M6 TTL"))
+    .stdout(contains("(camera only)"));
+}
+
+#[test]
+fn test_film_and_photographer_crud() {
+  let temp_dir = TempDir::new().unwrap();
+  let config = temp_dir.path();
+
+  run_json(
+    config,
+    &[
+      "film", "add", "--maker", "Kodak", "--name", "Tri-X", "--iso", "400",
+    ],
+  )
+  .unwrap();
+  let film = run_json(config, &["film", "edit", "Kodak Tri-X", "--iso", "320"]).unwrap();
+  assert_eq!(film["name"], "Tri-X");
+  assert_eq!(film["iso"], 320);
+
+  run_json(
+    config,
+    &[
+      "photographer",
+      "add",
+      "--name",
+      "Jane Doe",
+      "--email",
+      "jane@example.com",
+    ],
+  )
+  .unwrap();
+  let photographer = run_json(config, &["photographer", "edit", "Jane Doe", "--no-email"]).unwrap();
+  assert_eq!(photographer["email"], Value::Null);
+
+  ifex(config)
+    .args(["film", "delete", film["id"].as_str().unwrap()])
+    .assert()
+    .success()
+    .stdout(contains("Deleted film"));
+  assert_eq!(
+    run_json(config, &["film", "list"]).unwrap(),
+    Value::Array(vec![])
+  );
+  ifex(config)
+    .args(["film", "list"])
+    .assert()
+    .success()
+    .stdout(contains("No films found"));
+}
+
+#[test]
+fn test_photographer_rights() {
+  let temp_dir = TempDir::new().unwrap();
+  let config = temp_dir.path();
+
+  let photographer = run_json(
+    config,
+    &[
+      "photographer",
+      "add",
+      "--name",
+      "Jane Doe",
+      "--license",
+      "CC BY-NC 4.0",
+      "--usage-terms",
+      "Contact me for commercial use",
+    ],
+  )
+  .unwrap();
+  assert_eq!(photographer["rights"]["license"], "CC BY-NC 4.0");
+  assert_eq!(
+    photographer["rights"]["usageTerms"],
+    "Contact me for commercial use"
+  );
+  assert_eq!(photographer["rights"]["copyrightTemplate"], Value::Null);
+
+  let photographer = run_json(
+    config,
+    &[
+      "photographer",
+      "edit",
+      "Jane Doe",
+      "--copyright",
+      "(c) {year} {name}",
+      "--no-license",
+    ],
+  )
+  .unwrap();
+  assert_eq!(
+    photographer["rights"]["copyrightTemplate"],
+    "(c) {year} {name}"
+  );
+  assert_eq!(photographer["rights"]["license"], Value::Null);
+  assert_eq!(
+    photographer["rights"]["usageTerms"],
+    "Contact me for commercial use"
+  );
+
+  ifex(config)
+    .args(["photographer", "edit", "Jane Doe", "--license", "CC BY-XY"])
+    .assert()
+    .failure()
+    .stderr(contains("Unknown license \"CC BY-XY\""));
+  ifex(config)
+    .args(["photographer", "add", "--name", "John", "--license", "Mine"])
+    .assert()
+    .failure();
+  assert_eq!(
+    run_json(config, &["photographer", "list"])
+      .unwrap()
+      .as_array()
+      .unwrap()
+      .len(),
+    1
+  );
+}
+
+#[test]
+fn test_photographer_contact() {
+  let temp_dir = TempDir::new().unwrap();
+  let config = temp_dir.path();
+
+  let photographer = run_json(
+    config,
+    &[
+      "photographer",
+      "add",
+      "--name",
+      "Jane Doe",
+      "--city",
+      "Porto",
+      "--country",
+      "Portugal",
+      "--phone",
+      "+351 22 000 0000",
+    ],
+  )
+  .unwrap();
+  assert_eq!(photographer["contact"]["city"], "Porto");
+  assert_eq!(photographer["contact"]["website"], Value::Null);
+
+  let photographer = run_json(
+    config,
+    &[
+      "photographer",
+      "edit",
+      "Jane Doe",
+      "--website",
+      "https://example.com",
+      "--phone",
+      "",
+    ],
+  )
+  .unwrap();
+  assert_eq!(photographer["contact"]["city"], "Porto");
+  assert_eq!(photographer["contact"]["country"], "Portugal");
+  assert_eq!(photographer["contact"]["website"], "https://example.com");
+  assert_eq!(photographer["contact"]["phone"], Value::Null);
+}
+
+#[test]
+fn test_delete_fails_for_referenced_or_unknown_equipment() {
+  let temp_dir = TempDir::new().unwrap();
+  let config = temp_dir.path();
+
+  run_json(
+    config,
+    &["camera", "add", "--maker", "Nikon", "--model", "FM2"],
+  )
+  .unwrap();
+  run_json(
+    config,
+    &["setup", "add", "--name", "Travel", "--camera", "Nikon FM2"],
+  )
+  .unwrap();
+
+  ifex(config)
+    .args(["camera", "delete", "FM2"])
+    .assert()
+    .failure()
+    .stderr(contains("Cannot delete camera that is used in setups"));
+  ifex(config)
+    .args(["lens", "delete", "Nikkor"])
+    .assert()
+    .failure()
+    .stderr(contains("Lens not found: Nikkor"));
+  ifex(config)
+    .args(["setup", "add", "--name", "Other", "--camera", "F3"])
+    .assert()
+    .failure()
+    .stderr(contains("Camera not found: F3"));
+
+  ifex(config)
+    .args(["setup", "delete", "Travel"])
+    .assert()
+    .success();
+  ifex(config)
+    .args(["camera", "delete", "FM2"])
+    .assert()
+    .success();
+  assert_eq!(
+    run_json(config, &["camera", "list"]).unwrap(),
+    Value::Array(vec![])
+  );
+}
+
+/// Adds the camera, setup, film and photographer of a roll, returning the film.
+fn add_roll_equipment(config: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+  run_json(
+    config,
+    &["camera", "add", "--maker", "Nikon", "--model", "FM2"],
+  )?;
+  run_json(
+    config,
+    &["setup", "add", "--name", "FM2", "--camera", "FM2"],
+  )?;
+  let film = run_json(
+    config,
+    &[
+      "film", "add", "--maker", "Ilford", "--name", "HP5", "--iso", "400",
+    ],
+  )?;
+  run_json(config, &["photographer", "add", "--name", "Jane Doe"])?;
+  Ok(film)
+}
+
+#[test]
+fn test_roll_crud() {
+  let temp_dir = TempDir::new().unwrap();
+  let config = temp_dir.path();
+
+  let film = add_roll_equipment(config).unwrap();
+
+  let roll = run_json(
+    config,
+    &[
+      "roll",
+      "add",
+      "--name",
+      "Porto 01",
+      "--film",
+      "HP5",
+      "--setup",
+      "FM2",
+      "--photographer",
+      "Jane Doe",
+      "--iso",
+      "1600",
+      "--loaded",
+      "2024-07-01",
+      "--frames",
+      "36",
+      "--lab",
+      "Home",
+    ],
+  )
+  .unwrap();
+  assert_eq!(roll["filmId"], film["id"]);
+  assert_eq!(roll["shotIso"], 1600);
+  assert_eq!(roll["loadedOn"], "2024-07-01");
+  assert_eq!(roll["finishedOn"], Value::Null);
+  assert_eq!(roll["frameCount"], 36);
+  assert_eq!(roll["development"]["lab"], "Home");
+
+  let roll = run_json(
+    config,
+    &[
+      "roll",
+      "edit",
+      "porto 01",
+      "--box-speed",
+      "--finished",
+      "2024-07-14",
+      "--lab",
+      "",
+      "--notes",
+      "Rainy week",
+    ],
+  )
+  .unwrap();
+  assert_eq!(roll["shotIso"], Value::Null);
+  assert_eq!(roll["loadedOn"], "2024-07-01");
+  assert_eq!(roll["finishedOn"], "2024-07-14");
+  assert_eq!(roll["development"]["lab"], Value::Null);
+  assert_eq!(roll["notes"], "Rainy week");
+
+  ifex(config)
+    .args(["roll", "edit", "Porto 01", "--loaded", "July"])
+    .assert()
+    .failure()
+    .stderr(contains("Invalid date \"July\""));
+  ifex(config)
+    .args(["roll", "list"])
+    .assert()
+    .success()
+    .stdout(contains("Porto 01"))
+    .stdout(contains("Ilford HP5"));
+
+  for (kind, key) in [
+    ("film", "HP5"),
+    ("setup", "FM2"),
+    ("photographer", "Jane Doe"),
+  ] {
+    ifex(config)
+      .args([kind, "delete", key])
+      .assert()
+      .failure()
+      .stderr(contains("used by rolls"));
+  }
+
+  ifex(config)
+    .args(["roll", "delete", roll["id"].as_str().unwrap()])
+    .assert()
+    .success()
+    .stdout(contains("Deleted roll"));
+  ifex(config)
+    .args(["film", "delete", "HP5"])
+    .assert()
+    .success();
+}
+
+#[test]
+fn test_location_crud() {
+  let temp_dir = TempDir::new().unwrap();
+  let config = temp_dir.path();
+
+  let location = run_json(
+    config,
+    &[
+      "location",
+      "add",
+      "--name",
+      "Alfama",
+      "--latitude",
+      "38.7114",
+      "--longitude",
+      "-9.1300",
+      "--altitude",
+      "60",
+      "--city",
+      "Lisbon",
+      "--country",
+      "Portugal",
+      "--country-code",
+      "pt",
+    ],
+  )
+  .unwrap();
+  assert_eq!(location["longitude"], -9.13);
+  assert_eq!(location["altitude"], 60.0);
+  assert_eq!(location["countryCode"], "PT");
+  assert_eq!(location["sublocation"], Value::Null);
+
+  let location = run_json(
+    config,
+    &[
+      "location",
+      "edit",
+      "alfama",
+      "--sublocation",
+      "Miradouro de Santa Luzia",
+      "--altitude",
+      "",
+    ],
+  )
+  .unwrap();
+  assert_eq!(location["sublocation"], "Miradouro de Santa Luzia");
+  assert_eq!(location["altitude"], Value::Null);
+  assert_eq!(location["latitude"], 38.7114);
+
+  ifex(config)
+    .args(["location", "edit", "Alfama", "--latitude", "95"])
+    .assert()
+    .failure()
+    .stderr(contains("Invalid latitude 95"));
+  ifex(config)
+    .args(["location", "edit", "Alfama", "--country-code", "Portugal"])
+    .assert()
+    .failure()
+    .stderr(contains("Invalid country code"));
+  ifex(config)
+    .args(["location", "list"])
+    .assert()
+    .success()
+    .stdout(contains("Alfama"))
+    .stdout(contains("38.711400 N, 9.130000 W"));
+
+  ifex(config)
+    .args(["location", "delete", location["id"].as_str().unwrap()])
+    .assert()
+    .success()
+    .stdout(contains("Deleted location: Alfama (Lisbon, Portugal)"));
+  ifex(config)
+    .args(["location", "delete", "Alfama"])
+    .assert()
+    .failure()
+    .stderr(contains("Location not found"));
+}
@@ -0,0 +1,137 @@
+//! Tests for the non-interactive `ifex erase` command.
+
+use assert_cmd::Command;
+use ifex::exif::ifd::tag;
+use ifex::exif::{ExifValue, IfdTree};
+use predicates::str::contains;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const JFIF: &[u8] = &[
+  0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+  0x00, 0x00,
+];
+
+/// Builds a JPEG with a JFIF header and an EXIF segment carrying equipment,
+/// photographer, date and GPS tags.
+fn tagged_jpeg() -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+  let mut tree = IfdTree::new();
+  tree.ifd0.set(tag::MAKE, ExifValue::ascii("Pentax"));
+  tree.ifd0.set(tag::ARTIST, ExifValue::ascii("Test User"));
+  tree.ifd0.set(tag::ORIENTATION, ExifValue::Short(vec![1]));
+  tree.exif_mut().set(
+    tag::DATE_TIME_ORIGINAL,
+    ExifValue::ascii("2001:02:03 04:05:06"),
+  );
+  tree.gps_mut().set(0x0001, ExifValue::ascii("N"));
+  let payload = tree.to_app1_payload()?;
+
+  let mut data = vec![0xFF, 0xD8];
+  data.extend_from_slice(JFIF);
+  data.extend_from_slice(&[0xFF, 0xE1]);
+  data.extend_from_slice(&u16::try_from(payload.len() + 2)?.to_be_bytes());
+  data.extend_from_slice(&payload);
+  data.extend_from_slice(&[
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]);
+  Ok(data)
+}
+
+fn read_tree(path: &Path) -> Result<IfdTree, Box<dyn std::error::Error>> {
+  let data = fs::read(path)?;
+  let start = data
+    .windows(6)
+    .position(|window| window == b"Exif\0\0")
+    .ok_or("no EXIF segment")?;
+  IfdTree::parse(&data[start + 6..])
+}
+
+fn ifex(config_dir: &Path) -> Command {
+  let mut command = Command::new(env!("CARGO_BIN_EXE_ifex"));
+  command
+    .env("XDG_CONFIG_HOME", config_dir)
+    .env("HOME", config_dir);
+  command
+}
+
+#[test]
+fn test_erase_all_keeps_jfif_header() {
+  let temp_dir = TempDir::new().unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  fs::write(&photo, tagged_jpeg().unwrap()).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["erase", "--yes"])
+    .arg(&photo)
+    .assert()
+    .success();
+
+  let data = fs::read(&photo).unwrap();
+  assert_eq!(&data[2..2 + JFIF.len()], JFIF);
+  assert!(!data.windows(2).any(|window| window == [0xFF, 0xE1]));
+  assert!(data.ends_with(&[0xD2, 0xCF, 0x20, 0xFF, 0xD9]));
+}
+
+#[test]
+fn test_erase_only_and_keep_select_groups() {
+  let temp_dir = TempDir::new().unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  fs::write(&photo, tagged_jpeg().unwrap()).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["erase", "--only", "gps", "--yes", "--json"])
+    .arg(&photo)
+    .assert()
+    .success()
+    .stdout(contains("\"processed\": 1"));
+
+  let tree = read_tree(&photo).unwrap();
+  assert!(tree.gps.is_none());
+  assert!(tree.ifd0.contains(tag::MAKE));
+  assert!(tree.ifd0.contains(tag::ARTIST));
+
+  ifex(temp_dir.path())
+    .args(["erase", "--keep", "people,gps", "--yes"])
+    .arg(&photo)
+    .assert()
+    .success();
+
+  let data = fs::read(&photo).unwrap();
+  assert_eq!(&data[2..2 + JFIF.len()], JFIF);
+  let tree = read_tree(&photo).unwrap();
+  assert!(!tree.ifd0.contains(tag::MAKE));
+  assert!(tree.ifd0.contains(tag::ARTIST));
+  assert!(tree.ifd0.contains(tag::ORIENTATION));
+  assert!(!tree
+    .exif
+    .as_ref()
+    .is_some_and(|exif| exif.contains(tag::DATE_TIME_ORIGINAL)));
+}
+
+#[test]
+fn test_erase_fails_when_nothing_selected_or_a_file_fails() {
+  let temp_dir = TempDir::new().unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  let original = tagged_jpeg().unwrap();
+  fs::write(&photo, &original).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["erase", "--only", "gps", "--keep", "all", "--yes"])
+    .arg(&photo)
+    .assert()
+    .failure()
+    .stderr(contains("Nothing to erase"));
+  assert_eq!(fs::read(&photo).unwrap(), original);
+
+  ifex(temp_dir.path())
+    .args(["erase", "--only", "dates", "--yes", "--json"])
+    .arg(&photo)
+    .arg(temp_dir.path().join("missing.jpg"))
+    .assert()
+    .failure()
+    .stdout(contains("File does not exist"));
+  assert!(read_tree(&photo).unwrap().ifd0.contains(tag::MAKE));
+}
@@ -0,0 +1,117 @@
+//! Tests for the `ifex geotag` command.
+
+use assert_cmd::Command;
+use ifex::exif::processors::JpegProcessor;
+use predicates::str::contains;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const TRACK: &str = r#"<gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk><trkseg>
+    <trkpt lat="48.8580" lon="2.2950"><ele>35</ele><time>2024-05-01T08:00:00Z</time></trkpt>
+    <trkpt lat="48.8600" lon="2.2970"><ele>45</ele><time>2024-05-01T08:02:00Z</time></trkpt>
+  </trkseg></trk>
+</gpx>"#;
+
+fn create_minimal_jpeg() -> Vec<u8> {
+  vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+    0x00, 0x00, // APP0
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]
+}
+
+/// Writes a JPEG taken at `date` (camera time) into `dir`.
+fn write_photo(dir: &Path, name: &str, date: &str) -> Result<(), Box<dyn std::error::Error>> {
+  let path = dir.join(name);
+  fs::write(&path, create_minimal_jpeg())?;
+  JpegProcessor::set_creation_date(&path, date)
+}
+
+fn ifex(config_dir: &Path) -> Command {
+  let mut command = Command::new(env!("CARGO_BIN_EXE_ifex"));
+  command
+    .env("XDG_CONFIG_HOME", config_dir)
+    .env("HOME", config_dir);
+  command
+}
+
+#[test]
+fn test_geotag_with_clock_offset() {
+  let temp_dir = TempDir::new().unwrap();
+  let photos = temp_dir.path().join("photos");
+  fs::create_dir_all(&photos).unwrap();
+  let track = temp_dir.path().join("paris.gpx");
+  fs::write(&track, TRACK).unwrap();
+  write_photo(&photos, "a.jpg", "2024:05:01 10:01:00").unwrap();
+
+  ifex(temp_dir.path())
+    .args(["geotag", "--gpx"])
+    .arg(&track)
+    .args(["--offset", "+02:00", "--dry-run"])
+    .arg(&photos)
+    .assert()
+    .success()
+    .stdout(contains("48.859000 N"));
+  let exif_data = JpegProcessor::read_exif(&photos.join("a.jpg")).unwrap();
+  assert!(!exif_data.iter().any(|(key, _)| key.starts_with("GPS")));
+
+  let output = ifex(temp_dir.path())
+    .args(["geotag", "--gpx"])
+    .arg(&track)
+    .args(["--offset", "+02:00", "--backup", "off", "--json"])
+    .arg(&photos)
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+  let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  assert_eq!(report["processed"], 1);
+
+  let exif_data = JpegProcessor::read_exif(&photos.join("a.jpg")).unwrap();
+  let latitude = exif_data
+    .iter()
+    .find(|(key, _)| key.starts_with("GPS Latitude ["))
+    .map(|(_, value)| value.as_str());
+  // 48.859° is 48° 51' 32.4"
+  assert_eq!(latitude, Some("48/1, 51/1, 32400/1000"));
+}
+
+#[test]
+fn test_geotag_fails_away_from_track() {
+  let temp_dir = TempDir::new().unwrap();
+  let track = temp_dir.path().join("paris.gpx");
+  fs::write(&track, TRACK).unwrap();
+  write_photo(temp_dir.path(), "late.jpg", "2024:05:01 12:00:00").unwrap();
+  let photo = temp_dir.path().join("late.jpg");
+
+  ifex(temp_dir.path())
+    .args(["geotag", "--gpx"])
+    .arg(&track)
+    .args(["--offset", "+02:00", "--max-gap", "600"])
+    .arg(&photo)
+    .assert()
+    .failure()
+    .stdout(contains("No track position within 600 s"));
+
+  ifex(temp_dir.path())
+    .args(["geotag", "--gpx"])
+    .arg(&track)
+    .args(["--offset", "two hours"])
+    .arg(&photo)
+    .assert()
+    .failure()
+    .stderr(contains("Invalid UTC offset"));
+
+  fs::write(&track, "<gpx></gpx>").unwrap();
+  ifex(temp_dir.path())
+    .args(["geotag", "--gpx"])
+    .arg(&track)
+    .arg(&photo)
+    .assert()
+    .failure()
+    .stderr(contains("No track points with a time"));
+}
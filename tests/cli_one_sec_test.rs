@@ -82,11 +82,5 @@ fn create_test_selection() -> Selection {
   let photographer = Photographer::new("Test User".to_string(), None);
   let setup = Setup::new("Test Setup".to_string(), camera.id, Some(lens.id));
 
-  Selection {
-    setup,
-    camera,
-    lens: Some(lens),
-    film,
-    photographer,
-  }
+  Selection::new(setup, camera, Some(lens), film, photographer)
 }
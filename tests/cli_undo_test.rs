@@ -0,0 +1,172 @@
+//! Tests for the backup journal behind `ifex undo` and `ifex history`.
+
+use assert_cmd::Command;
+use ifex::config::Config;
+use ifex::models::*;
+use predicates::str::contains;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_minimal_jpeg() -> Vec<u8> {
+  vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+    0x00, 0x00, // APP0
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]
+}
+
+/// Writes an `ifex.json` with one setup, film and photographer.
+fn write_config(config_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+  let camera = Camera::new("Olympus".to_string(), "OM-1".to_string());
+  let setup = Setup::new("OM kit".to_string(), camera.id, None);
+  let config = Config {
+    cameras: vec![camera],
+    lenses: Vec::new(),
+    films: vec![Film::new("Ilford".to_string(), "HP5 Plus".to_string(), 400)],
+    photographers: vec![Photographer::new("Test User".to_string(), None)],
+    setups: vec![setup],
+    rolls: Vec::new(),
+    locations: Vec::new(),
+  };
+  fs::write(
+    config_dir.join("ifex.json"),
+    serde_json::to_string(&config)?,
+  )?;
+  Ok(())
+}
+
+fn ifex(config_dir: &Path) -> Command {
+  let mut command = Command::new(env!("CARGO_BIN_EXE_ifex"));
+  command
+    .env("XDG_CONFIG_HOME", config_dir)
+    .env("HOME", config_dir);
+  command
+}
+
+fn apply(
+  config_dir: &Path,
+  paths: &[&Path],
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+  let output = ifex(config_dir)
+    .args(["apply", "--setup", "OM kit", "--film", "HP5 Plus"])
+    .args(["--photographer", "Test User", "--json"])
+    .args(paths)
+    .output()?;
+  Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[test]
+fn test_undo_restores_files_and_removes_created_sidecars() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  let raw = temp_dir.path().join("b.cr2");
+  fs::write(&photo, create_minimal_jpeg()).unwrap();
+  fs::write(&raw, b"raw image data").unwrap();
+
+  let report = apply(temp_dir.path(), &[&photo, &raw]).unwrap();
+  let run_id = report["runId"].as_str().unwrap().to_string();
+  assert_ne!(fs::read(&photo).unwrap(), create_minimal_jpeg());
+  assert!(raw.with_extension("xmp").exists());
+
+  let output = ifex(temp_dir.path())
+    .args(["history", "--json"])
+    .output()
+    .unwrap();
+  let history: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  assert_eq!(history[0]["id"], run_id.as_str());
+  assert_eq!(history[0]["operation"], "apply");
+  assert_eq!(history[0]["files"].as_array().unwrap().len(), 2);
+
+  ifex(temp_dir.path())
+    .args(["undo", &run_id])
+    .assert()
+    .success()
+    .stdout(contains("Restored 2 files"));
+  assert_eq!(fs::read(&photo).unwrap(), create_minimal_jpeg());
+  assert_eq!(fs::read(&raw).unwrap(), b"raw image data");
+  assert!(!raw.with_extension("xmp").exists());
+
+  ifex(temp_dir.path())
+    .arg("history")
+    .assert()
+    .success()
+    .stdout(contains("undone"));
+  ifex(temp_dir.path())
+    .args(["undo", &run_id])
+    .assert()
+    .failure()
+    .stderr(contains("already been undone"));
+}
+
+#[test]
+fn test_undo_refuses_files_modified_since_the_run() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  fs::write(&photo, create_minimal_jpeg()).unwrap();
+  apply(temp_dir.path(), &[&photo]).unwrap();
+  let tagged = fs::read(&photo).unwrap();
+
+  ifex(temp_dir.path())
+    .args(["--backup", "full", "erase", "--yes"])
+    .arg(&photo)
+    .assert()
+    .success();
+  fs::write(&photo, b"edited elsewhere").unwrap();
+
+  ifex(temp_dir.path())
+    .arg("undo")
+    .assert()
+    .failure()
+    .stdout(contains("modified since the run"))
+    .stderr(contains("Failed to restore 1 files"));
+  assert_eq!(fs::read(&photo).unwrap(), b"edited elsewhere");
+
+  // Once the conflicting edit is reverted, the erase run can be undone
+  fs::write(&photo, create_minimal_jpeg()).unwrap();
+  ifex(temp_dir.path()).arg("undo").assert().success();
+  assert_eq!(fs::read(&photo).unwrap(), tagged);
+
+  ifex(temp_dir.path()).arg("undo").assert().success();
+  assert_eq!(fs::read(&photo).unwrap(), create_minimal_jpeg());
+}
+
+#[test]
+fn test_backups_can_be_disabled() {
+  let temp_dir = TempDir::new().unwrap();
+  write_config(temp_dir.path()).unwrap();
+  let photo = temp_dir.path().join("a.jpg");
+  fs::write(&photo, create_minimal_jpeg()).unwrap();
+
+  let output = ifex(temp_dir.path())
+    .args(["apply", "--backup", "off", "--setup", "OM kit"])
+    .args([
+      "--film",
+      "HP5 Plus",
+      "--photographer",
+      "Test User",
+      "--json",
+    ])
+    .arg(&photo)
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+  let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  assert!(report.get("runId").is_none());
+
+  ifex(temp_dir.path())
+    .arg("history")
+    .assert()
+    .success()
+    .stdout(contains("No runs recorded."));
+  ifex(temp_dir.path())
+    .arg("undo")
+    .assert()
+    .failure()
+    .stderr(contains("No run to undo"));
+}
@@ -14,6 +14,8 @@ fn test_config_default() {
   assert!(config.films.is_empty());
   assert!(config.photographers.is_empty());
   assert!(config.setups.is_empty());
+  assert!(config.rolls.is_empty());
+  assert!(config.locations.is_empty());
 }
 
 #[test]
@@ -69,3 +71,13 @@ fn test_config_save_and_load() {
   assert_eq!(loaded_config.films[0].name, "Tri-X");
   assert_eq!(loaded_config.photographers[0].name, "John Doe");
 }
+
+#[test]
+fn test_config_without_rolls_deserializes() {
+  let config: Config = serde_json::from_str(
+    r#"{"cameras": [], "lenses": [], "films": [], "photographers": [], "setups": []}"#,
+  )
+  .unwrap();
+  assert!(config.rolls.is_empty());
+  assert!(config.locations.is_empty());
+}
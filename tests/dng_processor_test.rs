@@ -0,0 +1,258 @@
+//! Tests for in-place EXIF writing in DNG files and the XMP sidecar fallback.
+
+use ifex::exif::ifd::tag;
+use ifex::exif::processors::DngProcessor;
+use ifex::exif::{ExifManager, ExifValue, Ifd, IfdTree, TagGroup};
+use ifex::models::*;
+use std::fs;
+use tempfile::TempDir;
+
+const SUB_IFD_OFFSET: u32 = 170;
+const RAW_DATA_OFFSET: u32 = 284;
+const EXIF_IFD_OFFSET: u32 = 288;
+const MAKER_NOTE: &[u8; 16] = b"MAKERNOTE-1234\0\0";
+const STRIP_BYTE_COUNTS: u16 = 0x0117;
+
+fn create_test_selection() -> Selection {
+  let camera = Camera::new("Hasselblad".to_string(), "500C/M".to_string());
+  let lens = Lens::new(
+    "Zeiss".to_string(),
+    "Planar".to_string(),
+    "80".to_string(),
+    "2.8".to_string(),
+    "V".to_string(),
+  );
+  let film = Film::new("Ilford".to_string(), "HP5 Plus".to_string(), 400);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Medium Format".to_string(), camera.id, Some(lens.id));
+
+  Selection::new(setup, camera, Some(lens), film, photographer)
+}
+
+fn put_entry(data: &mut Vec<u8>, tag: u16, field_type: u16, count: u32, value: u32) {
+  data.extend_from_slice(&tag.to_le_bytes());
+  data.extend_from_slice(&field_type.to_le_bytes());
+  data.extend_from_slice(&count.to_le_bytes());
+  data.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Builds a minimal little-endian DNG: a 2x2 preview in IFD0, a 2x2 CFA
+/// image in a sub-IFD and an Exif IFD carrying an out-of-line maker note.
+fn minimal_dng() -> Vec<u8> {
+  let mut data = b"II\x2a\x00\x08\x00\x00\x00".to_vec();
+
+  data.extend_from_slice(&12u16.to_le_bytes());
+  put_entry(&mut data, 0x00FE, 4, 1, 1); // NewSubfileType: preview
+  put_entry(&mut data, 0x0100, 3, 1, 2); // ImageWidth
+  put_entry(&mut data, 0x0101, 3, 1, 2); // ImageLength
+  put_entry(&mut data, 0x0102, 3, 1, 8); // BitsPerSample
+  put_entry(&mut data, 0x0103, 3, 1, 1); // Compression
+  put_entry(&mut data, 0x0106, 3, 1, 1); // PhotometricInterpretation
+  put_entry(&mut data, 0x0111, 4, 1, 166); // StripOffsets
+  put_entry(&mut data, 0x0115, 3, 1, 1); // SamplesPerPixel
+  put_entry(&mut data, 0x0117, 4, 1, 4); // StripByteCounts
+  put_entry(&mut data, 0x014A, 4, 1, SUB_IFD_OFFSET); // SubIFDs
+  put_entry(&mut data, 0x8769, 4, 1, EXIF_IFD_OFFSET); // ExifIFD
+  put_entry(&mut data, 0xC612, 1, 4, u32::from_le_bytes([1, 4, 0, 0])); // DNGVersion
+  data.extend_from_slice(&0u32.to_le_bytes());
+  assert_eq!(data.len(), 158);
+  data.extend_from_slice(&[0x80, 0x90, 0xA0, 0xB0]);
+  data.extend_from_slice(&[0; 4]);
+  assert_eq!(data.len(), 166);
+  data.extend_from_slice(&[60, 70, 80, 90]);
+  assert_eq!(data.len(), 170);
+
+  data.extend_from_slice(&9u16.to_le_bytes());
+  put_entry(&mut data, 0x00FE, 4, 1, 0); // NewSubfileType: main image
+  put_entry(&mut data, 0x0100, 3, 1, 2);
+  put_entry(&mut data, 0x0101, 3, 1, 2);
+  put_entry(&mut data, 0x0102, 3, 1, 8);
+  put_entry(&mut data, 0x0103, 3, 1, 1);
+  put_entry(&mut data, 0x0106, 3, 1, 32803); // PhotometricInterpretation: CFA
+  put_entry(&mut data, 0x0111, 4, 1, RAW_DATA_OFFSET);
+  put_entry(&mut data, 0x0115, 3, 1, 1);
+  put_entry(&mut data, 0x0117, 4, 1, 4);
+  data.extend_from_slice(&0u32.to_le_bytes());
+  assert_eq!(data.len(), 284);
+  data.extend_from_slice(&[1, 2, 3, 4]);
+  assert_eq!(data.len(), 288);
+
+  data.extend_from_slice(&2u16.to_le_bytes());
+  put_entry(&mut data, 0x8827, 3, 1, 100); // ISOSpeedRatings
+  put_entry(&mut data, 0x927C, 7, 16, 318); // MakerNote
+  data.extend_from_slice(&0u32.to_le_bytes());
+  assert_eq!(data.len(), 318);
+  data.extend_from_slice(MAKER_NOTE);
+  data
+}
+
+/// Parses the directory at `offset` as if it were IFD0, such as a sub-IFD.
+fn parse_ifd_at(data: &[u8], offset: u32) -> Result<IfdTree, Box<dyn std::error::Error>> {
+  let mut data = data.to_vec();
+  data
+    .get_mut(4..8)
+    .ok_or("truncated TIFF header")?
+    .copy_from_slice(&offset.to_le_bytes());
+  IfdTree::parse(&data)
+}
+
+/// Returns the single strip of image data a directory points at.
+fn strip<'a>(data: &'a [u8], ifd: &Ifd) -> Option<&'a [u8]> {
+  let offset = ifd.get(tag::STRIP_OFFSETS)?.first_u32()? as usize;
+  let length = ifd.get(STRIP_BYTE_COUNTS)?.first_u32()? as usize;
+  data.get(offset..offset + length)
+}
+
+fn has_tag(exif_data: &[(String, String)], tag: &str, value: &str) -> bool {
+  exif_data
+    .iter()
+    .any(|(key, val)| (key == tag || key.starts_with(&format!("{tag} ["))) && val.contains(value))
+}
+
+#[test]
+fn test_apply_exif_preserves_dng_structure() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.dng");
+  let original = minimal_dng();
+  fs::write(&test_file, &original).unwrap();
+
+  DngProcessor::apply_exif_with_iso(&test_file, &create_test_selection(), Some(800)).unwrap();
+
+  assert!(!test_file.with_extension("xmp").exists());
+  let updated = fs::read(&test_file).unwrap();
+  let tree = IfdTree::parse(&updated).unwrap();
+  assert_eq!(
+    tree.ifd0.get(tag::DNG_VERSION),
+    Some(&ExifValue::Byte(vec![1, 4, 0, 0]))
+  );
+  assert_eq!(strip(&updated, &tree.ifd0), Some(&[60, 70, 80, 90][..]));
+  assert_eq!(
+    tree.ifd0.get(tag::SUB_IFDS).and_then(ExifValue::first_u32),
+    Some(SUB_IFD_OFFSET)
+  );
+  let raw_image = parse_ifd_at(&updated, SUB_IFD_OFFSET).unwrap();
+  assert_eq!(
+    raw_image.ifd0,
+    parse_ifd_at(&original, SUB_IFD_OFFSET).unwrap().ifd0
+  );
+  assert_eq!(strip(&updated, &raw_image.ifd0), Some(&[1, 2, 3, 4][..]));
+
+  let exif = tree.exif.as_ref().unwrap();
+  assert_eq!(
+    exif.get(tag::MAKER_NOTE),
+    Some(&ExifValue::Undefined(MAKER_NOTE.to_vec()))
+  );
+  assert_eq!(
+    exif.get(tag::ISO_SPEED_RATINGS),
+    Some(&ExifValue::Short(vec![800]))
+  );
+
+  let exif_data = ExifManager::read_exif_data(&test_file).unwrap();
+  assert!(has_tag(&exif_data, "Make (Embedded)", "Hasselblad"));
+  assert!(has_tag(&exif_data, "Model (Embedded)", "500C/M"));
+  assert!(has_tag(&exif_data, "Artist (Embedded)", "Test User"));
+  assert!(has_tag(
+    &exif_data,
+    "Lens Model (Embedded)",
+    "Planar 80mm f/2.8"
+  ));
+}
+
+#[test]
+fn test_set_creation_date_dng() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.dng");
+  fs::write(&test_file, minimal_dng()).unwrap();
+
+  DngProcessor::set_creation_date(&test_file, "2023:11:05 08:15:00").unwrap();
+
+  let exif_data = DngProcessor::read_exif(&test_file).unwrap();
+  assert!(has_tag(
+    &exif_data,
+    "Date/Time Original (Embedded)",
+    "2023:11:05 08:15:00"
+  ));
+  assert!(!test_file.with_extension("xmp").exists());
+}
+
+#[test]
+fn test_erase_exif_keeps_dng_tags() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.dng");
+  fs::write(&test_file, minimal_dng()).unwrap();
+
+  DngProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+  DngProcessor::erase_exif(&test_file).unwrap();
+
+  let tree = IfdTree::parse(&fs::read(&test_file).unwrap()).unwrap();
+  assert!(!tree.ifd0.contains(tag::MAKE));
+  assert!(!tree.ifd0.contains(tag::ARTIST));
+  assert!(tree.ifd0.contains(tag::DNG_VERSION));
+  assert!(tree.ifd0.contains(tag::SUB_IFDS));
+  let exif = tree.exif.as_ref().unwrap();
+  assert!(!exif.contains(tag::LENS_MODEL));
+  assert!(exif.contains(tag::MAKER_NOTE));
+}
+
+#[test]
+fn test_falls_back_to_sidecar_when_not_a_dng() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("unknown.dng");
+  let original = b"not a tiff structure".to_vec();
+  fs::write(&test_file, &original).unwrap();
+
+  DngProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+
+  assert_eq!(fs::read(&test_file).unwrap(), original);
+  let xmp = fs::read_to_string(test_file.with_extension("xmp")).unwrap();
+  assert!(xmp.contains("Hasselblad"));
+
+  DngProcessor::erase_exif_groups(&test_file, &[TagGroup::People]).unwrap();
+  assert_eq!(fs::read(&test_file).unwrap(), original);
+  let xmp = fs::read_to_string(test_file.with_extension("xmp")).unwrap();
+  assert!(xmp.contains("Hasselblad"));
+  assert!(!xmp.contains("Test User"));
+
+  DngProcessor::erase_exif(&test_file).unwrap();
+  assert_eq!(fs::read(&test_file).unwrap(), original);
+  let xmp = fs::read_to_string(test_file.with_extension("xmp")).unwrap();
+  assert!(!xmp.contains("Hasselblad"));
+  assert!(!xmp.contains("Test User"));
+}
+
+#[test]
+fn test_read_exif_flags_embedded_fields_next_to_the_sidecar() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("converted.dng");
+  // Without a DNG version the file is not edited in place
+  let original = minimal_dng();
+  let mut tree = IfdTree::parse(&original).unwrap();
+  tree.ifd0.remove(tag::DNG_VERSION);
+  fs::write(&test_file, tree.append_to(&original).unwrap()).unwrap();
+
+  DngProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+  assert!(test_file.with_extension("xmp").exists());
+
+  let exif_data = DngProcessor::read_exif(&test_file).unwrap();
+  assert!(has_tag(&exif_data, "Make", "Hasselblad"));
+  assert!(has_tag(&exif_data, "ISO Speed (Embedded)", "100"));
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| key.starts_with("ISO Speed [")));
+  assert!(exif_data.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+}
+
+#[test]
+fn test_dry_run_compares_with_the_embedded_fields() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.dng");
+  fs::write(&test_file, minimal_dng()).unwrap();
+
+  let selection = create_test_selection();
+  let changes = ExifManager::preview_exif_with_iso(&test_file, &selection, None).unwrap();
+  assert!(changes.iter().any(|change| change.tag == "Make"));
+
+  DngProcessor::apply_exif(&test_file, &selection).unwrap();
+  let changes = ExifManager::preview_exif_with_iso(&test_file, &selection, None).unwrap();
+  assert!(changes.is_empty(), "unexpected changes: {changes:?}");
+}
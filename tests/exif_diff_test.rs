@@ -0,0 +1,117 @@
+//! Tests for comparing existing EXIF data with the tags a selection would write.
+
+use ifex::exif::{ChangeKind, ExifDiff, ExifTags};
+use ifex::models::*;
+
+fn create_test_selection() -> Selection {
+  let camera = Camera::new("Mamiya".to_string(), "RB67".to_string());
+  let lens = Lens::new(
+    "Mamiya".to_string(),
+    "Sekor C".to_string(),
+    "90".to_string(),
+    "3.8".to_string(),
+    "RB".to_string(),
+  );
+  let film = Film::new("Fujifilm".to_string(), "Provia 100F".to_string(), 100);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Studio".to_string(), camera.id, Some(lens.id));
+
+  Selection::new(setup, camera, Some(lens), film, photographer)
+}
+
+fn current(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+  pairs
+    .iter()
+    .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+    .collect()
+}
+
+#[test]
+fn test_matching_values_produce_no_changes() {
+  let selection = create_test_selection();
+  let planned = ExifTags::create_exif_object(&selection);
+  let existing = current(&[
+    ("Make", "Mamiya"),
+    ("Model", "RB67"),
+    ("Artist", "Test User"),
+    ("Film [Tag(Tiff, 649)]", "Fujifilm Provia 100F (ISO 100)"),
+    ("ISO Speed", "100"),
+    ("Lens Make", "Mamiya"),
+    ("Lens Model", "Sekor C 90mm f/3.8"),
+    (
+      "Lens Specification",
+      "90000/1000, 90000/1000, 3800/1000, 3800/1000",
+    ),
+    ("Focal Length", "90000/1000"),
+    ("Orientation", "1"),
+  ]);
+
+  assert!(ExifDiff::between(&existing, &planned).is_empty());
+}
+
+#[test]
+fn test_changed_and_removed_tags() {
+  let mut selection = create_test_selection();
+  selection.lens = None;
+  let planned = ExifTags::create_exif_object_with_iso(&selection, Some(400));
+  let existing = current(&[
+    ("Make", "Pentax"),
+    ("ISO Speed", "100"),
+    ("Lens Model", "Sekor C 90mm f/3.8"),
+  ]);
+
+  let changes = ExifDiff::between(&existing, &planned);
+  let kind_of = |tag: &str| {
+    changes
+      .iter()
+      .find(|change| change.tag == tag)
+      .map(|change| change.kind)
+  };
+  assert_eq!(kind_of("Make"), Some(ChangeKind::Changed));
+  assert_eq!(kind_of("ISOSpeedRatings"), Some(ChangeKind::Changed));
+  assert_eq!(kind_of("LensModel"), Some(ChangeKind::Removed));
+  assert_eq!(kind_of("Model"), Some(ChangeKind::Added));
+  assert_eq!(kind_of("LensMake"), None);
+}
+
+#[test]
+fn test_stale_development_summary_is_removed() {
+  let planned = ExifTags::create_exif_object(&create_test_selection());
+  let pushed = current(&[(
+    "User Comment",
+    "Fujifilm Provia 100F (ISO 100) shot at ISO 400, pushed 2 stops.",
+  )]);
+  let comment_change = |existing: &[(String, String)]| {
+    ExifDiff::between(existing, &planned)
+      .into_iter()
+      .find(|change| change.tag == "UserComment")
+      .map(|change| change.kind)
+  };
+  assert_eq!(comment_change(&pushed), Some(ChangeKind::Removed));
+
+  // Comments IFEX did not write are kept
+  let foreign = current(&[("User Comment", "Shot at ISO 400 on the beach")]);
+  assert_eq!(comment_change(&foreign), None);
+}
+
+#[test]
+fn test_gps_updates_match_stored_rationals() {
+  let position = GpsPosition {
+    latitude: 38.7175,
+    longitude: -9.1375,
+    altitude: Some(65.0),
+    time: chrono::DateTime::from_timestamp(1_722_507_345, 0),
+  };
+  let planned = ExifTags::create_gps_object(&position);
+  let existing = current(&[
+    ("GPS Latitude [Tag(Gps, 2)]", "38/1, 43/1, 3000/1000"),
+    ("GPS Longitude [Tag(Gps, 4)]", "9/1, 8/1, 16000/1000"),
+    ("GPS Altitude [Tag(Gps, 6)]", "6500/100"),
+    ("GPS Time Stamp [Tag(Gps, 7)]", "10/1, 15/1, 45/1"),
+  ]);
+
+  let changes = ExifDiff::updates(&existing, &planned);
+  let tags: Vec<&str> = changes.iter().map(|change| change.tag.as_str()).collect();
+  assert_eq!(tags, ["GPS Longitude"]);
+  assert_eq!(changes[0].kind, ChangeKind::Changed);
+}
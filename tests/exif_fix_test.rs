@@ -45,13 +45,7 @@ fn test_exif_apply_and_read_with_focal_length() {
   let photographer = Photographer::new("Test User".to_string(), None);
   let setup = Setup::new("Test Setup".to_string(), camera.id, Some(lens.id));
 
-  let selection = Selection {
-    setup,
-    camera,
-    lens: Some(lens),
-    film,
-    photographer,
-  };
+  let selection = Selection::new(setup, camera, Some(lens), film, photographer);
 
   // Apply EXIF data (this should not fail with truncated IFD count)
   let result = JpegProcessor::apply_exif_with_iso(&test_file, &selection, Some(800));
@@ -127,13 +121,7 @@ fn test_exif_apply_and_read_without_focal_length() {
   let photographer = Photographer::new("Test User".to_string(), None);
   let setup = Setup::new("Test Setup".to_string(), camera.id, Some(lens.id));
 
-  let selection = Selection {
-    setup,
-    camera,
-    lens: Some(lens),
-    film,
-    photographer,
-  };
+  let selection = Selection::new(setup, camera, Some(lens), film, photographer);
 
   // Apply EXIF data (this should not fail with truncated IFD count)
   let result = JpegProcessor::apply_exif_with_iso(&test_file, &selection, None);
@@ -24,13 +24,7 @@ fn test_film_info_in_exif_segment() {
     let photographer = Photographer::new("Daniel Filho".to_string(), None);
     let setup = Setup::new("Leica M7 + Summicron".to_string(), camera.id, Some(lens.id));
 
-    let selection = Selection {
-        setup,
-        camera,
-        lens: Some(lens),
-        film,
-        photographer,
-    };
+    let selection = Selection::new(setup, camera, Some(lens), film, photographer);
 
     // Create a minimal JPEG file for testing
     let test_image_path = temp_dir.path().join("test.jpg");
@@ -0,0 +1,243 @@
+//! Tests for GPX track parsing, position interpolation and GPS tag writing.
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use ifex::exif::processors::{JpegProcessor, RawProcessor, TiffProcessor};
+use ifex::exif::ExifManager;
+use ifex::gpx::{parse_utc_offset, Track};
+use ifex::models::GpsPosition;
+use image::{ImageBuffer, Rgb};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// A walk through Lisbon logged every minute, with a gap of an hour and a
+/// point without a time, listed out of order across two segments.
+const TRACK: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk>
+    <name>Alfama</name>
+    <trkseg>
+      <trkpt lat="38.7100" lon="-9.1300"><ele>50</ele><time>2024-08-01T10:15:00Z</time></trkpt>
+      <trkpt lat="38.7200" lon="-9.1400"><ele>70</ele><time>2024-08-01T10:16:00Z</time></trkpt>
+      <trkpt lat="38.7300" lon="-9.1500"></trkpt>
+    </trkseg>
+    <trkseg>
+      <trkpt lat="38.6900" lon="-9.2100"><time>2024-08-01T11:16:00+00:00</time></trkpt>
+      <trkpt lat="38.7000" lon="-9.1200"><ele>40</ele><time>2024-08-01T10:14:00Z</time></trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+fn utc(value: &str) -> Option<DateTime<Utc>> {
+  DateTime::parse_from_rfc3339(value)
+    .ok()
+    .map(|time| time.with_timezone(&Utc))
+}
+
+fn create_minimal_jpeg() -> Vec<u8> {
+  vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+    0x00, 0x00, // APP0
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]
+}
+
+fn value(exif_data: &[(String, String)], tag: &str) -> Option<String> {
+  exif_data
+    .iter()
+    .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+    .map(|(_, value)| value.clone())
+}
+
+#[test]
+fn test_parse_track() {
+  let track = Track::parse(TRACK).unwrap();
+  let times: Vec<String> = track
+    .points
+    .iter()
+    .map(|point| point.time.format("%H:%M").to_string())
+    .collect();
+  assert_eq!(times, ["10:14", "10:15", "10:16", "11:16"]);
+  assert_eq!(track.points[0].elevation, Some(40.0));
+  assert_eq!(track.points[3].elevation, None);
+  assert!((track.points[1].longitude + 9.13).abs() < 1e-9);
+
+  let error = |content: &str| Track::parse(content).err().map(|e| e.to_string());
+  assert!(
+    error("<gpx><trk><trkseg><trkpt lat=\"1\" lon=\"2\"/></trkseg></trk></gpx>")
+      .is_some_and(|e| e.contains("No track points"))
+  );
+  assert!(error(
+    "<gpx><trkpt lat=\"north\" lon=\"2\"><time>2024-08-01T10:00:00Z</time></trkpt></gpx>"
+  )
+  .is_some_and(|e| e.contains("Invalid coordinate")));
+  assert!(
+    error("<gpx><trkpt lat=\"1\" lon=\"2\"><time>yesterday</time></trkpt></gpx>")
+      .is_some_and(|e| e.contains("Invalid time"))
+  );
+  assert!(error("<gpx><trkpt lat=\"1\"><time>2024-08-01T10:00:00Z</time></trkpt></gpx>").is_some());
+}
+
+#[test]
+fn test_position_at() {
+  let track = Track::parse(TRACK).unwrap();
+  let max_gap = Duration::minutes(5);
+  let position_at = |time: &str| utc(time).and_then(|time| track.position_at(time, max_gap));
+
+  let exact = position_at("2024-08-01T10:15:00Z").unwrap();
+  assert!((exact.latitude - 38.71).abs() < 1e-9);
+  assert_eq!(exact.altitude, Some(50.0));
+  assert_eq!(exact.time, utc("2024-08-01T10:15:00Z"));
+
+  let between = position_at("2024-08-01T10:15:45Z").unwrap();
+  assert!((between.latitude - 38.7175).abs() < 1e-9);
+  assert!((between.longitude + 9.1375).abs() < 1e-9);
+  assert!(between
+    .altitude
+    .is_some_and(|altitude| (altitude - 65.0).abs() < 1e-9));
+  assert_eq!(between.time, utc("2024-08-01T10:15:45Z"));
+
+  // Points an hour apart are not interpolated, but the nearer one is used
+  // while it is within the maximum gap
+  let after_walk = position_at("2024-08-01T10:19:00Z").unwrap();
+  assert!((after_walk.latitude - 38.72).abs() < 1e-9);
+  assert!(position_at("2024-08-01T10:45:00Z").is_none());
+  assert!(position_at("2024-08-01T11:20:00Z").is_some_and(|p| p.altitude.is_none()));
+  assert!(position_at("2024-08-01T10:10:00Z").is_some());
+  assert!(position_at("2024-08-01T10:00:00Z").is_none());
+}
+
+#[test]
+fn test_parse_utc_offset() {
+  let seconds = |value: &str| parse_utc_offset(value).map(|offset| offset.local_minus_utc());
+  assert_eq!(seconds("+01:00"), Ok(3600));
+  assert_eq!(seconds("-05:30"), Ok(-19800));
+  assert_eq!(seconds("+0545"), Ok(20700));
+  assert_eq!(seconds("+2"), Ok(7200));
+  assert_eq!(seconds("Z"), Ok(0));
+  for invalid in ["01:00", "+1:75", "+15:00", "+-1", "UTC"] {
+    assert!(parse_utc_offset(invalid).is_err(), "{invalid}");
+  }
+}
+
+#[test]
+fn test_geotag_files() {
+  let temp_dir = TempDir::new().unwrap();
+  let jpeg = temp_dir.path().join("frame_01.jpg");
+  let tiff = temp_dir.path().join("frame_02.tif");
+  let raw = temp_dir.path().join("frame_03.nef");
+  let far = temp_dir.path().join("frame_04.jpg");
+  fs::write(&jpeg, create_minimal_jpeg()).unwrap();
+  ImageBuffer::from_pixel(4, 4, Rgb([200u8, 100, 50]))
+    .save(&tiff)
+    .unwrap();
+  fs::write(&raw, b"not really a raw file").unwrap();
+  fs::write(&far, create_minimal_jpeg()).unwrap();
+
+  // The camera clock was set to Lisbon summer time
+  JpegProcessor::set_creation_date(&jpeg, "2024:08:01 11:15:45").unwrap();
+  TiffProcessor::set_creation_date(&tiff, "2024:08:01 11:15:00").unwrap();
+  RawProcessor::set_creation_date(&raw, "2024:08:01 11:16:00").unwrap();
+  JpegProcessor::set_creation_date(&far, "2024:08:01 13:00:00").unwrap();
+
+  let track = Track::parse(TRACK).unwrap();
+  let offset = FixedOffset::east_opt(3600);
+  let files: Vec<PathBuf> = vec![jpeg.clone(), tiff.clone(), raw.clone(), far];
+
+  let preview = ExifManager::new().with_dry_run(true).geotag_files(
+    &files[..1],
+    &track,
+    offset,
+    Duration::minutes(5),
+  );
+  let changes = preview.results.files[0].changes.clone().unwrap_or_default();
+  let planned: Vec<(String, Option<String>)> = changes
+    .into_iter()
+    .map(|change| (change.tag, change.new))
+    .collect();
+  assert_eq!(
+    planned,
+    [
+      ("GPS Latitude".to_string(), Some("38.717500 N".to_string())),
+      ("GPS Longitude".to_string(), Some("9.137500 W".to_string())),
+      ("GPS Altitude".to_string(), Some("65.00 m".to_string())),
+      (
+        "GPS Time Stamp".to_string(),
+        Some("2024-08-01 10:15:45 UTC".to_string())
+      ),
+    ]
+  );
+  assert!(value(&JpegProcessor::read_exif(&jpeg).unwrap(), "GPS Latitude").is_none());
+
+  let result = ExifManager::new().geotag_files(&files, &track, offset, Duration::minutes(5));
+  assert_eq!(result.results.processed, 3);
+  assert_eq!(result.results.failed, 1);
+  assert!(result.results.files[3]
+    .error
+    .as_deref()
+    .is_some_and(|e| e.contains("No track position within 300 s of 2024-08-01 12:00:00 UTC")));
+
+  // 38.7175° is 38° 43' 3", and 9.1375° is 9° 8' 15"
+  let jpeg_data = JpegProcessor::read_exif(&jpeg).unwrap();
+  assert_eq!(value(&jpeg_data, "GPS Latitude Ref").as_deref(), Some("N"));
+  assert_eq!(
+    value(&jpeg_data, "GPS Latitude").as_deref(),
+    Some("38/1, 43/1, 3000/1000")
+  );
+  assert_eq!(value(&jpeg_data, "GPS Longitude Ref").as_deref(), Some("W"));
+  assert_eq!(
+    value(&jpeg_data, "GPS Longitude").as_deref(),
+    Some("9/1, 8/1, 15000/1000")
+  );
+  assert_eq!(
+    value(&jpeg_data, "GPS Altitude").as_deref(),
+    Some("6500/100")
+  );
+  assert_eq!(
+    value(&jpeg_data, "GPS Time Stamp").as_deref(),
+    Some("10/1, 15/1, 45/1")
+  );
+  assert_eq!(
+    value(&jpeg_data, "GPS Date Stamp").as_deref(),
+    Some("2024:08:01")
+  );
+  assert_eq!(
+    value(&jpeg_data, "GPS Latitude (XMP)").as_deref(),
+    Some("38,43.050000N")
+  );
+
+  let tiff_data = TiffProcessor::read_exif(&tiff).unwrap();
+  assert_eq!(
+    value(&tiff_data, "GPS Latitude").as_deref(),
+    Some("38/1, 42/1, 36000/1000")
+  );
+  assert_eq!(
+    value(&tiff_data, "GPS Altitude").as_deref(),
+    Some("5000/100")
+  );
+
+  let sidecar = fs::read_to_string(raw.with_extension("xmp")).unwrap();
+  for property in [
+    "2024-08-01T11:16:00",
+    "38,43.200000N",
+    "9,8.400000W",
+    "7000/100",
+    "2024-08-01T10:16:00Z",
+  ] {
+    assert!(sidecar.contains(property), "{property} missing: {sidecar}");
+  }
+}
+
+#[test]
+fn test_position_display_name() {
+  let position = GpsPosition {
+    latitude: -33.8568,
+    longitude: 151.2153,
+    altitude: Some(-2.5),
+    time: None,
+  };
+  assert_eq!(position.display_name(), "33.856800 S, 151.215300 E, -2.5 m");
+}
@@ -0,0 +1,188 @@
+//! Tests for the typed IFD model and its serializers.
+
+use ifex::exif::ifd::tag;
+use ifex::exif::{ByteOrder, ExifValue, Ifd, IfdTree, JpegProcessor};
+
+fn every_value_type() -> Vec<(u16, ExifValue)> {
+  vec![
+    (0x1001, ExifValue::Byte(vec![1, 2, 3, 4, 5])),
+    (0x1002, ExifValue::ascii("a longer ascii string")),
+    (0x1003, ExifValue::Short(vec![1, 65_535, 300])),
+    (0x1004, ExifValue::Long(vec![70_000])),
+    (0x1005, ExifValue::Rational(vec![(1, 125), (28, 10)])),
+    (0x1006, ExifValue::SByte(vec![-1, 2])),
+    (0x1007, ExifValue::Undefined(b"0232".to_vec())),
+    (0x1008, ExifValue::SShort(vec![-300])),
+    (0x1009, ExifValue::SLong(vec![-70_000, 5])),
+    (0x100A, ExifValue::SRational(vec![(-2, 3)])),
+    (0x100B, ExifValue::Float(vec![1.5])),
+    (0x100C, ExifValue::Double(vec![-0.25, 1e10])),
+  ]
+}
+
+#[test]
+fn test_round_trip_every_value_type_in_both_byte_orders() {
+  for byte_order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+    let mut tree = IfdTree::with_byte_order(byte_order);
+    for (number, value) in every_value_type() {
+      tree.ifd0.set(number, value.clone());
+      tree.exif_mut().set(number, value.clone());
+      tree.gps_mut().set(number, value);
+    }
+
+    let parsed = IfdTree::parse(&tree.to_bytes()).unwrap();
+    assert_eq!(parsed.byte_order, byte_order);
+    for (number, value) in every_value_type() {
+      assert_eq!(parsed.ifd0.get(number), Some(&value));
+      assert_eq!(parsed.exif.as_ref().unwrap().get(number), Some(&value));
+      assert_eq!(parsed.gps.as_ref().unwrap().get(number), Some(&value));
+    }
+    assert!(!parsed.ifd0.contains(tag::EXIF_IFD_POINTER));
+    assert!(!parsed.ifd0.contains(tag::GPS_IFD_POINTER));
+  }
+}
+
+#[test]
+fn test_thumbnail_is_relocated_on_serialization() {
+  let mut tree = IfdTree::with_byte_order(ByteOrder::BigEndian);
+  tree.ifd0.set(tag::MAKE, ExifValue::ascii("Canon"));
+  let mut ifd1 = Ifd::new();
+  ifd1.set(tag::JPEG_INTERCHANGE_FORMAT, ExifValue::Long(vec![0]));
+  ifd1.set(
+    tag::JPEG_INTERCHANGE_FORMAT_LENGTH,
+    ExifValue::Long(vec![0]),
+  );
+  tree.ifd1 = Some(ifd1);
+  tree.thumbnail = Some(b"thumb".to_vec());
+
+  let mut parsed = IfdTree::parse(&tree.to_bytes()).unwrap();
+  assert_eq!(parsed.thumbnail.as_deref(), Some(&b"thumb"[..]));
+
+  parsed
+    .ifd0
+    .set(tag::ARTIST, ExifValue::ascii("Someone with a long name"));
+  let reparsed = IfdTree::parse(&parsed.to_bytes()).unwrap();
+  assert_eq!(reparsed.thumbnail.as_deref(), Some(&b"thumb"[..]));
+  assert_eq!(
+    reparsed.ifd0.get(tag::MAKE).and_then(ExifValue::as_ascii),
+    Some("Canon".to_string())
+  );
+}
+
+#[test]
+fn test_append_reuses_unchanged_values_and_scrubs_stale_ones() {
+  let mut tree = IfdTree::new();
+  tree
+    .ifd0
+    .set(tag::SOFTWARE, ExifValue::ascii("Original software"));
+  tree
+    .ifd0
+    .set(tag::ARTIST, ExifValue::ascii("Previous Owner"));
+  tree.gps_mut().set(0x0001, ExifValue::ascii("N"));
+  let original = tree.to_bytes();
+
+  let mut edited = IfdTree::parse(&original).unwrap();
+  edited.ifd0.remove(tag::ARTIST);
+  edited.ifd0.set(tag::MAKE, ExifValue::ascii("Pentax"));
+  edited.gps = None;
+  let appended = edited.append_to(&original).unwrap();
+
+  let contains = |needle: &[u8]| appended.windows(needle.len()).any(|w| w == needle);
+  assert!(contains(b"Original software"));
+  assert!(!contains(b"Previous Owner"));
+  assert_eq!(
+    appended.windows(17).position(|w| w == b"Original software"),
+    original.windows(17).position(|w| w == b"Original software")
+  );
+
+  let parsed = IfdTree::parse(&appended).unwrap();
+  assert_eq!(
+    parsed.ifd0.get(tag::SOFTWARE).and_then(ExifValue::as_ascii),
+    Some("Original software".to_string())
+  );
+  assert_eq!(
+    parsed.ifd0.get(tag::MAKE).and_then(ExifValue::as_ascii),
+    Some("Pentax".to_string())
+  );
+  assert!(!parsed.ifd0.contains(tag::ARTIST));
+  assert!(parsed.gps.is_none());
+}
+
+#[test]
+fn test_maker_note_keeps_its_offset_when_serialized() {
+  let mut tree = IfdTree::with_byte_order(ByteOrder::LittleEndian);
+  tree.ifd0.set(tag::MAKE, ExifValue::ascii("Canon"));
+  let mut maker_note = b"MKNT\0\0\0\0TARGET".to_vec();
+  tree
+    .exif_mut()
+    .set(tag::MAKER_NOTE, ExifValue::Undefined(maker_note.clone()));
+  let mut original = tree.to_bytes();
+
+  // Point the maker note's internal offset at its own payload, the way
+  // vendors store absolute offsets relative to the TIFF header.
+  let position = original.windows(4).position(|w| w == b"MKNT").unwrap();
+  let target = u32::try_from(position + 8).unwrap().to_le_bytes();
+  original[position + 4..position + 8].copy_from_slice(&target);
+  maker_note[4..8].copy_from_slice(&target);
+
+  let mut edited = IfdTree::parse(&original).unwrap();
+  edited.ifd0.set(
+    tag::ARTIST,
+    ExifValue::ascii("An artist whose name pushes the directories past the maker note"),
+  );
+  let written = edited.to_bytes();
+
+  assert_eq!(
+    &written[position..position + maker_note.len()],
+    &maker_note[..]
+  );
+  assert_eq!(&written[position + 8..position + 14], b"TARGET");
+  let parsed = IfdTree::parse(&written).unwrap();
+  assert_eq!(
+    parsed.exif.as_ref().unwrap().get(tag::MAKER_NOTE),
+    Some(&ExifValue::Undefined(maker_note))
+  );
+  assert_eq!(
+    parsed.ifd0.get(tag::ARTIST).and_then(ExifValue::as_ascii),
+    Some("An artist whose name pushes the directories past the maker note".to_string())
+  );
+}
+
+#[test]
+fn test_format_tree_names_fields_by_directory() {
+  let mut tree = IfdTree::new();
+  tree.ifd0.set(tag::MAKE, ExifValue::ascii("Olympus"));
+  tree
+    .exif_mut()
+    .set(tag::ISO_SPEED_RATINGS, ExifValue::Short(vec![400]));
+  let mut ifd1 = Ifd::new();
+  ifd1.set(0x0103, ExifValue::Short(vec![6]));
+  tree.ifd1 = Some(ifd1);
+
+  let fields = JpegProcessor::format_tree(&tree, Some("Embedded"));
+  assert_eq!(
+    fields,
+    vec![
+      (
+        "Make (Embedded) [Tag(Tiff, 271)]".to_string(),
+        "Olympus".to_string()
+      ),
+      (
+        "ISO Speed (Embedded) [Tag(Exif, 34855)]".to_string(),
+        "400".to_string()
+      ),
+      (
+        "Compression (Embedded Thumbnail) [Tag(Tiff, 259)]".to_string(),
+        "6".to_string()
+      ),
+    ]
+  );
+}
+
+#[test]
+fn test_parse_rejects_invalid_headers() {
+  assert!(IfdTree::parse(b"II*\0").is_err());
+  assert!(IfdTree::parse(b"XX*\0\x08\0\0\0").is_err());
+  assert!(IfdTree::parse(b"II+\0\x08\0\0\0").is_err());
+  assert!(IfdTree::parse(b"II*\0\xff\0\0\0").is_err());
+}
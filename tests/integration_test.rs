@@ -73,13 +73,7 @@ fn test_selection_workflow() {
   let photographer = Photographer::new("Test User".to_string(), None);
   let setup = Setup::new("Wedding Setup".to_string(), camera.id, Some(lens.id));
 
-  let selection = Selection {
-    setup,
-    camera,
-    lens: Some(lens),
-    film,
-    photographer,
-  };
+  let selection = Selection::new(setup, camera, Some(lens), film, photographer);
 
   assert_eq!(selection.camera.display_name(), "Nikon D850");
   assert_eq!(
@@ -0,0 +1,348 @@
+//! Tests for reading and writing IPTC-IIM metadata in JPEG files.
+
+use chrono::{Datelike, Local};
+use ifex::exif::iptc::{
+  self, dataset, Dataset, IptcData, Resource, IPTC_DIGEST_RESOURCE, IPTC_RESOURCE, PHOTOSHOP_HEADER,
+};
+use ifex::exif::processors::JpegProcessor;
+use ifex::exif::TagGroup;
+use ifex::models::*;
+use md5::{Digest, Md5};
+use std::fs;
+use tempfile::TempDir;
+
+/// Resolution info, a resource Photoshop writes next to the IPTC data.
+const RESOLUTION_RESOURCE: u16 = 0x03ED;
+
+/// Largest amount of resource data an APP13 segment holds.
+const SEGMENT_ROOM: usize = 65533 - 14;
+
+fn create_test_selection(caption: Option<&str>, keywords: &[&str]) -> Selection {
+  let camera = Camera::new("Nikon".to_string(), "FM2".to_string());
+  let film = Film::new("Kodak".to_string(), "Portra".to_string(), 400);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Camera only".to_string(), camera.id, None);
+
+  Selection {
+    caption: caption.map(str::to_string),
+    keywords: keywords.iter().map(|k| (*k).to_string()).collect(),
+    ..Selection::new(setup, camera, None, film, photographer)
+  }
+}
+
+/// Builds a JPEG file whose APP13 segments hold the given resources, split
+/// across several segments when needed.
+fn jpeg_with_resources(resources: &[Resource]) -> Vec<u8> {
+  let mut data = vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+    0x00, 0x00, // APP0
+  ];
+  if !resources.is_empty() {
+    let contents = iptc::write_resources(resources).unwrap_or_default();
+    for chunk in contents.chunks(SEGMENT_ROOM) {
+      let payload = [PHOTOSHOP_HEADER, chunk].concat();
+      let length = u16::try_from(payload.len() + 2).unwrap_or(u16::MAX);
+      data.extend_from_slice(&[0xFF, 0xED]);
+      data.extend_from_slice(&length.to_be_bytes());
+      data.extend_from_slice(&payload);
+    }
+  }
+  data.extend_from_slice(&[
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]);
+  data
+}
+
+/// Returns the payloads of all APP13 segments before the image data.
+fn app13_payloads(data: &[u8]) -> Vec<&[u8]> {
+  let mut payloads = Vec::new();
+  let mut i = 2;
+  while i + 4 <= data.len() && data[i] == 0xFF && data[i + 1] != 0xDA {
+    let end = i + 2 + usize::from(u16::from_be_bytes([data[i + 2], data[i + 3]]));
+    if data[i + 1] == 0xED {
+      payloads.push(&data[i + 4..end.min(data.len())]);
+    }
+    i = end;
+  }
+  payloads
+}
+
+/// Parses the resources of all Photoshop APP13 segments of a JPEG file.
+fn read_resources(data: &[u8]) -> Vec<Resource> {
+  let contents: Vec<u8> = app13_payloads(data)
+    .into_iter()
+    .filter_map(|payload| payload.strip_prefix(PHOTOSHOP_HEADER))
+    .flatten()
+    .copied()
+    .collect();
+  iptc::parse_resources(&contents).unwrap_or_default()
+}
+
+fn read_iptc(data: &[u8]) -> IptcData {
+  iptc::read_iptc(&read_resources(data))
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+fn values(exif_data: &[(String, String)], tag: &str) -> Vec<String> {
+  exif_data
+    .iter()
+    .filter(|(key, _)| key == tag)
+    .map(|(_, value)| value.clone())
+    .collect()
+}
+
+#[test]
+fn test_apply_writes_iptc_that_reads_back() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_with_resources(&[])).unwrap();
+
+  let selection =
+    create_test_selection(Some("Tram 28 in Alfama, Lisboa"), &["street", "São Bento"]);
+  JpegProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  assert_eq!(values(&exif_data, "IPTC: Byline"), ["Test User"]);
+  assert_eq!(values(&exif_data, "IPTC: Credit"), ["Test User"]);
+  assert_eq!(
+    values(&exif_data, "IPTC: Copyright Notice"),
+    [format!("© {} Test User", Local::now().year())]
+  );
+  assert_eq!(
+    values(&exif_data, "IPTC: Caption/Abstract"),
+    ["Tram 28 in Alfama, Lisboa"]
+  );
+  assert_eq!(
+    values(&exif_data, "IPTC: Keywords"),
+    ["street", "São Bento"]
+  );
+
+  // The embedded XMP packet carries the same caption and keywords
+  assert_eq!(
+    values(&exif_data, "dc:description (XMP)"),
+    ["Tram 28 in Alfama, Lisboa"]
+  );
+  assert_eq!(
+    values(&exif_data, "dc:subject (XMP)"),
+    ["street, São Bento"]
+  );
+
+  // The datasets declare UTF-8 and are read back as written
+  let data = fs::read(&test_file).unwrap();
+  let iptc = read_iptc(&data);
+  assert!(iptc
+    .datasets
+    .iter()
+    .any(|d| d.record == 1 && d.number == 90 && d.value == b"\x1b%G"));
+  assert_eq!(iptc.get(dataset::KEYWORDS), ["street", "São Bento"]);
+  assert_eq!(iptc.get(dataset::CAPTION), ["Tram 28 in Alfama, Lisboa"]);
+}
+
+#[test]
+fn test_apply_writes_creator_contact_info() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_with_resources(&[])).unwrap();
+
+  let mut selection = create_test_selection(None, &[]);
+  selection.photographer.email = Some("test@example.com".to_string());
+  selection.photographer.contact = Contact {
+    city: Some("Lisboa".to_string()),
+    country: Some("Portugal".to_string()),
+    phone: Some("+351 21 000 0000".to_string()),
+    website: Some("https://example.com".to_string()),
+    ..Contact::default()
+  };
+  JpegProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  let contact_info = |field: &str| {
+    values(
+      &exif_data,
+      &format!("Iptc4xmpCore:CreatorContactInfo/{field} (XMP)"),
+    )
+  };
+  assert_eq!(contact_info("CiAdrCity"), ["Lisboa"]);
+  assert_eq!(contact_info("CiAdrCtry"), ["Portugal"]);
+  assert_eq!(contact_info("CiTelWork"), ["+351 21 000 0000"]);
+  assert_eq!(contact_info("CiEmailWork"), ["test@example.com"]);
+  assert_eq!(contact_info("CiUrlWork"), ["https://example.com"]);
+  assert!(contact_info("CiAdrExtadr").is_empty());
+  assert_eq!(values(&exif_data, "photoshop:Credit (XMP)"), ["Test User"]);
+  assert_eq!(
+    read_iptc(&fs::read(&test_file).unwrap()).get(dataset::CONTACT),
+    ["test@example.com, +351 21 000 0000, https://example.com"]
+  );
+
+  JpegProcessor::erase_exif_groups(&test_file, &[TagGroup::People]).unwrap();
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| key.starts_with("Iptc4xmpCore:") || key.starts_with("photoshop:Credit")));
+  assert!(values(&exif_data, "IPTC: Contact").is_empty());
+}
+
+#[test]
+fn test_apply_preserves_other_resources() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+
+  let mut existing = IptcData::default();
+  existing.set(5, &["Roll 12".to_string()]);
+  existing.set(dataset::KEYWORDS, &["archive".to_string()]);
+  // A thumbnail too large for one segment, so the resources span two
+  let thumbnail = Resource {
+    id: 0x040C,
+    name: b"thumb".to_vec(),
+    data: (0..70_000u32).map(|i| (i % 251) as u8).collect(),
+  };
+  let resolution = Resource {
+    id: RESOLUTION_RESOURCE,
+    name: Vec::new(),
+    data: vec![0, 0x48, 0, 0, 0, 1, 0, 1, 0, 0x48, 0, 0, 0, 1, 0, 1],
+  };
+  let resources = vec![
+    resolution.clone(),
+    Resource {
+      id: IPTC_RESOURCE,
+      name: Vec::new(),
+      data: existing.to_bytes().unwrap(),
+    },
+    Resource {
+      id: IPTC_DIGEST_RESOURCE,
+      name: Vec::new(),
+      data: vec![0; 16],
+    },
+    thumbnail.clone(),
+  ];
+  fs::write(&test_file, jpeg_with_resources(&resources)).unwrap();
+
+  JpegProcessor::apply_exif(&test_file, &create_test_selection(None, &[])).unwrap();
+
+  let data = fs::read(&test_file).unwrap();
+  assert_eq!(app13_payloads(&data).len(), 2);
+  let written = read_resources(&data);
+  let ids: Vec<u16> = written.iter().map(|r| r.id).collect();
+  assert_eq!(
+    ids,
+    [
+      RESOLUTION_RESOURCE,
+      IPTC_RESOURCE,
+      IPTC_DIGEST_RESOURCE,
+      0x040C
+    ]
+  );
+  assert_eq!(written[0], resolution);
+  assert_eq!(written[3], thumbnail);
+  assert_eq!(written[2].data, Md5::digest(&written[1].data).to_vec());
+
+  // Without a caption or keywords the existing ones are kept
+  let iptc = read_iptc(&data);
+  assert_eq!(iptc.get(5), ["Roll 12"]);
+  assert_eq!(iptc.get(dataset::KEYWORDS), ["archive"]);
+  assert_eq!(iptc.get(dataset::BY_LINE), ["Test User"]);
+  assert!(iptc.get(dataset::CAPTION).is_empty());
+}
+
+#[test]
+fn test_write_iptc_updates_the_digest() {
+  let iptc = IptcData {
+    datasets: vec![Dataset {
+      record: 2,
+      number: dataset::CAPTION,
+      value: b"Tram 28".to_vec(),
+    }],
+  };
+  let mut resources = vec![Resource {
+    id: IPTC_DIGEST_RESOURCE,
+    name: Vec::new(),
+    data: vec![0; 16],
+  }];
+  iptc::write_iptc(&mut resources, &iptc).unwrap();
+
+  // MD5 of the stream 1C 02 78 00 07 "Tram 28", as computed by md5sum
+  let digest = resources
+    .iter()
+    .find(|r| r.id == IPTC_DIGEST_RESOURCE)
+    .map(|r| r.data.clone());
+  assert_eq!(
+    digest,
+    Some(vec![
+      0xC0, 0x85, 0x49, 0x4D, 0x0C, 0x25, 0xBA, 0xBF, 0x32, 0x24, 0xA3, 0xB0, 0x99, 0xCA, 0xA5,
+      0xA9
+    ])
+  );
+}
+
+#[test]
+fn test_erase_removes_iptc_groups() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_with_resources(&[])).unwrap();
+  let selection = create_test_selection(Some("Harbour"), &["boats"]);
+  JpegProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  JpegProcessor::erase_exif_groups(&test_file, &[TagGroup::People]).unwrap();
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  assert!(values(&exif_data, "IPTC: Byline").is_empty());
+  assert!(values(&exif_data, "IPTC: Credit").is_empty());
+  assert!(values(&exif_data, "IPTC: Copyright Notice").is_empty());
+  assert_eq!(values(&exif_data, "IPTC: Caption/Abstract"), ["Harbour"]);
+  assert_eq!(values(&exif_data, "IPTC: Keywords"), ["boats"]);
+
+  // Erasing everything drops the segment once only the headers are left
+  let other = temp_dir.path().join("other.jpg");
+  fs::write(&other, jpeg_with_resources(&[])).unwrap();
+  JpegProcessor::apply_exif(&other, &create_test_selection(None, &[])).unwrap();
+  assert_eq!(app13_payloads(&fs::read(&other).unwrap()).len(), 1);
+  JpegProcessor::erase_exif(&other).unwrap();
+  assert!(app13_payloads(&fs::read(&other).unwrap()).is_empty());
+}
+
+#[test]
+fn test_datasets_and_resources_round_trip() {
+  let mut iptc = IptcData::default();
+  iptc.set(dataset::CAPTION, &["Überfahrt".to_string()]);
+  iptc.set(
+    dataset::BY_LINE,
+    &["Zoë Ångström-Łukasiewicz-Ødegård".to_string()],
+  );
+  iptc.set(dataset::KEYWORDS, &["one".to_string(), "two".to_string()]);
+
+  // Datasets are kept in order, after the coded character set and version
+  let numbers: Vec<(u8, u8)> = iptc.datasets.iter().map(|d| (d.record, d.number)).collect();
+  assert_eq!(
+    numbers,
+    [(1, 90), (2, 0), (2, 25), (2, 25), (2, 80), (2, 120)]
+  );
+  // The By-line is cut to 32 bytes without splitting a character
+  let by_line = &iptc.get(dataset::BY_LINE)[0];
+  assert!(by_line.len() <= 32);
+  assert!("Zoë Ångström-Łukasiewicz-Ødegård".starts_with(by_line.as_str()));
+
+  let parsed = IptcData::parse(&iptc.to_bytes().unwrap()).unwrap();
+  assert_eq!(parsed, iptc);
+
+  let resources = vec![
+    Resource {
+      id: IPTC_RESOURCE,
+      name: b"odd".to_vec(),
+      data: iptc.to_bytes().unwrap(),
+    },
+    Resource {
+      id: RESOLUTION_RESOURCE,
+      name: Vec::new(),
+      data: vec![1, 2, 3],
+    },
+  ];
+  let bytes = iptc::write_resources(&resources).unwrap();
+  assert_eq!(bytes.len() % 2, 0);
+  assert_eq!(iptc::parse_resources(&bytes).unwrap(), resources);
+
+  assert!(iptc::parse_resources(b"8BIM\x04\x04\0\0\0\0\0\x10abc").is_err());
+}
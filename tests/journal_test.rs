@@ -0,0 +1,107 @@
+//! Tests for the backup journal snapshots and their verification.
+
+use ifex::journal::{BackupMode, FileState, Journal, Snapshot};
+use std::fs;
+use tempfile::TempDir;
+
+/// A file with metadata edited in place and new data appended, as TIFF writes do.
+fn edit_in_place(original: &[u8]) -> Vec<u8> {
+  let mut modified = original.to_vec();
+  modified[100..104].copy_from_slice(b"EDIT");
+  modified[5000] ^= 0xFF;
+  modified.extend_from_slice(&[0xAB; 300]);
+  modified
+}
+
+#[test]
+fn test_metadata_backups_store_only_changed_bytes() {
+  let temp_dir = TempDir::new().unwrap();
+  let root = temp_dir.path().join("journal");
+  let photo = temp_dir.path().join("scan.tif");
+  let original: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+  fs::write(&photo, &original).unwrap();
+
+  let mut journal = Journal::start(&root, "apply", BackupMode::Metadata).unwrap();
+  journal.capture(&photo).unwrap();
+  fs::write(&photo, edit_in_place(&original)).unwrap();
+  let run_id = journal.finish().unwrap().unwrap();
+
+  let history = Journal::history(&root).unwrap();
+  assert_eq!(history.len(), 1);
+  let file = &history[0].files[0];
+  assert_eq!(file.before, Some(FileState::of(&original)));
+  let name = match &file.snapshot {
+    Some(Snapshot::Patch(name)) => Some(name),
+    _ => None,
+  }
+  .unwrap();
+  assert!(fs::metadata(root.join(&run_id).join(name)).unwrap().len() < 500);
+
+  let report = Journal::undo(&root, None).unwrap();
+  assert_eq!(report.restored.len(), 1);
+  assert!(report.failed.is_empty());
+  assert_eq!(fs::read(&photo).unwrap(), original);
+  assert_eq!(Journal::history(&root).unwrap()[0].status(), "undone");
+}
+
+#[test]
+fn test_full_backups_and_unchanged_runs() {
+  let temp_dir = TempDir::new().unwrap();
+  let root = temp_dir.path().join("journal");
+  let photo = temp_dir.path().join("scan.tif");
+  let original = vec![7u8; 2048];
+  fs::write(&photo, &original).unwrap();
+
+  // A run that changes nothing leaves no trace
+  let mut journal = Journal::start(&root, "erase", BackupMode::Full).unwrap();
+  journal.capture(&photo).unwrap();
+  assert_eq!(journal.finish().unwrap(), None);
+  assert!(Journal::history(&root).unwrap().is_empty());
+
+  let mut journal = Journal::start(&root, "erase", BackupMode::Full).unwrap();
+  journal.capture(&photo).unwrap();
+  fs::write(&photo, b"truncated").unwrap();
+  let run_id = journal.finish().unwrap().unwrap();
+  assert!(matches!(
+    Journal::history(&root).unwrap()[0].files[0].snapshot,
+    Some(Snapshot::Full(_))
+  ));
+
+  let report = Journal::undo(&root, Some(&run_id)).unwrap();
+  assert_eq!(report.run_id, run_id);
+  assert_eq!(fs::read(&photo).unwrap(), original);
+  assert!(Journal::undo(&root, Some("missing")).is_err());
+}
+
+#[test]
+fn test_each_write_is_recorded_before_the_run_finishes() {
+  let temp_dir = TempDir::new().unwrap();
+  let root = temp_dir.path().join("journal");
+  let photo = temp_dir.path().join("scan.tif");
+  let original: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+  fs::write(&photo, &original).unwrap();
+
+  let mut journal = Journal::start(&root, "apply", BackupMode::Metadata).unwrap();
+  journal.capture(&photo).unwrap();
+  let dated = edit_in_place(&original);
+  fs::write(&photo, &dated).unwrap();
+  journal.record().unwrap();
+
+  // An interrupted run can already be undone up to its last write
+  let history = Journal::history(&root).unwrap();
+  assert_eq!(history.len(), 1);
+  assert_eq!(history[0].files.len(), 1);
+
+  // A second write to the same file gets a record of its own
+  journal.capture(&photo).unwrap();
+  fs::write(&photo, b"rewritten").unwrap();
+  journal.record().unwrap();
+  let run_id = journal.finish().unwrap().unwrap();
+  let files = &Journal::history(&root).unwrap()[0].files;
+  assert_eq!(files.len(), 2);
+  assert_eq!(files[1].before, Some(FileState::of(&dated)));
+
+  let report = Journal::undo(&root, Some(&run_id)).unwrap();
+  assert_eq!(report.restored.len(), 2);
+  assert_eq!(fs::read(&photo).unwrap(), original);
+}
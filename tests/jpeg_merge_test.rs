@@ -0,0 +1,234 @@
+//! Tests for merging IFEX fields into existing JPEG EXIF data.
+
+use exif::{In, Reader, Tag};
+use ifex::exif::ifd::tag;
+use ifex::exif::processors::JpegProcessor;
+use ifex::exif::{ByteOrder, ExifValue, Ifd, IfdTree};
+use ifex::models::*;
+use std::fs;
+use std::io::Cursor;
+use tempfile::TempDir;
+
+const THUMBNAIL: &[u8] = b"\xff\xd8thumbnail-bytes\xff\xd9";
+
+fn create_test_selection() -> Selection {
+  let camera = Camera::new("Nikon".to_string(), "FM2".to_string());
+  let lens = Lens::new(
+    "Nikon".to_string(),
+    "Nikkor".to_string(),
+    "50".to_string(),
+    "1.4".to_string(),
+    "F".to_string(),
+  );
+  let film = Film::new("Kodak".to_string(), "Portra 400".to_string(), 400);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Test Setup".to_string(), camera.id, Some(lens.id));
+
+  Selection::new(setup, camera, Some(lens), film, photographer)
+}
+
+/// Builds the EXIF payload a scanner might write: orientation, software,
+/// resolution, a capture date, GPS coordinates and a JPEG thumbnail.
+fn scanner_exif(byte_order: ByteOrder) -> Vec<u8> {
+  let mut tree = IfdTree::with_byte_order(byte_order);
+  tree.ifd0.set(tag::MAKE, ExifValue::ascii("Scanner Inc."));
+  tree.ifd0.set(tag::ORIENTATION, ExifValue::Short(vec![6]));
+  tree
+    .ifd0
+    .set(tag::SOFTWARE, ExifValue::ascii("ScanApp 2.1"));
+  tree.ifd0.set(0x011A, ExifValue::Rational(vec![(3200, 1)]));
+  tree.exif_mut().set(
+    tag::DATE_TIME_ORIGINAL,
+    ExifValue::ascii("1998:07:14 16:45:00"),
+  );
+  tree.gps_mut().set(0x0001, ExifValue::ascii("N"));
+  tree.gps_mut().set(
+    0x0002,
+    ExifValue::Rational(vec![(52, 1), (22, 1), (1234, 100)]),
+  );
+
+  let mut ifd1 = Ifd::new();
+  ifd1.set(0x0103, ExifValue::Short(vec![6]));
+  ifd1.set(tag::JPEG_INTERCHANGE_FORMAT, ExifValue::Long(vec![0]));
+  ifd1.set(
+    tag::JPEG_INTERCHANGE_FORMAT_LENGTH,
+    ExifValue::Long(vec![0]),
+  );
+  tree.ifd1 = Some(ifd1);
+  tree.thumbnail = Some(THUMBNAIL.to_vec());
+
+  let mut payload = b"Exif\0\0".to_vec();
+  payload.extend_from_slice(&tree.to_bytes());
+  payload
+}
+
+fn jpeg_with_app1(app1_payload: Option<&[u8]>) -> Vec<u8> {
+  let mut data = vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+    0x00, 0x00, // APP0
+  ];
+  if let Some(payload) = app1_payload {
+    let length = u16::try_from(payload.len() + 2).unwrap_or(u16::MAX);
+    data.extend_from_slice(&[0xFF, 0xE1]);
+    data.extend_from_slice(&length.to_be_bytes());
+    data.extend_from_slice(payload);
+  }
+  data.extend_from_slice(&[
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]);
+  data
+}
+
+fn count_exif_segments(data: &[u8]) -> usize {
+  data
+    .windows(10)
+    .filter(|window| window[0..2] == [0xFF, 0xE1] && &window[4..10] == b"Exif\0\0")
+    .count()
+}
+
+#[test]
+fn test_apply_preserves_orientation_gps_and_thumbnail() {
+  for byte_order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("scan.jpg");
+    fs::write(&test_file, jpeg_with_app1(Some(&scanner_exif(byte_order)))).unwrap();
+
+    JpegProcessor::apply_exif_with_iso(&test_file, &create_test_selection(), Some(1600)).unwrap();
+
+    let data = fs::read(&test_file).unwrap();
+    assert_eq!(&data[2..6], &[0xFF, 0xE0, 0x00, 0x10]);
+    assert_eq!(count_exif_segments(&data), 1);
+
+    let exif = Reader::new()
+      .read_from_container(&mut Cursor::new(&data))
+      .unwrap();
+    let field = |tag, ifd| exif.get_field(tag, ifd).unwrap();
+
+    assert_eq!(
+      field(Tag::Orientation, In::PRIMARY).value.get_uint(0),
+      Some(6)
+    );
+    assert_eq!(
+      field(Tag::Software, In::PRIMARY)
+        .display_value()
+        .to_string(),
+      "\"ScanApp 2.1\""
+    );
+    assert_eq!(
+      field(Tag::DateTimeOriginal, In::PRIMARY)
+        .display_value()
+        .to_string(),
+      "1998-07-14 16:45:00"
+    );
+    assert!(exif.get_field(Tag::XResolution, In::PRIMARY).is_some());
+    assert!(exif.get_field(Tag::GPSLatitudeRef, In::PRIMARY).is_some());
+    assert_eq!(
+      field(Tag::GPSLatitude, In::PRIMARY)
+        .display_value()
+        .to_string(),
+      "52 deg 22 min 12.34 sec"
+    );
+
+    assert_eq!(
+      field(Tag::Make, In::PRIMARY).display_value().to_string(),
+      "\"Nikon\""
+    );
+    assert_eq!(
+      field(Tag::PhotographicSensitivity, In::PRIMARY)
+        .value
+        .get_uint(0),
+      Some(1600)
+    );
+
+    let offset = field(Tag::JPEGInterchangeFormat, In::THUMBNAIL)
+      .value
+      .get_uint(0)
+      .unwrap() as usize;
+    let length = field(Tag::JPEGInterchangeFormatLength, In::THUMBNAIL)
+      .value
+      .get_uint(0)
+      .unwrap() as usize;
+    assert_eq!(&exif.buf()[offset..offset + length], THUMBNAIL);
+  }
+}
+
+#[test]
+fn test_reapplying_keeps_a_single_exif_segment() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_with_app1(None)).unwrap();
+
+  let mut selection = create_test_selection();
+  JpegProcessor::apply_exif(&test_file, &selection).unwrap();
+  selection.lens = None;
+  JpegProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  let data = fs::read(&test_file).unwrap();
+  assert_eq!(&data[2..6], &[0xFF, 0xE0, 0x00, 0x10]);
+  assert_eq!(count_exif_segments(&data), 1);
+  assert!(data.ends_with(&[0xD2, 0xCF, 0x20, 0xFF, 0xD9]));
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  assert!(exif_data
+    .iter()
+    .any(|(key, value)| key.starts_with("Make") && value.contains("Nikon")));
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| key.starts_with("Lens Model")));
+}
+
+#[test]
+fn test_oversized_thumbnail_is_dropped_to_fit_app1() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+
+  let mut tree = IfdTree::new();
+  tree.ifd0.set(tag::ORIENTATION, ExifValue::Short(vec![3]));
+  let mut ifd1 = Ifd::new();
+  ifd1.set(tag::JPEG_INTERCHANGE_FORMAT, ExifValue::Long(vec![0]));
+  ifd1.set(
+    tag::JPEG_INTERCHANGE_FORMAT_LENGTH,
+    ExifValue::Long(vec![0]),
+  );
+  tree.ifd1 = Some(ifd1);
+  tree.thumbnail = Some(vec![0xAB; 65_300]);
+  let payload = tree.to_app1_payload().unwrap();
+  fs::write(&test_file, jpeg_with_app1(Some(&payload))).unwrap();
+
+  JpegProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+
+  let data = fs::read(&test_file).unwrap();
+  let exif = Reader::new()
+    .read_from_container(&mut Cursor::new(&data))
+    .unwrap();
+  assert_eq!(
+    exif
+      .get_field(Tag::Orientation, In::PRIMARY)
+      .unwrap()
+      .value
+      .get_uint(0),
+    Some(3)
+  );
+  assert!(exif.get_field(Tag::Make, In::PRIMARY).is_some());
+  assert!(exif
+    .get_field(Tag::JPEGInterchangeFormat, In::THUMBNAIL)
+    .is_none());
+}
+
+#[test]
+fn test_exif_larger_than_app1_is_rejected() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  let original = jpeg_with_app1(None);
+  fs::write(&test_file, &original).unwrap();
+
+  let mut selection = create_test_selection();
+  selection.photographer.name = "x".repeat(70_000);
+  let result = JpegProcessor::apply_exif(&test_file, &selection);
+
+  assert!(result.unwrap_err().to_string().contains("APP1"));
+  assert_eq!(fs::read(&test_file).unwrap(), original);
+}
@@ -0,0 +1,280 @@
+//! Tests for the XMP packet embedded in JPEG files.
+
+use ifex::exif::jpeg_xmp::{self, EXTENDED_XMP_HEADER, XMP_HEADER};
+use ifex::exif::processors::JpegProcessor;
+use ifex::exif::xmp::{prop, XmpDocument, XmpValue};
+use ifex::exif::TagGroup;
+use ifex::models::*;
+use std::fs;
+use tempfile::TempDir;
+
+const EDITOR_PACKET: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about=""
+        xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+        xmlns:dc="http://purl.org/dc/elements/1.1/"
+        xmlns:exif="http://ns.adobe.com/exif/1.0/"
+        xmp:Rating="4">
+      <dc:subject>
+        <rdf:Bag>
+          <rdf:li>street</rdf:li>
+          <rdf:li>night</rdf:li>
+        </rdf:Bag>
+      </dc:subject>
+      <exif:GPSLatitude>52,22.2057N</exif:GPSLatitude>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+fn create_test_selection() -> Selection {
+  let camera = Camera::new("Nikon".to_string(), "FM2".to_string());
+  let lens = Lens::new(
+    "Nikon".to_string(),
+    "Nikkor".to_string(),
+    "50".to_string(),
+    "1.4".to_string(),
+    "F".to_string(),
+  );
+  let film = Film::new("Kodak".to_string(), "Portra".to_string(), 400);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Test Setup".to_string(), camera.id, Some(lens.id));
+
+  Selection::new(setup, camera, Some(lens), film, photographer)
+}
+
+fn jpeg_with_xmp(packet: Option<&str>) -> Vec<u8> {
+  let mut data = vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48,
+    0x00, 0x00, // APP0
+  ];
+  if let Some(packet) = packet {
+    let payload = [XMP_HEADER, packet.as_bytes()].concat();
+    let length = u16::try_from(payload.len() + 2).unwrap_or(u16::MAX);
+    data.extend_from_slice(&[0xFF, 0xE1]);
+    data.extend_from_slice(&length.to_be_bytes());
+    data.extend_from_slice(&payload);
+  }
+  data.extend_from_slice(&[
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]);
+  data
+}
+
+/// Returns the payloads of all APP1 segments before the image data.
+fn app1_payloads(data: &[u8]) -> Vec<&[u8]> {
+  let mut payloads = Vec::new();
+  let mut i = 2;
+  while i + 4 <= data.len() && data[i] == 0xFF && data[i + 1] != 0xDA {
+    let end = i + 2 + usize::from(u16::from_be_bytes([data[i + 2], data[i + 3]]));
+    if data[i + 1] == 0xE1 {
+      payloads.push(&data[i + 4..end.min(data.len())]);
+    }
+    i = end;
+  }
+  payloads
+}
+
+fn read_packet(data: &[u8]) -> Option<XmpDocument> {
+  jpeg_xmp::read_packet(app1_payloads(data)).ok().flatten()
+}
+
+fn value(exif_data: &[(String, String)], tag: &str) -> Option<String> {
+  exif_data
+    .iter()
+    .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+    .map(|(_, value)| value.clone())
+}
+
+#[test]
+fn test_apply_writes_xmp_and_keeps_editor_properties() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_with_xmp(Some(EDITOR_PACKET))).unwrap();
+
+  JpegProcessor::apply_exif_with_iso(&test_file, &create_test_selection(), Some(800)).unwrap();
+  JpegProcessor::set_creation_date(&test_file, "2024:03:01 12:00:00").unwrap();
+
+  let data = fs::read(&test_file).unwrap();
+  let payloads = app1_payloads(&data);
+  // The EXIF segment comes first, followed by a single XMP packet
+  assert_eq!(payloads.len(), 2);
+  assert!(payloads[0].starts_with(b"Exif\0\0"));
+  assert!(payloads[1].starts_with(XMP_HEADER));
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  assert_eq!(value(&exif_data, "Make").as_deref(), Some("Nikon"));
+  assert_eq!(value(&exif_data, "Make (XMP)").as_deref(), Some("Nikon"));
+  assert_eq!(
+    value(&exif_data, "Film (XMP)").as_deref(),
+    Some("Kodak Portra (ISO 400)")
+  );
+  assert_eq!(value(&exif_data, "ISO Speed (XMP)").as_deref(), Some("800"));
+  assert_eq!(
+    value(&exif_data, "Date/Time Original (XMP)").as_deref(),
+    Some("2024:03:01 12:00:00")
+  );
+  assert_eq!(value(&exif_data, "xmp:Rating (XMP)").as_deref(), Some("4"));
+  assert_eq!(
+    value(&exif_data, "dc:subject (XMP)").as_deref(),
+    Some("street, night")
+  );
+
+  // Applying again updates the packet instead of adding another one
+  JpegProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+  let data = fs::read(&test_file).unwrap();
+  assert_eq!(app1_payloads(&data).len(), 2);
+  let xmp = read_packet(&data).unwrap();
+  assert_eq!(
+    xmp.get(prop::ISO_SPEED_RATINGS),
+    Some(XmpValue::Seq(vec!["400".to_string()]))
+  );
+  assert_eq!(
+    xmp.get(prop::DATE_CREATED),
+    Some(XmpValue::Text("2024-03-01T12:00:00".to_string()))
+  );
+}
+
+#[test]
+fn test_erase_keeps_unrelated_xmp_properties() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_with_xmp(Some(EDITOR_PACKET))).unwrap();
+  JpegProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+  JpegProcessor::set_creation_date(&test_file, "2024:03:01 12:00:00").unwrap();
+
+  JpegProcessor::erase_exif_groups(&test_file, &[TagGroup::Gps, TagGroup::Dates]).unwrap();
+  let xmp = read_packet(&fs::read(&test_file).unwrap()).unwrap();
+  assert_eq!(xmp.get(prop::DATE_CREATED), None);
+  assert_eq!(xmp.get(prop::DATE_TIME_ORIGINAL), None);
+  assert!(!xmp
+    .properties()
+    .iter()
+    .any(|entry| entry.local_name() == "GPSLatitude"));
+  assert!(xmp.get(prop::FILM).is_some());
+
+  JpegProcessor::erase_exif(&test_file).unwrap();
+  let data = fs::read(&test_file).unwrap();
+  let payloads = app1_payloads(&data);
+  assert_eq!(payloads.len(), 1);
+  assert!(payloads[0].starts_with(XMP_HEADER));
+  let xmp = read_packet(&data).unwrap();
+  assert_eq!(xmp.get(prop::MAKE), None);
+  assert_eq!(xmp.get(prop::FILM), None);
+  assert_eq!(xmp.get(prop::CREATOR), None);
+  let names: Vec<String> = xmp
+    .properties()
+    .into_iter()
+    .map(|entry| entry.qualified_name)
+    .collect();
+  assert_eq!(names, ["xmp:Rating", "dc:subject"]);
+}
+
+#[test]
+fn test_erase_drops_packet_left_empty() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_with_xmp(None)).unwrap();
+  JpegProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+  let data = fs::read(&test_file).unwrap();
+  let payloads = app1_payloads(&data);
+  assert_eq!(payloads.len(), 2);
+  // New packets get the wrapper the XMP specification recommends
+  assert!(
+    payloads[1].starts_with(&[XMP_HEADER, b"<?xpacket begin=\"\xef\xbb\xbf\"".as_slice()].concat())
+  );
+
+  JpegProcessor::erase_exif(&test_file).unwrap();
+  assert_eq!(fs::read(&test_file).unwrap(), jpeg_with_xmp(None));
+}
+
+#[test]
+fn test_large_packet_is_split_into_extended_xmp() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  let history = "Edited with many steps. ".repeat(6000);
+  let packet = EDITOR_PACKET.replace(
+    "      <exif:GPSLatitude>",
+    &format!(
+      "      <photoshop:History xmlns:photoshop=\"http://ns.adobe.com/photoshop/1.0/\">{history}</photoshop:History>\n      <exif:GPSLatitude>"
+    ),
+  );
+  let mut xmp = XmpDocument::parse(&packet).unwrap();
+  let mut data = jpeg_with_xmp(None);
+  let mut segments = Vec::new();
+  for payload in jpeg_xmp::to_payloads(&xmp).unwrap() {
+    segments.extend_from_slice(&[0xFF, 0xE1]);
+    segments.extend_from_slice(&u16::try_from(payload.len() + 2).unwrap().to_be_bytes());
+    segments.extend_from_slice(&payload);
+  }
+  data.splice(20..20, segments);
+  fs::write(&test_file, &data).unwrap();
+
+  JpegProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+  let data = fs::read(&test_file).unwrap();
+  let payloads = app1_payloads(&data);
+  assert!(payloads.len() > 3);
+  assert!(payloads[1].starts_with(XMP_HEADER));
+  assert!(payloads[2..]
+    .iter()
+    .all(|payload| payload.starts_with(EXTENDED_XMP_HEADER)));
+
+  // The standard packet keeps the small properties and names the extension
+  let standard =
+    XmpDocument::parse(std::str::from_utf8(&payloads[1][XMP_HEADER.len()..]).unwrap()).unwrap();
+  let guid = match standard.get(prop::HAS_EXTENDED_XMP) {
+    Some(XmpValue::Text(guid)) => guid,
+    _ => String::new(),
+  };
+  assert_eq!(guid.len(), 32);
+  assert!(payloads[2..].iter().all(|payload| {
+    payload[EXTENDED_XMP_HEADER.len()..EXTENDED_XMP_HEADER.len() + 32] == *guid.as_bytes()
+  }));
+  assert!(standard.get(prop::FILM).is_some());
+  assert!(standard.get(prop::MAKE).is_some());
+
+  // Reading merges both parts back together
+  xmp = read_packet(&data).unwrap();
+  assert_eq!(xmp.get(prop::HAS_EXTENDED_XMP), None);
+  assert!(xmp.get(prop::FILM).is_some());
+  let history_entry = xmp
+    .properties()
+    .into_iter()
+    .find(|entry| entry.qualified_name == "photoshop:History")
+    .unwrap();
+  assert_eq!(history_entry.value, XmpValue::Text(history));
+  assert_eq!(
+    xmp
+      .properties()
+      .iter()
+      .filter(|entry| entry.local_name() == "Rating")
+      .count(),
+    1
+  );
+
+  // Erasing everything leaves the editor's properties in the packet
+  JpegProcessor::erase_exif(&test_file).unwrap();
+  let xmp = read_packet(&fs::read(&test_file).unwrap()).unwrap();
+  assert_eq!(xmp.get(prop::FILM), None);
+  assert_eq!(xmp.properties().len(), 3);
+}
+
+#[test]
+fn test_incomplete_extended_xmp_is_reported() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  let packet = EDITOR_PACKET.replace(
+    "xmp:Rating=\"4\">",
+    "xmp:Rating=\"4\"\n        xmlns:xmpNote=\"http://ns.adobe.com/xmp/note/\"\n        xmpNote:HasExtendedXMP=\"0123456789ABCDEF0123456789ABCDEF\">",
+  );
+  let original = jpeg_with_xmp(Some(&packet));
+  fs::write(&test_file, &original).unwrap();
+
+  let result = JpegProcessor::apply_exif(&test_file, &create_test_selection());
+  assert!(result.is_err());
+  assert_eq!(fs::read(&test_file).unwrap(), original);
+}
@@ -127,3 +127,175 @@ fn test_setup_display_name() {
   let setup = Setup::new("My Setup".to_string(), camera_id, Some(lens_id));
   assert_eq!(setup.display_name(), "My Setup");
 }
+
+#[test]
+fn test_photographer_copyright_notice() {
+  let mut photographer = Photographer::new("John Doe".to_string(), None);
+  assert_eq!(photographer.copyright_notice(1998), "© 1998 John Doe");
+
+  photographer.rights.copyright_template = Some("Copyright {name}, {year}".to_string());
+  assert_eq!(
+    photographer.copyright_notice(2024),
+    "Copyright John Doe, 2024"
+  );
+}
+
+#[test]
+fn test_rights_license_url() {
+  let rights = |license: &str| Rights {
+    license: Some(license.to_string()),
+    ..Rights::default()
+  };
+
+  assert_eq!(
+    rights("CC BY-NC-SA 4.0").license_url().as_deref(),
+    Some("https://creativecommons.org/licenses/by-nc-sa/4.0/")
+  );
+  assert_eq!(
+    rights("cc-by").license_url().as_deref(),
+    Some("https://creativecommons.org/licenses/by/4.0/")
+  );
+  assert_eq!(
+    rights("CC BY 2.0").license_url().as_deref(),
+    Some("https://creativecommons.org/licenses/by/2.0/")
+  );
+  assert_eq!(
+    rights("https://example.com/license")
+      .license_url()
+      .as_deref(),
+    Some("https://example.com/license")
+  );
+  assert!(rights("CC0").is_public_domain());
+  assert!(!rights("CC BY 4.0").is_public_domain());
+  assert!(!Rights::default().is_public_domain());
+
+  assert!(Rights::default().validate().is_ok());
+  assert!(rights("CC BY-XY").validate().is_err());
+  assert!(rights("All rights reserved").validate().is_err());
+}
+
+#[test]
+fn test_photographer_without_rights_deserializes() {
+  let photographer: Photographer = serde_json::from_str(
+    r#"{
+      "id": "6a2f41a3-c54c-4fd4-a3bb-2b9c8c0d1e4f",
+      "name": "Jane Doe",
+      "email": null,
+      "createdAt": "2024-01-01T00:00:00Z"
+    }"#,
+  )
+  .unwrap();
+
+  assert_eq!(photographer.rights, Rights::default());
+  assert!(photographer.contact.is_empty());
+  assert_eq!(photographer.copyright_notice(2024), "© 2024 Jane Doe");
+}
+
+#[test]
+fn test_exposure_parsing() {
+  assert_eq!(Exposure::parse_shutter_speed("1/125"), Ok((1, 125)));
+  assert_eq!(Exposure::parse_shutter_speed("2/250"), Ok((1, 125)));
+  assert_eq!(Exposure::parse_shutter_speed("4s"), Ok((4, 1)));
+  assert_eq!(Exposure::parse_shutter_speed("0.5"), Ok((1, 2)));
+  assert!(Exposure::parse_shutter_speed("0").is_err());
+  assert!(Exposure::parse_shutter_speed("1/0").is_err());
+  assert!(Exposure::parse_shutter_speed("fast").is_err());
+
+  assert_eq!(Exposure::parse_aperture("5.6"), Ok((28, 5)));
+  assert_eq!(Exposure::parse_aperture("f/8"), Ok((8, 1)));
+  assert_eq!(Exposure::parse_aperture("F2.8"), Ok((14, 5)));
+  assert!(Exposure::parse_aperture("f/").is_err());
+  assert!(Exposure::parse_aperture("-2").is_err());
+
+  assert_eq!(Exposure::parse_compensation("+1/3"), Ok((1, 3)));
+  assert_eq!(Exposure::parse_compensation("-2/3"), Ok((-2, 3)));
+  assert_eq!(Exposure::parse_compensation("-0.7"), Ok((-7, 10)));
+  assert_eq!(Exposure::parse_compensation("0").map(|(num, _)| num), Ok(0));
+  assert!(Exposure::parse_compensation("+-1").is_err());
+}
+
+#[test]
+fn test_exposure_display_and_fallback() {
+  let exposure = Exposure {
+    exposure_time: Some((1, 125)),
+    f_number: Some((28, 5)),
+    compensation: Some((-1, 3)),
+    metering_mode: Some(MeteringMode::CenterWeighted),
+    program: Some(ExposureProgram::Manual),
+  };
+  assert_eq!(
+    exposure.display_name(),
+    "1/125 s, f/5.6, -1/3 EV, center weighted metering, manual"
+  );
+  assert!(Exposure::default().is_empty());
+  assert_eq!(Exposure::default().display_name(), "");
+
+  let frame = Exposure {
+    exposure_time: Some((2, 1)),
+    ..Exposure::default()
+  };
+  let merged = frame.or(exposure);
+  assert_eq!(merged.exposure_time, Some((2, 1)));
+  assert_eq!(merged.f_number, Some((28, 5)));
+  assert_eq!(merged.display_name().split(", ").next(), Some("2 s"));
+}
+
+#[test]
+fn test_roll_dates_and_display_name() {
+  let mut roll = Roll::new(
+    "Lisbon 03".to_string(),
+    Uuid::new_v4(),
+    Uuid::new_v4(),
+    Uuid::new_v4(),
+  );
+  assert_eq!(roll.display_name(), "Lisbon 03");
+  assert_eq!(roll.identifier().len(), 32);
+  assert!(Uuid::parse_str(&roll.identifier()).is_ok_and(|id| id == roll.id));
+
+  roll.loaded_on = Roll::parse_date("2024-07-01").unwrap_or_default();
+  assert_eq!(roll.display_name(), "Lisbon 03 (loaded 2024-07-01)");
+  roll.finished_on = Roll::parse_date(" 2024-07-14 ").unwrap_or_default();
+  assert_eq!(roll.display_name(), "Lisbon 03 (2024-07-01 - 2024-07-14)");
+
+  assert_eq!(Roll::parse_date(""), Ok(None));
+  assert!(Roll::parse_date("14/07/2024").is_err());
+}
+
+#[test]
+fn test_development_parsing_and_push_pull() {
+  assert_eq!(Development::parse_time("9:30"), Ok(570));
+  assert_eq!(Development::parse_time("9m30s"), Ok(570));
+  assert_eq!(Development::parse_time("9m"), Ok(540));
+  assert_eq!(Development::parse_time("9.5"), Ok(570));
+  assert!(Development::parse_time("9:75").is_err());
+  assert!(Development::parse_time("0").is_err());
+  assert_eq!(Development::format_time(570), "9:30");
+
+  assert_eq!(Development::parse_temperature("20"), Ok(20.0));
+  assert_eq!(Development::parse_temperature("20.5 °C"), Ok(20.5));
+  assert!(Development::parse_temperature("hot").is_err());
+
+  assert_eq!(Development::push_pull_thirds(400, 400), 0);
+  assert_eq!(Development::push_pull_thirds(400, 1600), 6);
+  assert_eq!(Development::push_pull_thirds(400, 200), -3);
+  assert_eq!(Development::push_pull_thirds(100, 125), 1);
+  assert_eq!(Development::push_pull_thirds(400, 1000), 4);
+  assert_eq!(Development::format_stops(6), "+2");
+  assert_eq!(Development::format_stops(-2), "-2/3");
+  assert_eq!(Development::format_stops(4), "+1 1/3");
+  assert_eq!(Development::format_stops(0), "0");
+
+  let development = Development {
+    developer: Some("HC-110".to_string()),
+    dilution: Some("1+31".to_string()),
+    time: Some(570),
+    temperature: Some(20.0),
+    lab: Some("Carmencita".to_string()),
+    scanned_on: Roll::parse_date("2024-08-01").unwrap_or_default(),
+  };
+  assert_eq!(
+    development.display_name(),
+    "HC-110 1+31 9:30 at 20°C, lab Carmencita, scanned 2024-08-01"
+  );
+  assert!(Development::default().is_empty());
+}
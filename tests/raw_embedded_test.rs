@@ -0,0 +1,222 @@
+//! Tests for reading the EXIF metadata embedded in RAW camera files.
+
+use ifex::exif::ifd::tag;
+use ifex::exif::processors::RawProcessor;
+use ifex::exif::{ExifManager, ExifValue, IfdTree};
+use ifex::models::*;
+use std::fs;
+use tempfile::TempDir;
+
+/// User type of the Canon metadata box in CR3 files.
+const CANON_UUID: [u8; 16] = [
+  0x85, 0xc0, 0xb6, 0x87, 0x82, 0x0f, 0x11, 0xe0, 0x81, 0x11, 0xf4, 0xce, 0x46, 0x2b, 0x6a, 0x48,
+];
+
+fn camera_tree(make: &str, date: &str) -> IfdTree {
+  let mut tree = IfdTree::new();
+  tree.ifd0.set(tag::MAKE, ExifValue::ascii(make));
+  tree.ifd0.set(tag::MODEL, ExifValue::ascii("Digital Body"));
+  tree
+    .exif_mut()
+    .set(tag::DATE_TIME_ORIGINAL, ExifValue::ascii(date));
+  tree
+    .exif_mut()
+    .set(tag::ISO_SPEED_RATINGS, ExifValue::Short(vec![200]));
+  tree
+}
+
+fn iso_box(kind: [u8; 4], contents: &[u8]) -> Vec<u8> {
+  let mut data = u32::try_from(contents.len() + 8)
+    .unwrap_or(u32::MAX)
+    .to_be_bytes()
+    .to_vec();
+  data.extend_from_slice(&kind);
+  data.extend_from_slice(contents);
+  data
+}
+
+fn cr3_file() -> Vec<u8> {
+  let mut ifd0 = IfdTree::new();
+  ifd0.ifd0.set(tag::MAKE, ExifValue::ascii("Canon"));
+  ifd0.ifd0.set(tag::MODEL, ExifValue::ascii("Canon EOS R6"));
+  let mut exif = IfdTree::new();
+  exif.ifd0.set(
+    tag::DATE_TIME_ORIGINAL,
+    ExifValue::ascii("2024:06:01 09:30:00"),
+  );
+  exif
+    .ifd0
+    .set(tag::ISO_SPEED_RATINGS, ExifValue::Short(vec![800]));
+
+  let mut canon = CANON_UUID.to_vec();
+  canon.extend(iso_box(*b"CMT1", &ifd0.to_bytes()));
+  canon.extend(iso_box(*b"CMT2", &exif.to_bytes()));
+  let mut data = iso_box(*b"ftyp", b"crx \0\0\0\x01crx isom");
+  data.extend(iso_box(*b"moov", &iso_box(*b"uuid", &canon)));
+  data.extend(iso_box(*b"mdat", &[0; 16]));
+  data
+}
+
+fn raf_file() -> Vec<u8> {
+  let mut payload = b"Exif\0\0".to_vec();
+  payload.extend(camera_tree("FUJIFILM", "2024:07:14 18:45:00").to_bytes());
+  let mut preview = vec![0xFF, 0xD8, 0xFF, 0xE1];
+  preview.extend_from_slice(
+    &u16::try_from(payload.len() + 2)
+      .unwrap_or(u16::MAX)
+      .to_be_bytes(),
+  );
+  preview.extend_from_slice(&payload);
+  preview.extend_from_slice(&[0xFF, 0xD9]);
+
+  let mut data = b"FUJIFILMCCD-RAW 0201FF383501X-T5".to_vec();
+  data.resize(84, 0);
+  data.extend_from_slice(&100u32.to_be_bytes());
+  data.extend_from_slice(
+    &u32::try_from(preview.len())
+      .unwrap_or(u32::MAX)
+      .to_be_bytes(),
+  );
+  data.resize(100, 0);
+  data.extend_from_slice(&preview);
+  data
+}
+
+fn has_tag(exif_data: &[(String, String)], tag: &str, value: &str) -> bool {
+  exif_data
+    .iter()
+    .any(|(key, val)| (key == tag || key.starts_with(&format!("{tag} ["))) && val == value)
+}
+
+fn create_test_selection() -> Selection {
+  let camera = Camera::new("Nikon".to_string(), "FM2".to_string());
+  let film = Film::new("Kodak".to_string(), "Portra".to_string(), 400);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Camera only".to_string(), camera.id, None);
+
+  Selection::new(setup, camera, None, film, photographer)
+}
+
+#[test]
+fn test_read_tiff_based_raw_merges_sidecar() {
+  let temp_dir = TempDir::new().unwrap();
+  let raw = temp_dir.path().join("DSC_0001.NEF");
+  fs::write(
+    &raw,
+    camera_tree("NIKON CORPORATION", "2024:05:01 10:00:00").to_bytes(),
+  )
+  .unwrap();
+
+  let exif_data = RawProcessor::read_exif(&raw).unwrap();
+  assert!(has_tag(&exif_data, "Make (Embedded)", "NIKON CORPORATION"));
+  assert!(has_tag(
+    &exif_data,
+    "Date/Time Original (Embedded)",
+    "2024:05:01 10:00:00"
+  ));
+  assert!(has_tag(&exif_data, "ISO Speed (Embedded)", "200"));
+
+  // Sidecar values keep the plain names next to the embedded ones
+  RawProcessor::apply_exif_with_iso(&raw, &create_test_selection(), None).unwrap();
+  let exif_data = ExifManager::read_exif_data(&raw).unwrap();
+  assert!(has_tag(&exif_data, "Make", "Nikon"));
+  assert!(has_tag(&exif_data, "Make (Embedded)", "NIKON CORPORATION"));
+}
+
+#[test]
+fn test_read_raw_variants() {
+  let temp_dir = TempDir::new().unwrap();
+
+  // Olympus ORF uses its own TIFF magic number
+  let mut orf = camera_tree("OLYMPUS", "2024:05:02 11:00:00").to_bytes();
+  orf[2..4].copy_from_slice(b"RO");
+  let orf_path = temp_dir.path().join("P5020001.ORF");
+  fs::write(&orf_path, orf).unwrap();
+  let exif_data = RawProcessor::read_exif(&orf_path).unwrap();
+  assert!(has_tag(&exif_data, "Make (Embedded)", "OLYMPUS"));
+
+  let cr3_path = temp_dir.path().join("IMG_0001.CR3");
+  fs::write(&cr3_path, cr3_file()).unwrap();
+  let exif_data = RawProcessor::read_exif(&cr3_path).unwrap();
+  assert!(has_tag(&exif_data, "Model (Embedded)", "Canon EOS R6"));
+  assert!(has_tag(
+    &exif_data,
+    "Date/Time Original (Embedded)",
+    "2024:06:01 09:30:00"
+  ));
+  assert!(has_tag(&exif_data, "ISO Speed (Embedded)", "800"));
+
+  let raf_path = temp_dir.path().join("DSCF0001.RAF");
+  fs::write(&raf_path, raf_file()).unwrap();
+  let exif_data = RawProcessor::read_exif(&raf_path).unwrap();
+  assert!(has_tag(&exif_data, "Make (Embedded)", "FUJIFILM"));
+  assert!(has_tag(
+    &exif_data,
+    "Date/Time Original (Embedded)",
+    "2024:07:14 18:45:00"
+  ));
+
+  // Containers without a known layout only report their sidecar
+  let x3f_path = temp_dir.path().join("SDIM0001.X3F");
+  fs::write(&x3f_path, b"FOVb unknown").unwrap();
+  assert!(RawProcessor::read_exif(&x3f_path).unwrap().is_empty());
+}
+
+#[test]
+fn test_damaged_raw_reads_from_its_sidecar() {
+  let temp_dir = TempDir::new().unwrap();
+  let raf_path = temp_dir.path().join("DSCF0002.RAF");
+  let mut raf = raf_file();
+  raf.truncate(120);
+  fs::write(&raf_path, raf).unwrap();
+
+  // Without a sidecar there is nothing else to read
+  assert!(RawProcessor::read_exif(&raf_path).is_err());
+
+  RawProcessor::apply_exif_with_iso(&raf_path, &create_test_selection(), None).unwrap();
+  let exif_data = ExifManager::read_exif_data(&raf_path).unwrap();
+  assert!(has_tag(&exif_data, "Make", "Nikon"));
+  assert!(exif_data
+    .iter()
+    .any(|(key, value)| key == "Warning (Embedded)" && value.contains("DSCF0002.RAF")));
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| key.ends_with("(Embedded)") && key.starts_with("Make")));
+}
+
+#[test]
+fn test_one_sec_uses_embedded_dates() {
+  let temp_dir = TempDir::new().unwrap();
+  let mut files = Vec::new();
+  for name in ["a.nef", "b.nef"] {
+    let raw = temp_dir.path().join(name);
+    fs::write(
+      &raw,
+      camera_tree("NIKON CORPORATION", "2024:05:01 10:00:00").to_bytes(),
+    )
+    .unwrap();
+    files.push(raw);
+  }
+
+  let result = ExifManager::new().process_selected_files_with_one_sec(
+    &files,
+    Some(&create_test_selection()),
+    "apply",
+    None,
+    true,
+  );
+  assert!(result.success);
+
+  // The new date goes to the sidecar, the RAW file is left untouched
+  let second = RawProcessor::read_exif(&files[1]).unwrap();
+  assert!(has_tag(
+    &second,
+    "Date/Time Original",
+    "2024:05:01 10:00:01"
+  ));
+  assert!(has_tag(
+    &second,
+    "Date/Time Original (Embedded)",
+    "2024:05:01 10:00:00"
+  ));
+}
@@ -0,0 +1,114 @@
+//! Tests for writing photographers' copyright and licensing terms.
+
+use chrono::{Datelike, Local};
+use ifex::exif::ifd::tag;
+use ifex::exif::processors::JpegProcessor;
+use ifex::exif::{ExifValue, IfdTree};
+use ifex::models::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn create_test_selection(rights: Rights) -> Selection {
+  let camera = Camera::new("Nikon".to_string(), "FM2".to_string());
+  let film = Film::new("Kodak".to_string(), "Portra".to_string(), 400);
+  let mut photographer = Photographer::new("Test User".to_string(), None);
+  photographer.rights = rights;
+  let setup = Setup::new("Camera only".to_string(), camera.id, None);
+
+  Selection::new(setup, camera, None, film, photographer)
+}
+
+/// Builds a JPEG whose EXIF segment records the given capture date.
+fn jpeg_captured_on(capture_date: &str) -> Vec<u8> {
+  let mut tree = IfdTree::new();
+  tree
+    .exif_mut()
+    .set(tag::DATE_TIME_ORIGINAL, ExifValue::ascii(capture_date));
+  let payload = tree.to_app1_payload().unwrap_or_default();
+
+  let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+  data.extend_from_slice(&u16::try_from(payload.len() + 2).unwrap_or(0).to_be_bytes());
+  data.extend_from_slice(&payload);
+  data.extend_from_slice(&[
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // scan data
+    0xFF, 0xD9, // EOI
+  ]);
+  data
+}
+
+fn value(exif_data: &[(String, String)], tag: &str) -> Option<String> {
+  exif_data
+    .iter()
+    .find(|(key, _)| {
+      key == tag
+        || key
+          .strip_prefix(tag)
+          .is_some_and(|rest| rest.starts_with(" ["))
+    })
+    .map(|(_, value)| value.clone())
+}
+
+#[test]
+fn test_apply_writes_rights_for_capture_year() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  fs::write(&test_file, jpeg_captured_on("2019:06:01 10:00:00")).unwrap();
+
+  let selection = create_test_selection(Rights {
+    copyright_template: None,
+    license: Some("CC BY-SA 4.0".to_string()),
+    usage_terms: Some("Credit the photographer".to_string()),
+  });
+  JpegProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  for tag in ["Copyright", "Copyright (XMP)", "IPTC: Copyright Notice"] {
+    assert_eq!(
+      value(&exif_data, tag).as_deref(),
+      Some("© 2019 Test User"),
+      "{tag}"
+    );
+  }
+  assert_eq!(
+    value(&exif_data, "xmpRights:Marked (XMP)").as_deref(),
+    Some("True")
+  );
+  assert_eq!(
+    value(&exif_data, "xmpRights:WebStatement (XMP)").as_deref(),
+    Some("https://creativecommons.org/licenses/by-sa/4.0/")
+  );
+  assert_eq!(
+    value(&exif_data, "xmpRights:UsageTerms (XMP)").as_deref(),
+    Some("Credit the photographer")
+  );
+}
+
+#[test]
+fn test_apply_public_domain_with_custom_template() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.jpg");
+  // Without a capture date the current year is used
+  fs::write(&test_file, jpeg_captured_on("    :  :     :  :  ")).unwrap();
+
+  let selection = create_test_selection(Rights {
+    copyright_template: Some("{name}, {year}. No rights reserved.".to_string()),
+    license: Some("CC0".to_string()),
+    usage_terms: None,
+  });
+  JpegProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  let exif_data = JpegProcessor::read_exif(&test_file).unwrap();
+  let notice = format!("Test User, {}. No rights reserved.", Local::now().year());
+  assert_eq!(value(&exif_data, "Copyright"), Some(notice.clone()));
+  assert_eq!(value(&exif_data, "Copyright (XMP)"), Some(notice));
+  assert_eq!(
+    value(&exif_data, "xmpRights:Marked (XMP)").as_deref(),
+    Some("False")
+  );
+  assert_eq!(
+    value(&exif_data, "xmpRights:WebStatement (XMP)").as_deref(),
+    Some("https://creativecommons.org/publicdomain/zero/1.0/")
+  );
+  assert_eq!(value(&exif_data, "xmpRights:UsageTerms (XMP)"), None);
+}
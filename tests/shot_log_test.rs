@@ -0,0 +1,203 @@
+//! Tests for shot log parsing, frame matching and per-frame values.
+
+use ifex::models::{Camera, Exposure, Film, Frame, Lens, Photographer, Selection, Setup};
+use ifex::shot_log::{LoggedFrame, ShotLog};
+use regex::Regex;
+use std::path::PathBuf;
+
+fn frame_numbers(log: &ShotLog) -> Vec<u32> {
+  log.frames.iter().map(|frame| frame.number).collect()
+}
+
+fn create_selection() -> Selection {
+  let camera = Camera::new("Nikon".to_string(), "FM2".to_string());
+  let lens = Lens::new(
+    "Nikon".to_string(),
+    "Nikkor".to_string(),
+    "50".to_string(),
+    "1.4".to_string(),
+    "F".to_string(),
+  );
+  Selection {
+    caption: Some("Lisbon".to_string()),
+    exposure: Exposure {
+      f_number: Some((8, 1)),
+      compensation: Some((0, 1)),
+      ..Exposure::default()
+    },
+    ..Selection::new(
+      Setup::new("FM2".to_string(), camera.id, Some(lens.id)),
+      camera,
+      Some(lens),
+      Film::new("Kodak".to_string(), "Portra 400".to_string(), 400),
+      Photographer::new("Test User".to_string(), None),
+    )
+  }
+}
+
+#[test]
+fn test_csv_shot_log() {
+  let log = ShotLog::from_csv(
+    "Frame No, Shutter Speed, f-stop, EV, Focal_Length, Lens, Notes, Place, Film\n\
+     2, 1/60, 2.8, , 35mm, , , , Portra\n\
+     1, 1/250, f/8, -1/3, , Nikkor,\"Tram 28, Alfama\", Miradouro, Portra\n",
+  )
+  .unwrap();
+
+  assert_eq!(frame_numbers(&log), [1, 2]);
+  assert_eq!(
+    log.frames[0],
+    LoggedFrame {
+      number: 1,
+      exposure: Exposure {
+        exposure_time: Some((1, 250)),
+        f_number: Some((8, 1)),
+        compensation: Some((-1, 3)),
+        ..Exposure::default()
+      },
+      focal_length: None,
+      lens: Some("Nikkor".to_string()),
+      notes: Some("Tram 28, Alfama".to_string()),
+      sublocation: Some("Miradouro".to_string()),
+    }
+  );
+  assert_eq!(log.frames[1].exposure.f_number, Some((14, 5)));
+  assert_eq!(log.frames[1].exposure.compensation, None);
+  assert_eq!(log.frames[1].focal_length, Some((35, 1)));
+  assert_eq!(log.frames[1].notes, None);
+}
+
+#[test]
+fn test_json_shot_log() {
+  let array = ShotLog::from_json(
+    r#"[{"frame": 3, "shutter": "1/125", "aperture": 5.6, "notes": null},
+        {"frame": "4", "focalLength": 90, "sublocation": "Miradouro"}]"#,
+  )
+  .unwrap();
+  assert_eq!(frame_numbers(&array), [3, 4]);
+  assert_eq!(array.frames[0].exposure.f_number, Some((28, 5)));
+  assert_eq!(array.frames[0].notes, None);
+  assert_eq!(array.frames[1].focal_length, Some((90, 1)));
+  assert_eq!(array.frames[1].sublocation.as_deref(), Some("Miradouro"));
+
+  let object = ShotLog::from_json(r#"{"roll": "Alfama", "frames": [{"frame": 1}]}"#).unwrap();
+  assert_eq!(frame_numbers(&object), [1]);
+}
+
+#[test]
+fn test_invalid_shot_logs() {
+  let error = |result: Result<ShotLog, Box<dyn std::error::Error>>| {
+    result.err().map(|e| e.to_string()).unwrap_or_default()
+  };
+
+  let missing = error(ShotLog::from_csv("Frame,Shutter\n1,1/60\n,1/125\n"));
+  assert!(
+    missing.contains("line 3") && missing.contains("Missing frame number"),
+    "{missing}"
+  );
+  let invalid = error(ShotLog::from_csv("Frame,Shutter\n1,fast\n"));
+  assert!(invalid.contains("Invalid shutter speed"), "{invalid}");
+  let duplicate = error(ShotLog::from_csv("Frame\n7\n7\n"));
+  assert!(duplicate.contains("frame 7 more than once"), "{duplicate}");
+  let entry = error(ShotLog::from_json(r#"[{"frame": 1}, {"aperture": 2}]"#));
+  assert!(entry.contains("entry 2"), "{entry}");
+  assert!(!error(ShotLog::from_json(r#"{"rolls": []}"#)).is_empty());
+}
+
+#[test]
+fn test_match_files_by_order() {
+  let log = ShotLog::from_csv("Frame\n1\n2\n3\n").unwrap();
+  let files = [PathBuf::from("scans/b.jpg"), PathBuf::from("scans/a.jpg")];
+
+  let matches = log.match_files(&files, None);
+  let pairs: Vec<(PathBuf, u32)> = matches
+    .matched
+    .iter()
+    .map(|(path, frame)| (path.clone(), frame.number))
+    .collect();
+  assert_eq!(
+    pairs,
+    [
+      (PathBuf::from("scans/a.jpg"), 1),
+      (PathBuf::from("scans/b.jpg"), 2)
+    ]
+  );
+  assert_eq!(matches.frames_without_files, [3]);
+  assert!(matches.files_without_frames.is_empty());
+  assert_eq!(
+    matches.warnings(),
+    ["Logged frames without a file: 3".to_string()]
+  );
+}
+
+#[test]
+fn test_match_files_by_pattern() {
+  let log = ShotLog::from_csv("Frame\n1\n2\n12\n").unwrap();
+  let files = [
+    PathBuf::from("roll_01.jpg"),
+    PathBuf::from("roll_01.tif"),
+    PathBuf::from("roll_12.jpg"),
+    PathBuf::from("roll_20.jpg"),
+    PathBuf::from("contact.jpg"),
+  ];
+
+  for pattern in [r"roll_(\d+)", r"(?P<roll>roll)_(?P<frame>\d+)"] {
+    let pattern = Regex::new(pattern).unwrap();
+    let matches = log.match_files(&files, Some(&pattern));
+    let pairs: Vec<(String, u32)> = matches
+      .matched
+      .iter()
+      .map(|(path, frame)| (path.display().to_string(), frame.number))
+      .collect();
+    assert_eq!(
+      pairs,
+      [
+        ("roll_01.jpg".to_string(), 1),
+        ("roll_01.tif".to_string(), 1),
+        ("roll_12.jpg".to_string(), 12)
+      ]
+    );
+    assert_eq!(matches.frames_without_files, [2]);
+    assert_eq!(
+      matches.files_without_frames,
+      [PathBuf::from("contact.jpg"), PathBuf::from("roll_20.jpg")]
+    );
+  }
+}
+
+#[test]
+fn test_frame_applies_on_top_of_selection() {
+  let selection = create_selection();
+  let lens = Lens::new(
+    "Nikon".to_string(),
+    "Zoom-Nikkor".to_string(),
+    "35-70".to_string(),
+    "3.5".to_string(),
+    "F".to_string(),
+  );
+  let frame = Frame {
+    exposure: Exposure {
+      exposure_time: Some((1, 500)),
+      f_number: Some((11, 1)),
+      ..Exposure::default()
+    },
+    lens: Some(lens.clone()),
+    focal_length: Frame::parse_focal_length("35 mm").ok(),
+    notes: Some("Tram 28".to_string()),
+    sublocation: Some("Alfama".to_string()),
+  };
+
+  let applied = frame.apply_to(&selection);
+  assert_eq!(applied.exposure.exposure_time, Some((1, 500)));
+  assert_eq!(applied.exposure.f_number, Some((11, 1)));
+  assert_eq!(applied.exposure.compensation, Some((0, 1)));
+  assert_eq!(applied.lens.map(|lens| lens.id), Some(lens.id));
+  assert_eq!(applied.focal_length, Some((35, 1)));
+  assert_eq!(applied.caption.as_deref(), Some("Tram 28"));
+  assert_eq!(applied.sublocation.as_deref(), Some("Alfama"));
+
+  let unchanged = Frame::default().apply_to(&selection);
+  assert_eq!(unchanged.exposure, selection.exposure);
+  assert_eq!(unchanged.caption, selection.caption);
+  assert!(Frame::parse_focal_length("wide").is_err());
+}
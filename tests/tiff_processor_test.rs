@@ -0,0 +1,274 @@
+//! Tests for in-place EXIF writing in TIFF files.
+
+use ifex::exif::ifd::tag;
+use ifex::exif::processors::TiffProcessor;
+use ifex::exif::{ByteOrder, ExifValue, IfdTree, TagGroup};
+use ifex::models::*;
+use image::{ImageBuffer, Rgb};
+use std::fs;
+use tempfile::TempDir;
+
+const STRIP_BYTE_COUNTS: u16 = 0x0117;
+
+fn create_test_selection() -> Selection {
+  let camera = Camera::new("Leica".to_string(), "M6".to_string());
+  let lens = Lens::new(
+    "Leica".to_string(),
+    "Summicron".to_string(),
+    "35".to_string(),
+    "2".to_string(),
+    "M".to_string(),
+  );
+  let film = Film::new("Kodak".to_string(), "Tri-X".to_string(), 400);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Test Setup".to_string(), camera.id, Some(lens.id));
+
+  Selection::new(setup, camera, Some(lens), film, photographer)
+}
+
+fn rgb16_image() -> ImageBuffer<Rgb<u16>, Vec<u16>> {
+  ImageBuffer::from_fn(8, 6, |x, y| {
+    Rgb([
+      u16::try_from(x * 1000).unwrap_or(u16::MAX),
+      u16::try_from(y * 2000).unwrap_or(u16::MAX),
+      u16::MAX,
+    ])
+  })
+}
+
+/// Builds a 2x2 8-bit grayscale big-endian TIFF with Orientation and Software tags.
+fn big_endian_tiff() -> Vec<u8> {
+  let mut data = b"MM\x00\x2a\x00\x00\x00\x08".to_vec();
+  let entries: [(u16, u16, u32, u32); 9] = [
+    (0x0100, 3, 1, 2 << 16), // ImageWidth
+    (0x0101, 3, 1, 2 << 16), // ImageLength
+    (0x0102, 3, 1, 8 << 16), // BitsPerSample
+    (0x0103, 3, 1, 1 << 16), // Compression
+    (0x0106, 3, 1, 1 << 16), // PhotometricInterpretation
+    (0x0111, 4, 1, 134),     // StripOffsets
+    (0x0112, 3, 1, 6 << 16), // Orientation
+    (0x0117, 4, 1, 4),       // StripByteCounts
+    (0x0131, 2, 11, 122),    // Software
+  ];
+  data.extend_from_slice(&9u16.to_be_bytes());
+  for (tag, field_type, count, value) in entries {
+    data.extend_from_slice(&tag.to_be_bytes());
+    data.extend_from_slice(&field_type.to_be_bytes());
+    data.extend_from_slice(&count.to_be_bytes());
+    data.extend_from_slice(&value.to_be_bytes());
+  }
+  data.extend_from_slice(&0u32.to_be_bytes());
+  assert_eq!(data.len(), 122);
+  data.extend_from_slice(b"ScannerApp\0\0");
+  assert_eq!(data.len(), 134);
+  data.extend_from_slice(&[10, 20, 30, 40]);
+  data
+}
+
+fn has_tag(exif_data: &[(String, String)], tag: &str, value: &str) -> bool {
+  exif_data
+    .iter()
+    .any(|(key, val)| tag_matches(key, tag) && val.contains(value))
+}
+
+fn tag_matches(key: &str, tag: &str) -> bool {
+  key == tag || key.starts_with(&format!("{tag} ["))
+}
+
+#[test]
+fn test_apply_exif_round_trip_16bit_tiff() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.tif");
+  let original = rgb16_image();
+  original.save(&test_file).unwrap();
+
+  let selection = create_test_selection();
+  TiffProcessor::apply_exif_with_iso(&test_file, &selection, Some(1600)).unwrap();
+
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(has_tag(&exif_data, "Make", "Leica"));
+  assert!(has_tag(&exif_data, "Model", "M6"));
+  assert!(has_tag(&exif_data, "Artist", "Test User"));
+  assert!(has_tag(&exif_data, "ISO Speed", "1600"));
+  assert!(has_tag(&exif_data, "Lens Make", "Leica"));
+  assert!(has_tag(&exif_data, "Lens Model", "Summicron 35mm f/2"));
+  assert!(has_tag(&exif_data, "Focal Length", "35000/1000"));
+  assert!(exif_data
+    .iter()
+    .any(|(key, value)| key.contains("Film") && value.contains("Kodak Tri-X (ISO 400)")));
+
+  let reopened = image::open(&test_file).unwrap().into_rgb16();
+  assert_eq!(reopened, original);
+}
+
+#[test]
+fn test_apply_exif_writes_exposure() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.tif");
+  rgb16_image().save(&test_file).unwrap();
+
+  let mut selection = create_test_selection();
+  TiffProcessor::apply_exif(&test_file, &selection).unwrap();
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| tag_matches(key, "F-Number")));
+
+  selection.exposure = Exposure {
+    exposure_time: Some((1, 125)),
+    f_number: Some((28, 5)),
+    compensation: Some((-1, 3)),
+    metering_mode: Some(MeteringMode::Spot),
+    program: Some(ExposureProgram::AperturePriority),
+  };
+  TiffProcessor::apply_exif(&test_file, &selection).unwrap();
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(has_tag(&exif_data, "Exposure Time", "1/125"));
+  assert!(has_tag(&exif_data, "F-Number", "28/5"));
+  assert!(has_tag(&exif_data, "Exposure Bias Value", "-1/3"));
+  assert!(has_tag(&exif_data, "Metering Mode", "3"));
+  assert!(has_tag(&exif_data, "Exposure Program", "3"));
+
+  selection.exposure = Exposure::default();
+  TiffProcessor::apply_exif(&test_file, &selection).unwrap();
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(has_tag(&exif_data, "Exposure Time", "1/125"));
+}
+
+#[test]
+fn test_apply_exif_preserves_big_endian_tags_and_strips() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan_be.tiff");
+  let original = big_endian_tiff();
+  fs::write(&test_file, &original).unwrap();
+
+  TiffProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+
+  let updated = fs::read(&test_file).unwrap();
+  let tree = IfdTree::parse(&updated).unwrap();
+  assert_eq!(tree.byte_order, ByteOrder::BigEndian);
+  assert_eq!(
+    tree.ifd0.get(tag::SOFTWARE).and_then(ExifValue::as_ascii),
+    Some("ScannerApp".to_string())
+  );
+  assert_eq!(
+    tree.ifd0.get(tag::ORIENTATION),
+    Some(&ExifValue::Short(vec![6]))
+  );
+  let strip_offset = tree
+    .ifd0
+    .get(tag::STRIP_OFFSETS)
+    .and_then(ExifValue::first_u32)
+    .unwrap() as usize;
+  assert_eq!(
+    tree
+      .ifd0
+      .get(STRIP_BYTE_COUNTS)
+      .and_then(ExifValue::first_u32),
+    Some(4)
+  );
+  assert_eq!(&updated[strip_offset..strip_offset + 4], &[10, 20, 30, 40]);
+
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(has_tag(&exif_data, "Software", "ScannerApp"));
+  assert!(has_tag(&exif_data, "Orientation", "6"));
+  assert!(has_tag(&exif_data, "Make", "Leica"));
+  assert!(has_tag(&exif_data, "ISO Speed", "400"));
+
+  let reopened = image::open(&test_file).unwrap().into_luma8();
+  assert_eq!(reopened.into_raw(), vec![10, 20, 30, 40]);
+}
+
+#[test]
+fn test_reapplying_updates_existing_tags() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.tif");
+  rgb16_image().save(&test_file).unwrap();
+
+  let mut selection = create_test_selection();
+  TiffProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  selection.camera.model = "M7".to_string();
+  selection.lens = None;
+  TiffProcessor::apply_exif(&test_file, &selection).unwrap();
+
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  let models: Vec<_> = exif_data
+    .iter()
+    .filter(|(key, _)| tag_matches(key, "Model"))
+    .collect();
+  assert_eq!(models.len(), 1);
+  assert_eq!(models[0].1, "M7");
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| tag_matches(key, "Lens Model")));
+}
+
+#[test]
+fn test_set_creation_date_tiff() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.tif");
+  let original = rgb16_image();
+  original.save(&test_file).unwrap();
+
+  TiffProcessor::apply_exif(&test_file, &create_test_selection()).unwrap();
+  TiffProcessor::set_creation_date(&test_file, "2024:05:01 10:20:30").unwrap();
+
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(has_tag(
+    &exif_data,
+    "Date/Time Original",
+    "2024:05:01 10:20:30"
+  ));
+  assert!(has_tag(
+    &exif_data,
+    "Date/Time Digitized",
+    "2024:05:01 10:20:30"
+  ));
+  assert!(has_tag(&exif_data, "Date/Time", "2024:05:01 10:20:30"));
+  assert!(has_tag(&exif_data, "Make", "Leica"));
+
+  let reopened = image::open(&test_file).unwrap().into_rgb16();
+  assert_eq!(reopened, original);
+}
+
+#[test]
+fn test_erase_exif_groups_tiff() {
+  let temp_dir = TempDir::new().unwrap();
+  let test_file = temp_dir.path().join("scan.tif");
+  fs::write(&test_file, big_endian_tiff()).unwrap();
+  let mut selection = create_test_selection();
+  selection.development.developer = Some("HC-110".to_string());
+  TiffProcessor::apply_exif(&test_file, &selection).unwrap();
+  TiffProcessor::set_creation_date(&test_file, "2024:05:01 10:20:30").unwrap();
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(exif_data
+    .iter()
+    .any(|(key, _)| tag_matches(key, "User Comment")));
+
+  TiffProcessor::erase_exif_groups(&test_file, &[TagGroup::Equipment]).unwrap();
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(!exif_data.iter().any(|(key, _)| tag_matches(key, "Make")));
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| tag_matches(key, "User Comment")));
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| tag_matches(key, "Lens Model")));
+  assert!(has_tag(&exif_data, "Artist", "Test User"));
+  assert!(has_tag(&exif_data, "Date/Time Original", "2024:05:01"));
+
+  TiffProcessor::erase_exif(&test_file).unwrap();
+  let exif_data = TiffProcessor::read_exif(&test_file).unwrap();
+  assert!(!exif_data.iter().any(|(key, _)| tag_matches(key, "Artist")));
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| tag_matches(key, "Date/Time Original")));
+  assert!(!exif_data
+    .iter()
+    .any(|(key, _)| tag_matches(key, "Software")));
+  assert!(has_tag(&exif_data, "Orientation", "6"));
+
+  let reopened = image::open(&test_file).unwrap().into_luma8();
+  assert_eq!(reopened.into_raw(), vec![10, 20, 30, 40]);
+}
@@ -1,7 +1,11 @@
 //! Tests for utility functions.
 
+use filetime::FileTime;
 use ifex::utils::*;
+use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
+use tempfile::TempDir;
 
 #[test]
 fn test_clean_path_removes_double_quotes() {
@@ -170,3 +174,94 @@ fn test_get_file_type_unsupported() {
     Some("raw".to_string())
   );
 }
+
+/// Lists the names of the files in a directory.
+fn file_names(dir: &Path) -> io::Result<Vec<String>> {
+  let mut names = fs::read_dir(dir)?
+    .map(|entry| entry.map(|e| e.file_name().to_string_lossy().to_string()))
+    .collect::<io::Result<Vec<_>>>()?;
+  names.sort();
+  Ok(names)
+}
+
+#[test]
+fn test_write_atomic_replaces_contents_and_keeps_permissions() {
+  let temp_dir = TempDir::new().unwrap();
+  let path = temp_dir.path().join("scan.tif");
+  fs::write(&path, b"original").unwrap();
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+  }
+
+  write_atomic(&path, b"updated contents").unwrap();
+
+  assert_eq!(fs::read(&path).unwrap(), b"updated contents");
+  assert_eq!(file_names(temp_dir.path()).unwrap(), ["scan.tif"]);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+  }
+
+  let new_file = temp_dir.path().join("new.xmp");
+  write_atomic(&new_file, b"sidecar").unwrap();
+  assert_eq!(fs::read(&new_file).unwrap(), b"sidecar");
+}
+
+#[test]
+fn test_write_atomic_leaves_original_intact_when_writing_fails() {
+  let temp_dir = TempDir::new().unwrap();
+  let path = temp_dir.path().join("scan.jpg");
+  fs::write(&path, b"irreplaceable negative scan").unwrap();
+
+  let result = write_atomic_with(&path, WriteOptions::default(), |file| {
+    file.write_all(b"half of the new")?;
+    Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+  });
+
+  assert_eq!(result.unwrap_err().kind(), io::ErrorKind::StorageFull);
+  assert_eq!(fs::read(&path).unwrap(), b"irreplaceable negative scan");
+  assert_eq!(file_names(temp_dir.path()).unwrap(), ["scan.jpg"]);
+
+  // Replacing a directory fails at the rename, which must not leave the temp file behind
+  let dir = temp_dir.path().join("folder.jpg");
+  fs::create_dir(&dir).unwrap();
+  fs::write(dir.join("inside"), b"").unwrap();
+  assert!(write_atomic(&dir, b"data").is_err());
+  assert_eq!(
+    file_names(temp_dir.path()).unwrap(),
+    ["folder.jpg", "scan.jpg"]
+  );
+}
+
+#[test]
+fn test_write_atomic_can_preserve_modification_time() {
+  let temp_dir = TempDir::new().unwrap();
+  let path = temp_dir.path().join("scan.dng");
+  fs::write(&path, b"original").unwrap();
+  let mtime = FileTime::from_unix_time(1_000_000_000, 0);
+  filetime::set_file_mtime(&path, mtime).unwrap();
+
+  let options = WriteOptions {
+    preserve_mtime: true,
+  };
+  write_atomic_with(&path, options, |file| file.write_all(b"updated")).unwrap();
+  let metadata = fs::metadata(&path).unwrap();
+  assert_eq!(FileTime::from_last_modification_time(&metadata), mtime);
+
+  write_atomic(&path, b"updated again").unwrap();
+  let metadata = fs::metadata(&path).unwrap();
+  assert_ne!(FileTime::from_last_modification_time(&metadata), mtime);
+}
+
+#[test]
+fn test_split_keywords_trims_and_drops_empty_entries() {
+  assert_eq!(
+    split_keywords(" film, portrait ,,Lisboa "),
+    ["film", "portrait", "Lisboa"]
+  );
+  assert!(split_keywords(" , ").is_empty());
+}
@@ -0,0 +1,128 @@
+//! Tests that RAW sidecars carry the same metadata as the EXIF IFEX writes.
+
+use ifex::exif::processors::{JpegProcessor, RawProcessor};
+use ifex::models::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Tag names and values as returned by `read_exif`.
+type ExifData = Vec<(String, String)>;
+
+/// Tags that both writers must agree on, as named by `read_exif`.
+const SHARED_TAGS: &[&str] = &[
+  "Make",
+  "Model",
+  "Artist",
+  "Film",
+  "ISO Speed",
+  "Lens Make",
+  "Lens Model",
+  "Lens Specification",
+  "Focal Length",
+  "Date/Time",
+  "Date/Time Original",
+  "Date/Time Digitized",
+];
+
+fn create_minimal_jpeg() -> Vec<u8> {
+  vec![
+    0xFF, 0xD8, // SOI
+    0xFF, 0xE0, 0x00, 0x10, // APP0 segment
+    b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48, 0x00, 0x00, 0xFF, 0xDB,
+    0x00, 0x43, 0x00, // DQT
+    // Quantization table (64 bytes)
+    0x08, 0x06, 0x06, 0x07, 0x06, 0x05, 0x08, 0x07, 0x07, 0x07, 0x09, 0x09, 0x08, 0x0A, 0x0C, 0x14,
+    0x0D, 0x0C, 0x0B, 0x0B, 0x0C, 0x19, 0x12, 0x13, 0x0F, 0x14, 0x1D, 0x1A, 0x1F, 0x1E, 0x1D, 0x1A,
+    0x1C, 0x1C, 0x20, 0x24, 0x2E, 0x27, 0x20, 0x22, 0x2C, 0x23, 0x1C, 0x1C, 0x28, 0x37, 0x29, 0x2C,
+    0x30, 0x31, 0x34, 0x34, 0x34, 0x1F, 0x27, 0x39, 0x3D, 0x38, 0x32, 0x3C, 0x2E, 0x33, 0x34, 0x32,
+    0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0x10, 0x00, 0x10, 0x01, 0x01, 0x11, 0x00, 0x02, 0x11, 0x01,
+    0x03, 0x11, 0x01, // SOF0
+    0xFF, 0xC4, 0x00, 0x14, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x08, // DHT
+    0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, // SOS
+    0xD2, 0xCF, 0x20, // minimal scan data
+    0xFF, 0xD9, // EOI
+  ]
+}
+
+fn create_selection(focal_length: &str, aperture: &str) -> Selection {
+  let camera = Camera::new("Leica".to_string(), "M6".to_string());
+  let lens = Lens::new(
+    "Leica".to_string(),
+    "Summicron <Asph> & Co".to_string(),
+    focal_length.to_string(),
+    aperture.to_string(),
+    "M".to_string(),
+  );
+  let film = Film::new("Ilford".to_string(), "HP5 Plus".to_string(), 400);
+  let photographer = Photographer::new("Ana O'Neill".to_string(), None);
+  let setup = Setup::new("Street".to_string(), camera.id, Some(lens.id));
+
+  Selection::new(setup, camera, Some(lens), film, photographer)
+}
+
+fn value(exif_data: &[(String, String)], tag: &str) -> Option<String> {
+  exif_data
+    .iter()
+    .find(|(key, _)| key == tag || key.starts_with(&format!("{tag} [")))
+    .map(|(_, value)| value.clone())
+}
+
+/// Applies the selection to a JPEG and to a RAW sidecar and reads both back.
+fn write_both(
+  dir: &Path,
+  selection: &Selection,
+) -> Result<(ExifData, ExifData), Box<dyn std::error::Error>> {
+  let jpeg = dir.join("scan.jpg");
+  let raw = dir.join("scan.nef");
+  fs::write(&jpeg, create_minimal_jpeg())?;
+  fs::write(&raw, b"raw data")?;
+
+  JpegProcessor::apply_exif_with_iso(&jpeg, selection, Some(1600))?;
+  RawProcessor::apply_exif_with_iso(&raw, selection, Some(1600))?;
+  JpegProcessor::set_creation_date(&jpeg, "2024:02:29 23:59:58")?;
+  RawProcessor::set_creation_date(&raw, "2024:02:29 23:59:58")?;
+
+  Ok((
+    JpegProcessor::read_exif(&jpeg)?,
+    RawProcessor::read_exif(&raw)?,
+  ))
+}
+
+#[test]
+fn test_sidecar_matches_jpeg_exif() {
+  let temp_dir = TempDir::new().unwrap();
+  let (jpeg, raw) = write_both(temp_dir.path(), &create_selection("35", "f/2")).unwrap();
+
+  for tag in SHARED_TAGS {
+    let expected = value(&jpeg, tag);
+    assert!(expected.is_some(), "JPEG is missing {tag}");
+    assert_eq!(value(&raw, tag), expected, "{tag} differs");
+  }
+  assert_eq!(
+    value(&raw, "Lens Specification").as_deref(),
+    Some("35000/1000, 35000/1000, 2000/1000, 2000/1000")
+  );
+  assert_eq!(
+    value(&raw, "Film").as_deref(),
+    Some("Ilford HP5 Plus (ISO 400)")
+  );
+  assert_eq!(value(&raw, "Image Description"), None);
+}
+
+#[test]
+fn test_zoom_lens_matches_jpeg_exif() {
+  let temp_dir = TempDir::new().unwrap();
+  let (jpeg, raw) = write_both(temp_dir.path(), &create_selection("24-70", "3.5-5.6")).unwrap();
+
+  for tag in SHARED_TAGS {
+    assert_eq!(value(&raw, tag), value(&jpeg, tag), "{tag} differs");
+  }
+  // A zoom range has no single focal length
+  assert_eq!(value(&raw, "Focal Length"), None);
+  assert_eq!(
+    value(&raw, "Lens Specification").as_deref(),
+    Some("24000/1000, 70000/1000, 3500/1000, 5600/1000")
+  );
+}
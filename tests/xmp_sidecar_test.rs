@@ -0,0 +1,459 @@
+//! Round-trip tests for merging IFEX metadata into existing XMP sidecars and
+//! reading them back.
+
+use ifex::exif::processors::RawProcessor;
+use ifex::exif::xmp::{ns, prop, XmpProperty};
+use ifex::exif::ExifManager;
+use ifex::exif::{ExifTags, TagGroup, XmpDocument, XmpValue};
+use ifex::models::*;
+use std::fs;
+use tempfile::TempDir;
+
+const RATING: XmpProperty = XmpProperty::new("xmp", "http://ns.adobe.com/xap/1.0/", "Rating");
+
+/// A sidecar as written by Lightroom Classic, with develop settings and
+/// camera properties written as attributes.
+const LIGHTROOM_SIDECAR: &str = r#"<?xpacket begin="﻿" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/" x:xmptk="Adobe XMP Core 7.0-c000 1.000000, 0000/00/00-00:00:00        ">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:tiff="http://ns.adobe.com/tiff/1.0/"
+    xmlns:exif="http://ns.adobe.com/exif/1.0/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:crs="http://ns.adobe.com/camera-raw-settings/1.0/"
+   tiff:Make="NIKON CORPORATION"
+   tiff:Model="NIKON D850"
+   exif:FocalLength="50/1"
+   xmp:Rating="4"
+   crs:Version="15.0"
+   crs:Exposure2012="+0.35"
+   crs:CropTop="0.05"
+   crs:HasCrop="True">
+   <crs:ToneCurvePV2012>
+    <rdf:Seq>
+     <rdf:li>0, 0</rdf:li>
+     <rdf:li>255, 255</rdf:li>
+    </rdf:Seq>
+   </crs:ToneCurvePV2012>
+   <dc:subject xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <rdf:Bag>
+     <rdf:li>film &amp; grain</rdf:li>
+    </rdf:Bag>
+   </dc:subject>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+/// The history stack of a darktable sidecar.
+const DARKTABLE_HISTORY: &str = r#"<darktable:history>
+    <rdf:Seq>
+     <rdf:li
+      darktable:operation="exposure"
+      darktable:enabled="1"
+      darktable:params="0000000000000000cdcccc3e0000803f"/>
+     <rdf:li
+      darktable:operation="crop"
+      darktable:enabled="1"
+      darktable:params="gz12eJxjYGBgkGAAgRNODGiAEQAbQAE="/>
+    </rdf:Seq>
+   </darktable:history>"#;
+
+fn darktable_sidecar() -> String {
+  format!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/" x:xmptk="XMP Core 4.4.0-Exiv2">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:exif="http://ns.adobe.com/exif/1.0/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:darktable="http://darktable.sf.net/"
+   exif:DateTimeOriginal="2023:05:01 10:00:00"
+   xmp:Rating="1"
+   darktable:xmp_version="5"
+   darktable:history_end="2">
+   <!-- edited with darktable 4.6 -->
+   {DARKTABLE_HISTORY}
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+"#
+  )
+}
+
+fn create_test_selection() -> Selection {
+  let camera = Camera::new("Nikon".to_string(), "FM2".to_string());
+  let lens = Lens::new(
+    "Nikon".to_string(),
+    "Nikkor".to_string(),
+    "50".to_string(),
+    "1.4".to_string(),
+    "F".to_string(),
+  );
+  let film = Film::new("Kodak".to_string(), "Tri-X".to_string(), 400);
+  let photographer = Photographer::new("Test User".to_string(), None);
+  let setup = Setup::new("Street".to_string(), camera.id, Some(lens.id));
+
+  Selection::new(setup, camera, Some(lens), film, photographer)
+}
+
+fn text(value: &str) -> XmpValue {
+  XmpValue::Text(value.to_string())
+}
+
+#[test]
+fn test_apply_merges_into_lightroom_sidecar() {
+  let temp_dir = TempDir::new().unwrap();
+  let raw = temp_dir.path().join("DSC_0001.nef");
+  fs::write(&raw, b"raw data").unwrap();
+  fs::write(raw.with_extension("xmp"), LIGHTROOM_SIDECAR).unwrap();
+
+  RawProcessor::apply_exif_with_iso(&raw, &create_test_selection(), Some(1600)).unwrap();
+  RawProcessor::set_creation_date(&raw, "2024:03:01 12:00:00").unwrap();
+
+  let xml = fs::read_to_string(raw.with_extension("xmp")).unwrap();
+  let xmp = XmpDocument::parse(&xml).unwrap();
+  // IFEX properties replace the camera's, including ones written as attributes
+  assert_eq!(xmp.get(prop::MAKE), Some(text("Nikon")));
+  assert_eq!(xmp.get(prop::MODEL), Some(text("FM2")));
+  assert_eq!(xmp.get(prop::FOCAL_LENGTH), Some(text("50000/1000")));
+  assert_eq!(xmp.get(prop::FILM), Some(text("Kodak Tri-X (ISO 400)")));
+  assert_eq!(xmp.get(prop::IMAGE_DESCRIPTION), None);
+  assert!(!xml.contains("NIKON CORPORATION"));
+  assert_eq!(
+    xmp.get(prop::ISO_SPEED_RATINGS),
+    Some(XmpValue::Seq(vec!["1600".to_string()]))
+  );
+  // Setting the date afterwards keeps the equipment
+  assert_eq!(
+    xmp.get(prop::DATE_TIME_ORIGINAL),
+    Some(text("2024-03-01T12:00:00"))
+  );
+  assert_eq!(
+    xmp.get(prop::CREATOR),
+    Some(XmpValue::Seq(vec!["Test User".to_string()]))
+  );
+
+  // Develop settings, ratings, keywords and the packet wrapper survive untouched
+  assert_eq!(xmp.get(RATING), Some(text("4")));
+  for preserved in [
+    r#"<?xpacket begin="﻿" id="W5M0MpCehiHzreSzNTczkc9d"?>"#,
+    r#"crs:Exposure2012="+0.35""#,
+    r#"crs:CropTop="0.05""#,
+    "<rdf:li>0, 0</rdf:li>",
+    "<rdf:li>film &amp; grain</rdf:li>",
+    r#"<?xpacket end="w"?>"#,
+  ] {
+    assert!(xml.contains(preserved), "lost {preserved}");
+  }
+  assert_eq!(
+    XmpDocument::parse(&xml)
+      .unwrap()
+      .get(XmpProperty::new("dc", ns::DC, "subject")),
+    Some(XmpValue::Bag(vec!["film & grain".to_string()]))
+  );
+}
+
+#[test]
+fn test_apply_merges_into_darktable_sidecar() {
+  let temp_dir = TempDir::new().unwrap();
+  let raw = temp_dir.path().join("IMG_0001.cr2");
+  let sidecar = raw.with_extension("xmp");
+  fs::write(&raw, b"raw data").unwrap();
+  fs::write(&sidecar, darktable_sidecar()).unwrap();
+
+  RawProcessor::apply_exif(&raw, &create_test_selection()).unwrap();
+
+  let xml = fs::read_to_string(&sidecar).unwrap();
+  assert!(xml.contains(DARKTABLE_HISTORY));
+  assert!(xml.contains("<!-- edited with darktable 4.6 -->"));
+  assert!(xml.contains(r#"darktable:history_end="2""#));
+  let xmp = XmpDocument::parse(&xml).unwrap();
+  assert_eq!(
+    xmp.get(prop::DATE_TIME_ORIGINAL),
+    Some(text("2023:05:01 10:00:00"))
+  );
+  assert_eq!(xmp.get(prop::MAKE), Some(text("Nikon")));
+  assert_eq!(xmp.get(prop::LENS_MODEL), Some(text("Nikkor 50mm f/1.4")));
+
+  // Applying a camera-only setup removes the stale lens properties
+  let mut selection = create_test_selection();
+  selection.lens = None;
+  RawProcessor::apply_exif(&raw, &selection).unwrap();
+  let xml = fs::read_to_string(&sidecar).unwrap();
+  let xmp = XmpDocument::parse(&xml).unwrap();
+  assert_eq!(xmp.get(prop::LENS_MODEL), None);
+  assert_eq!(xmp.get(prop::LENS), None);
+  assert_eq!(xmp.get(prop::FOCAL_LENGTH), None);
+  assert!(xml.contains(DARKTABLE_HISTORY));
+
+  // Dates written later replace darktable's attribute
+  RawProcessor::set_creation_date(&raw, "2024:03:01 12:00:00").unwrap();
+  let xmp = XmpDocument::parse(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+  assert_eq!(
+    xmp.get(prop::DATE_TIME_ORIGINAL),
+    Some(text("2024-03-01T12:00:00"))
+  );
+  assert_eq!(xmp.get(prop::MAKE), Some(text("Nikon")));
+}
+
+#[test]
+fn test_erase_groups_keeps_foreign_sidecar_properties() {
+  let temp_dir = TempDir::new().unwrap();
+  let raw = temp_dir.path().join("DSC_0002.nef");
+  let sidecar = raw.with_extension("xmp");
+  fs::write(&raw, b"raw data").unwrap();
+  fs::write(&sidecar, LIGHTROOM_SIDECAR).unwrap();
+  let mut selection = create_test_selection();
+  selection.development.developer = Some("HC-110".to_string());
+  RawProcessor::apply_exif(&raw, &selection).unwrap();
+  RawProcessor::set_creation_date(&raw, "2024:03:01 12:00:00").unwrap();
+  let xmp = XmpDocument::parse(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+  assert!(xmp.get(prop::USER_COMMENT).is_some());
+
+  RawProcessor::erase_exif_groups(&raw, &[TagGroup::Equipment]).unwrap();
+  let xmp = XmpDocument::parse(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+  assert_eq!(xmp.get(prop::MAKE), None);
+  assert_eq!(xmp.get(prop::LENS_MODEL), None);
+  assert_eq!(xmp.get(prop::FILM), None);
+  assert_eq!(xmp.get(prop::USER_COMMENT), None);
+  assert_eq!(
+    xmp.get(prop::CREATOR),
+    Some(XmpValue::Seq(vec!["Test User".to_string()]))
+  );
+  assert_eq!(
+    xmp.get(prop::DATE_TIME_ORIGINAL),
+    Some(text("2024-03-01T12:00:00"))
+  );
+  assert_eq!(xmp.get(RATING), Some(text("4")));
+
+  RawProcessor::erase_exif(&raw).unwrap();
+  let xml = fs::read_to_string(&sidecar).unwrap();
+  let xmp = XmpDocument::parse(&xml).unwrap();
+  assert_eq!(xmp.get(prop::CREATOR), None);
+  assert_eq!(xmp.get(prop::DATE_TIME_ORIGINAL), None);
+  assert_eq!(xmp.get(RATING), Some(text("4")));
+  for preserved in [
+    r#"crs:Exposure2012="+0.35""#,
+    "<rdf:li>0, 0</rdf:li>",
+    "<rdf:li>film &amp; grain</rdf:li>",
+  ] {
+    assert!(xml.contains(preserved), "lost {preserved}");
+  }
+}
+
+#[test]
+fn test_erase_without_sidecar_creates_none() {
+  let temp_dir = TempDir::new().unwrap();
+  let raw = temp_dir.path().join("DSC_0003.nef");
+  fs::write(&raw, b"raw data").unwrap();
+
+  RawProcessor::erase_exif_groups(&raw, &[TagGroup::Gps]).unwrap();
+  RawProcessor::erase_exif(&raw).unwrap();
+  assert!(!raw.with_extension("xmp").exists());
+}
+
+#[test]
+fn test_unparseable_sidecar_is_left_untouched() {
+  let temp_dir = TempDir::new().unwrap();
+  let raw = temp_dir.path().join("IMG_0002.cr2");
+  let sidecar = raw.with_extension("xmp");
+  fs::write(&raw, b"raw data").unwrap();
+  fs::write(&sidecar, "<x:xmpmeta><rdf:RDF>").unwrap();
+
+  let error = RawProcessor::apply_exif(&raw, &create_test_selection()).unwrap_err();
+  assert!(error.to_string().contains("Cannot update XMP sidecar"));
+  assert_eq!(
+    fs::read_to_string(&sidecar).unwrap(),
+    "<x:xmpmeta><rdf:RDF>"
+  );
+}
+
+#[test]
+fn test_document_values_round_trip() {
+  let mut xmp = XmpDocument::new();
+  let creator = XmpValue::Seq(vec!["O'Brien".to_string(), "Doe".to_string()]);
+  let rights = XmpValue::LangAlt("© 2024 Zeiss <Planar> & Co".to_string());
+  xmp.set(prop::CREATOR, &creator);
+  xmp.set(XmpProperty::new("dc", ns::DC, "rights"), &rights);
+  xmp.set(
+    prop::LENS_MODEL,
+    &XmpValue::Text("Zeiss <Planar> & Co".to_string()),
+  );
+
+  let xml = xmp.to_xml();
+  assert!(xml.contains("Zeiss &lt;Planar&gt; &amp; Co"));
+  assert!(xml.contains(r#"<rdf:li xml:lang="x-default">"#));
+  let parsed = XmpDocument::parse(&xml).unwrap();
+  assert_eq!(parsed.get(prop::CREATOR), Some(creator));
+  assert_eq!(
+    parsed.get(XmpProperty::new("dc", ns::DC, "rights")),
+    Some(rights)
+  );
+  assert_eq!(
+    parsed.get(prop::LENS_MODEL),
+    Some(text("Zeiss <Planar> & Co"))
+  );
+
+  let mut parsed = parsed;
+  assert!(parsed.remove(prop::CREATOR));
+  assert!(!parsed.remove(prop::CREATOR));
+  assert_eq!(parsed.get(prop::CREATOR), None);
+}
+
+#[test]
+fn test_struct_values_round_trip() {
+  let field = |name: &str, value: &str| (name.to_string(), value.to_string());
+  // Photoshop writes the fields as attributes, other tools as elements of a
+  // resource or of a nested description
+  for contact_info in [
+    r#"<Iptc4xmpCore:CreatorContactInfo Iptc4xmpCore:CiAdrCity="Porto" Iptc4xmpCore:CiEmailWork="jane@example.com"/>"#,
+    r#"<Iptc4xmpCore:CreatorContactInfo rdf:parseType="Resource"><Iptc4xmpCore:CiAdrCity>Porto</Iptc4xmpCore:CiAdrCity><Iptc4xmpCore:CiEmailWork>jane@example.com</Iptc4xmpCore:CiEmailWork></Iptc4xmpCore:CreatorContactInfo>"#,
+    r#"<Iptc4xmpCore:CreatorContactInfo><rdf:Description Iptc4xmpCore:CiAdrCity="Porto"><Iptc4xmpCore:CiEmailWork>jane@example.com</Iptc4xmpCore:CiEmailWork></rdf:Description></Iptc4xmpCore:CreatorContactInfo>"#,
+  ] {
+    let xmp = XmpDocument::parse(&format!(
+      r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:Iptc4xmpCore="http://iptc.org/std/Iptc4xmpCore/1.0/xmlns/">{contact_info}</rdf:Description></rdf:RDF></x:xmpmeta>"#
+    ))
+    .unwrap();
+    assert_eq!(
+      xmp.get(prop::CREATOR_CONTACT_INFO),
+      Some(XmpValue::Struct(vec![
+        field("CiAdrCity", "Porto"),
+        field("CiEmailWork", "jane@example.com")
+      ])),
+      "{contact_info}"
+    );
+  }
+
+  let contact_info = XmpValue::Struct(vec![
+    field("CiAdrExtadr", "Rua das Flores 12 & 14"),
+    field("CiUrlWork", "https://example.com"),
+  ]);
+  let mut xmp = XmpDocument::new();
+  xmp.set(prop::CREATOR_CONTACT_INFO, &contact_info);
+  let xml = xmp.to_xml();
+  assert!(xml.contains(r#"<Iptc4xmpCore:CreatorContactInfo rdf:parseType="Resource">"#));
+  assert!(
+    xml.contains("<Iptc4xmpCore:CiAdrExtadr>Rua das Flores 12 &amp; 14</Iptc4xmpCore:CiAdrExtadr>")
+  );
+  assert_eq!(
+    XmpDocument::parse(&xml)
+      .unwrap()
+      .get(prop::CREATOR_CONTACT_INFO),
+    Some(contact_info)
+  );
+}
+
+#[test]
+fn test_conflicting_prefix_gets_a_fresh_declaration() {
+  let mut xmp = XmpDocument::parse(
+    r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:tiff="urn:example:other" tiff:Make="keep"/></rdf:RDF></x:xmpmeta>"#,
+  )
+  .unwrap();
+
+  xmp.set(prop::MAKE, &XmpValue::Text("Leica".to_string()));
+
+  let xml = xmp.to_xml();
+  assert!(xml.contains(r#"tiff:Make="keep""#));
+  assert!(xml.contains(r#"xmlns:tiff1="http://ns.adobe.com/tiff/1.0/""#));
+  assert!(xml.contains("<tiff1:Make>Leica</tiff1:Make>"));
+  assert_eq!(
+    XmpDocument::parse(&xml).unwrap().get(prop::MAKE),
+    Some(text("Leica"))
+  );
+  assert!(XmpDocument::parse("<x:xmpmeta/>").is_err());
+}
+
+fn has_tag(exif_data: &[(String, String)], tag: &str, value: &str) -> bool {
+  exif_data.iter().any(|(t, v)| t == tag && v == value)
+}
+
+#[test]
+fn test_read_exif_lists_sidecar_properties() {
+  let temp_dir = TempDir::new().unwrap();
+  let raw = temp_dir.path().join("DSC_0001.nef");
+  fs::write(&raw, b"raw data").unwrap();
+  fs::write(raw.with_extension("xmp"), LIGHTROOM_SIDECAR).unwrap();
+
+  let exif_data = ExifManager::read_exif_data(&raw).unwrap();
+  assert!(has_tag(&exif_data, "Make", "NIKON CORPORATION"));
+  assert!(has_tag(&exif_data, "Model", "NIKON D850"));
+  assert!(has_tag(&exif_data, "Focal Length", "50/1"));
+  assert!(has_tag(&exif_data, "xmp:Rating", "4"));
+  assert!(has_tag(&exif_data, "crs:ToneCurvePV2012", "0, 0, 255, 255"));
+  assert!(has_tag(&exif_data, "dc:subject", "film & grain"));
+  assert!(!exif_data.iter().any(|(tag, _)| tag.starts_with("xmlns")));
+
+  // Properties written by IFEX read back under the JPEG tag names
+  RawProcessor::apply_exif_with_iso(&raw, &create_test_selection(), Some(1600)).unwrap();
+  let exif_data = RawProcessor::read_exif(&raw).unwrap();
+  assert!(has_tag(&exif_data, "Make", "Nikon"));
+  assert!(has_tag(&exif_data, "ISO Speed", "1600"));
+  assert!(has_tag(&exif_data, "Lens Model", "Nikkor 50mm f/1.4"));
+  assert!(has_tag(&exif_data, "Artist", "Test User"));
+}
+
+#[test]
+fn test_one_sec_spaces_raw_dates_from_sidecars() {
+  let temp_dir = TempDir::new().unwrap();
+  let sidecar = darktable_sidecar().replace(
+    r#"exif:DateTimeOriginal="2023:05:01 10:00:00""#,
+    r#"exif:DateTimeOriginal="2023-05-01T10:00:00.00+02:00""#,
+  );
+  let mut files = Vec::new();
+  for name in ["a.nef", "b.nef"] {
+    let raw = temp_dir.path().join(name);
+    fs::write(&raw, b"raw data").unwrap();
+    fs::write(raw.with_extension("xmp"), &sidecar).unwrap();
+    files.push(raw);
+  }
+
+  let result = ExifManager::new().process_selected_files_with_one_sec(
+    &files,
+    Some(&create_test_selection()),
+    "apply",
+    None,
+    true,
+  );
+  assert!(result.success);
+
+  let first = RawProcessor::read_exif(&files[0]).unwrap();
+  assert!(has_tag(&first, "Date/Time Original", "2023:05:01 10:00:00"));
+  let second = RawProcessor::read_exif(&files[1]).unwrap();
+  assert!(has_tag(
+    &second,
+    "Date/Time Original",
+    "2023:05:01 10:00:01"
+  ));
+}
+
+#[test]
+fn test_create_xmp_metadata_escapes_values() {
+  let mut selection = create_test_selection();
+  if let Some(lens) = selection.lens.as_mut() {
+    lens.model = "Zeiss <Planar> & Co".to_string();
+  }
+  selection.photographer.name = "Siobhán O'Brien".to_string();
+
+  let xml = ExifTags::create_xmp_metadata_with_iso(&selection, Some(800));
+  assert!(xml.contains("Zeiss &lt;Planar&gt; &amp; Co"));
+  assert!(!xml.contains("O'Brien"));
+
+  let xmp = XmpDocument::parse(&xml).unwrap();
+  assert_eq!(
+    xmp.get(prop::LENS_MODEL),
+    Some(text("Zeiss <Planar> & Co 50mm f/1.4"))
+  );
+  assert_eq!(
+    xmp.get(prop::CREATOR),
+    Some(XmpValue::Seq(vec!["Siobhán O'Brien".to_string()]))
+  );
+  assert_eq!(
+    xmp.get(prop::ISO_SPEED_RATINGS),
+    Some(XmpValue::Seq(vec!["800".to_string()]))
+  );
+  assert_eq!(
+    ExifTags::create_xmp_metadata(&selection),
+    ExifTags::create_xmp_metadata_with_iso(&selection, None)
+  );
+}